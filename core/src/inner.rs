@@ -40,48 +40,156 @@ impl IntoIterator for TabList {
     }
 }
 
+impl TabList {
+    /// Directory the scripts were extracted to. Commands' `LocalFile` paths point into it.
+    pub fn script_dir(&self) -> &Path {
+        self.1.path()
+    }
+
+    /// Names leading from the tab down to `node`, e.g. `["System Setup", "Arch", "Paru"]`.
+    pub fn command_path(&self, node: &Rc<ListNode>) -> Option<Vec<String>> {
+        self.iter().find_map(|tab| {
+            let node_ref = tab
+                .tree
+                .root()
+                .descendants()
+                .find(|candidate| Rc::ptr_eq(candidate.value(), node))?;
+            let mut path: Vec<String> = node_ref
+                .ancestors()
+                .filter(|ancestor| ancestor.parent().is_some())
+                .map(|ancestor| ancestor.value().name.clone())
+                .collect();
+            path.reverse();
+            path.insert(0, tab.name.clone());
+            path.push(node.name.clone());
+            Some(path)
+        })
+    }
+}
+
 pub fn get_tabs(validate: bool) -> TabList {
+    let (mut tabs, loader) = load_tabs(validate);
+    tabs.extend(loader);
+
+    if tabs.is_empty() {
+        panic!("No tabs found");
+    }
+    tabs
+}
+
+/// Extracts the tab data and returns an empty [`TabList`] together with a loader that parses
+/// the tabs one at a time, so a UI can show the first tab before the others are ready.
+pub fn load_tabs(validate: bool) -> (TabList, TabLoader) {
     let temp_dir = TempDir::with_prefix("linutil_scripts").unwrap();
     let tab_files = TabDirectories::get_tabs(&temp_dir);
-    let desktop_hint = current_desktop_label();
+    let loader = TabLoader {
+        tab_files: tab_files.into_iter(),
+        validate,
+        desktop_hint: current_desktop_label(),
+    };
+    (TabList(Vec::new(), temp_dir), loader)
+}
 
-    let tabs: Vec<_> = tab_files
-        .into_iter()
-        .map(|path| {
-            let directory = path.parent().unwrap().to_owned();
-            let data = std::fs::read_to_string(path).expect("Failed to read tab data");
-            let mut tab_data: TabEntry = toml::from_str(&data).expect("Failed to parse tab data");
+/// Iterator over the remaining tabs of a [`load_tabs`] call, in display order. The tabs
+/// reference scripts inside the [`TabList`]'s directory, which must outlive them.
+pub struct TabLoader {
+    tab_files: std::vec::IntoIter<PathBuf>,
+    validate: bool,
+    desktop_hint: String,
+}
 
-            if validate {
-                filter_entries(&mut tab_data.data);
-            }
-            if tab_data.name == "System Setup" {
-                annotate_desktop_env_entries(&mut tab_data.data, &desktop_hint);
-            }
-            (tab_data, directory)
-        })
-        .collect();
+impl Iterator for TabLoader {
+    type Item = Tab;
 
-    let tabs: Vec<Tab> = tabs
-        .into_iter()
-        .map(|(TabEntry { name, data }, directory)| {
-            let mut tree = Tree::new(Rc::new(ListNode {
-                name: "root".to_string(),
-                description: String::new(),
-                command: Command::None,
-                task_list: String::new(),
-                multi_select: false,
-            }));
-            let mut root = tree.root_mut();
-            create_directory(data, &mut root, &directory, validate, true);
-            Tab { name, tree }
-        })
-        .collect();
+    fn next(&mut self) -> Option<Tab> {
+        let path = self.tab_files.next()?;
+        Some(
+            read_tab(&path, self.validate, &self.desktop_hint)
+                .unwrap_or_else(|err| panic!("{err}")),
+        )
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.tab_files.size_hint()
+    }
+}
 
+/// Like [`get_tabs`], but with the tab data from `source`, e.g. a newer copy of the `tabs`
+/// directory. It is copied first, so `source` may change while the tabs are in use, and
+/// mistakes in it are returned instead of panicking.
+pub fn load_tabs_from(source: &Path, validate: bool) -> Result<TabList, String> {
+    let temp_dir = TempDir::with_prefix("linutil_scripts").map_err(|err| err.to_string())?;
+    copy_dir(source, temp_dir.path())
+        .map_err(|err| format!("Failed to copy {}: {err}", source.display()))?;
+    let desktop_hint = current_desktop_label();
+    let tabs = TabDirectories::read(temp_dir.path())?
+        .iter()
+        .map(|path| read_tab(path, validate, &desktop_hint))
+        .collect::<Result<Vec<_>, _>>()?;
     if tabs.is_empty() {
-        panic!("No tabs found");
+        return Err("No tabs found".to_string());
+    }
+    Ok(TabList(tabs, temp_dir))
+}
+
+/// Reads the `tab_data.toml` at `path`, with scripts next to it.
+fn read_tab(path: &Path, validate: bool, desktop_hint: &str) -> Result<Tab, String> {
+    let directory = path.parent().unwrap_or(Path::new("/")).to_owned();
+    let data = std::fs::read_to_string(path)
+        .map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+    let mut tab_data: TabEntry = toml::from_str(&data)
+        .map_err(|err| format!("Failed to parse {}: {err}", path.display()))?;
+
+    if validate {
+        filter_entries(&mut tab_data.data);
+    }
+    if tab_data.name == "System Setup" {
+        annotate_desktop_env_entries(&mut tab_data.data, desktop_hint);
+    }
+
+    build_tab(tab_data, &directory, validate)
+}
+
+/// Copies `from` and everything below it to `to`.
+pub fn copy_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            std::fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Parses a tab in the format of `tab_data.toml`, with scripts relative to `directory`. Meant
+/// for tabs that do not ship with linutil, so mistakes are returned instead of panicking.
+pub fn parse_tab(data: &str, directory: &Path, validate: bool) -> Result<Tab, String> {
+    let mut tab_data: TabEntry = toml::from_str(data).map_err(|err| err.to_string())?;
+    if validate {
+        filter_entries(&mut tab_data.data);
     }
-    TabList(tabs, temp_dir)
+    build_tab(tab_data, directory, validate)
+}
+
+fn build_tab(tab_data: TabEntry, directory: &Path, validate: bool) -> Result<Tab, String> {
+    let mut tree = Tree::new(Rc::new(ListNode {
+        name: "root".to_string(),
+        description: String::new(),
+        command: Command::None,
+        task_list: String::new(),
+        multi_select: false,
+        tags: Vec::new(),
+    }));
+    let mut root = tree.root_mut();
+    create_directory(tab_data.data, &mut root, directory, validate, true)?;
+    Ok(Tab {
+        name: tab_data.name,
+        tree,
+    })
 }
 
 #[derive(Deserialize)]
@@ -109,6 +217,8 @@ struct Entry {
     task_list: String,
     #[serde(default = "default_true")]
     multi_select: bool,
+    #[serde(default)]
+    tags: Vec<String>,
 }
 
 fn default_true() -> bool {
@@ -185,7 +295,11 @@ fn filter_entries(entries: &mut Vec<Entry>) {
 }
 
 fn current_desktop_label() -> String {
-    let vars = ["XDG_CURRENT_DESKTOP", "XDG_SESSION_DESKTOP", "DESKTOP_SESSION"];
+    let vars = [
+        "XDG_CURRENT_DESKTOP",
+        "XDG_SESSION_DESKTOP",
+        "DESKTOP_SESSION",
+    ];
     for var in vars {
         if let Ok(value) = std::env::var(var) {
             let desktop = value.split(':').find(|part| !part.is_empty()).unwrap_or("");
@@ -199,19 +313,18 @@ fn current_desktop_label() -> String {
 
 fn annotate_desktop_env_entries(entries: &mut [Entry], desktop: &str) {
     for entry in entries {
-        if entry.name == "Install Desktop Environment"
-            || entry.name == "Uninstall Desktop Environment"
+        if (entry.name == "Install Desktop Environment"
+            || entry.name == "Uninstall Desktop Environment")
+            && !entry.description.contains("Detected desktop:")
         {
-            if !entry.description.contains("Detected desktop:") {
-                if entry.description.is_empty() {
-                    entry.description = format!("Detected desktop: {desktop}.");
-                } else {
-                    entry.description = format!(
-                        "{} Detected desktop: {}.",
-                        entry.description.trim_end(),
-                        desktop
-                    );
-                }
+            if entry.description.is_empty() {
+                entry.description = format!("Detected desktop: {desktop}.");
+            } else {
+                entry.description = format!(
+                    "{} Detected desktop: {}.",
+                    entry.description.trim_end(),
+                    desktop
+                );
             }
         }
         if let EntryType::Entries(ref mut children) = entry.entry_type {
@@ -226,9 +339,10 @@ fn create_directory(
     command_dir: &Path,
     validate: bool,
     parent_multi_select: bool,
-) {
+) -> Result<(), String> {
     for entry in data {
         let multi_select = parent_multi_select && entry.multi_select;
+        let tags: Vec<String> = entry.tags.iter().map(|tag| tag.to_lowercase()).collect();
 
         match entry.entry_type {
             EntryType::Entries(entries) => {
@@ -238,8 +352,9 @@ fn create_directory(
                     command: Command::None,
                     task_list: String::new(),
                     multi_select,
+                    tags,
                 }));
-                create_directory(entries, &mut node, command_dir, validate, multi_select);
+                create_directory(entries, &mut node, command_dir, validate, multi_select)?;
             }
             EntryType::Command(command) => {
                 node.append(Rc::new(ListNode {
@@ -248,12 +363,13 @@ fn create_directory(
                     command: Command::Raw(command),
                     task_list: String::new(),
                     multi_select,
+                    tags,
                 }));
             }
             EntryType::Script(script) => {
                 let script = command_dir.join(script);
                 if !script.exists() {
-                    panic!("Script {} does not exist", script.display());
+                    return Err(format!("Script {} does not exist", script.display()));
                 }
 
                 if let Some((executable, args)) = get_shebang(&script, validate) {
@@ -267,11 +383,13 @@ fn create_directory(
                         },
                         task_list: entry.task_list,
                         multi_select,
+                        tags,
                     }));
                 }
             }
         }
     }
+    Ok(())
 }
 
 fn get_shebang(script_path: &Path, validate: bool) -> Option<(String, Vec<String>)> {
@@ -315,16 +433,20 @@ impl TabDirectories {
             .extract(temp_dir)
             .expect("Failed to extract the saved directory");
 
-        let tab_files = std::fs::read_to_string(temp_dir.path().join("tabs.toml"))
-            .expect("Failed to read tabs.toml");
-        let data: Self = toml::from_str(&tab_files).expect("Failed to parse tabs.toml");
-        let tab_paths = data
+        Self::read(temp_dir.path()).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Paths of the `tab_data.toml` files `tabs.toml` in `directory` lists, in order.
+    fn read(directory: &Path) -> Result<Vec<PathBuf>, String> {
+        let tab_files = std::fs::read_to_string(directory.join("tabs.toml"))
+            .map_err(|err| format!("Failed to read tabs.toml: {err}"))?;
+        let data: Self = toml::from_str(&tab_files)
+            .map_err(|err| format!("Failed to parse tabs.toml: {err}"))?;
+        Ok(data
             .directories
             .iter()
-            .map(|path| temp_dir.path().join(path).join("tab_data.toml"))
-            .collect();
-
-        tab_paths
+            .map(|path| directory.join(path).join("tab_data.toml"))
+            .collect())
     }
 }
 
@@ -345,12 +467,61 @@ mod tab_directories_tests {
 
         drop(temp_dir);
     }
+
+    #[test]
+    fn test_load_tabs_from() {
+        let source = crate::tests::create_temp_dir();
+        TAB_DATA.extract(source.path()).unwrap();
+        let tabs = load_tabs_from(source.path(), false).unwrap();
+
+        assert_eq!(
+            tabs.len(),
+            TabDirectories::read(source.path()).unwrap().len()
+        );
+        assert_ne!(tabs.script_dir(), source.path());
+        assert!(tabs.script_dir().join("tabs.toml").is_file());
+
+        fs::remove_file(source.path().join("tabs.toml")).unwrap();
+        assert!(load_tabs_from(source.path(), false).is_err());
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_command_path() {
+        let mut tab_list = crate::tests::create_tab_list();
+        let node = |name: &str| {
+            Rc::new(ListNode {
+                name: name.to_string(),
+                description: String::new(),
+                command: Command::None,
+                task_list: String::new(),
+                multi_select: true,
+                tags: Vec::new(),
+            })
+        };
+        let leaf = node("Paru");
+        let mut tree = Tree::new(node("root"));
+        tree.root_mut().append(node("Arch")).append(leaf.clone());
+        tab_list.push(Tab {
+            name: "System Setup".to_string(),
+            tree,
+        });
+
+        assert_eq!(
+            tab_list.command_path(&leaf),
+            Some(vec![
+                "System Setup".to_string(),
+                "Arch".to_string(),
+                "Paru".to_string()
+            ])
+        );
+        assert!(tab_list.command_path(&node("Paru")).is_none());
+    }
+
     #[test]
     fn test_is_executable() {
         let temp_dir = crate::tests::create_temp_dir();
@@ -374,6 +545,46 @@ mod tests {
         drop(temp_dir);
     }
 
+    #[test]
+    fn test_load_tabs() {
+        let (tabs, loader) = load_tabs(false);
+        assert!(tabs.is_empty());
+
+        let expected = loader.size_hint().0;
+        let names: Vec<String> = loader.map(|tab| tab.name).collect();
+        assert_eq!(names.len(), expected);
+        assert_eq!(
+            names,
+            get_tabs(false)
+                .iter()
+                .map(|tab| tab.name.clone())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_parse_tab() {
+        let temp_dir = crate::tests::create_temp_dir();
+        std::fs::write(temp_dir.path().join("hello.sh"), "echo hello\n").unwrap();
+        let data = r#"
+            name = "Extra"
+
+            [[data]]
+            name = "Hello"
+            script = "hello.sh"
+            tags = ["Demo"]
+        "#;
+
+        let tab = parse_tab(data, temp_dir.path(), false).unwrap();
+        assert_eq!(tab.name, "Extra");
+        let hello = tab.find_command_by_name("Hello").unwrap();
+        assert_eq!(hello.tags, ["demo"]);
+
+        let missing = data.replace("hello.sh", "missing.sh");
+        assert!(parse_tab(&missing, temp_dir.path(), false).is_err());
+        assert!(parse_tab("name = ", temp_dir.path(), false).is_err());
+    }
+
     #[test]
     fn test_get_shebang() {
         let temp_dir = crate::tests::create_temp_dir();
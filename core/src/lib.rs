@@ -8,7 +8,10 @@ use ego_tree::Tree;
 use std::path::PathBuf;
 
 pub use config::{Config, ConfigValues};
-pub use inner::{get_tabs, TabList};
+
+/// Version of linutil_core, which the bundled scripts ship with.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+pub use inner::{copy_dir, get_tabs, load_tabs, load_tabs_from, parse_tab, TabList, TabLoader};
 
 #[derive(Clone, Hash, Eq, PartialEq)]
 pub enum Command {
@@ -35,10 +38,13 @@ pub struct ListNode {
     pub command: Command,
     pub task_list: String,
     pub multi_select: bool,
+    /// Lowercase topics from the tab data, e.g. `gaming` or `aur`.
+    pub tags: Vec<String>,
 }
 
 impl Tab {
-    fn find_command_by_name(&self, name: &str) -> Option<Rc<ListNode>> {
+    /// Finds a command by its exact name, as used by `auto_execute` in config files.
+    pub fn find_command_by_name(&self, name: &str) -> Option<Rc<ListNode>> {
         self.tree.root().descendants().find_map(|node| {
             let node_value = node.value();
             (node_value.name == name && !node.has_children()).then_some(node_value.clone())
@@ -62,6 +68,7 @@ mod tests {
             command: Command::Raw("echo 'cat memes 🙀'".to_string()),
             task_list: "".to_string(),
             multi_select: false,
+            tags: Vec::new(),
         });
 
         Tab {
@@ -0,0 +1,24 @@
+use std::{path::Path, process::Command};
+
+// Records the commit the GUI was built from for the About dialog. Packagers building from a
+// tarball can set `LINUTIL_COMMIT` instead.
+fn main() {
+    println!("cargo:rerun-if-env-changed=LINUTIL_COMMIT");
+    let git_dir = Path::new("../.git");
+    if git_dir.exists() {
+        println!("cargo:rerun-if-changed=../.git/HEAD");
+        println!("cargo:rerun-if-changed=../.git/refs");
+    }
+    let commit = std::env::var("LINUTIL_COMMIT").ok().or_else(|| {
+        Command::new("git")
+            .args(["rev-parse", "--short=10", "HEAD"])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    });
+    println!(
+        "cargo:rustc-env=LINUTIL_COMMIT={}",
+        commit.unwrap_or_else(|| "unknown".to_string())
+    );
+}
@@ -0,0 +1,38 @@
+use crate::diagnostics::COMMIT;
+use gtk::prelude::*;
+use gtk4 as gtk;
+
+/// Opens the About dialog. Its System page shows `diagnostics`, which Copy Diagnostics puts on
+/// the clipboard for bug reports.
+pub fn open_about_dialog(parent: &gtk::Window, diagnostics: &str) {
+    let about = gtk::AboutDialog::builder()
+        .transient_for(parent)
+        .modal(true)
+        .program_name("Linutil")
+        .version(format!("{} ({COMMIT})", env!("CARGO_PKG_VERSION")))
+        .comments("Distro-agnostic toolbox designed to simplify everyday Linux tasks.")
+        .logo_icon_name("utilities-terminal")
+        .website("https://github.com/ChrisTitusTech/linutil")
+        .website_label("Source code")
+        .license_type(gtk::License::MitX11)
+        .copyright("© 2025 Chris Titus")
+        .system_information(diagnostics)
+        .build();
+
+    let copy_button = gtk::Button::with_label("Copy Diagnostics");
+    copy_button.set_halign(gtk::Align::Center);
+    copy_button.set_margin_bottom(12);
+    copy_button.update_property(&[gtk::accessible::Property::Description(
+        "Copy the version and system details for a bug report.",
+    )]);
+    let diagnostics = diagnostics.to_string();
+    copy_button.connect_clicked(move |button| {
+        button.clipboard().set_text(&diagnostics);
+        button.set_label("Copied");
+    });
+    // The dialog has no place for extra buttons, so this one goes below its pages
+    if let Some(content) = about.child().and_downcast::<gtk::Box>() {
+        content.append(&copy_button);
+    }
+    about.show();
+}
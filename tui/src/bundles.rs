@@ -0,0 +1,279 @@
+// Command bundles shared by communities: a plugin directory, with its `tab_data.toml` and
+// scripts, published as a tarball, a git repository or a folder. Importing one fetches it,
+// checks that its tab loads with validation on and installs it among the user's plugins,
+// where it shows up as an extra tab. Bundles cannot name a provider, as that would run a
+// program from the bundle just to list its commands, nor hold symlinks, which could pull files
+// from elsewhere on this system into the plugins directory.
+
+use crate::{logs::slug, plugins, settings::config_dir, settings::state_dir};
+use linutil_core::{copy_dir, Tab};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Where a bundle comes from, as the user gave it.
+#[derive(Debug, PartialEq)]
+pub enum Origin {
+    /// A git repository, cloned without history.
+    Git(String),
+    /// A tarball downloaded over HTTP.
+    Archive(String),
+    /// A tarball or a folder on this system.
+    File(PathBuf),
+}
+
+impl Origin {
+    pub fn parse(input: &str) -> Self {
+        let input = input.trim();
+        if input.starts_with("git@")
+            || input.starts_with("git://")
+            || input.ends_with(".git")
+            || input.ends_with(".git/")
+        {
+            Self::Git(input.to_string())
+        } else if input.starts_with("https://") || input.starts_with("http://") {
+            Self::Archive(input.to_string())
+        } else {
+            let path = input.strip_prefix("file://").unwrap_or(input);
+            Self::File(PathBuf::from(path))
+        }
+    }
+}
+
+/// A fetched bundle whose tab loaded, waiting to be installed.
+pub struct Bundle {
+    /// Directory holding the bundle's `tab_data.toml`.
+    pub dir: PathBuf,
+    pub tab: Tab,
+}
+
+impl Bundle {
+    /// The bundle fetched to `dir`, with its tab loaded and validated.
+    pub fn load(dir: &Path) -> Result<Self, String> {
+        let tab = plugins::load_plugin(dir, true)?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            tab,
+        })
+    }
+
+    /// Number of commands in the bundle's tab.
+    pub fn commands(&self) -> usize {
+        self.tab
+            .tree
+            .root()
+            .descendants()
+            .filter(|node| !node.has_children() && node.parent().is_some())
+            .count()
+    }
+}
+
+/// Fetches the bundle at `input` into the state directory, returning the directory with its
+/// tab data for [`Bundle::load`]. Blocks while `curl`, `tar` or `git` run.
+pub fn fetch(input: &str) -> Result<PathBuf, String> {
+    let staging = state_dir().join("bundle.new");
+    let _ = fs::remove_dir_all(&staging);
+    let fetched = fs::create_dir_all(&staging)
+        .map_err(|err| err.to_string())
+        .and_then(|()| fetch_into(input, &staging));
+    if fetched.is_err() {
+        let _ = fs::remove_dir_all(&staging);
+    }
+    fetched
+}
+
+fn fetch_into(input: &str, staging: &Path) -> Result<PathBuf, String> {
+    match Origin::parse(input) {
+        Origin::Git(url) => {
+            let status = Command::new("git")
+                .args(["clone", "--quiet", "--depth", "1", "--"])
+                .arg(&url)
+                .arg(staging.join("repository"))
+                .status()
+                .map_err(|err| format!("Could not run git: {err}"))?;
+            if !status.success() {
+                return Err(format!("Could not clone {url}."));
+            }
+            let _ = fs::remove_dir_all(staging.join("repository").join(".git"));
+        }
+        Origin::Archive(url) => {
+            let archive = state_dir().join("bundle.tar");
+            let status = Command::new("curl")
+                .args(["--fail", "--silent", "--location", "--max-time", "120"])
+                .arg("--output")
+                .arg(&archive)
+                .arg(&url)
+                .status()
+                .map_err(|err| format!("Could not run curl: {err}"))?;
+            if !status.success() {
+                let _ = fs::remove_file(&archive);
+                return Err(format!("Could not download {url}."));
+            }
+            let unpacked = unpack(&archive, staging);
+            let _ = fs::remove_file(&archive);
+            unpacked?;
+        }
+        Origin::File(path) if path.is_dir() => {
+            // Copying would follow the links
+            refuse_symlinks(&path)?;
+            copy_dir(&path, &staging.join("folder"))
+                .map_err(|err| format!("Could not copy {}: {err}", path.display()))?;
+        }
+        Origin::File(path) => unpack(&path, staging)?,
+    }
+    refuse_symlinks(staging)?;
+
+    let dir = bundle_root(staging)
+        .ok_or("The bundle has no tab_data.toml at its top or in a single folder there.")?;
+    if dir.join("plugin.toml").exists() {
+        return Err("The bundle names a provider, which imported bundles cannot do.".to_string());
+    }
+    Ok(dir)
+}
+
+/// Installs `bundle` among the user's plugins, in a directory named after its tab, replacing
+/// an earlier import of it. Returns that directory.
+pub fn install(bundle: &Bundle) -> Result<PathBuf, String> {
+    let name = slug(&bundle.tab.name);
+    if name.is_empty() {
+        return Err(format!("The tab name {:?} is not usable.", bundle.tab.name));
+    }
+    let target = config_dir().join("plugins").join(name);
+    refuse_symlinks(&bundle.dir)?;
+    if target.exists() {
+        fs::remove_dir_all(&target).map_err(|err| err.to_string())?;
+    }
+    copy_dir(&bundle.dir, &target).map_err(|err| err.to_string())?;
+    let _ = fs::remove_dir_all(state_dir().join("bundle.new"));
+    Ok(target)
+}
+
+/// Fails when `dir` holds a symlink anywhere below it, naming the first one found.
+fn refuse_symlinks(dir: &Path) -> Result<(), String> {
+    match find_symlink(dir) {
+        Ok(None) => Ok(()),
+        Ok(Some(link)) => Err(format!(
+            "The bundle holds the symlink {}, which imported bundles cannot have.",
+            link.strip_prefix(dir).unwrap_or(&link).display()
+        )),
+        Err(err) => Err(format!("Could not read {}: {err}", dir.display())),
+    }
+}
+
+/// The first symlink below `dir`, looking at the links themselves rather than their targets.
+fn find_symlink(dir: &Path) -> io::Result<Option<PathBuf>> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let file_type = fs::symlink_metadata(&path)?.file_type();
+        if file_type.is_symlink() {
+            return Ok(Some(path));
+        }
+        if file_type.is_dir() {
+            if let Some(link) = find_symlink(&path)? {
+                return Ok(Some(link));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Unpacks the tarball at `archive` into `dir`, whatever its compression.
+fn unpack(archive: &Path, dir: &Path) -> Result<(), String> {
+    let status = Command::new("tar")
+        .arg("-xf")
+        .arg(archive)
+        .arg("-C")
+        .arg(dir)
+        .status()
+        .map_err(|err| format!("Could not run tar: {err}"))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("Could not unpack {}.", archive.display()))
+    }
+}
+
+/// The directory in the unpacked `dir` with the tab data: `dir` itself, or its only folder,
+/// as tarballs and repositories usually wrap everything in one, possibly nested once more.
+fn bundle_root(dir: &Path) -> Option<PathBuf> {
+    let mut dir = dir.to_path_buf();
+    for _ in 0..3 {
+        if dir.join("tab_data.toml").is_file() {
+            return Some(dir);
+        }
+        let entries: Vec<PathBuf> = fs::read_dir(&dir)
+            .ok()?
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.file_name().is_none_or(|name| name != ".git"))
+            .collect();
+        match entries.as_slice() {
+            [only] if only.is_dir() => dir = only.clone(),
+            _ => return None,
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_origin() {
+        assert_eq!(
+            Origin::parse("https://github.com/example/pack.git"),
+            Origin::Git("https://github.com/example/pack.git".to_string())
+        );
+        assert_eq!(
+            Origin::parse("git@github.com:example/pack"),
+            Origin::Git("git@github.com:example/pack".to_string())
+        );
+        assert_eq!(
+            Origin::parse(" https://example.org/pack.tar.gz "),
+            Origin::Archive("https://example.org/pack.tar.gz".to_string())
+        );
+        assert_eq!(
+            Origin::parse("file:///home/me/pack.tar.gz"),
+            Origin::File(PathBuf::from("/home/me/pack.tar.gz"))
+        );
+    }
+
+    #[test]
+    fn test_bundle_root() {
+        let root = std::env::temp_dir().join(format!("linutil-bundle-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let nested = root.join("pack-main").join("pack");
+        fs::create_dir_all(&nested).unwrap();
+        assert_eq!(bundle_root(&root), None);
+
+        fs::write(nested.join("tab_data.toml"), "name = \"Pack\"\n").unwrap();
+        assert_eq!(bundle_root(&root), Some(nested));
+
+        fs::create_dir_all(root.join("other")).unwrap();
+        assert_eq!(bundle_root(&root), None);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_find_symlink() {
+        let root =
+            std::env::temp_dir().join(format!("linutil-bundle-links-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let scripts = root.join("pack").join("scripts");
+        fs::create_dir_all(&scripts).unwrap();
+        fs::write(scripts.join("setup.sh"), "#!/bin/sh\n").unwrap();
+        assert_eq!(find_symlink(&root).unwrap(), None);
+        assert!(refuse_symlinks(&root).is_ok());
+
+        let link = scripts.join("profile.sh");
+        std::os::unix::fs::symlink("/etc/profile", &link).unwrap();
+        assert_eq!(find_symlink(&root).unwrap(), Some(link));
+        assert!(refuse_symlinks(&root)
+            .unwrap_err()
+            .contains("pack/scripts/profile.sh"));
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,639 @@
+// Newer copies of the script catalog, downloaded from the linutil repository so commands added
+// there show up without a new build, or from mirrors and directories the settings name.
+// Refresh Scripts swaps them in for the built-in tabs, and the open folder is found again in
+// the new tabs by the names leading to it. The last few downloads that loaded are kept as
+// snapshots, to start from while offline or to go back to when new scripts break something.
+// The commands of the last catalog the user looked at are kept as well, so What's New can list
+// the ones added and removed since. Downloads have to come with a signature by a pinned key
+// unless the user lets them go without, as the scripts often run as root.
+
+use crate::{
+    settings::{state_dir, CatalogSource, UpdateSettings},
+    signature,
+};
+use linutil_core::{copy_dir, ego_tree::NodeId, Command as NodeCommand, Tab, TabList};
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Reverse,
+    collections::HashSet,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    process::Command,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// The pinned snapshot id standing for the scripts Linutil was built with.
+pub const BUILT_IN: &str = "built-in";
+
+// File in a downloaded catalog naming the commit it was downloaded at
+const COMMIT_FILE: &str = ".commit";
+
+// File in a catalog from a mirror or this system naming the source it came from
+const SOURCE_FILE: &str = ".source";
+
+/// Scripts [`fetch`] unpacked.
+#[derive(Debug)]
+pub struct Fetched {
+    pub dir: PathBuf,
+    /// Fingerprint of the pinned key that signed the download, `None` when unchecked.
+    pub signer: Option<String>,
+}
+
+/// Why [`fetch`] gave up.
+#[derive(Debug)]
+pub enum FetchError {
+    /// The download or unpacking failed.
+    Failed(String),
+    /// The download came without a signature by a pinned key.
+    Unverified(String),
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Failed(reason) | Self::Unverified(reason) => f.write_str(reason),
+        }
+    }
+}
+
+impl From<String> for FetchError {
+    fn from(reason: String) -> Self {
+        Self::Failed(reason)
+    }
+}
+
+/// A download kept with [`keep`].
+#[derive(Clone, Debug)]
+pub struct Snapshot {
+    /// Seconds since the epoch when it was kept, naming its directory.
+    pub id: String,
+    pub dir: PathBuf,
+    pub downloaded: SystemTime,
+}
+
+impl Snapshot {
+    pub fn version(&self) -> String {
+        version(&self.dir)
+    }
+}
+
+fn snapshot_dir() -> PathBuf {
+    state_dir().join("catalogs")
+}
+
+/// The kept downloads, newest first.
+pub fn snapshots() -> Vec<Snapshot> {
+    // Before several were kept, the only download was in `catalog`
+    let single = state_dir().join("catalog");
+    if let Ok(modified) = fs::metadata(&single).and_then(|metadata| metadata.modified()) {
+        let id = modified
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let _ = fs::create_dir_all(snapshot_dir());
+        let _ = fs::rename(&single, snapshot_dir().join(id.to_string()));
+    }
+
+    let Ok(entries) = fs::read_dir(snapshot_dir()) else {
+        return Vec::new();
+    };
+    let mut snapshots: Vec<(u64, Snapshot)> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let id = entry.file_name().to_str()?.to_string();
+            let seconds: u64 = id.parse().ok()?;
+            let snapshot = Snapshot {
+                id,
+                dir: entry.path(),
+                downloaded: UNIX_EPOCH + Duration::from_secs(seconds),
+            };
+            Some((seconds, snapshot))
+        })
+        .collect();
+    snapshots.sort_by_key(|(seconds, _)| Reverse(*seconds));
+    snapshots
+        .into_iter()
+        .map(|(_, snapshot)| snapshot)
+        .collect()
+}
+
+/// The kept download to start from: the pinned one, or the newest when the scripts are
+/// downloaded at startup. `None` for the built-in scripts.
+pub fn to_start_from(settings: &UpdateSettings) -> Option<Snapshot> {
+    match settings.pinned_catalog.as_deref() {
+        Some(BUILT_IN) => None,
+        Some(id) => snapshots().into_iter().find(|snapshot| snapshot.id == id),
+        None if settings.refresh_scripts => snapshots().into_iter().next(),
+        None => None,
+    }
+}
+
+/// Downloads the current tabs directory from the first enabled source that works and unpacks
+/// it next to the kept one, which stays until the download is known to load and is passed to
+/// [`keep`]. Downloads are checked for a signature, unless `allow_unsigned` or the settings
+/// allow going without. Blocks until `curl`, `gpg` and `tar` are done.
+pub fn fetch(settings: &UpdateSettings, allow_unsigned: bool) -> Result<Fetched, FetchError> {
+    let mut reasons = Vec::new();
+    let mut unverified = false;
+    for source in settings.sources.iter().filter(|source| source.enabled) {
+        match fetch_from(source, settings, allow_unsigned) {
+            Ok(fetched) => return Ok(fetched),
+            Err(err) => {
+                unverified |= matches!(err, FetchError::Unverified(_));
+                reasons.push(format!("{}: {err}", source.name));
+            }
+        }
+    }
+    if reasons.is_empty() {
+        return Err("No script source is enabled.".to_string().into());
+    }
+    let reasons = reasons.join("\n");
+    Err(if unverified {
+        FetchError::Unverified(reasons)
+    } else {
+        FetchError::Failed(reasons)
+    })
+}
+
+/// Where a [`CatalogSource`] points to.
+#[derive(Debug, PartialEq)]
+pub enum Location {
+    /// `OWNER/REPO` of a repository on GitHub with the tabs in `core/tabs`.
+    GitHub(String),
+    /// A tarball downloaded over HTTP.
+    Url(String),
+    /// A directory or tarball on this system.
+    Local(PathBuf),
+}
+
+impl Location {
+    pub fn parse(location: &str) -> Self {
+        let location = location.trim();
+        if let Some(repository) = location.strip_prefix("github:") {
+            Self::GitHub(repository.to_string())
+        } else if location.starts_with("https://") || location.starts_with("http://") {
+            Self::Url(location.to_string())
+        } else {
+            let path = location.strip_prefix("file://").unwrap_or(location);
+            Self::Local(PathBuf::from(path))
+        }
+    }
+}
+
+fn fetch_from(
+    source: &CatalogSource,
+    settings: &UpdateSettings,
+    allow_unsigned: bool,
+) -> Result<Fetched, FetchError> {
+    let unpacked = state_dir().join("catalog.new");
+    let archive = state_dir().join("catalog.tar.gz");
+    let _ = fs::remove_dir_all(&unpacked);
+    fs::create_dir_all(state_dir()).map_err(|err| err.to_string())?;
+    let verify = !allow_unsigned && !settings.allow_unsigned_scripts;
+    let mut signer = None;
+
+    match Location::parse(&source.location) {
+        Location::GitHub(repository) => {
+            fs::create_dir_all(&unpacked).map_err(|err| err.to_string())?;
+            // The archive of a fixed commit, so the catalog can name the one it comes from
+            let commit_url = format!("https://api.github.com/repos/{repository}/commits/main");
+            let commit = Command::new("curl")
+                .args(["--fail", "--silent", "--location", "--max-time", "15"])
+                .args(["--header", "Accept: application/vnd.github.sha"])
+                .arg(&commit_url)
+                .output()
+                .map_err(|err| format!("Could not run curl: {err}"))?;
+            let commit = String::from_utf8_lossy(&commit.stdout).trim().to_string();
+            if commit.len() != 40 || !commit.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(format!("Could not look up the latest commit at {commit_url}.").into());
+            }
+
+            let archive_url = format!("https://github.com/{repository}/archive/{commit}.tar.gz");
+            download(&archive_url, &archive)?;
+            if verify {
+                let signature_url = match source
+                    .signature
+                    .as_ref()
+                    .or(settings.signature_url.as_ref())
+                {
+                    Some(template) => template.replace("{commit}", &commit),
+                    None => format!("{archive_url}.asc"),
+                };
+                match verify_download(&archive, &signature_url) {
+                    Ok(fingerprint) => signer = Some(fingerprint),
+                    Err(err) => {
+                        let _ = fs::remove_file(&archive);
+                        return Err(FetchError::Unverified(err));
+                    }
+                }
+            }
+            // The tabs directory, below the archive's top-level `<repo>-<commit>` directory
+            let name = repository.rsplit('/').next().unwrap_or(&repository);
+            let extracted = Command::new("tar")
+                .arg("-xzf")
+                .arg(&archive)
+                .arg("-C")
+                .arg(&unpacked)
+                .arg("--strip-components=3")
+                .arg(format!("{name}-{commit}/core/tabs"))
+                .status()
+                .map_err(|err| format!("Could not run tar: {err}"))?;
+            let _ = fs::remove_file(&archive);
+            if !extracted.success() {
+                return Err("Could not unpack the downloaded scripts."
+                    .to_string()
+                    .into());
+            }
+            fs::write(unpacked.join(COMMIT_FILE), &commit).map_err(|err| err.to_string())?;
+        }
+        Location::Url(url) => {
+            download(&url, &archive)?;
+            if verify {
+                let signature_url = source.signature.clone().unwrap_or(format!("{url}.asc"));
+                match verify_download(&archive, &signature_url) {
+                    Ok(fingerprint) => signer = Some(fingerprint),
+                    Err(err) => {
+                        let _ = fs::remove_file(&archive);
+                        return Err(FetchError::Unverified(err));
+                    }
+                }
+            }
+            let unpacked_archive = unpack_tabs(&archive, &unpacked);
+            let _ = fs::remove_file(&archive);
+            unpacked_archive?;
+        }
+        Location::Local(path) if path.is_dir() => {
+            let tabs = tabs_root(&path).ok_or(format!("{} holds no tabs.toml.", path.display()))?;
+            copy_dir(&tabs, &unpacked).map_err(|err| err.to_string())?;
+        }
+        Location::Local(path) => unpack_tabs(&path, &unpacked)?,
+    }
+    if !unpacked.join(COMMIT_FILE).exists() {
+        fs::write(unpacked.join(SOURCE_FILE), &source.name).map_err(|err| err.to_string())?;
+    }
+    Ok(Fetched {
+        dir: unpacked,
+        signer,
+    })
+}
+
+fn download(url: &str, to: &Path) -> Result<(), String> {
+    let downloaded = Command::new("curl")
+        .args(["--fail", "--silent", "--location", "--max-time", "120"])
+        .arg("--output")
+        .arg(to)
+        .arg(url)
+        .status()
+        .map_err(|err| format!("Could not run curl: {err}"))?;
+    if downloaded.success() {
+        Ok(())
+    } else {
+        let _ = fs::remove_file(to);
+        Err(format!("Could not download {url}."))
+    }
+}
+
+/// Unpacks the tarball at `archive` and moves the tabs directory in it to `to`.
+fn unpack_tabs(archive: &Path, to: &Path) -> Result<(), String> {
+    let extracted = state_dir().join("catalog.unpacked");
+    let _ = fs::remove_dir_all(&extracted);
+    fs::create_dir_all(&extracted).map_err(|err| err.to_string())?;
+    let status = Command::new("tar")
+        .arg("-xf")
+        .arg(archive)
+        .arg("-C")
+        .arg(&extracted)
+        .status()
+        .map_err(|err| format!("Could not run tar: {err}"))?;
+    let moved = if !status.success() {
+        Err(format!("Could not unpack {}.", archive.display()))
+    } else {
+        match tabs_root(&extracted) {
+            Some(tabs) => fs::rename(tabs, to).map_err(|err| err.to_string()),
+            None => Err(format!("{} holds no tabs.toml.", archive.display())),
+        }
+    };
+    let _ = fs::remove_dir_all(&extracted);
+    moved
+}
+
+/// The directory with the `tabs.toml` in `dir`, the least deep one when there are several,
+/// e.g. `core/tabs` of a checkout of the linutil repository.
+fn tabs_root(dir: &Path) -> Option<PathBuf> {
+    let mut level = vec![dir.to_path_buf()];
+    for _ in 0..5 {
+        let mut below = Vec::new();
+        for dir in &level {
+            if dir.join("tabs.toml").is_file() {
+                return Some(dir.clone());
+            }
+            let Ok(entries) = fs::read_dir(dir) else {
+                continue;
+            };
+            let mut folders: Vec<PathBuf> = entries
+                .filter_map(Result::ok)
+                .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_dir()))
+                .map(|entry| entry.path())
+                .collect();
+            folders.sort();
+            below.extend(folders);
+        }
+        level = below;
+    }
+    None
+}
+
+/// Downloads the detached signature of `archive` from `signature_url` and checks it,
+/// returning the fingerprint of the key that made it.
+fn verify_download(archive: &Path, signature_url: &str) -> Result<String, String> {
+    let signature = archive.with_extension("gz.asc");
+    let downloaded = Command::new("curl")
+        .args(["--fail", "--silent", "--location", "--max-time", "15"])
+        .arg("--output")
+        .arg(&signature)
+        .arg(signature_url)
+        .status()
+        .map_err(|err| format!("Could not run curl: {err}"))?;
+    if !downloaded.success() {
+        let _ = fs::remove_file(&signature);
+        return Err(format!(
+            "The scripts are not signed: there is no signature at {signature_url}."
+        ));
+    }
+    let verified = signature::verify(archive, &signature);
+    let _ = fs::remove_file(&signature);
+    verified
+}
+
+/// Keeps `fetched`, a download that loaded, as the newest snapshot, and removes the oldest
+/// ones beyond the number the settings keep. The pinned one stays either way.
+pub fn keep(fetched: &Path, settings: &UpdateSettings) -> io::Result<()> {
+    let id = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let target = snapshot_dir().join(id.to_string());
+    if target.exists() {
+        fs::remove_dir_all(&target)?;
+    }
+    fs::create_dir_all(snapshot_dir())?;
+    fs::rename(fetched, target)?;
+
+    let ids: Vec<String> = snapshots()
+        .into_iter()
+        .map(|snapshot| snapshot.id)
+        .collect();
+    for id in outdated(
+        &ids,
+        settings.keep_catalogs,
+        settings.pinned_catalog.as_deref(),
+    ) {
+        fs::remove_dir_all(snapshot_dir().join(id))?;
+    }
+    Ok(())
+}
+
+/// Of the snapshots `ids`, newest first, the ones past the newest `keep`, leaving out
+/// `pinned`. At least one is kept.
+fn outdated<'a>(ids: &'a [String], keep: usize, pinned: Option<&str>) -> Vec<&'a String> {
+    ids.iter()
+        .skip(keep.max(1))
+        .filter(|id| Some(id.as_str()) != pinned)
+        .collect()
+}
+
+/// Version of the catalog extracted to `script_dir`: the commit it was downloaded at, e.g.
+/// `main@1a2b3c4`, the name of the mirror or directory it came from, or the version of the
+/// built-in scripts, e.g. `v25.12.18`.
+pub fn version(script_dir: &Path) -> String {
+    if let Ok(commit) = fs::read_to_string(script_dir.join(COMMIT_FILE)) {
+        let commit = commit.trim();
+        return format!("main@{}", commit.get(..7).unwrap_or(commit));
+    }
+    match fs::read_to_string(script_dir.join(SOURCE_FILE)) {
+        Ok(source) => source.trim().to_string(),
+        Err(_) => format!("v{}", linutil_core::VERSION),
+    }
+}
+
+/// Commands of the catalog in `tabs`, as `Tab / Folder / Command` paths. Commands of plugins
+/// and the user's own scripts, which live outside the catalog's directory, are left out.
+pub fn commands(tabs: &TabList) -> Vec<String> {
+    let mut commands = Vec::new();
+    for tab in tabs.iter() {
+        for node in tab.tree.root().descendants() {
+            let NodeCommand::LocalFile { file, .. } = &node.value().command else {
+                continue;
+            };
+            if !file.starts_with(tabs.script_dir()) {
+                continue;
+            }
+            let mut path: Vec<&str> = node
+                .ancestors()
+                .filter(|ancestor| ancestor.parent().is_some())
+                .map(|ancestor| ancestor.value().name.as_str())
+                .collect();
+            path.reverse();
+            path.insert(0, &tab.name);
+            path.push(&node.value().name);
+            commands.push(path.join(" / "));
+        }
+    }
+    commands
+}
+
+/// The catalog the user last looked at in What's New, stored in the state directory.
+#[derive(Default, Deserialize, Serialize)]
+pub struct Seen {
+    pub version: String,
+    pub commands: Vec<String>,
+}
+
+impl Seen {
+    fn path() -> PathBuf {
+        state_dir().join("catalog-seen.toml")
+    }
+
+    /// The seen catalog, or `None` before anything was seen.
+    pub fn load() -> Option<Self> {
+        let content = fs::read_to_string(Self::path()).ok()?;
+        toml::from_str(&content)
+            .inspect_err(|err| eprintln!("Failed to parse {}: {err}", Self::path().display()))
+            .ok()
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let content =
+            toml::to_string(self).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        fs::create_dir_all(state_dir())?;
+        fs::write(Self::path(), content)
+    }
+
+    /// Commands of `current` that were not there when this was seen, and the ones gone since.
+    pub fn changes(&self, current: &[String]) -> (Vec<String>, Vec<String>) {
+        let added = current
+            .iter()
+            .filter(|command| !self.commands.contains(command))
+            .cloned()
+            .collect();
+        let removed = self
+            .commands
+            .iter()
+            .filter(|command| !current.contains(command))
+            .cloned()
+            .collect();
+        (added, removed)
+    }
+}
+
+/// Names of the folders `visit_stack` leads through in `tab`, below its root.
+pub fn folder_names(tab: &Tab, visit_stack: &[NodeId]) -> Vec<String> {
+    visit_stack
+        .iter()
+        .skip(1)
+        .filter_map(|id| tab.tree.get(*id))
+        .map(|node| node.value().name.clone())
+        .collect()
+}
+
+/// The visit stack of `tab` entering the folders named `names` one inside the other, as far
+/// as they still exist.
+pub fn folder_path(tab: &Tab, names: &[String]) -> Vec<NodeId> {
+    let mut node = tab.tree.root();
+    let mut path = vec![node.id()];
+    for name in names {
+        let Some(child) = node
+            .children()
+            .find(|child| child.has_children() && child.value().name == *name)
+        else {
+            break;
+        };
+        path.push(child.id());
+        node = child;
+    }
+    path
+}
+
+/// Number of commands in `new` that no tab of `old` has, by name.
+pub fn new_commands(old: &[Tab], new: &[Tab]) -> usize {
+    let command_names = |tabs: &[Tab]| -> HashSet<String> {
+        tabs.iter()
+            .flat_map(|tab| tab.tree.root().descendants())
+            .filter(|node| !node.has_children() && node.parent().is_some())
+            .map(|node| node.value().name.clone())
+            .collect()
+    };
+    let old = command_names(old);
+    command_names(new)
+        .iter()
+        .filter(|name| !old.contains(*name))
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use linutil_core::{ego_tree::Tree, Command, ListNode};
+    use std::rc::Rc;
+
+    fn node(name: &str) -> Rc<ListNode> {
+        Rc::new(ListNode {
+            name: name.to_string(),
+            description: String::new(),
+            command: Command::None,
+            task_list: String::new(),
+            multi_select: true,
+            tags: Vec::new(),
+        })
+    }
+
+    fn tab(commands: &[&str]) -> Tab {
+        let mut tree = Tree::new(node("root"));
+        let mut root = tree.root_mut();
+        let mut arch = root.append(node("Arch"));
+        for command in commands {
+            arch.append(node(command));
+        }
+        Tab {
+            name: "System Setup".to_string(),
+            tree,
+        }
+    }
+
+    #[test]
+    fn test_folder_path() {
+        let old = tab(&["Paru"]);
+        let arch = old.tree.root().first_child().unwrap().id();
+        let names = folder_names(&old, &[old.tree.root().id(), arch]);
+        assert_eq!(names, ["Arch"]);
+
+        let new = tab(&["Paru", "Yay"]);
+        let path = folder_path(&new, &names);
+        assert_eq!(path.len(), 2);
+        assert_eq!(new.tree.get(path[1]).unwrap().value().name, "Arch");
+        assert_eq!(folder_path(&new, &["Fedora".to_string()]).len(), 1);
+    }
+
+    #[test]
+    fn test_location() {
+        assert_eq!(
+            Location::parse("github:ChrisTitusTech/linutil"),
+            Location::GitHub("ChrisTitusTech/linutil".to_string())
+        );
+        assert_eq!(
+            Location::parse("https://mirror.example.com/linutil.tar.gz"),
+            Location::Url("https://mirror.example.com/linutil.tar.gz".to_string())
+        );
+        assert_eq!(
+            Location::parse("file:///srv/linutil"),
+            Location::Local(PathBuf::from("/srv/linutil"))
+        );
+    }
+
+    #[test]
+    fn test_tabs_root() {
+        let root = std::env::temp_dir().join(format!("linutil-catalog-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let tabs = root.join("linutil-main").join("core").join("tabs");
+        fs::create_dir_all(tabs.join("system-setup")).unwrap();
+        assert_eq!(tabs_root(&root), None);
+
+        fs::write(tabs.join("tabs.toml"), "directories = []\n").unwrap();
+        fs::write(tabs.join("system-setup").join("tabs.toml"), "").unwrap();
+        assert_eq!(tabs_root(&root), Some(tabs));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_outdated() {
+        let ids: Vec<String> = ["400", "300", "200", "100"].map(String::from).to_vec();
+        assert_eq!(outdated(&ids, 2, None), ["200", "100"]);
+        assert_eq!(outdated(&ids, 2, Some("100")), ["200"]);
+        assert_eq!(outdated(&ids, 0, Some("400")), ["300", "200", "100"]);
+        assert!(outdated(&ids, 5, None).is_empty());
+    }
+
+    #[test]
+    fn test_changes() {
+        let seen = Seen {
+            version: "v25.12.18".to_string(),
+            commands: vec![
+                "Applications / Alacritty".into(),
+                "Utilities / Bluetooth".into(),
+            ],
+        };
+        let current = [
+            "Applications / Alacritty".into(),
+            "Applications / Kitty".into(),
+        ];
+        let (added, removed) = seen.changes(&current);
+        assert_eq!(added, ["Applications / Kitty"]);
+        assert_eq!(removed, ["Utilities / Bluetooth"]);
+    }
+
+    #[test]
+    fn test_new_commands() {
+        assert_eq!(new_commands(&[tab(&["Paru"])], &[tab(&["Paru", "Yay"])]), 1);
+        assert_eq!(new_commands(&[tab(&["Paru", "Yay"])], &[tab(&["Paru"])]), 0);
+    }
+}
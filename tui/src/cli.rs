@@ -8,10 +8,17 @@ pub struct Args {
     #[arg(short, long)]
     pub config: Option<PathBuf>,
 
-    /// Set the theme to use in the application
+    /// Path to a playbook to run, see the README for its format
+    #[arg(long)]
+    pub playbook: Option<PathBuf>,
+
+    /// Run the playbook in the terminal without opening a window
+    #[arg(long, requires = "playbook")]
+    pub headless: bool,
+
+    /// Set the theme to use in the application, overriding the settings file
     #[arg(short, long, value_enum)]
-    #[arg(default_value_t = Theme::Default)]
-    pub theme: Theme,
+    pub theme: Option<Theme>,
 
     /// Skip confirmation prompt before executing commands
     #[arg(short = 'y', long)]
@@ -32,4 +39,12 @@ pub struct Args {
     /// Bypass root user check
     #[arg(short = 'r', long)]
     pub bypass_root: bool,
+
+    /// Hide the tip below the command list, overriding the settings file
+    #[arg(long)]
+    pub hide_tips: bool,
+
+    /// Record timings of list updates and output rendering and save them as a report on exit
+    #[arg(long)]
+    pub trace_perf: bool,
 }
@@ -0,0 +1,112 @@
+use crate::{
+    history,
+    system_info::{format_uptime, format_usage, SystemInfo},
+};
+use gtk::{glib, prelude::*};
+use gtk4 as gtk;
+
+// Quick links shown at most
+const MOST_USED_LINKS: usize = 6;
+
+/// Home view shown before a tab is picked: an overview of the system and links to the
+/// commands run most often, which go to the command through `win.show-command`.
+pub struct Dashboard {
+    pub widget: gtk::Box,
+    facts: gtk::Grid,
+    links: gtk::Box,
+}
+
+impl Dashboard {
+    pub fn new() -> Self {
+        let widget = gtk::Box::new(gtk::Orientation::Vertical, 12);
+        widget.set_vexpand(true);
+        widget.update_property(&[
+            gtk::accessible::Property::Label("Home"),
+            gtk::accessible::Property::Description(
+                "Overview of the system and the commands run most often.",
+            ),
+        ]);
+
+        let title = gtk::Label::new(Some("System overview"));
+        title.set_xalign(0.0);
+        title.add_css_class("title-2");
+        let facts = gtk::Grid::new();
+        facts.set_row_spacing(6);
+        facts.set_column_spacing(24);
+        facts.update_property(&[gtk::accessible::Property::Label("System overview")]);
+
+        let links_title = gtk::Label::new(Some("Most used commands"));
+        links_title.set_xalign(0.0);
+        links_title.add_css_class("title-4");
+        let links = gtk::Box::new(gtk::Orientation::Vertical, 4);
+        links.set_halign(gtk::Align::Start);
+        links.update_property(&[gtk::accessible::Property::Label("Most used commands")]);
+
+        widget.append(&title);
+        widget.append(&facts);
+        widget.append(&links_title);
+        widget.append(&links);
+
+        let dashboard = Self {
+            widget,
+            facts,
+            links,
+        };
+        dashboard.refresh();
+        dashboard
+    }
+
+    /// Reads the system facts and run history again.
+    pub fn refresh(&self) {
+        while let Some(child) = self.facts.first_child() {
+            self.facts.remove(&child);
+        }
+        let info = SystemInfo::read();
+        let facts = [
+            ("Hostname", info.hostname),
+            ("Distribution", info.distro),
+            ("Kernel", info.kernel),
+            ("Uptime", info.uptime.map(format_uptime)),
+            ("Memory", info.memory.as_ref().map(format_usage)),
+            ("Disk (/)", info.disk.as_ref().map(format_usage)),
+        ];
+        for (row, (name, value)) in facts.into_iter().enumerate() {
+            let value = value.unwrap_or_else(|| "Unknown".to_string());
+            let name_label = gtk::Label::new(Some(name));
+            name_label.set_xalign(0.0);
+            name_label.add_css_class("dim-label");
+            let value_label = gtk::Label::new(Some(&value));
+            value_label.set_xalign(0.0);
+            value_label.set_selectable(true);
+            value_label.update_property(&[gtk::accessible::Property::Label(&format!(
+                "{name}: {value}"
+            ))]);
+            self.facts.attach(&name_label, 0, row as i32, 1, 1);
+            self.facts.attach(&value_label, 1, row as i32, 1, 1);
+        }
+
+        while let Some(child) = self.links.first_child() {
+            self.links.remove(&child);
+        }
+        let names = history::most_used(MOST_USED_LINKS);
+        if names.is_empty() {
+            let empty = gtk::Label::new(Some("Commands you run show up here."));
+            empty.set_xalign(0.0);
+            empty.add_css_class("dim-label");
+            self.links.append(&empty);
+        }
+        for name in names {
+            let runs = history::runs(&name);
+            let link = gtk::Button::with_label(&name);
+            link.add_css_class("flat");
+            link.set_halign(gtk::Align::Start);
+            link.set_action_name(Some("win.show-command"));
+            link.set_action_target_value(Some(&glib::Variant::from(name.as_str())));
+            let times = if runs == 1 { "time" } else { "times" };
+            link.update_property(&[gtk::accessible::Property::Description(&format!(
+                "Go to {name} in the command list. It ran {runs} {times}."
+            ))]);
+            self.links.append(&link);
+        }
+    }
+}
@@ -0,0 +1,227 @@
+// Metadata of a command shown below its description: where its script is, how large and how
+// recent it is, whether it asks for root, whether it can be part of a multi-selection, whether
+// it downloads, which package managers it calls, which desktop or display server it is for
+// and which other files it uses.
+
+use crate::{
+    logs, network,
+    platform::{self, Platform},
+};
+use linutil_core::{Command, ListNode};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+// Privileged actions of the `task_list` legend: disk, installation, kernel and systemd
+// changes. Codes starting with `P`, e.g. `PFM`, are privileged as well.
+const PRIVILEGED_TASKS: [&str; 4] = ["D", "I", "K", "SS"];
+
+// Ways scripts gain root; `$ESCALATION_TOOL` is set by `common-script.sh`
+const ESCALATION_MARKERS: [&str; 4] = ["$ESCALATION_TOOL", "sudo ", "doas ", "pkexec "];
+
+/// Lines describing `node`, e.g. its script path, size and modification date followed by
+/// whether it needs root and supports multi-select.
+pub fn command_details(node: &ListNode) -> String {
+    let mut lines = Vec::new();
+    let source = match &node.command {
+        Command::LocalFile { file, .. } => {
+            let mut line = format!("Script: {}", file.display());
+            if let Ok(metadata) = fs::metadata(file) {
+                line.push_str(&format!(" ({}", logs::format_size(metadata.len())));
+                if let Ok(modified) = metadata.modified() {
+                    line.push_str(&format!(", modified {}", logs::format_modified(modified)));
+                }
+                line.push(')');
+            }
+            lines.push(line);
+            fs::read_to_string(file).unwrap_or_default()
+        }
+        Command::Raw(command) => {
+            lines.push(format!("Command: {command}"));
+            command.clone()
+        }
+        Command::None => String::new(),
+    };
+    lines.push(format!(
+        "Needs root: {} · Multi-select: {}",
+        yes_no(needs_root(&node.task_list, &source)),
+        yes_no(node.multi_select)
+    ));
+    if network::needs_network(node) {
+        lines.push("Needs network access".to_string());
+    }
+    let managers = platform::package_managers_in(&source);
+    if !managers.is_empty() {
+        let mut line = format!("Package managers: {}", managers.join(", "));
+        if !Platform::current().fits(&source) {
+            line.push_str(" · Not for this system");
+        }
+        lines.push(line);
+    }
+    let desktops = platform::desktops_of(node);
+    if !desktops.is_empty() {
+        let mut line = format!("Desktop: {}", desktops.join(", "));
+        if !platform::fits_desktop(node) {
+            line.push_str(" · Not this desktop");
+        }
+        lines.push(line);
+    }
+    if let Some(session) = platform::session_of_node(node) {
+        let mut line = format!("Session: {session} only");
+        if platform::unsupported_session(node).is_some() {
+            line.push_str(" · Not this session");
+        }
+        lines.push(line);
+    }
+    lines.join("\n")
+}
+
+/// Files the script of `node` uses besides itself: scripts it sources, e.g.
+/// `../common-script.sh`, followed by data files next to it, e.g. a unit file it installs.
+pub fn related_files(node: &ListNode) -> Vec<PathBuf> {
+    let Command::LocalFile { file, .. } = &node.command else {
+        return Vec::new();
+    };
+    let Some(dir) = file.parent() else {
+        return Vec::new();
+    };
+    let source = fs::read_to_string(file).unwrap_or_default();
+    let mut files: Vec<PathBuf> = source
+        .lines()
+        .filter_map(sourced_path)
+        .map(|path| dir.join(path))
+        .filter(|path| path.is_file())
+        .collect();
+    if let Ok(entries) = fs::read_dir(dir) {
+        let mut data_files: Vec<PathBuf> = entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file() && is_data_file(path))
+            .collect();
+        data_files.sort();
+        files.extend(data_files);
+    }
+    files
+}
+
+/// Relative path of the file a line like `. ../common-script.sh` sources.
+fn sourced_path(line: &str) -> Option<&str> {
+    let line = line.trim();
+    let path = line
+        .strip_prefix(". ")
+        .or_else(|| line.strip_prefix("source "))?
+        .trim();
+    // Paths built from variables are only known when the script runs
+    let literal = !path.is_empty() && !path.contains(['$', ' ', ';']);
+    literal.then_some(path)
+}
+
+/// Whether `path` is data next to a script rather than another command or the tab layout.
+fn is_data_file(path: &Path) -> bool {
+    path.extension().is_none_or(|ext| ext != "sh")
+        && path.file_name().is_some_and(|name| name != "tab_data.toml")
+}
+
+/// Whether a command with the `task_list` codes and script `source` runs anything as root.
+fn needs_root(task_list: &str, source: &str) -> bool {
+    task_list
+        .split_whitespace()
+        .any(|task| PRIVILEGED_TASKS.contains(&task) || task.starts_with('P'))
+        || ESCALATION_MARKERS
+            .iter()
+            .any(|marker| source.contains(marker))
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value {
+        "yes"
+    } else {
+        "no"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_needs_root() {
+        assert!(needs_root("I FM", ""));
+        assert!(needs_root("PFM", ""));
+        assert!(needs_root(
+            "",
+            "#!/bin/sh\n\"$ESCALATION_TOOL\" pacman -Syu\n"
+        ));
+        assert!(!needs_root("FM FI", "flatpak install --user foo"));
+        assert!(!needs_root("DE setup", "echo done"));
+    }
+
+    #[test]
+    fn test_related_files() {
+        let dir = std::env::temp_dir().join(format!("linutil-related-test-{}", std::process::id()));
+        let command_dir = dir.join("services");
+        fs::create_dir_all(&command_dir).unwrap();
+        fs::write(dir.join("common-script.sh"), "").unwrap();
+        let file = command_dir.join("lock.sh");
+        fs::write(
+            &file,
+            "#!/bin/sh -e\n. ../common-script.sh\n. \"$DIR/other.sh\"\n",
+        )
+        .unwrap();
+        fs::write(command_dir.join("lock.service"), "").unwrap();
+        fs::write(command_dir.join("other.sh"), "").unwrap();
+        fs::write(command_dir.join("tab_data.toml"), "").unwrap();
+        let node = ListNode {
+            name: "Lock".to_string(),
+            description: String::new(),
+            command: Command::LocalFile {
+                executable: "sh".to_string(),
+                args: vec![file.to_string_lossy().into_owned()],
+                file,
+            },
+            task_list: String::new(),
+            multi_select: true,
+            tags: Vec::new(),
+        };
+
+        let files = related_files(&node);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            files,
+            [
+                command_dir.join("../common-script.sh"),
+                command_dir.join("lock.service"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_command_details() {
+        let dir = std::env::temp_dir().join(format!("linutil-details-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("setup.sh");
+        fs::write(&file, "#!/bin/sh\nsudo true\n").unwrap();
+        let node = ListNode {
+            name: "Setup".to_string(),
+            description: String::new(),
+            command: Command::LocalFile {
+                executable: "sh".to_string(),
+                args: vec![file.to_string_lossy().into_owned()],
+                file: file.clone(),
+            },
+            task_list: String::new(),
+            multi_select: false,
+            tags: Vec::new(),
+        };
+
+        let details = command_details(&node);
+        fs::remove_dir_all(&dir).unwrap();
+
+        let mut lines = details.lines();
+        let script = lines.next().unwrap();
+        assert!(script.starts_with(&format!("Script: {} (20 B, modified ", file.display())));
+        assert_eq!(lines.next(), Some("Needs root: yes · Multi-select: no"));
+    }
+}
@@ -0,0 +1,156 @@
+// Facts about this build and system for bug reports, shown in the About dialog and copied
+// from it as plain text.
+
+use crate::{
+    integrity::Manifest, logs, platform::Platform, settings::Settings, system_info::SystemInfo,
+};
+use gtk4 as gtk;
+use nix::unistd::Uid;
+use std::{env, path::Path};
+
+/// Commit the GUI was built from, or `unknown`.
+pub const COMMIT: &str = env!("LINUTIL_COMMIT");
+
+/// Lines of `Name: value` describing the build, where it was installed from and the system.
+/// `script_dir` is where the bundled scripts were extracted to.
+pub fn collect(script_dir: &Path) -> String {
+    let info = SystemInfo::read();
+    let platform = Platform::current();
+    let settings = Settings::load();
+    let fingerprint = Manifest::generate(script_dir)
+        .map(|manifest| manifest.fingerprint())
+        .unwrap_or_else(|err| format!("unreadable: {err}"));
+    let exe = env::current_exe().unwrap_or_default();
+    let unknown = || "unknown".to_string();
+    let fields = [
+        ("Linutil GUI", env!("CARGO_PKG_VERSION").to_string()),
+        ("Commit", COMMIT.to_string()),
+        (
+            "Scripts",
+            format!("linutil_core {} ({fingerprint})", linutil_core::VERSION),
+        ),
+        (
+            "Channel",
+            if env::var_os("APPIMAGE").is_some() {
+                "AppImage".to_string()
+            } else {
+                install_channel(&exe).to_string()
+            },
+        ),
+        (
+            "GTK",
+            format!(
+                "{}.{}.{}",
+                gtk::major_version(),
+                gtk::minor_version(),
+                gtk::micro_version()
+            ),
+        ),
+        ("Distribution", info.distro.unwrap_or_else(unknown)),
+        ("Family", platform.family.clone().unwrap_or_else(unknown)),
+        ("Kernel", info.kernel.unwrap_or_else(unknown)),
+        (
+            "Architecture",
+            platform.architecture.map_or_else(unknown, str::to_string),
+        ),
+        ("WSL", if platform.wsl { "yes" } else { "no" }.to_string()),
+        (
+            "Container",
+            platform
+                .container
+                .map_or("none", |container| container.name())
+                .to_string(),
+        ),
+        (
+            "Image-based",
+            platform
+                .immutable
+                .map_or("no", |immutable| immutable.name())
+                .to_string(),
+        ),
+        (
+            "Desktop",
+            env::var("XDG_CURRENT_DESKTOP").unwrap_or_else(|_| unknown()),
+        ),
+        (
+            "Session",
+            env::var("XDG_SESSION_TYPE").unwrap_or_else(|_| unknown()),
+        ),
+        (
+            "Display server",
+            platform.display_server.map_or_else(unknown, str::to_string),
+        ),
+        (
+            "Init system",
+            platform.init_system.map_or_else(unknown, str::to_string),
+        ),
+        (
+            "Root",
+            if Uid::effective().is_root() {
+                "yes"
+            } else {
+                "no"
+            }
+            .to_string(),
+        ),
+        (
+            "Package manager",
+            platform.package_manager.unwrap_or("none found").to_string(),
+        ),
+        (
+            "Integrity policy",
+            format!("{:?}", settings.integrity.policy).to_lowercase(),
+        ),
+        ("Settings", Settings::path().display().to_string()),
+        ("Logs", logs::log_dir().display().to_string()),
+    ];
+    format_fields(&fields)
+}
+
+fn format_fields(fields: &[(&str, String)]) -> String {
+    fields
+        .iter()
+        .map(|(name, value)| format!("{name}: {value}\n"))
+        .collect()
+}
+
+/// How the running binary was installed, guessed from its location.
+fn install_channel(exe: &Path) -> &'static str {
+    let path = exe.to_string_lossy();
+    if path.starts_with("/nix/store/") {
+        "Nix"
+    } else if path.contains("/.cargo/bin/") {
+        "cargo install"
+    } else if path.contains("/target/") {
+        "development build"
+    } else if path.starts_with("/usr/") || path.starts_with("/bin/") {
+        "distribution package"
+    } else {
+        "manual install"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_install_channel() {
+        for (exe, channel) in [
+            ("/usr/bin/linutil", "distribution package"),
+            ("/home/me/.cargo/bin/linutil", "cargo install"),
+            ("/nix/store/abc-linutil/bin/linutil", "Nix"),
+            (
+                "/home/me/linutil/target/release/linutil",
+                "development build",
+            ),
+            ("/opt/linutil/linutil", "manual install"),
+        ] {
+            assert_eq!(install_channel(Path::new(exe)), channel, "{exe}");
+        }
+        assert_eq!(
+            format_fields(&[("Commit", "abc".to_string()), ("Root", "no".to_string())]),
+            "Commit: abc\nRoot: no\n"
+        );
+    }
+}
@@ -0,0 +1,182 @@
+// Marks commands of the catalog that are new, or whose script changed, since the scripts
+// Linutil last ran with, e.g. after Refresh Scripts, so additions get noticed. A fingerprint
+// of each script is kept in the state directory, and a mark goes away once the command was
+// looked at.
+
+use crate::{
+    integrity::{hex, sha256},
+    settings::state_dir,
+};
+use gtk::glib;
+use gtk4 as gtk;
+use linutil_core::{Command, ListNode, TabList};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, io, path::Path, path::PathBuf};
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Mark {
+    New,
+    Updated,
+}
+
+impl Mark {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::New => "NEW",
+            Self::Updated => "UPDATED",
+        }
+    }
+
+    /// The mark as a pill in Pango markup, like the tags next to it.
+    pub fn pill_markup(self) -> String {
+        let background = match self {
+            Self::New => "#2e7d32",
+            Self::Updated => "#1565c0",
+        };
+        format!(
+            "<span size=\"small\" weight=\"bold\" foreground=\"#ffffff\" \
+             background=\"{background}\"> {} </span>",
+            glib::markup_escape_text(self.label())
+        )
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct FreshCommands {
+    /// Fingerprint of the script of each command, by its path in the tabs directory.
+    known: BTreeMap<String, String>,
+    /// Marked commands not looked at yet, by the same paths.
+    marks: BTreeMap<String, Mark>,
+}
+
+impl FreshCommands {
+    fn path() -> PathBuf {
+        state_dir().join("fresh-commands.toml")
+    }
+
+    pub fn load() -> Self {
+        let Ok(content) = fs::read_to_string(Self::path()) else {
+            return Self::default();
+        };
+        toml::from_str(&content).unwrap_or_else(|err| {
+            eprintln!("Failed to parse {}: {err}", Self::path().display());
+            Self::default()
+        })
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let content =
+            toml::to_string(self).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        fs::create_dir_all(state_dir())?;
+        fs::write(Self::path(), content)
+    }
+
+    /// Marks the commands of `current`, fingerprints by path, that were not known or whose
+    /// fingerprint changed, and remembers `current`. Nothing is marked the first time, when no
+    /// commands are known yet. Returns whether anything changed.
+    pub fn update(&mut self, current: BTreeMap<String, String>) -> bool {
+        if current == self.known {
+            return false;
+        }
+        if !self.known.is_empty() {
+            for (path, fingerprint) in &current {
+                match self.known.get(path) {
+                    None => {
+                        self.marks.insert(path.clone(), Mark::New);
+                    }
+                    Some(known) if known != fingerprint => {
+                        self.marks.entry(path.clone()).or_insert(Mark::Updated);
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+        self.marks.retain(|path, _| current.contains_key(path));
+        self.known = current;
+        true
+    }
+
+    /// The mark of `node`, a command of the catalog in `script_dir`.
+    pub fn mark(&self, node: &ListNode, script_dir: &Path) -> Option<Mark> {
+        self.marks.get(&key(node, script_dir)?).copied()
+    }
+
+    /// Takes the mark off `node` after it was looked at, returning whether it had one.
+    pub fn clear(&mut self, node: &ListNode, script_dir: &Path) -> bool {
+        key(node, script_dir).is_some_and(|key| self.marks.remove(&key).is_some())
+    }
+}
+
+/// Fingerprints of the scripts of the catalog's commands in `tabs`, by their path in the tabs
+/// directory. Commands of plugins and the user's own scripts are left out.
+pub fn fingerprints(tabs: &TabList) -> BTreeMap<String, String> {
+    let mut fingerprints = BTreeMap::new();
+    for tab in tabs.iter() {
+        for node in tab.tree.root().descendants() {
+            let (Some(key), Command::LocalFile { file, .. }) =
+                (key(node.value(), tabs.script_dir()), &node.value().command)
+            else {
+                continue;
+            };
+            if let Ok(content) = fs::read(file) {
+                fingerprints.insert(key, hex(&sha256(&content))[..16].to_string());
+            }
+        }
+    }
+    fingerprints
+}
+
+/// Path of the script of `node` in `script_dir`, which stays the same across downloads.
+fn key(node: &ListNode, script_dir: &Path) -> Option<String> {
+    let Command::LocalFile { file, .. } = &node.command else {
+        return None;
+    };
+    let relative = file.strip_prefix(script_dir).ok()?;
+    Some(relative.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fingerprints(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+        entries
+            .iter()
+            .map(|(path, fingerprint)| (path.to_string(), fingerprint.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_update() {
+        let mut fresh = FreshCommands::default();
+        assert!(fresh.update(fingerprints(&[
+            ("arch/paru.sh", "a1"),
+            ("arch/yay.sh", "b1")
+        ])));
+        assert!(fresh.marks.is_empty());
+
+        assert!(!fresh.update(fingerprints(&[
+            ("arch/paru.sh", "a1"),
+            ("arch/yay.sh", "b1")
+        ])));
+        fresh.update(fingerprints(&[
+            ("arch/paru.sh", "a2"),
+            ("arch/yay.sh", "b1"),
+            ("fedora/rpm-fusion.sh", "c1"),
+        ]));
+        assert_eq!(fresh.marks.get("arch/paru.sh"), Some(&Mark::Updated));
+        assert_eq!(fresh.marks.get("arch/yay.sh"), None);
+        assert_eq!(fresh.marks.get("fedora/rpm-fusion.sh"), Some(&Mark::New));
+
+        // A new command changed again is still new, and removed ones lose their mark
+        fresh.update(fingerprints(&[
+            ("arch/paru.sh", "a2"),
+            ("fedora/rpm-fusion.sh", "c2"),
+        ]));
+        assert_eq!(fresh.marks.get("fedora/rpm-fusion.sh"), Some(&Mark::New));
+        fresh.update(fingerprints(&[("arch/paru.sh", "a2")]));
+        assert_eq!(fresh.marks.len(), 1);
+    }
+}
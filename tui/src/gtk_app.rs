@@ -1,60 +1,137 @@
+use crate::about_dialog::open_about_dialog;
+use crate::bundles;
+use crate::catalog::{self, FetchError};
 use crate::cli::Args;
+use crate::dashboard::Dashboard;
+use crate::details;
+use crate::diagnostics;
+use crate::fresh::{self, FreshCommands};
+use crate::health_view::HealthView;
+use crate::history;
+use crate::integrity;
+use crate::jobs;
+use crate::log_viewer::open_log_viewer;
+use crate::logs;
+use crate::network;
+use crate::packages;
+use crate::perf;
+use crate::pins::Pins;
+use crate::platform::{self, Container, Platform};
+use crate::playbook::Playbook;
+use crate::plugins;
+use crate::power;
+use crate::presets::{self, Preset};
+use crate::prompts::Prompt;
+use crate::recording;
+use crate::report::{self, ReportFormat};
+use crate::runner::{self, CommandRecord, CommandRunner, CommandStatus, QueuedCommand, RunEvent};
+use crate::script_view::open_script_view;
+use crate::search_index::{self, SearchIndex};
+use crate::self_update::{self, InstallMethod, Release};
+use crate::settings::{
+    CommandLayout, FailurePolicy, IntegrityPolicy, OutputScroll, PerformanceSettings, Settings,
+    WindowGeometry, WindowSettings,
+};
+use crate::sources_window::open_sources_window;
+use crate::stats_window::open_statistics_window;
+use crate::tags::{self, UserTags};
+use crate::terminal_input;
 use crate::theme::Theme;
 #[cfg(feature = "tips")]
 use crate::tips;
-use gtk4 as gtk;
+#[cfg(feature = "tips")]
+use crate::tips_window::open_tips_window;
+use crate::toast::{show_toast, toast_overlay};
+use crate::updates;
+use crate::user_scripts;
+use crate::versions_window::open_versions_window;
+use crate::watch;
+use crate::welcome::open_welcome_wizard;
+use gtk::gio;
+use gtk::glib::{self, BoxedAnyObject, ControlFlow, Propagation};
 use gtk::prelude::*;
-use gtk::glib::source::timeout_add_local;
-use gtk::glib::{ControlFlow, Propagation};
+use gtk4 as gtk;
 use linutil_core::{Command, Config, ListNode, TabList};
 #[cfg(unix)]
 use nix::unistd::Uid;
-use portable_pty::{ChildKiller, CommandBuilder, MasterPty, NativePtySystem, PtySize, PtySystem};
 use std::{
-    cell::RefCell,
-    io::{Read, Write},
+    cell::{Cell, RefCell},
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
     rc::Rc,
-    sync::{Arc, Mutex},
-    thread,
-    time::Duration,
+    time::{Duration, SystemTime},
 };
-use time::{macros::format_description, OffsetDateTime};
 
 const APP_ID: &str = "com.christitustech.linutil";
 const ROOT_WARNING: &str = "WARNING: You are running this utility as root!\n\
 This means you have full system access and commands can potentially damage your system if used incorrectly.\n\
 Please proceed with caution and make sure you understand what each script does before executing it.";
+// WSL distributions often log in as root until a default user is set
+const WSL_ROOT_HINT: &str =
+    "\n\nWSL logs in as root unless a default user is set. Create one with \
+adduser and set it as default under [user] in /etc/wsl.conf.";
 
 struct AppState {
     tabs: TabList,
+    /// Tabs replaced by Refresh Scripts, kept until exit for the commands still using their
+    /// scripts.
+    retired_tabs: Vec<TabList>,
+    /// When the scripts in use were downloaded, `None` for the ones Linutil was built with.
+    catalog_from: Option<SystemTime>,
+    /// Set when the user chose to use scripts without a valid signature, for the next
+    /// Refresh Scripts only.
+    allow_unsigned_scripts: bool,
     theme: Theme,
     current_tab: usize,
     visit_stack: Vec<linutil_core::ego_tree::NodeId>,
+    /// Folders left with Back, most recent last, which Forward enters again.
+    forward_stack: Vec<linutil_core::ego_tree::NodeId>,
     filter: String,
-    entries: Vec<ListEntry>,
+    /// Entries of the current view, shared with the items of the list model.
+    entries: Vec<Rc<ListEntry>>,
+    search_index: SearchIndex,
+    /// Query the `search_matches` were computed for, lowercase.
+    search_query: String,
+    /// Ids in `search_index` matching `search_query`.
+    search_matches: Vec<usize>,
     multi_select: bool,
+    /// Commands selected in multi-select mode, in the order they were picked. They stay
+    /// selected while navigating, so one batch can span several categories.
+    selected_commands: Vec<Rc<ListNode>>,
+    /// Set while `refresh_list` replaces the rows, whose selection changes then say nothing
+    /// about what the user picked.
+    restoring_selection: bool,
     skip_confirmation: bool,
     _size_bypass: bool,
     pending_auto_execute: Vec<Rc<ListNode>>,
+    user_tags: UserTags,
+    /// Whether commands are listed by how often they ran instead of in tab order.
+    most_used_first: bool,
+    /// Whether commands calling only package managers this system lacks are hidden.
+    this_system_only: bool,
+    /// Whether commands written for other desktops are listed, see [`platform::fits_desktop`].
+    all_desktops: bool,
+    pins: Pins,
+    /// Commands marked as new or updated since the scripts Linutil last ran with.
+    fresh: FreshCommands,
 }
 
-#[derive(Clone)]
 struct ListEntry {
     node_id: Option<linutil_core::ego_tree::NodeId>,
     node: Option<Rc<ListNode>>,
     has_children: bool,
     is_up_dir: bool,
-}
-
-struct CommandRunner {
-    output: Arc<Mutex<String>>,
-    writer: Arc<Mutex<Box<dyn Write + Send>>>,
-    child_killer: Arc<Mutex<Option<Box<dyn ChildKiller + Send + Sync>>>>,
-    finished: Arc<Mutex<Option<bool>>>,
-    _pty_master: Box<dyn MasterPty + Send>,
+    /// Location of the command, shown for search results.
+    breadcrumb: Option<String>,
 }
 
 pub fn run(args: Args) -> Result<(), Box<dyn std::error::Error>> {
+    logs::apply_retention(&Settings::load().logging);
+
+    if args.trace_perf {
+        perf::enable();
+    }
+
     let app = gtk::Application::builder().application_id(APP_ID).build();
     let args = Rc::new(args);
 
@@ -63,14 +140,49 @@ pub fn run(args: Args) -> Result<(), Box<dyn std::error::Error>> {
     });
 
     app.run();
+    match perf::save_report() {
+        Some(Ok(path)) => eprintln!("Performance trace saved to {}", path.display()),
+        Some(Err(err)) => eprintln!("Failed to save performance trace: {err}"),
+        None => {}
+    }
     Ok(())
 }
 
 fn build_ui(app: &gtk::Application, args: Rc<Args>) {
-    let tabs = linutil_core::get_tabs(!args.override_validation);
+    // Only the first tab is loaded before the window shows up, the others are added while
+    // idle. A config file or playbook may name commands from any tab, so then all of them
+    // are loaded.
+    // A pinned download is loaded instead of the built-in scripts, and so is the last one
+    // when the scripts are downloaded at startup, so the list is there right away and offline
+    let cached = catalog::to_start_from(&Settings::load().updates).and_then(|snapshot| {
+        linutil_core::load_tabs_from(&snapshot.dir, !args.override_validation)
+            .inspect_err(|err| eprintln!("Failed to load the downloaded scripts: {err}"))
+            .ok()
+            .map(|tabs| (tabs, snapshot.downloaded))
+    });
+    let catalog_from = cached.as_ref().map(|(_, downloaded)| *downloaded);
+    let (tabs, mut tab_loader) = perf::measure("get_tabs", || {
+        if let Some((tabs, _)) = cached {
+            return (tabs, None);
+        }
+        let (mut tabs, mut tab_loader) = linutil_core::load_tabs(!args.override_validation);
+        if args.config.is_some() || args.playbook.is_some() {
+            tabs.extend(tab_loader.by_ref());
+        } else {
+            tabs.extend(tab_loader.next());
+        }
+        (tabs, Some(tab_loader))
+    });
+    if tabs.is_empty() {
+        panic!("No tabs found");
+    }
+    // Without a settings file the welcome wizard asks for them
+    let first_run = !Settings::path().exists();
+    let settings = Settings::load();
+    integrity::init(tabs.script_dir(), &settings.integrity);
     let root_id = tabs[0].tree.root().id();
 
-    let mut skip_confirmation = args.skip_confirmation;
+    let mut skip_confirmation = args.skip_confirmation || settings.runs.skip_confirmation;
     let mut size_bypass = args.size_bypass;
     let mut pending_auto_execute = Vec::new();
 
@@ -81,25 +193,47 @@ fn build_ui(app: &gtk::Application, args: Rc<Args>) {
         pending_auto_execute = config.auto_execute_commands;
     }
 
+    let search_index = SearchIndex::build(&tabs);
     let state = Rc::new(RefCell::new(AppState {
         tabs,
-        theme: args.theme,
+        retired_tabs: Vec::new(),
+        catalog_from,
+        allow_unsigned_scripts: false,
+        theme: args.theme.unwrap_or(settings.view.theme),
         current_tab: 0,
         visit_stack: vec![root_id],
+        forward_stack: Vec::new(),
         filter: String::new(),
         entries: Vec::new(),
+        search_index,
+        search_query: String::new(),
+        search_matches: Vec::new(),
         multi_select: false,
+        selected_commands: Vec::new(),
+        restoring_selection: false,
         skip_confirmation,
         _size_bypass: size_bypass,
         pending_auto_execute,
+        user_tags: UserTags::load(),
+        most_used_first: false,
+        this_system_only: false,
+        all_desktops: Settings::load().view.all_desktops,
+        pins: Pins::load(),
+        fresh: FreshCommands::load(),
     }));
 
     let window = gtk::ApplicationWindow::builder()
         .application(app)
-        .title(&window_title())
-        .default_width(1100)
-        .default_height(720)
+        .title(window_title(&state.borrow().tabs))
         .build();
+    confirm_close_while_running(
+        &window,
+        "Commands are still running. Stop them and quit Linutil, or keep them running in the \
+         background until they are done. Their logs are saved either way.",
+        || jobs::running() > 0,
+        jobs::stop_all,
+    );
+    remember_geometry(&window, &settings.windows.main, |windows| &mut windows.main);
 
     let root_box = gtk::Box::new(gtk::Orientation::Vertical, 8);
     root_box.set_margin_top(12);
@@ -115,6 +249,13 @@ fn build_ui(app: &gtk::Application, args: Rc<Args>) {
             "Go back to the previous view or clear the current search.",
         ),
     ]);
+    // Activates `win.go-forward`, which is enabled after going back
+    let forward_button = gtk::Button::with_label("Forward");
+    forward_button.set_action_name(Some("win.go-forward"));
+    forward_button.update_property(&[
+        gtk::accessible::Property::Label("Forward"),
+        gtk::accessible::Property::Description("Enter the folder left with Back again."),
+    ]);
     let multi_select_toggle = gtk::ToggleButton::with_label("Multi-select");
     multi_select_toggle.update_property(&[
         gtk::accessible::Property::Label("Multi-select"),
@@ -125,19 +266,178 @@ fn build_ui(app: &gtk::Application, args: Rc<Args>) {
     search_entry.set_placeholder_text(Some("Search commands"));
     search_entry.update_property(&[
         gtk::accessible::Property::Label("Search commands"),
-        gtk::accessible::Property::Description("Type to filter commands by name."),
+        gtk::accessible::Property::Description(
+            "Type to filter commands by name or description, or by tag with tag:<name>.",
+        ),
         gtk::accessible::Property::Placeholder("Search commands"),
     ]);
+    let sort_dropdown = gtk::DropDown::from_strings(&["Default order", "Most used"]);
+    sort_dropdown.update_property(&[
+        gtk::accessible::Property::Label("Sort order"),
+        gtk::accessible::Property::Description(
+            "List commands in tab order or the most often run first.",
+        ),
+    ]);
+    let systems_dropdown = gtk::DropDown::from_strings(&["All systems", "This system"]);
+    systems_dropdown.update_property(&[
+        gtk::accessible::Property::Label("Systems"),
+        gtk::accessible::Property::Description(
+            "List every command or hide those written for other package managers.",
+        ),
+    ]);
+    let grid_toggle = gtk::ToggleButton::with_label("Grid");
+    grid_toggle.set_active(Settings::load().view.layout == CommandLayout::Grid);
+    grid_toggle.update_property(&[
+        gtk::accessible::Property::Label("Grid view"),
+        gtk::accessible::Property::Description(
+            "Show commands as large tiles instead of a list, e.g. for touch screens.",
+        ),
+    ]);
     let run_button = gtk::Button::with_label("Run");
     run_button.set_sensitive(false);
     run_button.update_property(&[
         gtk::accessible::Property::Label("Run"),
         gtk::accessible::Property::Description("Run the selected command(s)."),
     ]);
+    let view_script_button = gtk::Button::with_label("View script");
+    view_script_button.update_property(&[
+        gtk::accessible::Property::Label("View script"),
+        gtk::accessible::Property::Description(
+            "Read the script of the selected command without running it (Ctrl+U).",
+        ),
+    ]);
+    let logs_button = gtk::Button::with_label("Logs");
+    logs_button.update_property(&[
+        gtk::accessible::Property::Label("Logs"),
+        gtk::accessible::Property::Description("Browse, search and delete saved command logs."),
+    ]);
+    let selection_box = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+    selection_box.set_visible(false);
+    let select_all_button = gtk::Button::with_label("Select all");
+    select_all_button.update_property(&[
+        gtk::accessible::Property::Label("Select all"),
+        gtk::accessible::Property::Description("Select every command in the list."),
+    ]);
+    let select_none_button = gtk::Button::with_label("Select none");
+    select_none_button.update_property(&[
+        gtk::accessible::Property::Label("Select none"),
+        gtk::accessible::Property::Description("Clear the selection."),
+    ]);
+    let invert_selection_button = gtk::Button::with_label("Invert");
+    invert_selection_button.update_property(&[
+        gtk::accessible::Property::Label("Invert selection"),
+        gtk::accessible::Property::Description(
+            "Select the commands that are not selected and unselect the others.",
+        ),
+    ]);
+    selection_box.append(&select_all_button);
+    selection_box.append(&select_none_button);
+    selection_box.append(&invert_selection_button);
     top_bar.append(&back_button);
+    top_bar.append(&forward_button);
     top_bar.append(&multi_select_toggle);
+    top_bar.append(&selection_box);
     top_bar.append(&search_entry);
+    top_bar.append(&sort_dropdown);
+    top_bar.append(&systems_dropdown);
+    top_bar.append(&grid_toggle);
     top_bar.append(&run_button);
+    top_bar.append(&view_script_button);
+    top_bar.append(&logs_button);
+    top_bar.append(&jobs::jobs_button());
+
+    // Shown while the package manager reports pending updates
+    let updates_banner = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+    updates_banner.set_visible(false);
+    updates_banner.set_accessible_role(gtk::AccessibleRole::Status);
+    let updates_label = gtk::Label::new(None);
+    updates_label.set_xalign(0.0);
+    updates_label.set_hexpand(true);
+    let update_button = gtk::Button::with_label("Update Now");
+    update_button.update_property(&[
+        gtk::accessible::Property::Label("Update now"),
+        gtk::accessible::Property::Description(&format!(
+            "Run {} to install the pending updates.",
+            updates::UPDATE_COMMAND
+        )),
+    ]);
+    updates_banner.append(&updates_label);
+    updates_banner.append(&update_button);
+
+    // Shown when a newer release of Linutil itself is out, if checking for one is turned on
+    let release_banner = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+    release_banner.set_visible(false);
+    release_banner.set_accessible_role(gtk::AccessibleRole::Status);
+    let release_label = gtk::Label::new(None);
+    release_label.set_xalign(0.0);
+    release_label.set_hexpand(true);
+    let release_notes_button = gtk::Button::with_label("What's New");
+    release_notes_button.update_property(&[
+        gtk::accessible::Property::Label("What's new"),
+        gtk::accessible::Property::Description("Show the changelog of the new release."),
+    ]);
+    let release_install_button = gtk::Button::with_label("Install");
+    release_install_button.update_property(&[
+        gtk::accessible::Property::Label("Install"),
+        gtk::accessible::Property::Description(
+            "Update Linutil with the package manager, or download the new release in place of \
+             this one.",
+        ),
+    ]);
+    let release_dismiss_button = gtk::Button::with_label("Dismiss");
+    release_dismiss_button.update_property(&[
+        gtk::accessible::Property::Label("Dismiss"),
+        gtk::accessible::Property::Description("Hide the release banner until the next start."),
+    ]);
+    release_banner.append(&release_label);
+    release_banner.append(&release_notes_button);
+    release_banner.append(&release_install_button);
+    release_banner.append(&release_dismiss_button);
+
+    // Entries activate window actions added further down
+    let main_menu = gio::Menu::new();
+    main_menu.append(Some("Statistics"), Some("win.show-statistics"));
+    main_menu.append(
+        Some("Open My Scripts Folder"),
+        Some("win.open-scripts-folder"),
+    );
+    #[cfg(feature = "tips")]
+    {
+        main_menu.append(Some("Tips"), Some("win.show-tips"));
+        main_menu.append(Some("Tip of the Day"), Some("win.tip-of-the-day"));
+        main_menu.append(Some("Show Tip Below List"), Some("win.show-tip-label"));
+    }
+    main_menu.append(Some("Show Output in Main Window"), Some("win.output-pane"));
+    main_menu.append(
+        Some("Prevent Suspend While Running"),
+        Some("win.inhibit-suspend"),
+    );
+    main_menu.append(
+        Some("Show Commands for All Desktops"),
+        Some("win.all-desktops"),
+    );
+    main_menu.append(
+        Some("Check for Linutil Updates"),
+        Some("win.check-releases"),
+    );
+    main_menu.append(Some("Refresh Scripts"), Some("win.refresh-scripts(false)"));
+    main_menu.append(Some("Script Sources…"), Some("win.show-sources"));
+    main_menu.append(Some("Script Versions…"), Some("win.show-versions"));
+    main_menu.append(Some("What's New in Scripts"), Some("win.whats-new-scripts"));
+    main_menu.append(Some("Import Bundle…"), Some("win.import-bundle"));
+    main_menu.append(Some("Run Playbook…"), Some("win.run-playbook"));
+    main_menu.append(Some("Record Playbook"), Some("win.record-playbook"));
+    main_menu.append(Some("Stop and Close All Jobs"), Some("win.stop-all-jobs"));
+    main_menu.append(Some("About Linutil"), Some("win.about"));
+    let menu_button = gtk::MenuButton::new();
+    menu_button.set_icon_name("open-menu-symbolic");
+    menu_button.set_menu_model(Some(&main_menu));
+    menu_button.set_visible(main_menu.n_items() > 0);
+    menu_button.update_property(&[
+        gtk::accessible::Property::Label("Main menu"),
+        gtk::accessible::Property::Description("Open the menu with more windows and options."),
+    ]);
+    top_bar.append(&menu_button);
 
     let content_box = gtk::Box::new(gtk::Orientation::Horizontal, 12);
     content_box.set_hexpand(true);
@@ -153,28 +453,32 @@ fn build_ui(app: &gtk::Application, args: Rc<Args>) {
     ]);
     let state_ref = state.borrow();
     for tab in state_ref.tabs.iter() {
-        let label = gtk::Label::new(Some(&format!(
-            "{} {}",
-            state_ref.theme.tab_icon(),
-            tab.name
-        )));
-        label.set_xalign(0.0);
-        let row = gtk::ListBoxRow::new();
-        row.update_property(&[gtk::accessible::Property::Label(&format!(
-            "Tab: {}",
-            tab.name
-        ))]);
-        row.set_child(Some(&label));
-        tab_list.append(&row);
+        append_tab_row(&tab_list, state_ref.theme, &tab.name);
     }
     drop(state_ref);
-    tab_list.select_row(tab_list.row_at_index(0).as_ref());
+    // Pages other than tabs are in a list of their own, so tab rows keep their index. Only
+    // one of the lists has a row selected at a time.
+    let page_list = gtk::ListBox::new();
+    page_list.set_selection_mode(gtk::SelectionMode::Single);
+    page_list.add_css_class("tab-list");
+    let home_row = page_row(
+        "Home",
+        "Show an overview of the system and the commands run most often.",
+    );
+    page_list.append(&home_row);
+    page_list.append(&page_row(
+        "Health",
+        "Check the system for problems and fix them.",
+    ));
+    let sidebar_box = gtk::Box::new(gtk::Orientation::Vertical, 4);
+    sidebar_box.append(&page_list);
+    sidebar_box.append(&tab_list);
 
     let tab_scroll = gtk::ScrolledWindow::new();
     tab_scroll.set_policy(gtk::PolicyType::Never, gtk::PolicyType::Automatic);
-    tab_scroll.set_min_content_width(240);
+    tab_scroll.set_min_content_width(120);
     tab_scroll.set_vexpand(true);
-    tab_scroll.set_child(Some(&tab_list));
+    tab_scroll.set_child(Some(&sidebar_box));
 
     let right_box = gtk::Box::new(gtk::Orientation::Vertical, 8);
     right_box.set_hexpand(true);
@@ -187,10 +491,132 @@ fn build_ui(app: &gtk::Application, args: Rc<Args>) {
         gtk::accessible::Property::Description("Shows the current category path."),
     ]);
 
-    let list_box = gtk::ListBox::new();
-    list_box.set_selection_mode(gtk::SelectionMode::Single);
-    list_box.set_focusable(true);
-    list_box.update_property(&[
+    // Rows are created by the factory only for visible entries and rebound as the list
+    // scrolls. Refreshing the list only replaces the items of `entry_store` that changed.
+    let entry_store = gio::ListStore::new::<BoxedAnyObject>();
+    let single_selection = gtk::SingleSelection::new(Some(entry_store.clone()));
+    single_selection.set_autoselect(false);
+    single_selection.set_can_unselect(true);
+    let multi_selection = gtk::MultiSelection::new(Some(entry_store.clone()));
+
+    let entry_factory = gtk::SignalListItemFactory::new();
+    // Items of the rows, whose tags and badges are updated when they change
+    let bound_rows: BoundRows = Rc::new(RefCell::new(Vec::new()));
+    let multi_selection_clone = multi_selection.clone();
+    let state_clone = state.clone();
+    let bound_rows_clone = bound_rows.clone();
+    entry_factory.connect_setup(move |_, item| {
+        let Some(item) = item.downcast_ref::<gtk::ListItem>() else {
+            return;
+        };
+        // Shown for commands in multi-select mode, where Ctrl+click is hard to discover. The
+        // check box mirrors the selection of the row.
+        let check = gtk::CheckButton::new();
+        check.set_visible(false);
+        item.bind_property("selected", &check, "active")
+            .sync_create()
+            .build();
+        // The check box belongs to the item, so holding a strong reference would leak both
+        let item_weak = item.downgrade();
+        let multi_selection = multi_selection_clone.clone();
+        check.connect_toggled(move |check| {
+            let Some(item) = item_weak.upgrade() else {
+                return;
+            };
+            if check.is_active() == item.is_selected() {
+                return;
+            }
+            let position = item.position();
+            if check.is_active() {
+                multi_selection.select_item(position, false);
+            } else {
+                multi_selection.unselect_item(position);
+            }
+        });
+        let label = gtk::Label::new(None);
+        label.set_xalign(0.0);
+        label.set_hexpand(true);
+        let tags = gtk::Label::new(None);
+        // Spins while the command runs, then the badge shows how it went
+        let spinner = gtk::Spinner::new();
+        spinner.set_visible(false);
+        spinner.update_property(&[gtk::accessible::Property::Label("Running")]);
+        // Outcome of the last run of a command
+        let badge = gtk::Label::new(None);
+        badge.add_css_class("dim-label");
+        bound_rows_clone.borrow_mut().push(item.downgrade());
+        let row = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+        row.append(&check);
+        row.append(&label);
+        row.append(&tags);
+        row.append(&spinner);
+        row.append(&badge);
+        // Right-click menu, filled in for the entry the row shows when it opens
+        let row_menu = gtk::PopoverMenu::from_model(None::<&gio::MenuModel>);
+        row_menu.set_has_arrow(false);
+        row_menu.set_parent(&row);
+        let click = gtk::GestureClick::new();
+        click.set_button(gtk::gdk::BUTTON_SECONDARY);
+        let item_weak = item.downgrade();
+        let state = state_clone.clone();
+        click.connect_pressed(move |_, _, x, y| {
+            let Some(item) = item_weak.upgrade() else {
+                return;
+            };
+            let Some(menu) = entry_menu(&state.borrow(), item.position()) else {
+                return;
+            };
+            row_menu.set_menu_model(Some(&menu));
+            row_menu.set_pointing_to(Some(&gtk::gdk::Rectangle::new(x as i32, y as i32, 1, 1)));
+            row_menu.popup();
+        });
+        row.add_controller(click);
+        item.set_child(Some(&row));
+    });
+    let state_clone = state.clone();
+    entry_factory.connect_bind(move |_, item| {
+        let Some(item) = item.downcast_ref::<gtk::ListItem>() else {
+            return;
+        };
+        let (Some(row), Some(entry)) = (
+            row_widgets(item),
+            item.item().and_downcast::<BoxedAnyObject>(),
+        ) else {
+            return;
+        };
+        let RowWidgets { check, label, .. } = &row;
+        let state = state_clone.borrow();
+        let entry = entry.borrow::<Rc<ListEntry>>();
+        label.set_text(&format_entry(state.theme, state.multi_select, &entry));
+        let is_command = !entry.is_up_dir && !entry.has_children;
+        check.set_visible(state.multi_select && is_command);
+        if let Some(node) = &entry.node {
+            check.update_property(&[gtk::accessible::Property::Label(&format!(
+                "Select {}",
+                node.name
+            ))]);
+        }
+        // The row itself stays sensitive so its menu still opens, e.g. to view the script
+        let reason = unavailable_reason(&state, &entry);
+        item.set_selectable(reason.is_none());
+        item.set_activatable(reason.is_none());
+        check.set_sensitive(reason.is_none());
+        label.set_sensitive(reason.is_none());
+        if let Some(row_box) = check.parent() {
+            row_box.set_tooltip_text(reason.as_deref());
+            row_box.update_property(&[gtk::accessible::Property::Description(
+                reason.as_deref().unwrap_or(""),
+            )]);
+        }
+        update_row_extras(&row, &entry, &state);
+        update_spinner(&row.spinner, &entry, &jobs::running_commands());
+    });
+    let bound_rows_clone = bound_rows.clone();
+    jobs::connect_changed(move || update_running_rows(&bound_rows_clone));
+
+    let list_view = gtk::ListView::new(Some(single_selection.clone()), Some(entry_factory));
+    list_view.set_focusable(true);
+    list_view.update_property(&[
         gtk::accessible::Property::Label("Command list"),
         gtk::accessible::Property::Description("Select a command to view details and run it."),
     ]);
@@ -198,7 +624,102 @@ fn build_ui(app: &gtk::Application, args: Rc<Args>) {
     list_scroll.set_policy(gtk::PolicyType::Automatic, gtk::PolicyType::Automatic);
     list_scroll.set_hexpand(true);
     list_scroll.set_vexpand(true);
-    list_scroll.set_child(Some(&list_box));
+    list_scroll.set_child(Some(&list_view));
+
+    // Alternative to the list for touch screens and TVs, showing the same items as large
+    // tiles. The tiles are toggle buttons mirroring the selection, like the check boxes of rows.
+    let command_grid = gtk::FlowBox::new();
+    command_grid.set_selection_mode(gtk::SelectionMode::None);
+    command_grid.set_homogeneous(true);
+    command_grid.set_valign(gtk::Align::Start);
+    command_grid.set_max_children_per_line(12);
+    command_grid.set_row_spacing(8);
+    command_grid.set_column_spacing(8);
+    command_grid.update_property(&[
+        gtk::accessible::Property::Label("Command grid"),
+        gtk::accessible::Property::Description("Select a command to view details and run it."),
+    ]);
+    let state_clone = state.clone();
+    let list_view_clone = list_view.clone();
+    command_grid.bind_model(Some(&entry_store), move |item| {
+        let Some(entry) = item
+            .downcast_ref::<BoxedAnyObject>()
+            .map(|entry| entry.borrow::<Rc<ListEntry>>().clone())
+        else {
+            return gtk::Label::new(None).upcast();
+        };
+        let tile = grid_tile(&state_clone.borrow(), &entry);
+        let is_command = !entry.is_up_dir && !entry.has_children;
+        let state = state_clone.clone();
+        let list_view = list_view_clone.clone();
+        tile.connect_clicked(move |tile| {
+            let (Some(child), Some(model)) = (
+                tile.parent().and_downcast::<gtk::FlowBoxChild>(),
+                list_view.model(),
+            ) else {
+                return;
+            };
+            let position = child.index() as u32;
+            if !is_command {
+                // Folders open like rows do when activated
+                tile.set_active(false);
+                list_view.emit_by_name::<()>("activate", &[&position]);
+            } else if tile.is_active() {
+                model.select_item(position, !state.borrow().multi_select);
+            } else {
+                model.unselect_item(position);
+            }
+        });
+        tile.upcast()
+    });
+    // New tiles start unselected
+    let command_grid_clone = command_grid.clone();
+    let list_view_clone = list_view.clone();
+    entry_store.connect_items_changed(move |_, _, _, _| {
+        if let Some(model) = list_view_clone.model() {
+            sync_grid_selection(&command_grid_clone, &model);
+        }
+    });
+    for selection in [
+        single_selection.upcast_ref::<gtk::SelectionModel>(),
+        multi_selection.upcast_ref(),
+    ] {
+        let command_grid_clone = command_grid.clone();
+        selection.connect_selection_changed(move |selection, _, _| {
+            sync_grid_selection(&command_grid_clone, selection);
+        });
+    }
+    let grid_scroll = gtk::ScrolledWindow::new();
+    grid_scroll.set_policy(gtk::PolicyType::Never, gtk::PolicyType::Automatic);
+    grid_scroll.set_hexpand(true);
+    grid_scroll.set_vexpand(true);
+    grid_scroll.set_child(Some(&command_grid));
+    let layout_stack = gtk::Stack::new();
+    layout_stack.add_named(&list_scroll, Some("list"));
+    layout_stack.add_named(&grid_scroll, Some("grid"));
+    layout_stack.set_visible_child_name(if grid_toggle.is_active() {
+        "grid"
+    } else {
+        "list"
+    });
+    let navigation_stack = navigation_stack(&layout_stack);
+    let layout_stack_clone = layout_stack.clone();
+    grid_toggle.connect_toggled(move |toggle| {
+        let layout = if toggle.is_active() {
+            CommandLayout::Grid
+        } else {
+            CommandLayout::List
+        };
+        layout_stack_clone.set_visible_child_name(match layout {
+            CommandLayout::List => "list",
+            CommandLayout::Grid => "grid",
+        });
+        let mut settings = Settings::load();
+        settings.view.layout = layout;
+        if let Err(err) = settings.save() {
+            eprintln!("Failed to save settings: {err}");
+        }
+    });
 
     let info_label = gtk::Label::new(Some("Select a command to view its description."));
     info_label.set_xalign(0.0);
@@ -207,35 +728,219 @@ fn build_ui(app: &gtk::Application, args: Rc<Args>) {
         gtk::accessible::Property::Label("Command description"),
         gtk::accessible::Property::Description("Displays details about the selected command."),
     ]);
+    // Related files open in an editor, since the default for scripts may be to run them
+    let window_weak = window.downgrade();
+    info_label.connect_activate_link(move |_, uri| {
+        let Some(path) = gio::File::for_uri(uri).path() else {
+            return Propagation::Proceed;
+        };
+        if let Err(err) = open_in_editor(&path) {
+            if let Some(window) = window_weak.upgrade() {
+                show_info_dialog(
+                    window.upcast_ref(),
+                    "Failed to open the file",
+                    &format!("{}: {err}", path.display()),
+                );
+            }
+        }
+        Propagation::Stop
+    });
+
+    // Commands picked in multi-select mode, from every tab
+    let queue_list = gtk::ListBox::new();
+    queue_list.set_selection_mode(gtk::SelectionMode::None);
+    queue_list.set_placeholder(Some(&gtk::Label::new(Some("No commands queued."))));
+    queue_list.update_property(&[
+        gtk::accessible::Property::Label("Queued commands"),
+        gtk::accessible::Property::Description("Commands that Run executes, in order."),
+    ]);
+    let queue_scroll = gtk::ScrolledWindow::new();
+    queue_scroll.set_policy(gtk::PolicyType::Never, gtk::PolicyType::Automatic);
+    queue_scroll.set_min_content_width(260);
+    queue_scroll.set_vexpand(true);
+    queue_scroll.set_child(Some(&queue_list));
+    let save_preset_button = gtk::Button::with_label("Save preset");
+    save_preset_button.update_property(&[
+        gtk::accessible::Property::Label("Save preset"),
+        gtk::accessible::Property::Description("Save the queued commands as a named preset."),
+    ]);
+    let load_preset_button = gtk::Button::with_label("Load preset");
+    load_preset_button.update_property(&[
+        gtk::accessible::Property::Label("Load preset"),
+        gtk::accessible::Property::Description("Replace the queue with a saved preset."),
+    ]);
+    let preset_box = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+    preset_box.append(&save_preset_button);
+    preset_box.append(&load_preset_button);
+    let queue_box = gtk::Box::new(gtk::Orientation::Vertical, 8);
+    queue_box.append(&preset_box);
+    queue_box.append(&queue_scroll);
+    let queue_expander = gtk::Expander::new(Some(&queue_title(0)));
+    queue_expander.set_expanded(true);
+    queue_expander.set_visible(false);
+    queue_expander.set_child(Some(&queue_box));
+    queue_expander.update_property(&[
+        gtk::accessible::Property::Label("Queue"),
+        gtk::accessible::Property::Description("Show or hide the commands selected for running."),
+    ]);
 
     #[cfg(feature = "tips")]
-    let tip_label = {
-        let tip = tips::get_random_tip();
-        let label = gtk::Label::new(Some(&format!("Tip: {tip}")));
+    let (tip_box, tip_label, show_me_button) = {
+        let label = gtk::Label::new(None);
         label.set_xalign(0.0);
         label.set_wrap(true);
+        label.set_hexpand(true);
         label.update_property(&[
             gtk::accessible::Property::Label("Tip"),
             gtk::accessible::Property::Description("Displays a usage tip."),
         ]);
-        label
+        let show_me = gtk::Button::with_label("Show me");
+        show_me.set_valign(gtk::Align::Center);
+        show_me.update_property(&[
+            gtk::accessible::Property::Label("Show me"),
+            gtk::accessible::Property::Description(
+                "Go to the command the tip is about in the command list.",
+            ),
+        ]);
+        set_tip(&label, &show_me, tips::get_random_tip());
+        let tip_box = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+        tip_box.append(&label);
+        tip_box.append(&show_me);
+        (tip_box, label, show_me)
     };
 
-    right_box.append(&path_label);
-    right_box.append(&list_scroll);
-    right_box.append(&info_label);
+    // Pinned commands, whatever folder is open. Each runs with a click and has its own unpin
+    // button.
+    let pins_strip = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+    pins_strip.update_property(&[
+        gtk::accessible::Property::Label("Pinned commands"),
+        gtk::accessible::Property::Description("Commands pinned from their right-click menu."),
+    ]);
+    let pins_scroll = gtk::ScrolledWindow::new();
+    pins_scroll.set_policy(gtk::PolicyType::Automatic, gtk::PolicyType::Never);
+    pins_scroll.set_child(Some(&pins_strip));
+    update_pins_strip(&pins_scroll, &pins_strip, &state.borrow().pins);
+
+    let dashboard = Rc::new(Dashboard::new());
+    let dashboard_scroll = gtk::ScrolledWindow::new();
+    dashboard_scroll.set_policy(gtk::PolicyType::Never, gtk::PolicyType::Automatic);
+    dashboard_scroll.set_vexpand(true);
+    dashboard_scroll.set_child(Some(&dashboard.widget));
+    let commands_box = gtk::Box::new(gtk::Orientation::Vertical, 8);
+    commands_box.append(&pins_scroll);
+    commands_box.append(&path_label);
+    commands_box.append(&navigation_stack);
+    commands_box.append(&info_label);
+    let health_view = Rc::new(HealthView::new());
+    let health_scroll = gtk::ScrolledWindow::new();
+    health_scroll.set_policy(gtk::PolicyType::Never, gtk::PolicyType::Automatic);
+    health_scroll.set_vexpand(true);
+    health_scroll.set_child(Some(&health_view.widget));
+    let view_stack = gtk::Stack::new();
+    view_stack.set_vexpand(true);
+    view_stack.add_named(&dashboard_scroll, Some("home"));
+    view_stack.add_named(&health_scroll, Some("health"));
+    view_stack.add_named(&commands_box, Some("commands"));
+    view_stack.set_visible_child_name("home");
+    page_list.select_row(Some(&home_row));
+    right_box.append(&view_stack);
     #[cfg(feature = "tips")]
-    right_box.append(&tip_label);
+    right_box.append(&tip_box);
+    #[cfg(feature = "tips")]
+    {
+        let tip_settings = Settings::load().tips;
+        tip_box.set_visible(tip_settings.show_label && !args.hide_tips);
+        let rotate_secs = tip_settings.rotate_secs;
+        if rotate_secs > 0 {
+            let tip_label_weak = tip_label.downgrade();
+            let show_me_button_weak = show_me_button.downgrade();
+            let state_clone = state.clone();
+            glib::timeout_add_seconds_local(rotate_secs, move || {
+                let (Some(tip_label), Some(show_me_button)) =
+                    (tip_label_weak.upgrade(), show_me_button_weak.upgrade())
+                else {
+                    return ControlFlow::Break;
+                };
+                show_next_tip(&tip_label, &show_me_button, &state_clone.borrow());
+                ControlFlow::Continue
+            });
+        }
+    }
 
-    content_box.append(&tab_scroll);
-    content_box.append(&right_box);
+    // The divider resizes the list of tabs for long names or small screens
+    let page_box = gtk::Box::new(gtk::Orientation::Horizontal, 12);
+    page_box.set_margin_start(12);
+    page_box.append(&right_box);
+    page_box.append(&queue_expander);
+    let sidebar_paned = gtk::Paned::new(gtk::Orientation::Horizontal);
+    sidebar_paned.set_hexpand(true);
+    sidebar_paned.set_start_child(Some(&tab_scroll));
+    sidebar_paned.set_end_child(Some(&page_box));
+    sidebar_paned.set_resize_start_child(false);
+    sidebar_paned.set_shrink_start_child(false);
+    sidebar_paned.set_shrink_end_child(false);
+    sidebar_paned.set_position(settings.windows.sidebar_width());
+    sidebar_paned.update_property(&[gtk::accessible::Property::Label("Sidebar width")]);
+    content_box.append(&sidebar_paned);
+    window.connect_close_request(move |_| {
+        let mut settings = Settings::load();
+        settings.windows.sidebar = sidebar_paned.position();
+        if let Err(err) = settings.save() {
+            eprintln!("Failed to save settings: {err}");
+        }
+        Propagation::Proceed
+    });
     root_box.append(&top_bar);
+    root_box.append(&updates_banner);
+    root_box.append(&release_banner);
     root_box.append(&content_box);
-    window.set_child(Some(&root_box));
+    // Filled by runs when the output goes into the main window
+    let output_pane = gtk::Expander::new(Some("Output"));
+    output_pane.set_visible(false);
+    output_pane.update_property(&[
+        gtk::accessible::Property::Label("Output"),
+        gtk::accessible::Property::Description("Show or hide the output of the last run."),
+    ]);
+    root_box.append(&output_pane);
+    let platform = Platform::current();
+    let status_label = gtk::Label::new(Some(&platform.summary()));
+    status_label.set_xalign(0.0);
+    status_label.set_ellipsize(gtk::pango::EllipsizeMode::End);
+    status_label.add_css_class("dim-label");
+    status_label.add_css_class("caption");
+    status_label.set_margin_start(12);
+    status_label.set_margin_end(12);
+    status_label.set_margin_top(4);
+    status_label.set_margin_bottom(4);
+    if let Some(family) = &platform.family {
+        status_label.set_tooltip_text(Some(&format!("Distribution family: {family}")));
+    }
+    status_label.update_property(&[gtk::accessible::Property::Label(&format!(
+        "System: {}",
+        platform.summary()
+    ))]);
+    status_label.set_hexpand(true);
+    // Shown when the latest scripts could not be downloaded
+    let offline_label = gtk::Label::new(None);
+    offline_label.set_visible(false);
+    offline_label.add_css_class("caption");
+    offline_label.set_margin_end(12);
+    let status_bar = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+    status_bar.append(&status_label);
+    status_bar.append(&offline_label);
+    root_box.append(&status_bar);
+    OUTPUT_PANE.with(|pane| {
+        pane.replace(Some(OutputPane {
+            expander: output_pane.downgrade(),
+            finished: Rc::new(Cell::new(true)),
+        }))
+    });
+    window.set_child(Some(&toast_overlay(&root_box)));
 
     refresh_list(
         state.clone(),
-        &list_box,
+        &list_view,
+        &entry_store,
         &path_label,
         &run_button,
         &back_button,
@@ -244,17 +949,31 @@ fn build_ui(app: &gtk::Application, args: Rc<Args>) {
 
     #[cfg(unix)]
     if !args.bypass_root && Uid::effective().is_root() {
-        show_info_dialog(window.upcast_ref(), "Root User Warning", ROOT_WARNING);
+        let warning = if Platform::current().wsl {
+            format!("{ROOT_WARNING}{WSL_ROOT_HINT}")
+        } else {
+            ROOT_WARNING.to_string()
+        };
+        show_info_dialog(window.upcast_ref(), "Root User Warning", &warning);
     }
 
     let state_clone = state.clone();
-    let list_box_clone = list_box.clone();
+    let list_view_clone = list_view.clone();
+    let entry_store_clone = entry_store.clone();
     let path_label_clone = path_label.clone();
     let run_button_clone = run_button.clone();
     let back_button_clone = back_button.clone();
     let info_label_clone = info_label.clone();
+    #[cfg(feature = "tips")]
+    let tip_label_clone = tip_label.clone();
+    #[cfg(feature = "tips")]
+    let show_me_button_clone = show_me_button.clone();
+    let page_list_clone = page_list.clone();
+    let view_stack_clone = view_stack.clone();
     tab_list.connect_row_selected(move |_, row| {
         let Some(row) = row else { return };
+        page_list_clone.unselect_all();
+        view_stack_clone.set_visible_child_name("commands");
         let mut state = state_clone.borrow_mut();
         let new_tab = row.index() as usize;
         if new_tab == state.current_tab {
@@ -262,13 +981,17 @@ fn build_ui(app: &gtk::Application, args: Rc<Args>) {
         }
         state.current_tab = new_tab;
         state.visit_stack.clear();
+        state.forward_stack.clear();
         let root_id = state.tabs[new_tab].tree.root().id();
         state.visit_stack.push(root_id);
         state.filter.clear();
+        #[cfg(feature = "tips")]
+        show_next_tip(&tip_label_clone, &show_me_button_clone, &state);
         drop(state);
         refresh_list(
             state_clone.clone(),
-            &list_box_clone,
+            &list_view_clone,
+            &entry_store_clone,
             &path_label_clone,
             &run_button_clone,
             &back_button_clone,
@@ -277,18 +1000,29 @@ fn build_ui(app: &gtk::Application, args: Rc<Args>) {
     });
 
     let state_clone = state.clone();
-    let list_box_clone = list_box.clone();
+    let list_view_clone = list_view.clone();
+    let entry_store_clone = entry_store.clone();
     let path_label_clone = path_label.clone();
     let run_button_clone = run_button.clone();
     let back_button_clone = back_button.clone();
     let info_label_clone = info_label.clone();
-    search_entry.connect_changed(move |entry| {
+    let page_list_clone = page_list.clone();
+    let view_stack_clone = view_stack.clone();
+    // search-changed is emitted after a short pause in typing, so fast typing does not
+    // rebuild the results for every key.
+    search_entry.connect_search_changed(move |entry| {
         let mut state = state_clone.borrow_mut();
         state.filter = entry.text().trim().to_string();
+        // Results come from every tab, so they are shown from the home view as well
+        if !state.filter.is_empty() {
+            page_list_clone.unselect_all();
+            view_stack_clone.set_visible_child_name("commands");
+        }
         drop(state);
         refresh_list(
             state_clone.clone(),
-            &list_box_clone,
+            &list_view_clone,
+            &entry_store_clone,
             &path_label_clone,
             &run_button_clone,
             &back_button_clone,
@@ -297,24 +1031,18 @@ fn build_ui(app: &gtk::Application, args: Rc<Args>) {
     });
 
     let state_clone = state.clone();
-    let list_box_clone = list_box.clone();
+    let list_view_clone = list_view.clone();
+    let entry_store_clone = entry_store.clone();
     let path_label_clone = path_label.clone();
     let run_button_clone = run_button.clone();
     let back_button_clone = back_button.clone();
     let info_label_clone = info_label.clone();
-    let search_entry_clone = search_entry.clone();
-    back_button.connect_clicked(move |_| {
-        let mut state = state_clone.borrow_mut();
-        if !state.filter.is_empty() {
-            state.filter.clear();
-            search_entry_clone.set_text("");
-        } else if state.visit_stack.len() > 1 {
-            state.visit_stack.pop();
-        }
-        drop(state);
+    sort_dropdown.connect_selected_notify(move |dropdown| {
+        state_clone.borrow_mut().most_used_first = dropdown.selected() == 1;
         refresh_list(
             state_clone.clone(),
-            &list_box_clone,
+            &list_view_clone,
+            &entry_store_clone,
             &path_label_clone,
             &run_button_clone,
             &back_button_clone,
@@ -323,18 +1051,18 @@ fn build_ui(app: &gtk::Application, args: Rc<Args>) {
     });
 
     let state_clone = state.clone();
-    let list_box_clone = list_box.clone();
+    let list_view_clone = list_view.clone();
+    let entry_store_clone = entry_store.clone();
     let path_label_clone = path_label.clone();
     let run_button_clone = run_button.clone();
     let back_button_clone = back_button.clone();
     let info_label_clone = info_label.clone();
-    multi_select_toggle.connect_toggled(move |toggle| {
-        let mut state = state_clone.borrow_mut();
-        state.multi_select = toggle.is_active();
-        drop(state);
+    systems_dropdown.connect_selected_notify(move |dropdown| {
+        state_clone.borrow_mut().this_system_only = dropdown.selected() == 1;
         refresh_list(
             state_clone.clone(),
-            &list_box_clone,
+            &list_view_clone,
+            &entry_store_clone,
             &path_label_clone,
             &run_button_clone,
             &back_button_clone,
@@ -343,370 +1071,3862 @@ fn build_ui(app: &gtk::Application, args: Rc<Args>) {
     });
 
     let state_clone = state.clone();
-    let info_label_clone = info_label.clone();
-    let run_button_clone = run_button.clone();
-    list_box.connect_selected_rows_changed(move |list| {
-        let state = state_clone.borrow();
-        let (desc, has_command) = describe_selection(&state, &list.selected_rows());
-        run_button_clone.set_sensitive(has_command);
-        info_label_clone.set_text(desc.as_deref().unwrap_or("Select a command to view its description."));
-    });
-
-    let search_entry_clone = search_entry.clone();
-    let list_box_clone = list_box.clone();
-    let tab_list_clone = tab_list.clone();
+    let list_view_clone = list_view.clone();
+    let entry_store_clone = entry_store.clone();
+    let path_label_clone = path_label.clone();
     let run_button_clone = run_button.clone();
     let back_button_clone = back_button.clone();
-    let key_controller = gtk::EventControllerKey::new();
-    key_controller.connect_key_pressed(move |_, key, _, modifiers| {
-        let ctrl = modifiers.contains(gtk::gdk::ModifierType::CONTROL_MASK);
-        let alt = modifiers.contains(gtk::gdk::ModifierType::ALT_MASK);
-        let key_char = key.to_unicode().map(|c| c.to_ascii_lowercase());
-
-        if ctrl && key_char == Some('f') {
-            search_entry_clone.grab_focus();
-            search_entry_clone.select_region(0, -1);
-            return Propagation::Stop;
-        }
-        if ctrl && key_char == Some('l') {
-            list_box_clone.grab_focus();
-            return Propagation::Stop;
-        }
-        if ctrl && key_char == Some('t') {
-            tab_list_clone.grab_focus();
-            return Propagation::Stop;
-        }
-        if ctrl && key_char == Some('r') {
-            run_button_clone.emit_clicked();
-            return Propagation::Stop;
-        }
-        if alt && key.name().as_deref() == Some("Left") {
-            back_button_clone.emit_clicked();
-            return Propagation::Stop;
-        }
-        if key.name().as_deref() == Some("Escape") {
-            if !search_entry_clone.text().is_empty() {
-                search_entry_clone.set_text("");
-                search_entry_clone.grab_focus();
-                return Propagation::Stop;
-            }
-        }
-        Propagation::Proceed
-    });
-    window.add_controller(key_controller);
-
-    let state_clone = state.clone();
-    let window_clone = window.clone();
-    let list_box_clone = list_box.clone();
-    run_button.connect_clicked(move |_| {
-        let state = state_clone.borrow();
-        let selection = list_box_clone.selected_rows();
-        let (commands, rejected) = collect_selected_commands(&state, &selection);
-        drop(state);
-        if !rejected.is_empty() {
-            show_info_dialog(
-                window_clone.upcast_ref(),
-                "Some commands were skipped",
-                &format!(
-                    "These commands do not support multi-select and were skipped:\n{}",
-                    rejected.join(", ")
-                ),
-            );
-        }
-        if commands.is_empty() {
-            show_info_dialog(
-                window_clone.upcast_ref(),
-                "No command selected",
-                "Select a command to run.",
-            );
-            return;
+    let info_label_clone = info_label.clone();
+    let search_entry_clone = search_entry.clone();
+    let navigation_stack_clone = navigation_stack.clone();
+    back_button.connect_clicked(move |_| {
+        let refresh = || {
+            refresh_list(
+                state_clone.clone(),
+                &list_view_clone,
+                &entry_store_clone,
+                &path_label_clone,
+                &run_button_clone,
+                &back_button_clone,
+                &info_label_clone,
+            )
+        };
+        let mut state = state_clone.borrow_mut();
+        if !state.filter.is_empty() {
+            state.filter.clear();
+            search_entry_clone.set_text("");
+            drop(state);
+            refresh();
+        } else if state.visit_stack.len() > 1 {
+            let left = state.visit_stack.pop();
+            state.forward_stack.extend(left);
+            drop(state);
+            slide_navigation(&navigation_stack_clone, Navigation::Ascend, refresh);
         }
-        let skip_confirmation = state_clone.borrow().skip_confirmation;
-        confirm_and_run(window_clone.upcast_ref(), commands, skip_confirmation);
     });
 
+    let go_forward_action = gio::SimpleAction::new("go-forward", None);
+    go_forward_action.set_enabled(false);
     let state_clone = state.clone();
-    let window_clone = window.clone();
-    let list_box_clone = list_box.clone();
+    let list_view_clone = list_view.clone();
+    let entry_store_clone = entry_store.clone();
     let path_label_clone = path_label.clone();
     let run_button_clone = run_button.clone();
     let back_button_clone = back_button.clone();
     let info_label_clone = info_label.clone();
-    list_box.connect_row_activated(move |_, row| {
+    let navigation_stack_clone = navigation_stack.clone();
+    go_forward_action.connect_activate(move |_, _| {
         let mut state = state_clone.borrow_mut();
-        let idx = row.index() as usize;
-        let Some(entry) = state.entries.get(idx).cloned() else { return };
-        if entry.is_up_dir {
-            if state.visit_stack.len() > 1 {
-                state.visit_stack.pop();
-            }
-            drop(state);
-            refresh_list(
-                state_clone.clone(),
-                &list_box_clone,
-                &path_label_clone,
-                &run_button_clone,
-                &back_button_clone,
-                &info_label_clone,
-            );
+        let Some(node_id) = state.forward_stack.pop() else {
             return;
-        }
-        if entry.has_children && state.filter.is_empty() {
-            if let Some(node_id) = entry.node_id {
-                state.visit_stack.push(node_id);
-            }
-            drop(state);
+        };
+        state.visit_stack.push(node_id);
+        state.filter.clear();
+        drop(state);
+        slide_navigation(&navigation_stack_clone, Navigation::Descend, || {
             refresh_list(
                 state_clone.clone(),
-                &list_box_clone,
+                &list_view_clone,
+                &entry_store_clone,
                 &path_label_clone,
                 &run_button_clone,
                 &back_button_clone,
                 &info_label_clone,
-            );
-            return;
-        }
-        let Some(node) = entry.node else { return };
-        drop(state);
-        let skip_confirmation = state_clone.borrow().skip_confirmation;
-        confirm_and_run(window_clone.upcast_ref(), vec![node], skip_confirmation);
+            )
+        });
     });
+    window.add_action(&go_forward_action);
 
     let state_clone = state.clone();
-    let window_clone = window.clone();
-    gtk::glib::idle_add_local_once(move || {
+    let list_view_clone = list_view.clone();
+    let entry_store_clone = entry_store.clone();
+    let single_selection_clone = single_selection.clone();
+    let multi_selection_clone = multi_selection.clone();
+    let selection_box_clone = selection_box.clone();
+    let queue_expander_clone = queue_expander.clone();
+    let queue_list_clone = queue_list.clone();
+    let path_label_clone = path_label.clone();
+    let run_button_clone = run_button.clone();
+    let back_button_clone = back_button.clone();
+    let info_label_clone = info_label.clone();
+    multi_select_toggle.connect_toggled(move |toggle| {
         let mut state = state_clone.borrow_mut();
-        if !state.pending_auto_execute.is_empty() {
-            let commands = std::mem::take(&mut state.pending_auto_execute);
-            let skip_confirmation = state.skip_confirmation;
-            drop(state);
-            confirm_and_run(window_clone.upcast_ref(), commands, skip_confirmation);
+        state.multi_select = toggle.is_active();
+        state.selected_commands.clear();
+        drop(state);
+        selection_box_clone.set_visible(toggle.is_active());
+        queue_expander_clone.set_visible(toggle.is_active());
+        update_queue(
+            &state_clone.borrow(),
+            &queue_list_clone,
+            &queue_expander_clone,
+        );
+        if toggle.is_active() {
+            list_view_clone.set_model(Some(&multi_selection_clone));
+        } else {
+            list_view_clone.set_model(Some(&single_selection_clone));
         }
+        refresh_list(
+            state_clone.clone(),
+            &list_view_clone,
+            &entry_store_clone,
+            &path_label_clone,
+            &run_button_clone,
+            &back_button_clone,
+            &info_label_clone,
+        );
+        // Commands that cannot be multi-selected are labelled differently in that mode
+        let n_items = entry_store_clone.n_items();
+        entry_store_clone.items_changed(0, n_items, n_items);
     });
 
-    window.show();
-}
+    for selection in [
+        single_selection.upcast_ref::<gtk::SelectionModel>(),
+        multi_selection.upcast_ref(),
+    ] {
+        let state_clone = state.clone();
+        let info_label_clone = info_label.clone();
+        let run_button_clone = run_button.clone();
+        let queue_list_clone = queue_list.clone();
+        let queue_expander_clone = queue_expander.clone();
+        let bound_rows_clone = bound_rows.clone();
+        selection.connect_selection_changed(move |selection, _, _| {
+            let mut state = state_clone.borrow_mut();
+            if state.multi_select && !state.restoring_selection {
+                remember_selection(&mut state, selection);
+                update_queue(&state, &queue_list_clone, &queue_expander_clone);
+            }
+            let positions = selected_positions(selection);
+            if clear_fresh_marks(&mut state, &positions) {
+                refresh_rows(&bound_rows_clone, &state);
+            }
+            let (desc, has_command) = describe_selection(&state, &positions);
+            run_button_clone.set_sensitive(has_command);
+            update_run_label(&run_button_clone, &state, &positions);
+            info_label_clone.set_markup(
+                desc.as_deref()
+                    .unwrap_or("Select a command to view its description."),
+            );
+        });
+    }
 
-fn window_title() -> String {
-    format!(
-        "Linux Toolbox - {}",
-        env!("CARGO_PKG_VERSION")
-    )
-}
+    let show_statistics_action = gio::SimpleAction::new("show-statistics", None);
+    let window_clone = window.clone();
+    show_statistics_action.connect_activate(move |_, _| {
+        if let Some(app) = window_clone.application() {
+            open_statistics_window(&app);
+        }
+    });
+    window.add_action(&show_statistics_action);
 
-fn refresh_list(
-    state: Rc<RefCell<AppState>>,
-    list_box: &gtk::ListBox,
-    path_label: &gtk::Label,
-    run_button: &gtk::Button,
-    back_button: &gtk::Button,
-    info_label: &gtk::Label,
-) {
-    let (entries, theme, multi_select, path_text, back_enabled) = {
-        let mut state = state.borrow_mut();
-        build_entries(&mut state);
-        let entries = state.entries.clone();
-        let theme = state.theme;
-        let multi_select = state.multi_select;
-        let path_text = path_label_text(&state);
-        let back_enabled = !state.filter.is_empty() || state.visit_stack.len() > 1;
-        (entries, theme, multi_select, path_text, back_enabled)
-    };
+    let show_sources_action = gio::SimpleAction::new("show-sources", None);
+    let window_clone = window.clone();
+    show_sources_action.connect_activate(move |_, _| {
+        if let Some(app) = window_clone.application() {
+            open_sources_window(&app);
+        }
+    });
+    window.add_action(&show_sources_action);
 
-    clear_list_box(list_box);
-    for entry in &entries {
-        let label = gtk::Label::new(Some(&format_entry(theme, multi_select, entry)));
-        label.set_xalign(0.0);
-        let row = gtk::ListBoxRow::new();
-        row.set_child(Some(&label));
-        list_box.append(&row);
-    }
+    // A check item in the menu; the choice is kept for the next start
+    let output_pane_action = gio::SimpleAction::new_stateful(
+        "output-pane",
+        None,
+        &Settings::load().runs.output_pane.into(),
+    );
+    output_pane_action.connect_activate(move |action, _| {
+        let mut settings = Settings::load();
+        settings.runs.output_pane = !settings.runs.output_pane;
+        action.set_state(&settings.runs.output_pane.into());
+        if let Err(err) = settings.save() {
+            eprintln!("Failed to save settings: {err}");
+        }
+    });
+    window.add_action(&output_pane_action);
 
-    list_box.set_selection_mode(if multi_select {
-        gtk::SelectionMode::Multiple
-    } else {
-        gtk::SelectionMode::Single
+    let inhibit_suspend_action = gio::SimpleAction::new_stateful(
+        "inhibit-suspend",
+        None,
+        &Settings::load().runs.inhibit_suspend.into(),
+    );
+    inhibit_suspend_action.connect_activate(move |action, _| {
+        let mut settings = Settings::load();
+        settings.runs.inhibit_suspend = !settings.runs.inhibit_suspend;
+        action.set_state(&settings.runs.inhibit_suspend.into());
+        if let Err(err) = settings.save() {
+            eprintln!("Failed to save settings: {err}");
+        }
+        if !settings.runs.inhibit_suspend {
+            power::allow_suspend();
+        } else if jobs::running() > 0 {
+            power::inhibit_suspend();
+        }
     });
+    window.add_action(&inhibit_suspend_action);
 
-    path_label.set_text(&path_text);
-    back_button.set_sensitive(back_enabled);
-    run_button.set_sensitive(false);
-    info_label.set_text("Select a command to view its description.");
-}
+    let all_desktops_action =
+        gio::SimpleAction::new_stateful("all-desktops", None, &state.borrow().all_desktops.into());
+    let state_clone = state.clone();
+    let list_view_clone = list_view.clone();
+    let entry_store_clone = entry_store.clone();
+    let path_label_clone = path_label.clone();
+    let run_button_clone = run_button.clone();
+    let back_button_clone = back_button.clone();
+    let info_label_clone = info_label.clone();
+    all_desktops_action.connect_activate(move |action, _| {
+        let mut settings = Settings::load();
+        settings.view.all_desktops = !settings.view.all_desktops;
+        action.set_state(&settings.view.all_desktops.into());
+        if let Err(err) = settings.save() {
+            eprintln!("Failed to save settings: {err}");
+        }
+        state_clone.borrow_mut().all_desktops = settings.view.all_desktops;
+        refresh_list(
+            state_clone.clone(),
+            &list_view_clone,
+            &entry_store_clone,
+            &path_label_clone,
+            &run_button_clone,
+            &back_button_clone,
+            &info_label_clone,
+        );
+    });
+    window.add_action(&all_desktops_action);
 
-fn build_entries(state: &mut AppState) {
-    state.entries.clear();
-    if state.filter.is_empty() {
-        if state.visit_stack.len() > 1 {
-            state.entries.push(ListEntry {
-                node_id: None,
-                node: None,
-                has_children: false,
-                is_up_dir: true,
-            });
+    // The parameter says whether the refresh is the quiet one at startup, which only shows
+    // being offline in the status bar instead of reporting it in a dialog
+    let refresh_scripts_action =
+        gio::SimpleAction::new("refresh-scripts", Some(glib::VariantTy::BOOLEAN));
+    let state_clone = state.clone();
+    let offline_label_clone = offline_label.clone();
+    let window_clone = window.clone();
+    let tab_list_clone = tab_list.clone();
+    let list_view_clone = list_view.clone();
+    let entry_store_clone = entry_store.clone();
+    let path_label_clone = path_label.clone();
+    let run_button_clone = run_button.clone();
+    let back_button_clone = back_button.clone();
+    let info_label_clone = info_label.clone();
+    let validate = !args.override_validation;
+    refresh_scripts_action.connect_activate(move |action, parameter| {
+        let quiet = parameter.and_then(bool::from_variant).unwrap_or(false);
+        // One download at a time
+        action.set_enabled(false);
+        let action = action.clone();
+        let offline_label = offline_label_clone.clone();
+        let state = state_clone.clone();
+        let window = window_clone.clone();
+        let tab_list = tab_list_clone.clone();
+        let list_view = list_view_clone.clone();
+        let entry_store = entry_store_clone.clone();
+        let path_label = path_label_clone.clone();
+        let run_button = run_button_clone.clone();
+        let back_button = back_button_clone.clone();
+        let info_label = info_label_clone.clone();
+        if !quiet {
+            show_toast(
+                window.upcast_ref(),
+                "Refreshing scripts",
+                "Downloading the latest scripts from the linutil repository.",
+            );
         }
-        let node_id = *state.visit_stack.last().unwrap();
-        let tree = &state.tabs[state.current_tab].tree;
-        let node = tree.get(node_id).unwrap();
-        for child in node.children() {
-            state.entries.push(ListEntry {
-                node_id: Some(child.id()),
-                node: Some(child.value().clone()),
-                has_children: child.has_children(),
-                is_up_dir: false,
+        let settings = Settings::load().updates;
+        let allow_unsigned = std::mem::take(&mut state.borrow_mut().allow_unsigned_scripts);
+        glib::spawn_future_local(async move {
+            let fetched = gio::spawn_blocking(move || catalog::fetch(&settings, allow_unsigned))
+                .await
+                .unwrap_or_else(|_| {
+                    Err(FetchError::Failed(
+                        "The download stopped unexpectedly.".to_string(),
+                    ))
+                });
+            action.set_enabled(true);
+            let loaded = fetched.and_then(|fetched| {
+                let tabs = linutil_core::load_tabs_from(&fetched.dir, validate)?;
+                // Choosing the latest scripts ends staying on a pinned version
+                let mut settings = Settings::load();
+                if settings.updates.pinned_catalog.take().is_some() {
+                    if let Err(err) = settings.save() {
+                        eprintln!("Failed to save settings: {err}");
+                    }
+                }
+                if let Err(err) = catalog::keep(&fetched.dir, &settings.updates) {
+                    eprintln!("Failed to keep the downloaded scripts: {err}");
+                }
+                Ok((tabs, fetched.signer))
             });
-        }
-    } else {
-        let query = state.filter.to_lowercase();
-        for tab in state.tabs.iter() {
-            let mut stack = vec![tab.tree.root().id()];
-            while let Some(node_id) = stack.pop() {
-                let node = tab.tree.get(node_id).unwrap();
-                if node.value().name.to_lowercase().contains(&query) && !node.has_children() {
-                    state.entries.push(ListEntry {
-                        node_id: Some(node.id()),
-                        node: Some(node.value().clone()),
-                        has_children: false,
-                        is_up_dir: false,
-                    });
+            let (tabs, signer) = match loaded {
+                Ok(loaded) => loaded,
+                Err(FetchError::Unverified(err)) if quiet => {
+                    show_toast(
+                        window.upcast_ref(),
+                        "Downloaded scripts not used",
+                        &format!("{err} Use Refresh Scripts to decide."),
+                    );
+                    return;
+                }
+                Err(FetchError::Unverified(err)) => {
+                    confirm_unsigned_scripts(&window, &state, &err);
+                    return;
                 }
-                stack.extend(node.children().map(|child| child.id()));
+                Err(err) => {
+                    let catalog_from = state.borrow().catalog_from;
+                    show_offline(&offline_label, catalog_from);
+                    if !quiet {
+                        show_info_dialog(
+                            window.upcast_ref(),
+                            "Refreshing Scripts Failed",
+                            &format!("{err}\n\nThe scripts in use stay as they are."),
+                        );
+                    }
+                    return;
+                }
+            };
+            offline_label.set_visible(false);
+            let added = replace_tabs(&state, &tab_list, tabs, validate);
+            state.borrow_mut().catalog_from = Some(SystemTime::now());
+            window.set_title(Some(&window_title(&state.borrow().tabs)));
+            refresh_list(
+                state.clone(),
+                &list_view,
+                &entry_store,
+                &path_label,
+                &run_button,
+                &back_button,
+                &info_label,
+            );
+            if quiet && added == 0 {
+                return;
             }
-        }
-        state
-            .entries
-            .sort_by(|a, b| a.node.as_ref().unwrap().name.cmp(&b.node.as_ref().unwrap().name));
-    }
-}
+            let mut text = match added {
+                0 => "The scripts are up to date with the linutil repository.".to_string(),
+                1 => "1 new command was added.".to_string(),
+                added => format!("{added} new commands were added."),
+            };
+            match signer {
+                Some(fingerprint) => text.push_str(&format!(" Signed by key {fingerprint}.")),
+                None => text.push_str(" The signature was not checked."),
+            }
+            show_toast(window.upcast_ref(), "Scripts refreshed", &text);
+        });
+    });
+    window.add_action(&refresh_scripts_action);
 
-fn format_entry(theme: Theme, multi_select: bool, entry: &ListEntry) -> String {
-    if entry.is_up_dir {
-        return ".. (Up)".to_string();
-    }
-    let Some(node) = &entry.node else { return String::new() };
-    if entry.has_children {
-        format!("{} {}", theme.dir_icon(), node.name)
-    } else if multi_select && !node.multi_select {
-        format!("{} {} (single only)", theme.cmd_icon(), node.name)
-    } else {
-        format!("{} {}", theme.cmd_icon(), node.name)
-    }
-}
+    let whats_new_scripts_action = gio::SimpleAction::new("whats-new-scripts", None);
+    let state_clone = state.clone();
+    let window_clone = window.clone();
+    whats_new_scripts_action.connect_activate(move |_, _| {
+        show_catalog_changes(window_clone.upcast_ref(), &state_clone.borrow().tabs);
+    });
+    window.add_action(&whats_new_scripts_action);
 
-fn path_label_text(state: &AppState) -> String {
-    if !state.filter.is_empty() {
-        return "Search results".to_string();
-    }
-    let tab_name = &state.tabs[state.current_tab].name;
-    let tree = &state.tabs[state.current_tab].tree;
-    let mut parts = vec![tab_name.clone()];
-    for node_id in state.visit_stack.iter().skip(1) {
-        if let Some(node) = tree.get(*node_id) {
-            parts.push(node.value().name.clone());
+    // Reads the tab of a plugin or of the user's scripts again, given its directory
+    let reload_tab_action = gio::SimpleAction::new("reload-tab", Some(glib::VariantTy::STRING));
+    let state_clone = state.clone();
+    let window_clone = window.clone();
+    let tab_list_clone = tab_list.clone();
+    let list_view_clone = list_view.clone();
+    let entry_store_clone = entry_store.clone();
+    let path_label_clone = path_label.clone();
+    let run_button_clone = run_button.clone();
+    let back_button_clone = back_button.clone();
+    let info_label_clone = info_label.clone();
+    reload_tab_action.connect_activate(move |_, parameter| {
+        let Some(dir) = parameter.and_then(String::from_variant) else {
+            return;
+        };
+        let source = watch::Source::from_dir(PathBuf::from(dir));
+        match reload_tab(&state_clone, &tab_list_clone, &source, validate) {
+            Ok(true) => refresh_list(
+                state_clone.clone(),
+                &list_view_clone,
+                &entry_store_clone,
+                &path_label_clone,
+                &run_button_clone,
+                &back_button_clone,
+                &info_label_clone,
+            ),
+            Ok(false) => {}
+            Err(err) => show_toast(
+                window_clone.upcast_ref(),
+                "Failed to reload scripts",
+                &format!("{}: {err}", source.dir().display()),
+            ),
         }
-    }
-    parts.join(" / ")
-}
+    });
+    window.add_action(&reload_tab_action);
 
-fn describe_selection(
-    state: &AppState,
-    rows: &[gtk::ListBoxRow],
-) -> (Option<String>, bool) {
-    if rows.is_empty() {
-        return (None, false);
-    }
-    let mut has_command = false;
-    for row in rows {
-        let idx = row.index() as usize;
-        let Some(entry) = state.entries.get(idx) else { continue };
-        if entry.is_up_dir || entry.has_children {
-            continue;
+    let import_bundle_action = gio::SimpleAction::new("import-bundle", None);
+    let window_clone = window.clone();
+    import_bundle_action.connect_activate(move |_, _| {
+        let window = window_clone.clone();
+        show_import_bundle_dialog(window_clone.upcast_ref(), move |input| {
+            import_bundle(&window, input);
+        });
+    });
+    window.add_action(&import_bundle_action);
+
+    let run_playbook_action = gio::SimpleAction::new("run-playbook", None);
+    let window_clone = window.clone();
+    let state_clone = state.clone();
+    run_playbook_action.connect_activate(move |_, _| {
+        let chooser = gtk::FileChooserNative::new(
+            Some("Playbook"),
+            Some(&window_clone),
+            gtk::FileChooserAction::Open,
+            Some("Run"),
+            Some("Cancel"),
+        );
+        let filter = gtk::FileFilter::new();
+        filter.set_name(Some("Playbooks"));
+        filter.add_pattern("*.toml");
+        chooser.add_filter(&filter);
+        let window = window_clone.clone();
+        let state = state_clone.clone();
+        chooser.connect_response(move |chooser, response| {
+            if response == gtk::ResponseType::Accept {
+                if let Some(path) = chooser.file().and_then(|file| file.path()) {
+                    run_playbook(&window, &state, &path);
+                }
+            }
+        });
+        chooser.show();
+    });
+    window.add_action(&run_playbook_action);
+
+    // A check item in the menu, on while the commands run are recorded
+    let record_playbook_action =
+        gio::SimpleAction::new_stateful("record-playbook", None, &false.into());
+    let window_clone = window.clone();
+    record_playbook_action.connect_activate(move |action, _| {
+        if !recording::is_recording() {
+            recording::start();
+            action.set_state(&true.into());
+            show_toast(
+                window_clone.upcast_ref(),
+                "Recording",
+                "The commands you run and your answers to their questions are recorded until \
+                 Record Playbook is turned off.",
+            );
+            return;
         }
-        if let Some(node) = &entry.node {
-            has_command = true;
-            let desc = if node.description.is_empty() {
-                format!("Command: {}", node.name)
-            } else {
-                format!("{}: {}", node.name, node.description)
-            };
-            return (Some(desc), has_command);
+        action.set_state(&false.into());
+        match recording::stop() {
+            Some(playbook) => save_playbook(window_clone.upcast_ref(), playbook),
+            None => show_info_dialog(
+                window_clone.upcast_ref(),
+                "Nothing Recorded",
+                "No commands ran while recording.",
+            ),
+        }
+    });
+    window.add_action(&record_playbook_action);
+
+    // Switches to a kept download given by its snapshot id, or to the built-in scripts, and
+    // stays on it
+    let use_catalog_action = gio::SimpleAction::new("use-catalog", Some(glib::VariantTy::STRING));
+    let state_clone = state.clone();
+    let window_clone = window.clone();
+    let offline_label_clone = offline_label.clone();
+    let tab_list_clone = tab_list.clone();
+    let list_view_clone = list_view.clone();
+    let entry_store_clone = entry_store.clone();
+    let path_label_clone = path_label.clone();
+    let run_button_clone = run_button.clone();
+    let back_button_clone = back_button.clone();
+    let info_label_clone = info_label.clone();
+    use_catalog_action.connect_activate(move |_, parameter| {
+        let Some(id) = parameter.and_then(String::from_variant) else {
+            return;
+        };
+        let loaded = if id == catalog::BUILT_IN {
+            Ok((linutil_core::get_tabs(validate), None))
+        } else {
+            catalog::snapshots()
+                .into_iter()
+                .find(|snapshot| snapshot.id == id)
+                .ok_or_else(|| "The version is no longer kept.".to_string())
+                .and_then(|snapshot| {
+                    let tabs = linutil_core::load_tabs_from(&snapshot.dir, validate)?;
+                    Ok((tabs, Some(snapshot.downloaded)))
+                })
+        };
+        let (tabs, downloaded) = match loaded {
+            Ok(loaded) => loaded,
+            Err(err) => {
+                show_info_dialog(
+                    window_clone.upcast_ref(),
+                    "Switching Scripts Failed",
+                    &format!("{err}\n\nThe scripts in use stay as they are."),
+                );
+                return;
+            }
+        };
+        let mut settings = Settings::load();
+        settings.updates.pinned_catalog = Some(id);
+        if let Err(err) = settings.save() {
+            eprintln!("Failed to save settings: {err}");
         }
+        replace_tabs(&state_clone, &tab_list_clone, tabs, validate);
+        state_clone.borrow_mut().catalog_from = downloaded;
+        offline_label_clone.set_visible(false);
+        let title = window_title(&state_clone.borrow().tabs);
+        window_clone.set_title(Some(&title));
+        refresh_list(
+            state_clone.clone(),
+            &list_view_clone,
+            &entry_store_clone,
+            &path_label_clone,
+            &run_button_clone,
+            &back_button_clone,
+            &info_label_clone,
+        );
+        let version = catalog::version(state_clone.borrow().tabs.script_dir());
+        show_toast(
+            window_clone.upcast_ref(),
+            "Scripts switched",
+            &format!("Using scripts {version} until the next Refresh Scripts."),
+        );
+    });
+    window.add_action(&use_catalog_action);
+
+    let show_versions_action = gio::SimpleAction::new("show-versions", None);
+    let window_clone = window.clone();
+    show_versions_action.connect_activate(move |_, _| open_versions_window(&window_clone));
+    window.add_action(&show_versions_action);
+
+    let updates_settings = Settings::load().updates;
+    if updates_settings.refresh_scripts && updates_settings.pinned_catalog.is_none() {
+        refresh_scripts_action.activate(Some(&true.to_variant()));
     }
-    (None, has_command)
-}
 
-fn collect_selected_commands(
-    state: &AppState,
-    rows: &[gtk::ListBoxRow],
-) -> (Vec<Rc<ListNode>>, Vec<String>) {
-    let mut commands = Vec::new();
-    let mut rejected = Vec::new();
-    let multiple = rows.len() > 1;
+    let stop_all_jobs_action = gio::SimpleAction::new("stop-all-jobs", None);
+    stop_all_jobs_action.connect_activate(|_, _| jobs::stop_and_close_all());
+    window.add_action(&stop_all_jobs_action);
 
-    for row in rows {
-        let idx = row.index() as usize;
-        let Some(entry) = state.entries.get(idx) else { continue };
-        if entry.is_up_dir || entry.has_children {
-            continue;
+    let about_action = gio::SimpleAction::new("about", None);
+    let state_clone = state.clone();
+    let window_clone = window.clone();
+    about_action.connect_activate(move |_, _| {
+        let diagnostics = diagnostics::collect(state_clone.borrow().tabs.script_dir());
+        open_about_dialog(window_clone.upcast_ref(), &diagnostics);
+    });
+    window.add_action(&about_action);
+
+    let open_scripts_folder_action = gio::SimpleAction::new("open-scripts-folder", None);
+    let window_clone = window.clone();
+    open_scripts_folder_action.connect_activate(move |_, _| {
+        let dir = user_scripts::scripts_dir();
+        let result = std::fs::create_dir_all(&dir)
+            .map_err(|err| err.to_string())
+            .and_then(|()| {
+                gio::AppInfo::launch_default_for_uri(
+                    &gio::File::for_path(&dir).uri(),
+                    None::<&gio::AppLaunchContext>,
+                )
+                .map_err(|err| err.to_string())
+            });
+        if let Err(err) = result {
+            show_info_dialog(
+                window_clone.upcast_ref(),
+                "Failed to open the folder",
+                &format!("{}: {err}", dir.display()),
+            );
         }
-        let Some(node) = &entry.node else { continue };
-        if multiple && !node.multi_select {
-            rejected.push(node.name.clone());
+    });
+    window.add_action(&open_scripts_folder_action);
+
+    // Goes to a command given by its path, e.g. `System Setup / Arch / Paru`, or its name
+    let show_command_action = gio::SimpleAction::new("show-command", Some(glib::VariantTy::STRING));
+    let state_clone = state.clone();
+    let window_clone = window.clone();
+    let tab_list_clone = tab_list.clone();
+    let view_stack_clone = view_stack.clone();
+    let search_entry_clone = search_entry.clone();
+    let list_view_clone = list_view.clone();
+    let entry_store_clone = entry_store.clone();
+    let path_label_clone = path_label.clone();
+    let run_button_clone = run_button.clone();
+    let back_button_clone = back_button.clone();
+    let info_label_clone = info_label.clone();
+    show_command_action.connect_activate(move |_, parameter| {
+        let Some(path) = parameter.and_then(|parameter| parameter.get::<String>()) else {
+            return;
+        };
+        let location = locate_command(&state_clone.borrow().tabs, &path);
+        let Some((tab_index, visit_stack, node_id)) = location else {
+            let message = format!("The command {path} is not available.");
+            if view_stack_clone.visible_child_name().as_deref() != Some("commands") {
+                show_info_dialog(window_clone.upcast_ref(), "Command not found", &message);
+            } else {
+                info_label_clone.set_text(&message);
+            }
+            return;
+        };
+        // Switching rows resets the view to the root of the tab, so it comes first
+        tab_list_clone.select_row(tab_list_clone.row_at_index(tab_index as i32).as_ref());
+        if !search_entry_clone.text().is_empty() {
+            search_entry_clone.set_text("");
+        }
+        let mut state = state_clone.borrow_mut();
+        state.current_tab = tab_index;
+        state.visit_stack = visit_stack;
+        state.forward_stack.clear();
+        state.filter.clear();
+        drop(state);
+        refresh_list(
+            state_clone.clone(),
+            &list_view_clone,
+            &entry_store_clone,
+            &path_label_clone,
+            &run_button_clone,
+            &back_button_clone,
+            &info_label_clone,
+        );
+        let state = state_clone.borrow();
+        let position = state
+            .entries
+            .iter()
+            .position(|entry| entry.node_id == Some(node_id));
+        let multi_select = state.multi_select;
+        drop(state);
+        let Some(position) = position else { return };
+        // Selecting would change the batch in multi-select mode
+        if let Some(model) = list_view_clone.model().filter(|_| !multi_select) {
+            model.select_item(position as u32, true);
+        }
+        let _ = list_view_clone
+            .activate_action("list.scroll-to-item", Some(&(position as u32).to_variant()));
+        list_view_clone.grab_focus();
+    });
+    window.add_action(&show_command_action);
+
+    let tab_list_clone = tab_list.clone();
+    let view_stack_clone = view_stack.clone();
+    let dashboard_clone = dashboard.clone();
+    let health_view_clone = health_view.clone();
+    page_list.connect_row_selected(move |_, row| {
+        let Some(row) = row else { return };
+        tab_list_clone.unselect_all();
+        if row.index() == 0 {
+            dashboard_clone.refresh();
+            view_stack_clone.set_visible_child_name("home");
         } else {
-            commands.push(node.clone());
+            health_view_clone.refresh();
+            view_stack_clone.set_visible_child_name("health");
         }
+    });
+
+    #[cfg(feature = "tips")]
+    {
+        let window_clone = window.clone();
+        let tip_label_clone = tip_label.clone();
+        show_me_button.connect_clicked(move |_| {
+            let text = tip_label_clone.text();
+            let Some(path) = tips::command_of(text.strip_prefix("Tip: ").unwrap_or_default())
+            else {
+                return;
+            };
+            if let Some(action) = window_clone.lookup_action("show-command") {
+                action.activate(Some(&path.to_variant()));
+            }
+        });
     }
-    (commands, rejected)
-}
 
-fn confirm_and_run(parent: &gtk::Window, commands: Vec<Rc<ListNode>>, skip: bool) {
-    if skip {
-        if let Some(app) = parent.application() {
-            open_command_window(&app, commands);
-        }
+    for (button, change) in [
+        (&select_all_button, SelectionChange::All),
+        (&select_none_button, SelectionChange::None),
+        (&invert_selection_button, SelectionChange::Invert),
+    ] {
+        let state_clone = state.clone();
+        let multi_selection_clone = multi_selection.clone();
+        let queue_list_clone = queue_list.clone();
+        let queue_expander_clone = queue_expander.clone();
+        button.connect_clicked(move |_| {
+            if let SelectionChange::None = change {
+                // Also drop the commands picked in other categories
+                state_clone.borrow_mut().selected_commands.clear();
+            }
+            // The selection-changed handler borrows the state mutably
+            let (selected, mask) =
+                change_selection(&state_clone.borrow(), &multi_selection_clone, change);
+            multi_selection_clone.set_selection(&selected, &mask);
+            update_queue(
+                &state_clone.borrow(),
+                &queue_list_clone,
+                &queue_expander_clone,
+            );
+        });
+    }
+
+    // The remove buttons of the queue rows activate this with the position of their command
+    let remove_queued_action =
+        gio::SimpleAction::new("remove-queued", Some(glib::VariantTy::UINT32));
+    let state_clone = state.clone();
+    let list_view_clone = list_view.clone();
+    let entry_store_clone = entry_store.clone();
+    let path_label_clone = path_label.clone();
+    let run_button_clone = run_button.clone();
+    let back_button_clone = back_button.clone();
+    let info_label_clone = info_label.clone();
+    let queue_list_clone = queue_list.clone();
+    let queue_expander_clone = queue_expander.clone();
+    remove_queued_action.connect_activate(move |_, parameter| {
+        let Some(position) = parameter.and_then(|parameter| parameter.get::<u32>()) else {
+            return;
+        };
+        let mut state = state_clone.borrow_mut();
+        if (position as usize) < state.selected_commands.len() {
+            state.selected_commands.remove(position as usize);
+        }
+        drop(state);
+        // Unselects the command if it is in view
+        refresh_list(
+            state_clone.clone(),
+            &list_view_clone,
+            &entry_store_clone,
+            &path_label_clone,
+            &run_button_clone,
+            &back_button_clone,
+            &info_label_clone,
+        );
+        update_queue(
+            &state_clone.borrow(),
+            &queue_list_clone,
+            &queue_expander_clone,
+        );
+    });
+    window.add_action(&remove_queued_action);
+
+    let state_clone = state.clone();
+    let window_clone = window.clone();
+    save_preset_button.connect_clicked(move |_| {
+        let commands = state_clone.borrow().selected_commands.clone();
+        show_save_preset_dialog(window_clone.upcast_ref(), commands);
+    });
+
+    let state_clone = state.clone();
+    let window_clone = window.clone();
+    let list_view_clone = list_view.clone();
+    let entry_store_clone = entry_store.clone();
+    let path_label_clone = path_label.clone();
+    let run_button_clone = run_button.clone();
+    let back_button_clone = back_button.clone();
+    let info_label_clone = info_label.clone();
+    let queue_list_clone = queue_list.clone();
+    let queue_expander_clone = queue_expander.clone();
+    load_preset_button.connect_clicked(move |_| {
+        let state = state_clone.clone();
+        let list_view = list_view_clone.clone();
+        let entry_store = entry_store_clone.clone();
+        let path_label = path_label_clone.clone();
+        let run_button = run_button_clone.clone();
+        let back_button = back_button_clone.clone();
+        let info_label = info_label_clone.clone();
+        let queue_list = queue_list_clone.clone();
+        let queue_expander = queue_expander_clone.clone();
+        show_load_preset_dialog(window_clone.upcast_ref(), state_clone.clone(), move || {
+            // Selects the loaded commands that are in view
+            refresh_list(
+                state.clone(),
+                &list_view,
+                &entry_store,
+                &path_label,
+                &run_button,
+                &back_button,
+                &info_label,
+            );
+            update_queue(&state.borrow(), &queue_list, &queue_expander);
+        });
+    });
+
+    // Execution order matters, e.g. adding a repository before installing from it
+    for (name, offset) in [("move-queued-up", -1), ("move-queued-down", 1)] {
+        let action = gio::SimpleAction::new(name, Some(glib::VariantTy::UINT32));
+        let state_clone = state.clone();
+        let queue_list_clone = queue_list.clone();
+        let queue_expander_clone = queue_expander.clone();
+        action.connect_activate(move |_, parameter| {
+            let Some(position) = parameter.and_then(|parameter| parameter.get::<u32>()) else {
+                return;
+            };
+            let mut state = state_clone.borrow_mut();
+            move_queued(&mut state.selected_commands, position as usize, offset);
+            update_queue(&state, &queue_list_clone, &queue_expander_clone);
+        });
+        window.add_action(&action);
+    }
+
+    let search_entry_clone = search_entry.clone();
+    let list_view_clone = list_view.clone();
+    let command_grid_clone = command_grid.clone();
+    let grid_toggle_clone = grid_toggle.clone();
+    let tab_list_clone = tab_list.clone();
+    let run_button_clone = run_button.clone();
+    let view_script_button_clone = view_script_button.clone();
+    let back_button_clone = back_button.clone();
+    let multi_select_toggle_clone = multi_select_toggle.clone();
+    let select_all_button_clone = select_all_button.clone();
+    let state_clone = state.clone();
+    let window_clone = window.clone();
+    let key_controller = gtk::EventControllerKey::new();
+    key_controller.connect_key_pressed(move |_, key, _, modifiers| {
+        let ctrl = modifiers.contains(gtk::gdk::ModifierType::CONTROL_MASK);
+        let alt = modifiers.contains(gtk::gdk::ModifierType::ALT_MASK);
+        let shift = modifiers.contains(gtk::gdk::ModifierType::SHIFT_MASK);
+        let key_char = key.to_unicode().map(|c| c.to_ascii_lowercase());
+
+        if ctrl && key_char == Some('f') {
+            search_entry_clone.grab_focus();
+            search_entry_clone.select_region(0, -1);
+            return Propagation::Stop;
+        }
+        if ctrl && key_char == Some('l') {
+            if grid_toggle_clone.is_active() {
+                command_grid_clone.grab_focus();
+            } else {
+                list_view_clone.grab_focus();
+            }
+            return Propagation::Stop;
+        }
+        if ctrl && key_char == Some('t') {
+            tab_list_clone.grab_focus();
+            return Propagation::Stop;
+        }
+        if ctrl && key_char == Some('r') {
+            run_button_clone.emit_clicked();
+            return Propagation::Stop;
+        }
+        if ctrl && key_char == Some('u') {
+            view_script_button_clone.emit_clicked();
+            return Propagation::Stop;
+        }
+        if ctrl && shift && key_char == Some('k') {
+            jobs::stop_and_close_all();
+            return Propagation::Stop;
+        }
+        if ctrl && key_char == Some('e') {
+            activate_for_selected_script(
+                &window_clone,
+                &state_clone,
+                &list_view_clone,
+                "open-in-editor",
+            );
+            return Propagation::Stop;
+        }
+        // Ctrl+A in the search entry keeps selecting its text
+        if ctrl
+            && key_char == Some('a')
+            && multi_select_toggle_clone.is_active()
+            && !search_entry_clone.has_focus()
+        {
+            select_all_button_clone.emit_clicked();
+            return Propagation::Stop;
+        }
+        if alt && key.name().as_deref() == Some("Left") {
+            back_button_clone.emit_clicked();
+            return Propagation::Stop;
+        }
+        if alt && key.name().as_deref() == Some("Right") {
+            let _ = list_view_clone.activate_action("win.go-forward", None);
+            return Propagation::Stop;
+        }
+        if key.name().as_deref() == Some("Escape") && !search_entry_clone.text().is_empty() {
+            search_entry_clone.set_text("");
+            search_entry_clone.grab_focus();
+            return Propagation::Stop;
+        }
+        Propagation::Proceed
+    });
+    window.add_controller(key_controller);
+
+    let state_clone = state.clone();
+    let window_clone = window.clone();
+    let list_view_clone = list_view.clone();
+    run_button.connect_clicked(move |_| {
+        let state = state_clone.borrow();
+        let selection = list_view_clone
+            .model()
+            .map(|model| selected_positions(&model))
+            .unwrap_or_default();
+        let (commands, rejected) = collect_selected_commands(&state, &selection);
+        let commands = queue_commands(&state, commands);
+        drop(state);
+        if !rejected.is_empty() {
+            show_toast(
+                window_clone.upcast_ref(),
+                "Some commands were skipped",
+                &format!(
+                    "These commands do not support multi-select and were skipped:\n{}",
+                    rejected.join(", ")
+                ),
+            );
+        }
+        if commands.is_empty() {
+            show_toast(
+                window_clone.upcast_ref(),
+                "No command selected",
+                "Select a command to run.",
+            );
+            return;
+        }
+        let skip_confirmation = state_clone.borrow().skip_confirmation;
+        confirm_and_run(window_clone.upcast_ref(), commands, skip_confirmation);
+    });
+
+    let state_clone = state.clone();
+    let window_clone = window.clone();
+    let list_view_clone = list_view.clone();
+    let entry_store_clone = entry_store.clone();
+    let path_label_clone = path_label.clone();
+    let run_button_clone = run_button.clone();
+    let back_button_clone = back_button.clone();
+    let info_label_clone = info_label.clone();
+    let navigation_stack_clone = navigation_stack.clone();
+    list_view.connect_activate(move |_, position| {
+        let refresh = || {
+            refresh_list(
+                state_clone.clone(),
+                &list_view_clone,
+                &entry_store_clone,
+                &path_label_clone,
+                &run_button_clone,
+                &back_button_clone,
+                &info_label_clone,
+            )
+        };
+        let mut state = state_clone.borrow_mut();
+        let Some(entry) = state.entries.get(position as usize).cloned() else {
+            return;
+        };
+        if entry.is_up_dir {
+            if state.visit_stack.len() > 1 {
+                let left = state.visit_stack.pop();
+                state.forward_stack.extend(left);
+            }
+            drop(state);
+            slide_navigation(&navigation_stack_clone, Navigation::Ascend, refresh);
+            return;
+        }
+        if entry.has_children && state.filter.is_empty() {
+            if let Some(node_id) = entry.node_id {
+                state.visit_stack.push(node_id);
+                // Entering the folder Forward would enter keeps the folders after it
+                if state.forward_stack.last() == Some(&node_id) {
+                    state.forward_stack.pop();
+                } else {
+                    state.forward_stack.clear();
+                }
+            }
+            drop(state);
+            slide_navigation(&navigation_stack_clone, Navigation::Descend, refresh);
+            return;
+        }
+        let Some(node) = entry.node.clone() else {
+            return;
+        };
+        let commands = queue_commands(&state, vec![node]);
+        drop(state);
+        let skip_confirmation = state_clone.borrow().skip_confirmation;
+        confirm_and_run(window_clone.upcast_ref(), commands, skip_confirmation);
+    });
+
+    // The row menus activate these with the position of their entry
+    let view_script_action = gio::SimpleAction::new("view-script", Some(glib::VariantTy::UINT32));
+    let state_clone = state.clone();
+    let window_clone = window.clone();
+    view_script_action.connect_activate(move |_, parameter| {
+        let Some(position) = parameter.and_then(|parameter| parameter.get::<u32>()) else {
+            return;
+        };
+        let script = entry_script(&state_clone.borrow(), position as usize);
+        if let (Some((name, path)), Some(app)) = (script, window_clone.application()) {
+            open_script_view(&app, &name, &path);
+        }
+    });
+    window.add_action(&view_script_action);
+
+    let open_in_editor_action =
+        gio::SimpleAction::new("open-in-editor", Some(glib::VariantTy::UINT32));
+    let state_clone = state.clone();
+    let window_clone = window.clone();
+    open_in_editor_action.connect_activate(move |_, parameter| {
+        let Some(position) = parameter.and_then(|parameter| parameter.get::<u32>()) else {
+            return;
+        };
+        let Some((_, path)) = entry_script(&state_clone.borrow(), position as usize) else {
+            return;
+        };
+        if let Err(err) = open_in_editor(&path) {
+            show_info_dialog(
+                window_clone.upcast_ref(),
+                "Failed to open the script",
+                &format!("{}: {err}", path.display()),
+            );
+        }
+    });
+    window.add_action(&open_in_editor_action);
+
+    let preview_packages_action =
+        gio::SimpleAction::new("preview-packages", Some(glib::VariantTy::UINT32));
+    let state_clone = state.clone();
+    let window_clone = window.clone();
+    preview_packages_action.connect_activate(move |_, parameter| {
+        let Some(position) = parameter.and_then(|parameter| parameter.get::<u32>()) else {
+            return;
+        };
+        let state = state_clone.borrow();
+        let Some(node) = state
+            .entries
+            .get(position as usize)
+            .and_then(|entry| entry.node.as_ref())
+        else {
+            return;
+        };
+        let Some(command) = packages::preview_command(node) else {
+            return;
+        };
+        // A dry run changes nothing, so it runs without confirmation
+        let preview = Rc::new(ListNode {
+            name: format!("Preview of {}", node.name),
+            description: format!("Packages {} would install", node.name),
+            command: Command::Raw(command),
+            task_list: String::new(),
+            multi_select: false,
+            tags: Vec::new(),
+        });
+        let commands = queue_commands(&state, vec![preview]);
+        drop(state);
+        if let Some(app) = window_clone.application() {
+            open_command_window(&app, commands, Settings::load().runs.on_failure);
+        }
+    });
+    window.add_action(&preview_packages_action);
+
+    let copy_command_action = gio::SimpleAction::new("copy-command", Some(glib::VariantTy::UINT32));
+    let state_clone = state.clone();
+    let window_clone = window.clone();
+    let info_label_clone = info_label.clone();
+    copy_command_action.connect_activate(move |_, parameter| {
+        let Some(position) = parameter.and_then(|parameter| parameter.get::<u32>()) else {
+            return;
+        };
+        let state = state_clone.borrow();
+        let Some(node) = state
+            .entries
+            .get(position as usize)
+            .and_then(|entry| entry.node.as_ref())
+        else {
+            return;
+        };
+        window_clone
+            .clipboard()
+            .set_text(&runner::command_invocation(node));
+        info_label_clone.set_text(&format!(
+            "Copied the command of {} to the clipboard.",
+            node.name
+        ));
+    });
+    window.add_action(&copy_command_action);
+
+    let edit_tags_action = gio::SimpleAction::new("edit-tags", Some(glib::VariantTy::UINT32));
+    let state_clone = state.clone();
+    let window_clone = window.clone();
+    let bound_rows_clone = bound_rows.clone();
+    edit_tags_action.connect_activate(move |_, parameter| {
+        let Some(position) = parameter.and_then(|parameter| parameter.get::<u32>()) else {
+            return;
+        };
+        let state = state_clone.borrow();
+        let Some(node) = state
+            .entries
+            .get(position as usize)
+            .and_then(|entry| entry.node.clone())
+        else {
+            return;
+        };
+        let current = state.user_tags.get(&node.name).to_vec();
+        drop(state);
+        let state = state_clone.clone();
+        let bound_rows = bound_rows_clone.clone();
+        let parent = window_clone.clone();
+        let name = node.name.clone();
+        show_edit_tags_dialog(window_clone.upcast_ref(), &node, &current, move |tags| {
+            let mut state_mut = state.borrow_mut();
+            state_mut.user_tags.set(&name, tags);
+            if let Err(err) = state_mut.user_tags.save() {
+                drop(state_mut);
+                show_info_dialog(
+                    parent.upcast_ref(),
+                    "Could not save tags",
+                    &format!("Saving the tags failed: {err}"),
+                );
+                return;
+            }
+            drop(state_mut);
+            refresh_rows(&bound_rows, &state.borrow());
+        });
+    });
+    window.add_action(&edit_tags_action);
+
+    let toggle_pin_action = gio::SimpleAction::new("toggle-pin", Some(glib::VariantTy::UINT32));
+    let state_clone = state.clone();
+    let window_clone = window.clone();
+    let pins_scroll_clone = pins_scroll.clone();
+    let pins_strip_clone = pins_strip.clone();
+    toggle_pin_action.connect_activate(move |_, parameter| {
+        let Some(position) = parameter.and_then(|parameter| parameter.get::<u32>()) else {
+            return;
+        };
+        let mut state = state_clone.borrow_mut();
+        let Some(node) = state
+            .entries
+            .get(position as usize)
+            .and_then(|entry| entry.node.clone())
+        else {
+            return;
+        };
+        if state.pins.contains(&node.name) {
+            state.pins.unpin(&node.name);
+        } else {
+            let max_pins = Settings::load().view.max_pins;
+            if !state.pins.pin(&node.name, max_pins) {
+                drop(state);
+                show_toast(
+                    window_clone.upcast_ref(),
+                    "Too many pinned commands",
+                    &format!("Up to {max_pins} commands can be pinned. Unpin one first."),
+                );
+                return;
+            }
+        }
+        save_pins(&window_clone, &state.pins);
+        update_pins_strip(&pins_scroll_clone, &pins_strip_clone, &state.pins);
+    });
+    window.add_action(&toggle_pin_action);
+
+    // The unpin buttons of the strip activate this with the command's name
+    let unpin_action = gio::SimpleAction::new("unpin", Some(glib::VariantTy::STRING));
+    let state_clone = state.clone();
+    let window_clone = window.clone();
+    let pins_scroll_clone = pins_scroll.clone();
+    let pins_strip_clone = pins_strip.clone();
+    unpin_action.connect_activate(move |_, parameter| {
+        let Some(name) = parameter.and_then(|parameter| parameter.get::<String>()) else {
+            return;
+        };
+        let mut state = state_clone.borrow_mut();
+        state.pins.unpin(&name);
+        save_pins(&window_clone, &state.pins);
+        update_pins_strip(&pins_scroll_clone, &pins_strip_clone, &state.pins);
+    });
+    window.add_action(&unpin_action);
+
+    let state_clone = state.clone();
+    let window_clone = window.clone();
+    let list_view_clone = list_view.clone();
+    view_script_button.connect_clicked(move |_| {
+        activate_for_selected_script(&window_clone, &state_clone, &list_view_clone, "view-script");
+    });
+
+    let window_clone = window.clone();
+    logs_button.connect_clicked(move |_| {
+        if let Some(app) = window_clone.application() {
+            open_log_viewer(&app);
+        }
+    });
+
+    #[cfg(feature = "tips")]
+    {
+        let show_tips_action = gio::SimpleAction::new("show-tips", None);
+        let window_clone = window.clone();
+        show_tips_action.connect_activate(move |_, _| {
+            if let Some(app) = window_clone.application() {
+                open_tips_window(&app);
+            }
+        });
+        window.add_action(&show_tips_action);
+
+        let tip_of_the_day_action = gio::SimpleAction::new("tip-of-the-day", None);
+        let window_clone = window.clone();
+        tip_of_the_day_action.connect_activate(move |_, _| {
+            show_tip_dialog(window_clone.upcast_ref());
+        });
+        window.add_action(&tip_of_the_day_action);
+
+        // A check item in the menu; the choice is kept for the next start
+        let show_tip_label_action =
+            gio::SimpleAction::new_stateful("show-tip-label", None, &tip_box.is_visible().into());
+        let tip_box_clone = tip_box.clone();
+        show_tip_label_action.connect_activate(move |action, _| {
+            let show = !tip_box_clone.is_visible();
+            tip_box_clone.set_visible(show);
+            action.set_state(&show.into());
+            let mut settings = Settings::load();
+            settings.tips.show_label = show;
+            if let Err(err) = settings.save() {
+                eprintln!("Failed to save settings: {err}");
+            }
+        });
+        window.add_action(&show_tip_label_action);
+    }
+
+    let state_clone = state.clone();
+    let window_clone = window.clone();
+    let tab_list_clone = tab_list.clone();
+    let entry_store_clone = entry_store.clone();
+    let theme_overridden = args.theme.is_some();
+    gtk::glib::idle_add_local_once(move || {
+        let mut state = state_clone.borrow_mut();
+        if !state.pending_auto_execute.is_empty() {
+            let commands = std::mem::take(&mut state.pending_auto_execute);
+            let commands = queue_commands(&state, commands);
+            let skip_confirmation = state.skip_confirmation;
+            drop(state);
+            confirm_and_run(window_clone.upcast_ref(), commands, skip_confirmation);
+            return;
+        }
+        drop(state);
+        if first_run {
+            let state = state_clone.clone();
+            open_welcome_wizard(window_clone.upcast_ref(), move |settings| {
+                let mut state = state.borrow_mut();
+                state.skip_confirmation |= settings.runs.skip_confirmation;
+                if !theme_overridden && state.theme != settings.view.theme {
+                    state.theme = settings.view.theme;
+                    relabel_tab_rows(&tab_list_clone, &state);
+                    drop(state);
+                    let n_items = entry_store_clone.n_items();
+                    entry_store_clone.items_changed(0, n_items, n_items);
+                }
+            });
+        } else {
+            // Not on top of the confirmation of a config's commands or the welcome wizard
+            #[cfg(feature = "tips")]
+            if Settings::load().tips.startup_dialog {
+                show_tip_dialog(window_clone.upcast_ref());
+            }
+        }
+    });
+
+    // Runs finish in their own windows, so the badges catch up when this one is focused, or
+    // when a run in the output pane finishes
+    let history_changed_action = gio::SimpleAction::new("history-changed", None);
+    let seen_generation = Cell::new(history::generation());
+    let state_clone = state.clone();
+    let bound_rows_clone = bound_rows.clone();
+    let updates_banner_clone = updates_banner.clone();
+    let updates_label_clone = updates_label.clone();
+    history_changed_action.connect_activate(move |_, _| {
+        if seen_generation.get() != history::generation() {
+            seen_generation.set(history::generation());
+            refresh_rows(&bound_rows_clone, &state_clone.borrow());
+            // The run may have installed them
+            if updates_banner_clone.is_visible() {
+                check_updates(&updates_banner_clone, &updates_label_clone);
+            }
+        }
+    });
+    window.add_action(&history_changed_action);
+    window.connect_is_active_notify(move |window| {
+        if window.is_active() {
+            history_changed_action.activate(None);
+        }
+    });
+    let check_interval_mins = Settings::load().updates.check_interval_mins;
+    if check_interval_mins > 0 {
+        check_updates(&updates_banner, &updates_label);
+        let updates_banner_weak = updates_banner.downgrade();
+        let updates_label_weak = updates_label.downgrade();
+        glib::timeout_add_seconds_local(check_interval_mins * 60, move || {
+            let (Some(updates_banner), Some(updates_label)) =
+                (updates_banner_weak.upgrade(), updates_label_weak.upgrade())
+            else {
+                return ControlFlow::Break;
+            };
+            check_updates(&updates_banner, &updates_label);
+            ControlFlow::Continue
+        });
+    }
+    update_button.set_action_name(Some("win.run-command"));
+    update_button.set_action_target_value(Some(&updates::UPDATE_COMMAND.to_variant()));
+
+    let release: Rc<RefCell<Option<Release>>> = Rc::new(RefCell::new(None));
+    if Settings::load().updates.check_releases {
+        check_release(&release_banner, &release_label, release.clone());
+    }
+    // Off unless chosen, since it asks GitHub on every start
+    let check_releases_action = gio::SimpleAction::new_stateful(
+        "check-releases",
+        None,
+        &Settings::load().updates.check_releases.into(),
+    );
+    let release_banner_clone = release_banner.clone();
+    let release_label_clone = release_label.clone();
+    let release_clone = release.clone();
+    check_releases_action.connect_activate(move |action, _| {
+        let mut settings = Settings::load();
+        settings.updates.check_releases = !settings.updates.check_releases;
+        action.set_state(&settings.updates.check_releases.into());
+        if let Err(err) = settings.save() {
+            eprintln!("Failed to save settings: {err}");
+        }
+        if settings.updates.check_releases {
+            check_release(
+                &release_banner_clone,
+                &release_label_clone,
+                release_clone.clone(),
+            );
+        } else {
+            release_banner_clone.set_visible(false);
+        }
+    });
+    window.add_action(&check_releases_action);
+    let window_clone = window.clone();
+    let release_clone = release.clone();
+    release_notes_button.connect_clicked(move |_| {
+        if let Some(release) = release_clone.borrow().as_ref() {
+            show_info_dialog(
+                window_clone.upcast_ref(),
+                &format!("Linutil {}", release.version),
+                &format!("{}\n\n{}", release.notes.trim(), release.page),
+            );
+        }
+    });
+    let window_clone = window.clone();
+    let release_banner_clone = release_banner.clone();
+    release_install_button.connect_clicked(move |button| {
+        let Some(release) = release.borrow().clone() else {
+            return;
+        };
+        install_release(
+            window_clone.upcast_ref(),
+            &release_banner_clone,
+            button,
+            release,
+        );
+    });
+    let release_banner_clone = release_banner.clone();
+    release_dismiss_button.connect_clicked(move |_| release_banner_clone.set_visible(false));
+
+    // Confirms and runs the command with the given name, e.g. a fix offered by a view
+    let run_command_action = gio::SimpleAction::new("run-command", Some(glib::VariantTy::STRING));
+    let state_clone = state.clone();
+    let window_clone = window.clone();
+    run_command_action.connect_activate(move |_, parameter| {
+        let Some(name) = parameter.and_then(|parameter| parameter.get::<String>()) else {
+            return;
+        };
+        let state = state_clone.borrow();
+        let node = state
+            .tabs
+            .iter()
+            .find_map(|tab| tab.find_command_by_name(&name));
+        let Some(node) = node else {
+            drop(state);
+            // Reports that the command is missing
+            if let Some(action) = window_clone.lookup_action("show-command") {
+                action.activate(Some(&name.to_variant()));
+            }
+            return;
+        };
+        let commands = queue_commands(&state, vec![node]);
+        let skip_confirmation = state.skip_confirmation;
+        drop(state);
+        confirm_and_run(window_clone.upcast_ref(), commands, skip_confirmation);
+    });
+    window.add_action(&run_command_action);
+
+    // Keeps times like `5 min ago` current
+    let state_clone = state.clone();
+    let bound_rows_clone = bound_rows.clone();
+    glib::timeout_add_seconds_local(60, move || {
+        refresh_rows(&bound_rows_clone, &state_clone.borrow());
+        ControlFlow::Continue
+    });
+
+    window.show();
+
+    let state_clone = state.clone();
+    let tab_list_clone = tab_list.clone();
+    let list_view_clone = list_view.clone();
+    let entry_store_clone = entry_store.clone();
+    let path_label_clone = path_label.clone();
+    let run_button_clone = run_button.clone();
+    let back_button_clone = back_button.clone();
+    let info_label_clone = info_label.clone();
+    // Plugins and then the user's own scripts come after the built-in tabs
+    let mut plugin_loader = plugins::PluginLoader::new(!args.override_validation);
+    let mut user_tab = user_scripts::load_tab();
+    let window_clone = window.clone();
+    let bound_rows_clone = bound_rows.clone();
+    // Run once every tab is there, as steps may name commands of plugins
+    let mut playbook = args.playbook.clone();
+    glib::idle_add_local(move || {
+        let tab = perf::measure("get_tabs (deferred tab)", || {
+            tab_loader
+                .as_mut()
+                .and_then(Iterator::next)
+                .or_else(|| plugin_loader.next())
+        });
+        let Some(tab) = tab.or_else(|| user_tab.take()) else {
+            notify_catalog_changes(window_clone.upcast_ref(), &state_clone.borrow().tabs);
+            mark_fresh_commands(&mut state_clone.borrow_mut(), &bound_rows_clone);
+            if let Some(path) = playbook.take() {
+                run_playbook(&window_clone, &state_clone, &path);
+            }
+            // Edits to plugins and the user's scripts show up without restarting
+            watch_tabs(&window_clone, watch::sources());
+            return ControlFlow::Break;
+        };
+        let mut state = state_clone.borrow_mut();
+        append_tab_row(&tab_list_clone, state.theme, &tab.name);
+        state.tabs.push(tab);
+        state.search_index = SearchIndex::build(&state.tabs);
+        state.search_query.clear();
+        let searching = !state.filter.is_empty();
+        drop(state);
+        if searching {
+            refresh_list(
+                state_clone.clone(),
+                &list_view_clone,
+                &entry_store_clone,
+                &path_label_clone,
+                &run_button_clone,
+                &back_button_clone,
+                &info_label_clone,
+            );
+        }
+        ControlFlow::Continue
+    });
+}
+
+fn page_row(name: &str, description: &str) -> gtk::ListBoxRow {
+    let label = gtk::Label::new(Some(name));
+    label.set_xalign(0.0);
+    let row = gtk::ListBoxRow::new();
+    row.update_property(&[
+        gtk::accessible::Property::Label(name),
+        gtk::accessible::Property::Description(description),
+    ]);
+    row.set_child(Some(&label));
+    row
+}
+
+/// Shows that the latest scripts could not be downloaded, and how old the ones in use are:
+/// downloaded at `catalog_from`, or built in.
+fn show_offline(offline_label: &gtk::Label, catalog_from: Option<SystemTime>) {
+    let text = match catalog_from {
+        Some(time) => format!("Offline – catalog from {}", logs::format_modified(time)),
+        None => "Offline – built-in catalog".to_string(),
+    };
+    offline_label.set_text(&text);
+    offline_label.update_property(&[gtk::accessible::Property::Label(&text)]);
+    offline_label.set_visible(true);
+}
+
+/// Tells that downloaded scripts came without a signature by a pinned key, offering to refresh
+/// again and use them anyway.
+fn confirm_unsigned_scripts(
+    window: &gtk::ApplicationWindow,
+    state: &Rc<RefCell<AppState>>,
+    reason: &str,
+) {
+    let message = format!(
+        "{reason}\n\nThe downloaded scripts were not used. Scripts often run as root, so only \
+         use unsigned ones from a source you trust."
+    );
+    let (dialog, use_button, cancel_button) =
+        build_confirmation_dialog(window.upcast_ref(), "Scripts Not Verified", &message);
+    use_button.set_label("Use Unsigned Scripts");
+    use_button.update_property(&[
+        gtk::accessible::Property::Label("Use unsigned scripts"),
+        gtk::accessible::Property::Description(
+            "Download the scripts again and use them without a valid signature.",
+        ),
+    ]);
+    cancel_button.update_property(&[gtk::accessible::Property::Description(
+        "Keep the scripts in use.",
+    )]);
+    // Cancelling is the default, unlike in other confirmations
+    dialog.set_default_widget(Some(&cancel_button));
+    let dialog_clone = dialog.clone();
+    cancel_button.connect_clicked(move |_| dialog_clone.close());
+    let dialog_clone = dialog.clone();
+    let state = state.clone();
+    let window = window.clone();
+    use_button.connect_clicked(move |_| {
+        dialog_clone.close();
+        state.borrow_mut().allow_unsigned_scripts = true;
+        ActionGroupExt::activate_action(&window, "refresh-scripts", Some(&false.to_variant()));
+    });
+}
+
+/// Puts the freshly downloaded `builtin` tabs in place of the built-in ones, reloading the
+/// plugins and the user's scripts after them, marks the commands that are new or changed, and
+/// opens the same folder again where it still exists. Returns the number of commands that are
+/// new.
+fn replace_tabs(
+    state: &Rc<RefCell<AppState>>,
+    tab_list: &gtk::ListBox,
+    mut tabs: TabList,
+    validate: bool,
+) -> usize {
+    tabs.extend(plugins::PluginLoader::new(validate));
+    tabs.extend(user_scripts::load_tab());
+    integrity::init(tabs.script_dir(), &Settings::load().integrity);
+
+    let mut state_mut = state.borrow_mut();
+    let added = catalog::new_commands(&state_mut.tabs, &tabs);
+    let current = &state_mut.tabs[state_mut.current_tab];
+    let tab_name = current.name.clone();
+    let folders = catalog::folder_names(current, &state_mut.visit_stack);
+    let current_tab = tabs
+        .iter()
+        .position(|tab| tab.name == tab_name)
+        .unwrap_or(0);
+    let visit_stack = catalog::folder_path(&tabs[current_tab], &folders);
+    let old_tabs = std::mem::replace(&mut state_mut.tabs, tabs);
+    // Commands still running use the scripts in the old directory, removed along with it
+    state_mut.retired_tabs.push(old_tabs);
+    state_mut.current_tab = current_tab;
+    state_mut.visit_stack = visit_stack;
+    state_mut.forward_stack.clear();
+    state_mut.selected_commands.clear();
+    state_mut.search_index = SearchIndex::build(&state_mut.tabs);
+    state_mut.search_query.clear();
+    let fingerprints = fresh::fingerprints(&state_mut.tabs);
+    if state_mut.fresh.update(fingerprints) {
+        if let Err(err) = state_mut.fresh.save() {
+            eprintln!("Failed to save the command marks: {err}");
+        }
+    }
+    let theme = state_mut.theme;
+    let names: Vec<String> = state_mut.tabs.iter().map(|tab| tab.name.clone()).collect();
+    drop(state_mut);
+
+    // Selecting the row of the current tab again leaves the restored folder open
+    while let Some(row) = tab_list.row_at_index(0) {
+        tab_list.remove(&row);
+    }
+    for name in &names {
+        append_tab_row(tab_list, theme, name);
+    }
+    tab_list.select_row(tab_list.row_at_index(current_tab as i32).as_ref());
+    added
+}
+
+/// Reloads the tabs of `sources` through the `reload-tab` action of `window` whenever their
+/// files change.
+fn watch_tabs(window: &gtk::ApplicationWindow, sources: Vec<watch::Source>) {
+    let window = window.clone();
+    watch::watch(sources, move |source| {
+        let dir = source.dir().to_string_lossy().to_variant();
+        ActionGroupExt::activate_action(&window, "reload-tab", Some(&dir));
+    });
+}
+
+/// Runs the steps of the playbook at `path` after the usual checks and confirmation, in an
+/// output window showing how far it got. Nothing runs when a step names a command that is not
+/// there.
+fn run_playbook(window: &gtk::ApplicationWindow, state: &Rc<RefCell<AppState>>, path: &Path) {
+    let state = state.borrow();
+    let commands = Playbook::load(path).and_then(|playbook| playbook.commands(&state.tabs));
+    let skip_confirmation = state.skip_confirmation;
+    drop(state);
+    match commands {
+        Ok(commands) => confirm_and_run(window.upcast_ref(), commands, skip_confirmation),
+        Err(err) => show_info_dialog(window.upcast_ref(), "Playbook Not Run", &err),
+    }
+}
+
+/// Asks where to save the recorded `playbook` and saves it there.
+fn save_playbook(parent: &gtk::Window, playbook: Playbook) {
+    let chooser = gtk::FileChooserNative::new(
+        Some("Save Playbook"),
+        Some(parent),
+        gtk::FileChooserAction::Save,
+        Some("Save"),
+        Some("Cancel"),
+    );
+    chooser.set_current_name("playbook.toml");
+    let parent = parent.clone();
+    chooser.connect_response(move |chooser, response| {
+        if response != gtk::ResponseType::Accept {
+            return;
+        }
+        let Some(path) = chooser.file().and_then(|file| file.path()) else {
+            return;
+        };
+        match playbook.save(&path) {
+            Ok(()) => show_toast(
+                &parent,
+                "Playbook saved",
+                &format!(
+                    "{} steps saved to {}. Run them with linutil --playbook.",
+                    playbook.steps.len(),
+                    path.display()
+                ),
+            ),
+            Err(err) => show_info_dialog(
+                &parent,
+                "Could not save playbook",
+                &format!("Saving the playbook failed: {err}"),
+            ),
+        }
+    });
+    chooser.show();
+}
+
+/// Fetches the bundle at `input` in the background and, once it loads, asks before adding its
+/// tab. The added tab is watched like the other plugins.
+fn import_bundle(window: &gtk::ApplicationWindow, input: String) {
+    show_toast(
+        window.upcast_ref(),
+        "Importing bundle",
+        &format!("Fetching {input}."),
+    );
+    let window = window.clone();
+    glib::spawn_future_local(async move {
+        let fetch_input = input.clone();
+        let bundle = gio::spawn_blocking(move || bundles::fetch(&fetch_input))
+            .await
+            .unwrap_or_else(|_| Err("Fetching stopped unexpectedly.".to_string()))
+            .and_then(|dir| bundles::Bundle::load(&dir));
+        let bundle = match bundle {
+            Ok(bundle) => Rc::new(bundle),
+            Err(err) => {
+                show_info_dialog(window.upcast_ref(), "Importing Bundle Failed", &err);
+                return;
+            }
+        };
+        let message = format!(
+            "“{}” adds {} commands in a tab of its own, from {input}.\n\nLinutil has not \
+             reviewed these scripts. Look through them before running any, as commands often \
+             run as root. Importing the bundle again later updates it.",
+            bundle.tab.name,
+            bundle.commands(),
+        );
+        let (dialog, add_button, cancel_button) =
+            build_confirmation_dialog(window.upcast_ref(), "Import Bundle", &message);
+        add_button.set_label("Add Tab");
+        add_button.update_property(&[
+            gtk::accessible::Property::Label("Add tab"),
+            gtk::accessible::Property::Description(
+                "Install the bundle among your plugins and show its tab.",
+            ),
+        ]);
+        cancel_button.update_property(&[gtk::accessible::Property::Description(
+            "Leave the bundle out.",
+        )]);
+        let dialog_clone = dialog.clone();
+        cancel_button.connect_clicked(move |_| dialog_clone.close());
+        let dialog_clone = dialog.clone();
+        add_button.connect_clicked(move |_| {
+            dialog_clone.close();
+            match bundles::install(&bundle) {
+                Ok(dir) => {
+                    let variant = dir.to_string_lossy().to_variant();
+                    ActionGroupExt::activate_action(&window, "reload-tab", Some(&variant));
+                    watch_tabs(&window, vec![watch::Source::Plugin(dir)]);
+                    show_toast(
+                        window.upcast_ref(),
+                        "Bundle imported",
+                        &format!("The {} tab was added.", bundle.tab.name),
+                    );
+                }
+                Err(err) => show_info_dialog(
+                    window.upcast_ref(),
+                    "Importing Bundle Failed",
+                    &format!("Installing the bundle failed: {err}"),
+                ),
+            }
+        });
+    });
+}
+
+/// Asks for the URL or file of a bundle to import, calling `on_import` with it.
+fn show_import_bundle_dialog(parent: &gtk::Window, on_import: impl Fn(String) + 'static) {
+    let dialog = gtk::Window::builder()
+        .title("Import Bundle")
+        .transient_for(parent)
+        .modal(true)
+        .default_width(480)
+        .build();
+    dialog.update_property(&[
+        gtk::accessible::Property::Label("Import Bundle"),
+        gtk::accessible::Property::Description(
+            "Add a tab of commands shared as a tarball, a git repository or a folder.",
+        ),
+    ]);
+
+    let box_root = gtk::Box::new(gtk::Orientation::Vertical, 12);
+    box_root.set_margin_top(12);
+    box_root.set_margin_bottom(12);
+    box_root.set_margin_start(12);
+    box_root.set_margin_end(12);
+    let source_entry = gtk::Entry::new();
+    source_entry.set_hexpand(true);
+    source_entry.set_placeholder_text(Some("https://example.org/pack.tar.gz"));
+    source_entry.set_activates_default(true);
+    source_entry.update_property(&[
+        gtk::accessible::Property::Label("Bundle URL or file"),
+        gtk::accessible::Property::Description(
+            "A tarball URL, a git repository URL, or a tarball or folder on this system.",
+        ),
+    ]);
+    let choose_button = gtk::Button::with_label("Choose File…");
+    choose_button.update_property(&[gtk::accessible::Property::Description(
+        "Pick a bundle tarball on this system.",
+    )]);
+    let source_box = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+    source_box.append(&source_entry);
+    source_box.append(&choose_button);
+    let hint = gtk::Label::new(Some(
+        "A bundle holds a tab_data.toml in the format of the built-in tabs, next to the \
+         scripts it names.",
+    ));
+    hint.set_xalign(0.0);
+    hint.set_wrap(true);
+
+    let button_box = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+    button_box.set_halign(gtk::Align::End);
+    let cancel = gtk::Button::with_label("Cancel");
+    let import = gtk::Button::with_label("Import");
+    import.update_property(&[
+        gtk::accessible::Property::Label("Import"),
+        gtk::accessible::Property::Description("Fetch the bundle and check that it loads."),
+    ]);
+    button_box.append(&cancel);
+    button_box.append(&import);
+
+    box_root.append(&source_box);
+    box_root.append(&hint);
+    box_root.append(&button_box);
+    dialog.set_child(Some(&box_root));
+    dialog.set_default_widget(Some(&import));
+
+    let dialog_clone = dialog.clone();
+    let source_entry_clone = source_entry.clone();
+    choose_button.connect_clicked(move |_| {
+        let chooser = gtk::FileChooserNative::new(
+            Some("Bundle"),
+            Some(&dialog_clone),
+            gtk::FileChooserAction::Open,
+            Some("Select"),
+            Some("Cancel"),
+        );
+        let source_entry = source_entry_clone.clone();
+        chooser.connect_response(move |chooser, response| {
+            if response == gtk::ResponseType::Accept {
+                if let Some(path) = chooser.file().and_then(|file| file.path()) {
+                    source_entry.set_text(&path.to_string_lossy());
+                }
+            }
+        });
+        chooser.show();
+    });
+    let dialog_clone = dialog.clone();
+    cancel.connect_clicked(move |_| dialog_clone.close());
+    let dialog_clone = dialog.clone();
+    import.connect_clicked(move |_| {
+        let input = source_entry.text();
+        if input.trim().is_empty() {
+            source_entry.grab_focus();
+            return;
+        }
+        dialog_clone.close();
+        on_import(input.trim().to_string());
+    });
+    dialog.show();
+}
+
+/// Reads the tab of `source` again after its files changed and puts it in place of the old
+/// one, keeping the open folder where it still exists. A tab whose scripts are all gone is
+/// removed, and a new one added after the others. Returns whether the tabs changed; when the
+/// files no longer load, the old tab stays.
+fn reload_tab(
+    state: &Rc<RefCell<AppState>>,
+    tab_list: &gtk::ListBox,
+    source: &watch::Source,
+    validate: bool,
+) -> Result<bool, String> {
+    let dir = source.dir();
+    platform::forget_scripts(&dir);
+    let tab = source.load(validate)?;
+
+    let mut state_mut = state.borrow_mut();
+    let name = tab.as_ref().map(|tab| tab.name.as_str());
+    let index = watch::tab_index(&state_mut.tabs, &dir, name);
+    let theme = state_mut.theme;
+    let mut select_first = false;
+    match (index, tab) {
+        (Some(index), Some(tab)) => {
+            if index == state_mut.current_tab {
+                let folders = catalog::folder_names(&state_mut.tabs[index], &state_mut.visit_stack);
+                state_mut.visit_stack = catalog::folder_path(&tab, &folders);
+                state_mut.forward_stack.clear();
+            }
+            if let Some(label) = tab_list
+                .row_at_index(index as i32)
+                .and_then(|row| row.child())
+                .and_downcast::<gtk::Label>()
+            {
+                label.set_text(&format!("{} {}", theme.tab_icon(), tab.name));
+            }
+            state_mut.tabs[index] = tab;
+        }
+        (None, Some(tab)) => {
+            append_tab_row(tab_list, theme, &tab.name);
+            state_mut.tabs.push(tab);
+        }
+        (Some(index), None) => {
+            state_mut.tabs.remove(index);
+            if let Some(row) = tab_list.row_at_index(index as i32) {
+                tab_list.remove(&row);
+            }
+            if state_mut.current_tab == index {
+                state_mut.current_tab = 0;
+                state_mut.visit_stack = vec![state_mut.tabs[0].tree.root().id()];
+                state_mut.forward_stack.clear();
+                select_first = true;
+            } else if state_mut.current_tab > index {
+                state_mut.current_tab -= 1;
+            }
+        }
+        (None, None) => return Ok(false),
+    }
+    // Selected commands of the old tree would no longer show as selected
+    state_mut.selected_commands.retain(
+        |node| !matches!(&node.command, Command::LocalFile { file, .. } if file.starts_with(&dir)),
+    );
+    state_mut.search_index = SearchIndex::build(&state_mut.tabs);
+    state_mut.search_query.clear();
+    drop(state_mut);
+    if select_first {
+        tab_list.select_row(tab_list.row_at_index(0).as_ref());
+    }
+    Ok(true)
+}
+
+fn append_tab_row(tab_list: &gtk::ListBox, theme: Theme, name: &str) {
+    let label = gtk::Label::new(Some(&format!("{} {}", theme.tab_icon(), name)));
+    label.set_xalign(0.0);
+    let row = gtk::ListBoxRow::new();
+    row.update_property(&[gtk::accessible::Property::Label(&format!("Tab: {name}"))]);
+    row.set_child(Some(&label));
+    tab_list.append(&row);
+}
+
+/// Updates the icons of the tab rows after the theme changed.
+fn relabel_tab_rows(tab_list: &gtk::ListBox, state: &AppState) {
+    for (index, tab) in state.tabs.iter().enumerate() {
+        if let Some(label) = tab_list
+            .row_at_index(index as i32)
+            .and_then(|row| row.child())
+            .and_downcast::<gtk::Label>()
+        {
+            label.set_text(&format!("{} {}", state.theme.tab_icon(), tab.name));
+        }
+    }
+}
+
+/// Opens `window` with the size it had when last closed and saves its size when it closes.
+fn remember_geometry(
+    window: &gtk::ApplicationWindow,
+    geometry: &WindowGeometry,
+    pick: fn(&mut WindowSettings) -> &mut WindowGeometry,
+) {
+    let (width, height) = geometry.size();
+    window.set_default_size(width, height);
+    if geometry.maximized {
+        window.maximize();
+    }
+    window.connect_close_request(move |window| {
+        // The default size follows resizes, but not maximizing
+        let (width, height) = window.default_size();
+        let mut settings = Settings::load();
+        *pick(&mut settings.windows) = WindowGeometry {
+            width,
+            height,
+            maximized: window.is_maximized(),
+        };
+        if let Err(err) = settings.save() {
+            eprintln!("Failed to save settings: {err}");
+        }
+        Propagation::Proceed
+    });
+}
+
+/// Asks before `window` closes while `running` says commands are still going, offering to stop
+/// them with `stop` and close, to close and keep them running in the background, or to cancel.
+/// Connect it before [`remember_geometry`] so cancelling does not save the geometry.
+fn confirm_close_while_running(
+    window: &gtk::ApplicationWindow,
+    message: &'static str,
+    running: impl Fn() -> bool + 'static,
+    stop: impl Fn() + 'static,
+) {
+    let confirmed = Rc::new(Cell::new(false));
+    let stop = Rc::new(stop);
+    window.connect_close_request(move |window| {
+        if confirmed.get() || !running() {
+            return Propagation::Proceed;
+        }
+        let (dialog, keep_button, cancel_button) =
+            build_confirmation_dialog(window.upcast_ref(), "A command is still running", message);
+        keep_button.set_label("Keep Running");
+        keep_button.update_property(&[
+            gtk::accessible::Property::Label("Keep running"),
+            gtk::accessible::Property::Description(
+                "Close the window and let the commands finish in the background.",
+            ),
+        ]);
+        cancel_button.update_property(&[gtk::accessible::Property::Description(
+            "Keep the window open.",
+        )]);
+        let stop_button = gtk::Button::with_label("Stop and Close");
+        stop_button.add_css_class("destructive-action");
+        stop_button.update_property(&[
+            gtk::accessible::Property::Label("Stop and close"),
+            gtk::accessible::Property::Description("Stop the commands and close the window."),
+        ]);
+        if let Some(button_box) = keep_button.parent().and_downcast::<gtk::Box>() {
+            button_box.insert_child_after(&stop_button, Some(&cancel_button));
+        }
+
+        let close = {
+            let dialog = dialog.clone();
+            let window = window.clone();
+            let confirmed = confirmed.clone();
+            move || {
+                dialog.close();
+                confirmed.set(true);
+                window.close();
+            }
+        };
+        let close_clone = close.clone();
+        let stop_clone = stop.clone();
+        stop_button.connect_clicked(move |_| {
+            stop_clone();
+            close_clone();
+        });
+        let app = window.application();
+        keep_button.connect_clicked(move |_| {
+            if let Some(app) = &app {
+                jobs::hold_until_done(app);
+            }
+            close();
+        });
+        let dialog_clone = dialog.clone();
+        cancel_button.connect_clicked(move |_| dialog_clone.close());
+        Propagation::Stop
+    });
+}
+
+/// Direction of a move through the tree of commands.
+#[derive(Clone, Copy)]
+enum Navigation {
+    /// Into a folder.
+    Descend,
+    /// Up to the parent folder.
+    Ascend,
+}
+
+/// Stack showing `content`, the commands, and a still image of them that [`slide_navigation`]
+/// slides out when moving to another folder.
+fn navigation_stack(content: &impl IsA<gtk::Widget>) -> gtk::Stack {
+    let still = gtk::Picture::new();
+    still.set_keep_aspect_ratio(false);
+    still.set_can_shrink(true);
+    let stack = gtk::Stack::new();
+    stack.set_vexpand(true);
+    stack.add_named(content, Some("content"));
+    stack.add_named(&still, Some("still"));
+    stack.set_visible_child_name("content");
+    stack
+}
+
+/// Calls `navigate`, which changes the commands shown, sliding the new ones in from the side
+/// `direction` goes to. GTK skips the animation when animations are turned off.
+fn slide_navigation(stack: &gtk::Stack, direction: Navigation, navigate: impl FnOnce()) {
+    let (Some(content), Some(still)) = (
+        stack.child_by_name("content"),
+        stack.child_by_name("still").and_downcast::<gtk::Picture>(),
+    ) else {
+        navigate();
+        return;
+    };
+    // A slide still waiting to start shows the image of where it started from
+    if stack.visible_child_name().as_deref() == Some("still") {
+        navigate();
+        return;
+    }
+    still.set_paintable(Some(
+        &gtk::WidgetPaintable::new(Some(&content)).current_image(),
+    ));
+    let window = stack.root().and_downcast::<gtk::Window>();
+    let focus = window
+        .as_ref()
+        .and_then(gtk::prelude::GtkWindowExt::focus)
+        .filter(|focus| focus.is_ancestor(&content));
+    stack.set_visible_child_full("still", gtk::StackTransitionType::None);
+    navigate();
+    let transition = match direction {
+        Navigation::Descend => gtk::StackTransitionType::SlideLeft,
+        Navigation::Ascend => gtk::StackTransitionType::SlideRight,
+    };
+    // The stack slides out what it last drew, so the image has to be drawn once first
+    let stack_weak = stack.downgrade();
+    still.add_tick_callback(move |still, _| {
+        if still.width() == 0 {
+            return ControlFlow::Continue;
+        }
+        if let Some(stack) = stack_weak.upgrade() {
+            stack.set_visible_child_full("content", transition);
+        }
+        // Hiding the commands took the focus from the list
+        if let Some(focus) = &focus {
+            focus.grab_focus();
+        }
+        ControlFlow::Break
+    });
+}
+
+/// Title of the main window, with the version of the scripts in `tabs`.
+fn window_title(tabs: &TabList) -> String {
+    format!(
+        "Linux Toolbox - {} · Scripts {}",
+        env!("CARGO_PKG_VERSION"),
+        catalog::version(tabs.script_dir())
+    )
+}
+
+/// Points to What's New when commands were added or removed since it was last opened. On the
+/// first start the catalog just counts as seen.
+fn notify_catalog_changes(window: &gtk::Window, tabs: &TabList) {
+    let commands = catalog::commands(tabs);
+    let Some(seen) = catalog::Seen::load() else {
+        let seen = catalog::Seen {
+            version: catalog::version(tabs.script_dir()),
+            commands,
+        };
+        if let Err(err) = seen.save() {
+            eprintln!("Failed to save the seen scripts: {err}");
+        }
+        return;
+    };
+    let (added, removed) = seen.changes(&commands);
+    if added.is_empty() && removed.is_empty() {
+        return;
+    }
+    show_toast(
+        window,
+        "Scripts changed",
+        &format!(
+            "Commands since you last looked: {} added, {} removed. What's New in Scripts in \
+             the main menu lists them.",
+            added.len(),
+            removed.len()
+        ),
+    );
+}
+
+/// Lists the commands added to and removed from the catalog since What's New was last
+/// opened, and remembers the current one as seen.
+fn show_catalog_changes(window: &gtk::Window, tabs: &TabList) {
+    let version = catalog::version(tabs.script_dir());
+    let commands = catalog::commands(tabs);
+    let seen = catalog::Seen::load().unwrap_or_else(|| catalog::Seen {
+        version: version.clone(),
+        commands: commands.clone(),
+    });
+    let (added, removed) = seen.changes(&commands);
+    let mut message = if seen.version.is_empty() || seen.version == version {
+        format!("Scripts {version}")
+    } else {
+        format!("Scripts {} → {version}", seen.version)
+    };
+    if added.is_empty() && removed.is_empty() {
+        message.push_str("\n\nNo commands were added or removed since you last looked.");
+    }
+    for (heading, list) in [("Added", &added), ("Removed", &removed)] {
+        if !list.is_empty() {
+            message.push_str(&format!("\n\n{heading}:"));
+            for command in list {
+                message.push_str(&format!("\n• {command}"));
+            }
+        }
+    }
+    show_info_dialog(window, "What's New in Scripts", &message);
+    if let Err(err) = (catalog::Seen { version, commands }).save() {
+        eprintln!("Failed to save the seen scripts: {err}");
+    }
+}
+
+fn refresh_list(
+    state: Rc<RefCell<AppState>>,
+    list_view: &gtk::ListView,
+    entry_store: &gio::ListStore,
+    path_label: &gtk::Label,
+    run_button: &gtk::Button,
+    back_button: &gtk::Button,
+    info_label: &gtk::Label,
+) {
+    perf::measure("refresh_list", || {
+        let (entries, path_text, back_enabled, forward_enabled) = {
+            let mut state = state.borrow_mut();
+            build_entries(&mut state);
+            let entries = state.entries.clone();
+            let path_text = path_label_text(&state);
+            let back_enabled = !state.filter.is_empty() || state.visit_stack.len() > 1;
+            (
+                entries,
+                path_text,
+                back_enabled,
+                !state.forward_stack.is_empty(),
+            )
+        };
+
+        // Binding rows borrows the state, so it must not be borrowed while the items change.
+        state.borrow_mut().restoring_selection = true;
+        update_entry_store(entry_store, &entries);
+        if let Some(model) = list_view.model() {
+            // In single-select mode entries that were kept stay selected
+            let remembered = {
+                let state = state.borrow();
+                state.multi_select.then(|| remembered_selection(&state))
+            };
+            if let Some((selected, mask)) = remembered {
+                model.set_selection(&selected, &mask);
+            }
+        }
+        state.borrow_mut().restoring_selection = false;
+
+        let selection = list_view
+            .model()
+            .map(|model| selected_positions(&model))
+            .unwrap_or_default();
+        let (desc, has_command) = describe_selection(&state.borrow(), &selection);
+        path_label.set_text(&path_text);
+        back_button.set_sensitive(back_enabled);
+        // The Forward button follows its action
+        if let Some(action) = back_button
+            .root()
+            .and_downcast::<gtk::ApplicationWindow>()
+            .and_then(|window| window.lookup_action("go-forward"))
+            .and_downcast::<gio::SimpleAction>()
+        {
+            action.set_enabled(forward_enabled);
+        }
+        run_button.set_sensitive(has_command);
+        update_run_label(run_button, &state.borrow(), &selection);
+        info_label.set_markup(
+            desc.as_deref()
+                .unwrap_or("Select a command to view its description."),
+        );
+    })
+}
+
+/// Replaces the items of `entry_store` with `entries`, keeping the unchanged items at the
+/// start and end so only rows in between are rebound.
+fn update_entry_store(entry_store: &gio::ListStore, entries: &[Rc<ListEntry>]) {
+    let old: Vec<Rc<ListEntry>> = (0..entry_store.n_items())
+        .filter_map(|position| entry_store.item(position).and_downcast::<BoxedAnyObject>())
+        .map(|item| item.borrow::<Rc<ListEntry>>().clone())
+        .collect();
+    let prefix = old
+        .iter()
+        .zip(entries)
+        .take_while(|(old, new)| same_entry(old, new))
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(entries[prefix..].iter().rev())
+        .take_while(|(old, new)| same_entry(old, new))
+        .count();
+
+    let removed = old.len() - prefix - suffix;
+    let added: Vec<BoxedAnyObject> = entries[prefix..entries.len() - suffix]
+        .iter()
+        .map(|entry| BoxedAnyObject::new(entry.clone()))
+        .collect();
+    if removed > 0 || !added.is_empty() {
+        entry_store.splice(prefix as u32, removed as u32, &added);
+    }
+}
+
+/// Whether two entries show the same row. Nodes are compared by identity since node ids are
+/// only unique within one tab.
+fn same_entry(a: &ListEntry, b: &ListEntry) -> bool {
+    let same_node = match (&a.node, &b.node) {
+        (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+        (None, None) => true,
+        _ => false,
+    };
+    same_node
+        && a.is_up_dir == b.is_up_dir
+        && a.has_children == b.has_children
+        && a.breadcrumb == b.breadcrumb
+}
+
+fn build_entries(state: &mut AppState) {
+    perf::measure("build_entries", || {
+        state.entries.clear();
+        if state.filter.is_empty() {
+            if state.visit_stack.len() > 1 {
+                state.entries.push(Rc::new(ListEntry {
+                    node_id: None,
+                    node: None,
+                    has_children: false,
+                    is_up_dir: true,
+                    breadcrumb: None,
+                }));
+            }
+            let node_id = *state.visit_stack.last().unwrap();
+            let tree = &state.tabs[state.current_tab].tree;
+            let node = tree.get(node_id).unwrap();
+            for child in node.children() {
+                state.entries.push(Rc::new(ListEntry {
+                    node_id: Some(child.id()),
+                    node: Some(child.value().clone()),
+                    has_children: child.has_children(),
+                    is_up_dir: false,
+                    breadcrumb: None,
+                }));
+            }
+        } else {
+            let (query, tag_filter) = search_index::split_query(&state.filter.to_lowercase());
+            let matches =
+                if !state.search_query.is_empty() && query.starts_with(&state.search_query) {
+                    let previous = std::mem::take(&mut state.search_matches);
+                    state.search_index.refine(previous, &query)
+                } else {
+                    state.search_index.search(&query)
+                };
+            let entries = matches
+                .iter()
+                .filter_map(|&id| state.search_index.get(id))
+                .filter(|entry| {
+                    let tags = tags::tags_of(&entry.node, &state.user_tags);
+                    tag_filter
+                        .iter()
+                        .all(|filter| tags.iter().any(|tag| tag.starts_with(filter.as_str())))
+                })
+                .map(|entry| {
+                    Rc::new(ListEntry {
+                        node_id: Some(entry.node_id),
+                        node: Some(entry.node.clone()),
+                        has_children: false,
+                        is_up_dir: false,
+                        breadcrumb: Some(entry.breadcrumb.clone()),
+                    })
+                })
+                .collect();
+            state.entries = entries;
+            state.search_matches = matches;
+            state.search_query = query;
+        }
+        if state.this_system_only {
+            state.entries.retain(|entry| match &entry.node {
+                Some(node) if !entry.has_children => platform::node_fits(node),
+                _ => true,
+            });
+        }
+        if !state.all_desktops {
+            state.entries.retain(|entry| match &entry.node {
+                Some(node) if !entry.has_children => platform::fits_desktop(node),
+                _ => true,
+            });
+        }
+        if state.most_used_first {
+            // Stable, so commands that ran equally often and categories keep their order
+            // and the up entry stays first
+            state.entries.sort_by_key(|entry| {
+                let runs = match &entry.node {
+                    _ if entry.is_up_dir => u32::MAX,
+                    Some(node) if !entry.has_children => history::runs(&node.name),
+                    _ => 0,
+                };
+                std::cmp::Reverse(runs)
+            });
+        }
+    })
+}
+
+/// Asks the package manager for pending updates in the background and shows the banner
+/// while there are any.
+fn check_updates(updates_banner: &gtk::Box, updates_label: &gtk::Label) {
+    let updates_banner = updates_banner.downgrade();
+    let updates_label = updates_label.downgrade();
+    glib::spawn_future_local(async move {
+        let pending = gio::spawn_blocking(updates::pending_updates)
+            .await
+            .ok()
+            .flatten();
+        let (Some(updates_banner), Some(updates_label)) =
+            (updates_banner.upgrade(), updates_label.upgrade())
+        else {
+            return;
+        };
+        match pending {
+            Some(count) if count > 0 => {
+                let text = format!("Updates available ({count})");
+                updates_label.set_text(&text);
+                updates_banner.update_property(&[gtk::accessible::Property::Label(&text)]);
+                updates_banner.set_visible(true);
+            }
+            _ => updates_banner.set_visible(false),
+        }
+    });
+}
+
+/// Asks GitHub for a newer release in the background and shows the banner when there is one.
+fn check_release(
+    release_banner: &gtk::Box,
+    release_label: &gtk::Label,
+    release: Rc<RefCell<Option<Release>>>,
+) {
+    let release_banner = release_banner.downgrade();
+    let release_label = release_label.downgrade();
+    glib::spawn_future_local(async move {
+        let newer = gio::spawn_blocking(self_update::newer_release)
+            .await
+            .ok()
+            .flatten();
+        let (Some(release_banner), Some(release_label), Some(newer)) =
+            (release_banner.upgrade(), release_label.upgrade(), newer)
+        else {
+            return;
+        };
+        let text = format!("Linutil {} is available", newer.version);
+        release_label.set_text(&text);
+        release_banner.update_property(&[gtk::accessible::Property::Label(&text)]);
+        release_banner.set_visible(true);
+        *release.borrow_mut() = Some(newer);
+    });
+}
+
+/// Updates Linutil to `release` the way it was installed: a package through Full System
+/// Update, a binary of its own by downloading the one for this architecture over it.
+fn install_release(
+    window: &gtk::Window,
+    release_banner: &gtk::Box,
+    button: &gtk::Button,
+    release: Release,
+) {
+    let exe = match InstallMethod::current() {
+        Some(InstallMethod::Binary(exe)) => exe,
+        Some(InstallMethod::PackageManager) => {
+            let _ = window.activate_action(
+                "win.run-command",
+                Some(&updates::UPDATE_COMMAND.to_variant()),
+            );
+            return;
+        }
+        None => return,
+    };
+    let architecture = Platform::current()
+        .architecture
+        .unwrap_or(std::env::consts::ARCH);
+    let Some(url) = release.binary_for(architecture).map(str::to_string) else {
+        show_info_dialog(
+            window,
+            "No Download",
+            &format!(
+                "Linutil {} has no download for {architecture}. See {}",
+                release.version, release.page
+            ),
+        );
+        return;
+    };
+    let Some(checksum_url) = release.checksum_for(&url).map(str::to_string) else {
+        show_info_dialog(
+            window,
+            "No Checksum",
+            &format!(
+                "Linutil {} publishes no checksum for its download, so it cannot be checked. \
+                 See {}",
+                release.version, release.page
+            ),
+        );
+        return;
+    };
+    button.set_sensitive(false);
+    let window = window.clone();
+    let release_banner = release_banner.downgrade();
+    let button = button.downgrade();
+    glib::spawn_future_local(async move {
+        let installed =
+            gio::spawn_blocking(move || self_update::install_binary(&url, &checksum_url, &exe))
+                .await
+                .unwrap_or_else(|_| Err("The download stopped unexpectedly.".to_string()));
+        if let Some(button) = button.upgrade() {
+            button.set_sensitive(true);
+        }
+        match installed {
+            Ok(()) => {
+                if let Some(release_banner) = release_banner.upgrade() {
+                    release_banner.set_visible(false);
+                }
+                show_toast(
+                    &window,
+                    "Linutil updated",
+                    &format!("Restart Linutil to use version {}.", release.version),
+                );
+            }
+            Err(err) => show_info_dialog(&window, "Update Failed", &err),
+        }
+    });
+}
+
+type BoundRows = Rc<RefCell<Vec<glib::WeakRef<gtk::ListItem>>>>;
+
+/// Widgets of a command list row, as created by the factory.
+struct RowWidgets {
+    check: gtk::CheckButton,
+    label: gtk::Label,
+    tags: gtk::Label,
+    spinner: gtk::Spinner,
+    badge: gtk::Label,
+}
+
+fn row_widgets(item: &gtk::ListItem) -> Option<RowWidgets> {
+    let check = item
+        .child()?
+        .first_child()
+        .and_downcast::<gtk::CheckButton>()?;
+    let label = check.next_sibling().and_downcast::<gtk::Label>()?;
+    let tags = label.next_sibling().and_downcast::<gtk::Label>()?;
+    let spinner = tags.next_sibling().and_downcast::<gtk::Spinner>()?;
+    // The row menu comes after the badge
+    let badge = spinner.next_sibling().and_downcast::<gtk::Label>()?;
+    Some(RowWidgets {
+        check,
+        label,
+        tags,
+        spinner,
+        badge,
+    })
+}
+
+/// Shows `spinner` when the command `entry` shows is one of the `running` ones.
+fn update_spinner(spinner: &gtk::Spinner, entry: &ListEntry, running: &[String]) {
+    let is_running = entry
+        .node
+        .as_ref()
+        .filter(|_| !entry.is_up_dir && !entry.has_children)
+        .is_some_and(|node| running.contains(&node.name));
+    spinner.set_visible(is_running);
+    spinner.set_spinning(is_running);
+}
+
+/// Updates the spinners of the rows that still exist after jobs changed.
+fn update_running_rows(bound_rows: &BoundRows) {
+    let running = jobs::running_commands();
+    for item in bound_rows.borrow().iter().filter_map(|item| item.upgrade()) {
+        if let (Some(row), Some(entry)) = (
+            row_widgets(&item),
+            item.item().and_downcast::<BoxedAnyObject>(),
+        ) {
+            update_spinner(&row.spinner, &entry.borrow::<Rc<ListEntry>>(), &running);
+        }
+    }
+}
+
+/// Shows whether the command `entry` shows is new or updated, its tags and the outcome of its
+/// last run.
+fn update_row_extras(row: &RowWidgets, entry: &ListEntry, state: &AppState) {
+    let node = entry
+        .node
+        .as_ref()
+        .filter(|_| !entry.is_up_dir && !entry.has_children);
+    let tags = node
+        .map(|node| tags::tags_of(node, &state.user_tags))
+        .unwrap_or_default();
+    let mark = node.and_then(|node| state.fresh.mark(node, state.tabs.script_dir()));
+    let markup = match mark {
+        Some(mark) if tags.is_empty() => mark.pill_markup(),
+        Some(mark) => format!("{} {}", mark.pill_markup(), tags::pills_markup(&tags)),
+        None => tags::pills_markup(&tags),
+    };
+    row.tags.set_markup(&markup);
+    row.tags.set_visible(mark.is_some() || !tags.is_empty());
+    let label = match mark {
+        Some(mark) => format!("{}, tags: {}", mark.label(), tags.join(", ")),
+        None => format!("Tags: {}", tags.join(", ")),
+    };
+    row.tags
+        .update_property(&[gtk::accessible::Property::Label(&label)]);
+    update_badge(
+        &row.badge,
+        node.and_then(|node| history::last_run(&node.name)),
+    );
+}
+
+/// Shows the outcome of a command's last run on `badge`, e.g. `✓ 3 h ago`.
+fn update_badge(badge: &gtk::Label, last_run: Option<history::CommandHistory>) {
+    let Some(last) = last_run else {
+        badge.set_visible(false);
+        return;
+    };
+    let outcome = if last.last_success {
+        "succeeded"
+    } else {
+        "failed"
+    };
+    let text = history::badge(&last, runner::now().unix_timestamp());
+    badge.set_text(&text);
+    badge.set_tooltip_text(Some(&format!("The last run {outcome}")));
+    badge.update_property(&[gtk::accessible::Property::Label(&format!(
+        "Last run {outcome}, {}",
+        text.split_once(' ').map_or("", |(_, ago)| ago)
+    ))]);
+    badge.set_visible(true);
+}
+
+/// Takes the new or updated mark off the commands at `positions` now that they were looked
+/// at, returning whether any had one.
+fn clear_fresh_marks(state: &mut AppState, positions: &[usize]) -> bool {
+    let mut cleared = false;
+    for &position in positions {
+        let Some(node) = state
+            .entries
+            .get(position)
+            .and_then(|entry| entry.node.clone())
+        else {
+            continue;
+        };
+        cleared |= state.fresh.clear(&node, state.tabs.script_dir());
+    }
+    if cleared {
+        if let Err(err) = state.fresh.save() {
+            eprintln!("Failed to save the command marks: {err}");
+        }
+    }
+    cleared
+}
+
+/// Marks the commands of the scripts in use that are new or changed since the ones Linutil
+/// last ran with, showing the marks on the rows.
+fn mark_fresh_commands(state: &mut AppState, bound_rows: &BoundRows) {
+    if !state.fresh.update(fresh::fingerprints(&state.tabs)) {
+        return;
+    }
+    if let Err(err) = state.fresh.save() {
+        eprintln!("Failed to save the command marks: {err}");
+    }
+    refresh_rows(bound_rows, state);
+}
+
+/// Updates the tags and badges of the rows that still exist, forgetting the others.
+fn refresh_rows(bound_rows: &BoundRows, state: &AppState) {
+    bound_rows.borrow_mut().retain(|item| {
+        let Some(item) = item.upgrade() else {
+            return false;
+        };
+        if let (Some(row), Some(entry)) = (
+            row_widgets(&item),
+            item.item().and_downcast::<BoxedAnyObject>(),
+        ) {
+            update_row_extras(&row, &entry.borrow::<Rc<ListEntry>>(), state);
+        }
+        true
+    });
+}
+
+/// Why the command `entry` shows cannot run right now, shown on its disabled row.
+fn unavailable_reason(state: &AppState, entry: &ListEntry) -> Option<String> {
+    let node = entry
+        .node
+        .as_ref()
+        .filter(|_| !entry.is_up_dir && !entry.has_children)?;
+    if state.multi_select && !node.multi_select {
+        return Some("Runs only on its own. Turn off Multi-select to run it.".to_string());
+    }
+    if let Some(reason) = platform::unsupported_architecture(node)
+        .or_else(|| platform::unsupported_session(node))
+        .or_else(|| platform::unsupported_on_immutable(node))
+        .or_else(|| platform::unsupported_on_wsl(node))
+    {
+        return Some(reason);
+    }
+    // Only commands shown with --override-validation can lack their interpreter
+    if let Command::LocalFile { executable, .. } = &node.command {
+        let path = Path::new(executable);
+        let executable_file = path
+            .metadata()
+            .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0);
+        if path.is_absolute() && !executable_file {
+            return Some(format!("Needs {executable}, which is not installed."));
+        }
+    }
+    None
+}
+
+fn format_entry(theme: Theme, multi_select: bool, entry: &ListEntry) -> String {
+    if entry.is_up_dir {
+        return ".. (Up)".to_string();
+    }
+    let Some(node) = &entry.node else {
+        return String::new();
+    };
+    let mut text = if entry.has_children {
+        format!("{} {}", theme.dir_icon(), node.name)
+    } else if multi_select && !node.multi_select {
+        format!("{} {} (single only)", theme.cmd_icon(), node.name)
+    } else {
+        format!("{} {}", theme.cmd_icon(), node.name)
+    };
+    if let Some(breadcrumb) = &entry.breadcrumb {
+        text.push_str(&format!("  ({breadcrumb})"));
+    }
+    text
+}
+
+/// Replaces the buttons of the pinned commands strip, hiding it without pins.
+fn update_pins_strip(scroll: &gtk::ScrolledWindow, strip: &gtk::Box, pins: &Pins) {
+    while let Some(child) = strip.first_child() {
+        strip.remove(&child);
+    }
+    scroll.set_visible(!pins.names().is_empty());
+    for name in pins.names() {
+        let run_button = gtk::Button::with_label(name);
+        run_button.set_action_name(Some("win.run-command"));
+        run_button.set_action_target_value(Some(&name.to_variant()));
+        run_button.update_property(&[
+            gtk::accessible::Property::Label(name),
+            gtk::accessible::Property::Description(&format!("Run {name}.")),
+        ]);
+        let unpin_button = gtk::Button::with_label("✕");
+        unpin_button.set_action_name(Some("win.unpin"));
+        unpin_button.set_action_target_value(Some(&name.to_variant()));
+        unpin_button.set_tooltip_text(Some("Unpin"));
+        unpin_button.update_property(&[gtk::accessible::Property::Label(&format!("Unpin {name}"))]);
+        let pin_box = gtk::Box::new(gtk::Orientation::Horizontal, 0);
+        pin_box.add_css_class("linked");
+        pin_box.append(&run_button);
+        pin_box.append(&unpin_button);
+        strip.append(&pin_box);
+    }
+}
+
+fn save_pins(window: &gtk::ApplicationWindow, pins: &Pins) {
+    if let Err(err) = pins.save() {
+        show_info_dialog(
+            window.upcast_ref(),
+            "Could not save pins",
+            &format!("Saving the pinned commands failed: {err}"),
+        );
+    }
+}
+
+/// Tile of the grid view: the icon above the name, and where a search result was found.
+fn grid_tile(state: &AppState, entry: &ListEntry) -> gtk::ToggleButton {
+    let tile = gtk::ToggleButton::new();
+    tile.set_size_request(150, 110);
+    let (icon, name) = match &entry.node {
+        _ if entry.is_up_dir => (state.theme.dir_icon(), "Up"),
+        Some(node) if entry.has_children => (state.theme.dir_icon(), node.name.as_str()),
+        Some(node) => (state.theme.cmd_icon(), node.name.as_str()),
+        None => return tile,
+    };
+    let content = gtk::Box::new(gtk::Orientation::Vertical, 6);
+    content.set_valign(gtk::Align::Center);
+    let icon_label = gtk::Label::new(None);
+    icon_label.set_markup(&format!(
+        "<span size=\"xx-large\">{}</span>",
+        glib::markup_escape_text(icon)
+    ));
+    let name_label = gtk::Label::new(Some(name));
+    name_label.set_wrap(true);
+    name_label.set_wrap_mode(gtk::pango::WrapMode::WordChar);
+    name_label.set_justify(gtk::Justification::Center);
+    name_label.set_max_width_chars(16);
+    content.append(&icon_label);
+    content.append(&name_label);
+    let mark = entry
+        .node
+        .as_ref()
+        .filter(|_| !entry.is_up_dir && !entry.has_children)
+        .and_then(|node| state.fresh.mark(node, state.tabs.script_dir()));
+    if let Some(mark) = mark {
+        let mark_label = gtk::Label::new(None);
+        mark_label.set_markup(&mark.pill_markup());
+        content.append(&mark_label);
+    }
+    if let Some(breadcrumb) = &entry.breadcrumb {
+        let breadcrumb_label = gtk::Label::new(Some(breadcrumb));
+        breadcrumb_label.add_css_class("dim-label");
+        breadcrumb_label.set_wrap(true);
+        breadcrumb_label.set_justify(gtk::Justification::Center);
+        breadcrumb_label.set_max_width_chars(16);
+        content.append(&breadcrumb_label);
+    }
+    tile.set_child(Some(&content));
+
+    let label = if entry.has_children && !entry.is_up_dir {
+        format!("{name}, folder")
+    } else if let Some(mark) = mark {
+        format!("{name}, {}", mark.label())
+    } else {
+        name.to_string()
+    };
+    let reason = unavailable_reason(state, entry);
+    let description = reason.clone().or_else(|| {
+        entry
+            .node
+            .as_ref()
+            .map(|node| node.description.clone())
+            .filter(|description| !description.is_empty())
+    });
+    tile.set_sensitive(reason.is_none());
+    tile.set_tooltip_text(description.as_deref());
+    tile.update_property(&[
+        gtk::accessible::Property::Label(&label),
+        gtk::accessible::Property::Description(description.as_deref().unwrap_or("")),
+    ]);
+    tile
+}
+
+/// Sets the tiles of the grid view to the selection of the list.
+fn sync_grid_selection(grid: &gtk::FlowBox, model: &gtk::SelectionModel) {
+    let mut position = 0;
+    while let Some(child) = grid.child_at_index(position) {
+        if let Some(tile) = child.child().and_downcast::<gtk::ToggleButton>() {
+            tile.set_active(model.is_selected(position as u32));
+        }
+        position += 1;
+    }
+}
+
+/// Tip of the day dialog, browsable with Previous and Next. Unchecking "Show tips at
+/// startup" stores the opt-out in the settings file.
+#[cfg(feature = "tips")]
+fn show_tip_dialog(parent: &gtk::Window) {
+    let dialog = gtk::Window::builder()
+        .title("Tip of the Day")
+        .transient_for(parent)
+        .modal(true)
+        .default_width(420)
+        .default_height(200)
+        .build();
+    dialog.update_property(&[gtk::accessible::Property::Label("Tip of the day")]);
+
+    let box_root = gtk::Box::new(gtk::Orientation::Vertical, 12);
+    box_root.set_margin_top(12);
+    box_root.set_margin_bottom(12);
+    box_root.set_margin_start(12);
+    box_root.set_margin_end(12);
+    let count_label = gtk::Label::new(None);
+    count_label.set_xalign(0.0);
+    let tip_label = gtk::Label::new(None);
+    tip_label.set_xalign(0.0);
+    tip_label.set_wrap(true);
+    tip_label.set_selectable(true);
+    tip_label.set_vexpand(true);
+    tip_label.set_valign(gtk::Align::Start);
+    tip_label.update_property(&[gtk::accessible::Property::Label("Tip")]);
+    let startup_check = gtk::CheckButton::with_label("Show tips at startup");
+    startup_check.set_active(true);
+    startup_check.update_property(&[
+        gtk::accessible::Property::Label("Show tips at startup"),
+        gtk::accessible::Property::Description("Open this dialog when Linutil starts."),
+    ]);
+
+    let button_box = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+    button_box.set_halign(gtk::Align::End);
+    let previous = gtk::Button::with_label("Previous");
+    let next = gtk::Button::with_label("Next");
+    let close = gtk::Button::with_label("Close");
+    previous.update_property(&[gtk::accessible::Property::Label("Previous tip")]);
+    next.update_property(&[gtk::accessible::Property::Label("Next tip")]);
+    close.update_property(&[gtk::accessible::Property::Label("Close")]);
+    button_box.append(&previous);
+    button_box.append(&next);
+    button_box.append(&close);
+
+    box_root.append(&count_label);
+    box_root.append(&tip_label);
+    box_root.append(&startup_check);
+    box_root.append(&button_box);
+    dialog.set_child(Some(&box_root));
+    dialog.set_default_widget(Some(&next));
+
+    let count = tips::tip_count().max(1);
+    let index = Rc::new(Cell::new(rand::random::<usize>() % count));
+    let show_tip = Rc::new(move |index: usize| {
+        count_label.set_text(&format!("Tip {} of {count}", index + 1));
+        let tip = tips::get_tip(index);
+        tip_label.set_markup(&tips::to_markup(tip));
+        tips::mark_seen(tip);
+    });
+    show_tip(index.get());
+
+    let index_clone = index.clone();
+    let show_tip_clone = show_tip.clone();
+    previous.connect_clicked(move |_| {
+        index_clone.set((index_clone.get() + count - 1) % count);
+        show_tip_clone(index_clone.get());
+    });
+    next.connect_clicked(move |_| {
+        index.set((index.get() + 1) % count);
+        show_tip(index.get());
+    });
+    let dialog_clone = dialog.clone();
+    close.connect_clicked(move |_| dialog_clone.close());
+    dialog.connect_close_request(move |_| {
+        if !startup_check.is_active() {
+            let mut settings = Settings::load();
+            settings.tips.startup_dialog = false;
+            if let Err(err) = settings.save() {
+                eprintln!("Failed to save settings: {err}");
+            }
+        }
+        Propagation::Proceed
+    });
+    dialog.show();
+}
+
+/// Replaces the tip with another one, preferring tips about the open tab and categories.
+#[cfg(feature = "tips")]
+fn show_next_tip(tip_label: &gtk::Label, show_me_button: &gtk::Button, state: &AppState) {
+    let tab = &state.tabs[state.current_tab];
+    let mut context = vec![tab.name.to_lowercase()];
+    context.extend(
+        state
+            .visit_stack
+            .iter()
+            .filter_map(|node_id| tab.tree.get(*node_id))
+            .map(|node| node.value().name.to_lowercase()),
+    );
+    let current = tip_label.text();
+    let next = tips::get_next_tip(current.strip_prefix("Tip: ").unwrap_or_default(), &context);
+    set_tip(tip_label, show_me_button, next);
+}
+
+/// Shows `tip`, with the Show me button if it refers to a command.
+#[cfg(feature = "tips")]
+fn set_tip(tip_label: &gtk::Label, show_me_button: &gtk::Button, tip: &str) {
+    tip_label.set_markup(&format!("Tip: {}", tips::to_markup(tip)));
+    show_me_button.set_visible(tips::command_of(&tips::to_plain(tip)).is_some());
+    tips::mark_seen(tip);
+}
+
+/// Tab, folders from the root down and id of the command at `path`, e.g.
+/// `System Setup / Arch / Paru AUR Helper`, or of the first command named `path`.
+fn locate_command(
+    tabs: &TabList,
+    path: &str,
+) -> Option<(
+    usize,
+    Vec<linutil_core::ego_tree::NodeId>,
+    linutil_core::ego_tree::NodeId,
+)> {
+    let mut by_name = None;
+    for (tab_index, tab) in tabs.iter().enumerate() {
+        for node in tab.tree.root().descendants() {
+            if node.has_children() {
+                continue;
+            }
+            let mut folders: Vec<_> = node.ancestors().collect();
+            folders.reverse();
+            let mut parts = vec![tab.name.as_str()];
+            parts.extend(
+                folders
+                    .iter()
+                    .skip(1)
+                    .map(|folder| folder.value().name.as_str()),
+            );
+            parts.push(&node.value().name);
+            let location = (
+                tab_index,
+                folders.iter().map(|folder| folder.id()).collect(),
+                node.id(),
+            );
+            if parts.join(" / ") == path {
+                return Some(location);
+            }
+            if by_name.is_none() && node.value().name == path {
+                by_name = Some(location);
+            }
+        }
+    }
+    by_name
+}
+
+fn path_label_text(state: &AppState) -> String {
+    if !state.filter.is_empty() {
+        return "Search results".to_string();
+    }
+    let tab_name = &state.tabs[state.current_tab].name;
+    let tree = &state.tabs[state.current_tab].tree;
+    let mut parts = vec![tab_name.clone()];
+    for node_id in state.visit_stack.iter().skip(1) {
+        if let Some(node) = tree.get(*node_id) {
+            parts.push(node.value().name.clone());
+        }
+    }
+    parts.join(" / ")
+}
+
+#[derive(Clone, Copy)]
+enum SelectionChange {
+    All,
+    None,
+    Invert,
+}
+
+/// Selection and mask for selecting all, none or the currently unselected commands in
+/// multi-select mode. Folders and the up entry are never selected since they cannot be run.
+fn change_selection(
+    state: &AppState,
+    selection: &gtk::MultiSelection,
+    change: SelectionChange,
+) -> (gtk::Bitset, gtk::Bitset) {
+    let selected = gtk::Bitset::new_empty();
+    for (position, entry) in state.entries.iter().enumerate() {
+        let position = position as u32;
+        let is_command =
+            !entry.is_up_dir && !entry.has_children && unavailable_reason(state, entry).is_none();
+        let select = match change {
+            SelectionChange::All => is_command,
+            SelectionChange::Invert => is_command && !selection.is_selected(position),
+            SelectionChange::None => false,
+        };
+        if select {
+            selected.add(position);
+        }
+    }
+    let mask = gtk::Bitset::new_range(0, state.entries.len() as u32);
+    (selected, mask)
+}
+
+/// Updates `selected_commands` from the selection of the entries in view.
+fn remember_selection(state: &mut AppState, selection: &gtk::SelectionModel) {
+    let AppState {
+        entries,
+        selected_commands,
+        ..
+    } = state;
+    for (position, entry) in entries.iter().enumerate() {
+        let Some(node) = entry.node.as_ref().filter(|_| !entry.has_children) else {
+            continue;
+        };
+        let remembered = selected_commands
+            .iter()
+            .position(|selected| Rc::ptr_eq(selected, node));
+        match (selection.is_selected(position as u32), remembered) {
+            (true, None) => selected_commands.push(node.clone()),
+            (false, Some(index)) => {
+                selected_commands.remove(index);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Selection and mask restoring `selected_commands` in the entries in view.
+fn remembered_selection(state: &AppState) -> (gtk::Bitset, gtk::Bitset) {
+    let selected = gtk::Bitset::new_empty();
+    for (position, entry) in state.entries.iter().enumerate() {
+        let Some(node) = &entry.node else { continue };
+        if !entry.has_children
+            && state
+                .selected_commands
+                .iter()
+                .any(|selected| Rc::ptr_eq(selected, node))
+        {
+            selected.add(position as u32);
+        }
+    }
+    (
+        selected,
+        gtk::Bitset::new_range(0, state.entries.len() as u32),
+    )
+}
+
+fn queue_title(count: usize) -> String {
+    format!("Queue ({count})")
+}
+
+/// Rebuilds the rows of the queue pane from `selected_commands`.
+fn update_queue(state: &AppState, queue_list: &gtk::ListBox, queue_expander: &gtk::Expander) {
+    while let Some(row) = queue_list.first_child() {
+        queue_list.remove(&row);
+    }
+    for (position, node) in state.selected_commands.iter().enumerate() {
+        let path = state
+            .tabs
+            .command_path(node)
+            .map_or_else(|| node.name.clone(), |path| path.join(" / "));
+        let row_box = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+        let label = gtk::Label::new(Some(&path));
+        label.set_xalign(0.0);
+        label.set_hexpand(true);
+        label.set_wrap(true);
+        let up_button = gtk::Button::with_label("Up");
+        up_button.set_sensitive(position > 0);
+        up_button.set_action_name(Some("win.move-queued-up"));
+        up_button.set_action_target_value(Some(&(position as u32).to_variant()));
+        up_button.update_property(&[
+            gtk::accessible::Property::Label(&format!("Move {} up", node.name)),
+            gtk::accessible::Property::Description("Run the command one step earlier."),
+        ]);
+        let down_button = gtk::Button::with_label("Down");
+        down_button.set_sensitive(position + 1 < state.selected_commands.len());
+        down_button.set_action_name(Some("win.move-queued-down"));
+        down_button.set_action_target_value(Some(&(position as u32).to_variant()));
+        down_button.update_property(&[
+            gtk::accessible::Property::Label(&format!("Move {} down", node.name)),
+            gtk::accessible::Property::Description("Run the command one step later."),
+        ]);
+        let remove_button = gtk::Button::with_label("Remove");
+        remove_button.set_action_name(Some("win.remove-queued"));
+        remove_button.set_action_target_value(Some(&(position as u32).to_variant()));
+        remove_button.update_property(&[
+            gtk::accessible::Property::Label(&format!("Remove {}", node.name)),
+            gtk::accessible::Property::Description("Remove the command from the queue."),
+        ]);
+        row_box.append(&label);
+        row_box.append(&up_button);
+        row_box.append(&down_button);
+        row_box.append(&remove_button);
+        queue_list.append(&row_box);
+    }
+    queue_expander.set_label(Some(&queue_title(state.selected_commands.len())));
+}
+
+/// Moves the queued command at `position` by `offset` places, if it stays in the queue.
+fn move_queued<T>(queue: &mut [T], position: usize, offset: isize) {
+    let Some(target) = position.checked_add_signed(offset) else {
+        return;
+    };
+    if position < queue.len() && target < queue.len() {
+        queue.swap(position, target);
+    }
+}
+
+/// Positions of the selected items, in list order.
+fn selected_positions(selection: &gtk::SelectionModel) -> Vec<usize> {
+    let selected = selection.selection();
+    (0..selected.size() as u32)
+        .map(|nth| selected.nth(nth) as usize)
+        .collect()
+}
+
+/// Name and script file of the entry at `position`, if it is a command running a script.
+fn entry_script(state: &AppState, position: usize) -> Option<(String, PathBuf)> {
+    let node = state.entries.get(position)?.node.as_ref()?;
+    match &node.command {
+        Command::LocalFile { file, .. } => Some((node.name.clone(), file.clone())),
+        _ => None,
+    }
+}
+
+/// Activates the window action `name` with the position of the first selected command that
+/// runs a script, or asks to select one.
+fn activate_for_selected_script(
+    window: &gtk::ApplicationWindow,
+    state: &Rc<RefCell<AppState>>,
+    list_view: &gtk::ListView,
+    name: &str,
+) {
+    let selection = list_view
+        .model()
+        .map(|model| selected_positions(&model))
+        .unwrap_or_default();
+    let position = {
+        let state = state.borrow();
+        selection
+            .into_iter()
+            .find(|&position| entry_script(&state, position).is_some())
+    };
+    match (position, window.lookup_action(name)) {
+        (Some(position), Some(action)) => action.activate(Some(&(position as u32).to_variant())),
+        (None, _) => show_toast(
+            window.upcast_ref(),
+            "No script selected",
+            "Select a command that runs a script.",
+        ),
+        (Some(_), None) => {}
+    }
+}
+
+/// Right-click menu of the entry at `position`, or `None` when it is not a command.
+fn entry_menu(state: &AppState, position: u32) -> Option<gio::Menu> {
+    let entry = state.entries.get(position as usize)?;
+    if entry.is_up_dir || entry.has_children {
+        return None;
+    }
+    let node = entry.node.as_ref()?;
+    let menu = gio::Menu::new();
+    let target = position.to_variant();
+    if matches!(node.command, Command::LocalFile { .. }) {
+        for (label, action) in [
+            ("View Script", "win.view-script"),
+            ("Open in Editor", "win.open-in-editor"),
+        ] {
+            let item = gio::MenuItem::new(Some(label), None);
+            item.set_action_and_target_value(Some(action), Some(&target));
+            menu.append_item(&item);
+        }
+    }
+    if packages::preview_command(node).is_some() {
+        let item = gio::MenuItem::new(Some("Preview Packages"), None);
+        item.set_action_and_target_value(Some("win.preview-packages"), Some(&target));
+        menu.append_item(&item);
+    }
+    for (label, action) in [
+        ("Copy Command", "win.copy-command"),
+        ("Edit Tags…", "win.edit-tags"),
+        (
+            if state.pins.contains(&node.name) {
+                "Unpin"
+            } else {
+                "Pin"
+            },
+            "win.toggle-pin",
+        ),
+    ] {
+        let item = gio::MenuItem::new(Some(label), None);
+        item.set_action_and_target_value(Some(action), Some(&target));
+        menu.append_item(&item);
+    }
+    Some(menu)
+}
+
+/// Opens `path` in the default text editor, falling back to the default application for
+/// the file. Shell scripts are opened as text, since their own default may run them.
+fn open_in_editor(path: &Path) -> Result<(), glib::Error> {
+    let file = gio::File::for_path(path);
+    match gio::AppInfo::default_for_type("text/plain", false) {
+        Some(app_info) => app_info.launch(&[file], None::<&gio::AppLaunchContext>),
+        None => gio::AppInfo::launch_default_for_uri(&file.uri(), None::<&gio::AppLaunchContext>),
+    }
+}
+
+/// Markup describing the selection, and whether there is a command to run.
+fn describe_selection(state: &AppState, positions: &[usize]) -> (Option<String>, bool) {
+    // Commands picked in other categories can be run from anywhere
+    let has_batch = state.multi_select && !state.selected_commands.is_empty();
+    if positions.is_empty() {
+        let desc = has_batch.then(|| match state.selected_commands.len() {
+            1 => "1 command selected.".to_string(),
+            count => format!("{count} commands selected."),
+        });
+        return (desc, has_batch);
+    }
+    let mut has_command = has_batch;
+    for &position in positions {
+        let Some(entry) = state.entries.get(position) else {
+            continue;
+        };
+        if entry.is_up_dir || entry.has_children {
+            continue;
+        }
+        if let Some(node) = &entry.node {
+            has_command = true;
+            let desc = if node.description.is_empty() {
+                node.name.clone()
+            } else {
+                format!("{}: {}", node.name, node.description)
+            };
+            let usage = history::usage(
+                history::last_run(&node.name).as_ref(),
+                runner::now().unix_timestamp(),
+            );
+            let mut desc = glib::markup_escape_text(&format!(
+                "{desc}\n{}\n{usage}",
+                details::command_details(node)
+            ))
+            .to_string();
+            let files = details::related_files(node);
+            if !files.is_empty() {
+                desc.push_str(&format!("\nRelated files: {}", file_links(&files)));
+            }
+            return (Some(desc), has_command);
+        }
+    }
+    (None, has_command)
+}
+
+/// Links opening `files` in an editor from the description label, named after the files.
+fn file_links(files: &[PathBuf]) -> String {
+    files
+        .iter()
+        .map(|path| {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            format!(
+                "<a href=\"{}\">{}</a>",
+                glib::markup_escape_text(&gio::File::for_path(path).uri()),
+                glib::markup_escape_text(&name)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Labels the Run button with what it is going to run, e.g. `Run 5 commands (1 skipped)`.
+fn update_run_label(run_button: &gtk::Button, state: &AppState, positions: &[usize]) {
+    let (commands, rejected) = collect_selected_commands(state, positions);
+    let label = run_label(commands.len(), rejected.len());
+    run_button.set_label(&label);
+    run_button.update_property(&[gtk::accessible::Property::Label(&label)]);
+}
+
+fn run_label(commands: usize, skipped: usize) -> String {
+    let mut label = match commands {
+        0 | 1 if skipped == 0 => return "Run".to_string(),
+        1 => "Run 1 command".to_string(),
+        count => format!("Run {count} commands"),
+    };
+    if skipped > 0 {
+        label.push_str(&format!(" ({skipped} skipped)"));
+    }
+    label
+}
+
+/// Commands to run, and the names of those skipped because they cannot run in a batch. In
+/// multi-select mode these are the commands selected in every category.
+fn collect_selected_commands(
+    state: &AppState,
+    positions: &[usize],
+) -> (Vec<Rc<ListNode>>, Vec<String>) {
+    let nodes: Vec<&Rc<ListNode>> = if state.multi_select {
+        state.selected_commands.iter().collect()
+    } else {
+        positions
+            .iter()
+            .filter_map(|&position| state.entries.get(position))
+            .filter(|entry| !entry.is_up_dir && !entry.has_children)
+            .filter_map(|entry| entry.node.as_ref())
+            .collect()
+    };
+    let mut commands = Vec::new();
+    let mut rejected = Vec::new();
+    let multiple = nodes.len() > 1;
+
+    for node in nodes {
+        if multiple && !node.multi_select {
+            rejected.push(node.name.clone());
+        } else {
+            commands.push(node.clone());
+        }
+    }
+    (commands, rejected)
+}
+
+fn queue_commands(state: &AppState, nodes: Vec<Rc<ListNode>>) -> Vec<QueuedCommand> {
+    nodes
+        .into_iter()
+        .map(|node| QueuedCommand {
+            path: state
+                .tabs
+                .command_path(&node)
+                .map_or_else(|| node.name.clone(), |path| path.join(" / ")),
+            node,
+            env: Vec::new(),
+            answers: Vec::new(),
+            on_failure: None,
+        })
+        .collect()
+}
+
+/// Checks the scripts of `commands` and, unless the integrity policy stops them, confirms
+/// and runs them.
+fn confirm_and_run(parent: &gtk::Window, commands: Vec<QueuedCommand>, skip: bool) {
+    let (policy, problems) = integrity::check(&commands);
+    if problems.is_empty() {
+        check_network(parent, commands, skip);
+        return;
+    }
+    let problems = problems
+        .iter()
+        .map(|problem| format!("• {problem}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    if policy == IntegrityPolicy::Refuse {
+        show_info_dialog(
+            parent,
+            "Scripts Modified",
+            &format!("Not running, the scripts do not match their checksums:\n{problems}"),
+        );
+        return;
+    }
+    let message = format!(
+        "The scripts do not match their checksums:\n{problems}\n\nOnly run them if you changed them yourself."
+    );
+    let (dialog, run_button, cancel_button) =
+        build_confirmation_dialog(parent, "Scripts Modified", &message);
+    run_button.set_label("Run Anyway");
+    run_button.update_property(&[
+        gtk::accessible::Property::Label("Run anyway"),
+        gtk::accessible::Property::Description("Run the commands with the modified scripts."),
+    ]);
+    // Running modified scripts has to be chosen, not just confirmed with Enter
+    dialog.set_default_widget(Some(&cancel_button));
+    let parent = parent.clone();
+    let dialog_clone = dialog.clone();
+    run_button.connect_clicked(move |_| {
+        dialog_clone.close();
+        check_network(&parent, commands.clone(), skip);
+    });
+    let dialog_clone = dialog.clone();
+    cancel_button.connect_clicked(move |_| dialog_clone.close());
+}
+
+/// Checks the network first when any of `commands` downloads, offering to retry or continue
+/// offline while it is down, then confirms and runs them.
+fn check_network(parent: &gtk::Window, commands: Vec<QueuedCommand>, skip: bool) {
+    if !commands
+        .iter()
+        .any(|command| network::needs_network(&command.node))
+    {
+        confirm_commands(parent, commands, skip);
+        return;
+    }
+    let parent = parent.clone();
+    glib::spawn_future_local(async move {
+        let checked = gio::spawn_blocking(network::check)
+            .await
+            .unwrap_or_else(|_| Err("The network check stopped unexpectedly.".to_string()));
+        let Err(problem) = checked else {
+            confirm_commands(&parent, commands, skip);
+            return;
+        };
+        let message = format!(
+            "{problem}\n\nThe selected commands download files and are likely to fail \
+             without a network connection."
+        );
+        let (dialog, retry_button, cancel_button) =
+            build_confirmation_dialog(&parent, "No Network Connection", &message);
+        retry_button.set_label("Retry");
+        retry_button.update_property(&[
+            gtk::accessible::Property::Label("Retry"),
+            gtk::accessible::Property::Description("Check the network connection again."),
+        ]);
+        let offline_button = gtk::Button::with_label("Continue Offline");
+        offline_button.update_property(&[
+            gtk::accessible::Property::Label("Continue offline"),
+            gtk::accessible::Property::Description(
+                "Run the commands without a network connection.",
+            ),
+        ]);
+        if let Some(button_box) = dialog
+            .child()
+            .and_downcast::<gtk::Box>()
+            .and_then(|box_root| box_root.last_child())
+            .and_downcast::<gtk::Box>()
+        {
+            button_box.prepend(&offline_button);
+        }
+
+        let dialog_clone = dialog.clone();
+        let parent_clone = parent.clone();
+        let commands_clone = commands.clone();
+        retry_button.connect_clicked(move |_| {
+            dialog_clone.close();
+            check_network(&parent_clone, commands_clone.clone(), skip);
+        });
+        let dialog_clone = dialog.clone();
+        offline_button.connect_clicked(move |_| {
+            dialog_clone.close();
+            confirm_commands(&parent, commands.clone(), skip);
+        });
+        let dialog_clone = dialog.clone();
+        cancel_button.connect_clicked(move |_| dialog_clone.close());
+    });
+}
+
+fn confirm_commands(parent: &gtk::Window, commands: Vec<QueuedCommand>, skip: bool) {
+    let default_policy = Settings::load().runs.on_failure;
+    if skip {
+        if let Some(app) = parent.application() {
+            open_command_window(&app, commands, default_policy);
+        }
+        return;
+    }
+
+    let names = commands
+        .iter()
+        .map(|c| c.node.name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut message = format!("Run the following command(s)?\n{names}");
+    // Updates and driver changes cut off by an empty battery can leave the system unbootable
+    if commands.iter().any(|command| power::is_long(&command.path))
+        && power::on_battery() == Some(true)
+    {
+        message.push_str(
+            "\n\nThe system runs on battery. Updates and driver changes take a while and can \
+             leave the system unable to boot if it runs out of power, so connect the charger \
+             first.",
+        );
+        if !Settings::load().runs.inhibit_suspend {
+            message.push_str(
+                " Prevent Suspend While Running in the main menu keeps it from suspending \
+                 meanwhile.",
+            );
+        }
+    }
+    let container = Platform::current().container;
+    if let Some(container) = container {
+        message.push_str(&format!(
+            "\n\nLinutil runs in a {} container, so the commands change the container, not \
+             the host.",
+            container.name()
+        ));
+    }
+    let parent = parent.clone();
+    let parent_clone = parent.clone();
+    let (dialog, run_button, cancel_button) =
+        build_confirmation_dialog(&parent_clone, "Confirm Commands", &message);
+    let abort_check = gtk::CheckButton::with_label("Stop at the first failed command");
+    abort_check.set_active(default_policy == FailurePolicy::Abort);
+    abort_check.update_property(&[
+        gtk::accessible::Property::Label("Stop at the first failed command"),
+        gtk::accessible::Property::Description(
+            "Skip the remaining commands when one of them fails.",
+        ),
+    ]);
+    let review_button = gtk::Button::with_label("Review one by one");
+    review_button.update_property(&[
+        gtk::accessible::Property::Label("Review one by one"),
+        gtk::accessible::Property::Description(
+            "Step through the commands with their descriptions and pick which ones to run.",
+        ),
+    ]);
+    // Only a batch has remaining commands, and playbooks decide what happens to them. The
+    // check box goes between the message and the buttons, the review button before Cancel.
+    if commands.len() > 1 {
+        if let Some(box_root) = dialog.child().and_downcast::<gtk::Box>() {
+            if commands.iter().any(|command| command.on_failure.is_none()) {
+                box_root.insert_child_after(&abort_check, box_root.first_child().as_ref());
+            }
+            if let Some(button_box) = box_root.last_child().and_downcast::<gtk::Box>() {
+                button_box.prepend(&review_button);
+            }
+        }
+    }
+    // Containers with a way to the host offer running there, remembered for later runs
+    if container.and_then(Container::host_exec).is_some() {
+        let host_check = gtk::CheckButton::with_label("Run on the host instead");
+        host_check.set_active(Settings::load().runs.run_on_host);
+        host_check.update_property(&[
+            gtk::accessible::Property::Label("Run on the host instead"),
+            gtk::accessible::Property::Description(
+                "Run the commands on the host system rather than in the container.",
+            ),
+        ]);
+        host_check.connect_toggled(|check| {
+            let mut settings = Settings::load();
+            settings.runs.run_on_host = check.is_active();
+            if let Err(err) = settings.save() {
+                eprintln!("Failed to save settings: {err}");
+            }
+        });
+        if let Some(box_root) = dialog.child().and_downcast::<gtk::Box>() {
+            box_root.insert_child_after(&host_check, box_root.first_child().as_ref());
+        }
+    }
+    let failure_policy = {
+        let abort_check = abort_check.clone();
+        move || {
+            if abort_check.is_active() {
+                FailurePolicy::Abort
+            } else {
+                FailurePolicy::Continue
+            }
+        }
+    };
+    let dialog_clone = dialog.clone();
+    let commands_clone = commands.clone();
+    let failure_policy_clone = failure_policy.clone();
+    run_button.connect_clicked(move |_| {
+        dialog_clone.close();
+        if let Some(app) = parent_clone.application() {
+            open_command_window(&app, commands_clone.clone(), failure_policy_clone());
+        }
+    });
+    let dialog_clone = dialog.clone();
+    let commands_clone = commands.clone();
+    review_button.connect_clicked(move |_| {
+        dialog_clone.close();
+        review_commands(&parent, commands_clone.clone(), failure_policy());
+    });
+    let dialog_clone = dialog.clone();
+    cancel_button.connect_clicked(move |_| {
+        dialog_clone.close();
+    });
+}
+
+/// Confirms a batch one command at a time, showing each description with Run, Skip and Run
+/// all remaining choices, then runs the accepted commands.
+fn review_commands(parent: &gtk::Window, commands: Vec<QueuedCommand>, on_failure: FailurePolicy) {
+    let dialog = gtk::Window::builder()
+        .title("Review Commands")
+        .transient_for(parent)
+        .modal(true)
+        .default_width(480)
+        .default_height(260)
+        .build();
+    dialog.set_accessible_role(gtk::AccessibleRole::AlertDialog);
+
+    let box_root = gtk::Box::new(gtk::Orientation::Vertical, 12);
+    box_root.set_margin_top(12);
+    box_root.set_margin_bottom(12);
+    box_root.set_margin_start(12);
+    box_root.set_margin_end(12);
+    let step_label = gtk::Label::new(None);
+    step_label.set_xalign(0.0);
+    step_label.update_property(&[gtk::accessible::Property::Label("Review step")]);
+    let name_label = gtk::Label::new(None);
+    name_label.set_xalign(0.0);
+    name_label.set_wrap(true);
+    name_label.add_css_class("heading");
+    let description = gtk::TextView::new();
+    description.set_editable(false);
+    description.set_cursor_visible(false);
+    description.set_wrap_mode(gtk::WrapMode::WordChar);
+    description.set_vexpand(true);
+    description.set_accessible_role(gtk::AccessibleRole::TextBox);
+    description.update_property(&[
+        gtk::accessible::Property::Label("Command description"),
+        gtk::accessible::Property::ReadOnly(true),
+    ]);
+
+    let button_box = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+    button_box.set_halign(gtk::Align::End);
+    let cancel = gtk::Button::with_label("Cancel");
+    let skip = gtk::Button::with_label("Skip");
+    let run_all = gtk::Button::with_label("Run all remaining");
+    let run = gtk::Button::with_label("Run");
+    cancel.update_property(&[
+        gtk::accessible::Property::Label("Cancel"),
+        gtk::accessible::Property::Description("Cancel without running anything."),
+    ]);
+    skip.update_property(&[
+        gtk::accessible::Property::Label("Skip"),
+        gtk::accessible::Property::Description("Leave this command out of the batch."),
+    ]);
+    run_all.update_property(&[
+        gtk::accessible::Property::Label("Run all remaining"),
+        gtk::accessible::Property::Description(
+            "Include this and every following command without asking.",
+        ),
+    ]);
+    run.update_property(&[
+        gtk::accessible::Property::Label("Run"),
+        gtk::accessible::Property::Description("Include this command in the batch."),
+    ]);
+    button_box.append(&cancel);
+    button_box.append(&skip);
+    button_box.append(&run_all);
+    button_box.append(&run);
+
+    box_root.append(&step_label);
+    box_root.append(&name_label);
+    box_root.append(&description);
+    box_root.append(&button_box);
+    dialog.set_child(Some(&box_root));
+    dialog.set_default_widget(Some(&run));
+
+    let commands = Rc::new(commands);
+    let accepted: Rc<RefCell<Vec<QueuedCommand>>> = Rc::new(RefCell::new(Vec::new()));
+    let position = Rc::new(Cell::new(0));
+    let show_step = {
+        let commands = commands.clone();
+        let position = position.clone();
+        let dialog = dialog.clone();
+        move || {
+            let Some(command) = commands.get(position.get()) else {
+                return;
+            };
+            let step = format!("Command {} of {}", position.get() + 1, commands.len());
+            step_label.set_text(&step);
+            name_label.set_text(&command.path);
+            let text = if command.node.description.is_empty() {
+                "No description available."
+            } else {
+                command.node.description.as_str()
+            };
+            description.buffer().set_text(text);
+            dialog.update_property(&[
+                gtk::accessible::Property::Label(&format!("{step}: {}", command.path)),
+                gtk::accessible::Property::Description(text),
+            ]);
+        }
+    };
+    show_step();
+
+    // Moves past the current command, including it and, with `all`, every later one too
+    let advance = {
+        let dialog = dialog.clone();
+        let parent = parent.clone();
+        move |include: bool, all: bool| {
+            let current = position.get();
+            let end = if all { commands.len() } else { current + 1 };
+            if include {
+                accepted
+                    .borrow_mut()
+                    .extend(commands[current..end].iter().cloned());
+            }
+            position.set(end);
+            if end < commands.len() {
+                show_step();
+                return;
+            }
+            dialog.close();
+            let accepted = accepted.take();
+            if accepted.is_empty() {
+                return;
+            }
+            if let Some(app) = parent.application() {
+                open_command_window(&app, accepted, on_failure);
+            }
+        }
+    };
+    let advance = Rc::new(advance);
+
+    let advance_clone = advance.clone();
+    run.connect_clicked(move |_| advance_clone(true, false));
+    let advance_clone = advance.clone();
+    skip.connect_clicked(move |_| advance_clone(false, false));
+    run_all.connect_clicked(move |_| advance(true, true));
+    let dialog_clone = dialog.clone();
+    cancel.connect_clicked(move |_| dialog_clone.close());
+    dialog.show();
+}
+
+pub fn build_confirmation_dialog(
+    parent: &gtk::Window,
+    title: &str,
+    message: &str,
+) -> (gtk::Window, gtk::Button, gtk::Button) {
+    let dialog = gtk::Window::builder()
+        .title(title)
+        .transient_for(parent)
+        .modal(true)
+        .default_width(420)
+        .default_height(180)
+        .build();
+    dialog.set_accessible_role(gtk::AccessibleRole::AlertDialog);
+    dialog.update_property(&[
+        gtk::accessible::Property::Label(title),
+        gtk::accessible::Property::Description(message),
+    ]);
+
+    let box_root = gtk::Box::new(gtk::Orientation::Vertical, 12);
+    box_root.set_margin_top(12);
+    box_root.set_margin_bottom(12);
+    box_root.set_margin_start(12);
+    box_root.set_margin_end(12);
+    let label = gtk::TextView::new();
+    label.set_editable(false);
+    label.set_cursor_visible(false);
+    label.set_wrap_mode(gtk::WrapMode::WordChar);
+    label.set_focusable(true);
+    label.set_accessible_role(gtk::AccessibleRole::TextBox);
+    label.buffer().set_text(message);
+    label.update_property(&[
+        gtk::accessible::Property::Label("Commands to run"),
+        gtk::accessible::Property::Description(message),
+        gtk::accessible::Property::ReadOnly(true),
+    ]);
+
+    let button_box = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+    button_box.set_halign(gtk::Align::End);
+    let cancel = gtk::Button::with_label("Cancel");
+    let run = gtk::Button::with_label("Run");
+    cancel.update_property(&[
+        gtk::accessible::Property::Label("Cancel"),
+        gtk::accessible::Property::Description("Cancel running the selected commands."),
+    ]);
+    run.update_property(&[
+        gtk::accessible::Property::Label("Run"),
+        gtk::accessible::Property::Description("Run the selected commands."),
+    ]);
+    button_box.append(&cancel);
+    button_box.append(&run);
+
+    box_root.append(&label);
+    box_root.append(&button_box);
+    dialog.set_child(Some(&box_root));
+    dialog.update_relation(&[
+        gtk::accessible::Relation::LabelledBy(&[label.upcast_ref()]),
+        gtk::accessible::Relation::DescribedBy(&[label.upcast_ref()]),
+    ]);
+    dialog.set_default_widget(Some(&run));
+    gtk::prelude::GtkWindowExt::set_focus(&dialog, Some(&label));
+    dialog.show();
+    (dialog, run, cancel)
+}
+
+fn show_save_preset_dialog(parent: &gtk::Window, commands: Vec<Rc<ListNode>>) {
+    if commands.is_empty() {
+        show_toast(
+            parent,
+            "No command queued",
+            "Select the commands to save in the preset.",
+        );
         return;
     }
+    let dialog = gtk::Window::builder()
+        .title("Save preset")
+        .transient_for(parent)
+        .modal(true)
+        .default_width(420)
+        .build();
+    dialog.update_property(&[
+        gtk::accessible::Property::Label("Save preset"),
+        gtk::accessible::Property::Description("Save the queued commands under a name."),
+    ]);
+
+    let box_root = gtk::Box::new(gtk::Orientation::Vertical, 12);
+    box_root.set_margin_top(12);
+    box_root.set_margin_bottom(12);
+    box_root.set_margin_start(12);
+    box_root.set_margin_end(12);
+    let name_entry = gtk::Entry::new();
+    name_entry.set_placeholder_text(Some("Preset name"));
+    name_entry.set_activates_default(true);
+    name_entry.update_property(&[
+        gtk::accessible::Property::Label("Preset name"),
+        gtk::accessible::Property::Placeholder("Preset name"),
+    ]);
+    let hint = gtk::Label::new(Some(&format!(
+        "Presets are saved in {} and can be passed to --config.",
+        presets::preset_dir().display()
+    )));
+    hint.set_xalign(0.0);
+    hint.set_wrap(true);
+
+    let button_box = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+    button_box.set_halign(gtk::Align::End);
+    let cancel = gtk::Button::with_label("Cancel");
+    let save = gtk::Button::with_label("Save");
+    save.update_property(&[
+        gtk::accessible::Property::Label("Save"),
+        gtk::accessible::Property::Description(
+            "Save the preset, replacing one with the same name.",
+        ),
+    ]);
+    button_box.append(&cancel);
+    button_box.append(&save);
+
+    box_root.append(&name_entry);
+    box_root.append(&hint);
+    box_root.append(&button_box);
+    dialog.set_child(Some(&box_root));
+    dialog.set_default_widget(Some(&save));
+
+    let dialog_clone = dialog.clone();
+    cancel.connect_clicked(move |_| dialog_clone.close());
+    let dialog_clone = dialog.clone();
+    let parent = parent.clone();
+    save.connect_clicked(move |_| {
+        let name = name_entry.text();
+        if name.trim().is_empty() {
+            name_entry.grab_focus();
+            return;
+        }
+        dialog_clone.close();
+        if let Err(err) = presets::save_preset(&name, &commands) {
+            show_info_dialog(
+                &parent,
+                "Could not save preset",
+                &format!("Saving the preset failed: {err}"),
+            );
+        }
+    });
+    dialog.show();
+}
+
+/// Lets the user edit their own tags of `node`, calling `on_save` with the new ones.
+fn show_edit_tags_dialog(
+    parent: &gtk::Window,
+    node: &ListNode,
+    current: &[String],
+    on_save: impl Fn(Vec<String>) + 'static,
+) {
+    let dialog = gtk::Window::builder()
+        .title(format!("Tags of {}", node.name))
+        .transient_for(parent)
+        .modal(true)
+        .default_width(420)
+        .build();
+    dialog.update_property(&[
+        gtk::accessible::Property::Label(&format!("Tags of {}", node.name)),
+        gtk::accessible::Property::Description("Edit your own tags of the command."),
+    ]);
+
+    let box_root = gtk::Box::new(gtk::Orientation::Vertical, 12);
+    box_root.set_margin_top(12);
+    box_root.set_margin_bottom(12);
+    box_root.set_margin_start(12);
+    box_root.set_margin_end(12);
+    let tags_entry = gtk::Entry::new();
+    tags_entry.set_text(&current.join(", "));
+    tags_entry.set_placeholder_text(Some("e.g. fresh-install, work"));
+    tags_entry.set_activates_default(true);
+    tags_entry.update_property(&[
+        gtk::accessible::Property::Label("Your tags"),
+        gtk::accessible::Property::Description("Tags separated by commas or spaces."),
+    ]);
+    let mut hint = "Search for a tag with tag:<name>.".to_string();
+    if !node.tags.is_empty() {
+        hint = format!("Built-in tags: {}. {hint}", node.tags.join(", "));
+    }
+    let hint = gtk::Label::new(Some(&hint));
+    hint.set_xalign(0.0);
+    hint.set_wrap(true);
+
+    let button_box = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+    button_box.set_halign(gtk::Align::End);
+    let cancel = gtk::Button::with_label("Cancel");
+    let save = gtk::Button::with_label("Save");
+    save.update_property(&[
+        gtk::accessible::Property::Label("Save"),
+        gtk::accessible::Property::Description("Save the tags of the command."),
+    ]);
+    button_box.append(&cancel);
+    button_box.append(&save);
+
+    box_root.append(&tags_entry);
+    box_root.append(&hint);
+    box_root.append(&button_box);
+    dialog.set_child(Some(&box_root));
+    dialog.set_default_widget(Some(&save));
 
-    let names = commands
-        .iter()
-        .map(|c| c.name.as_str())
-        .collect::<Vec<_>>()
-        .join(", ");
-    let message = format!("Run the following command(s)?\n{names}");
-    let parent = parent.clone();
-    let parent_clone = parent.clone();
-    let (dialog, run_button, cancel_button) =
-        build_confirmation_dialog(&parent_clone, "Confirm Commands", &message);
     let dialog_clone = dialog.clone();
-    let commands_clone = commands.clone();
-    run_button.connect_clicked(move |_| {
-        dialog_clone.close();
-        if let Some(app) = parent_clone.application() {
-            open_command_window(&app, commands_clone.clone());
-        }
-    });
+    cancel.connect_clicked(move |_| dialog_clone.close());
     let dialog_clone = dialog.clone();
-    cancel_button.connect_clicked(move |_| {
+    save.connect_clicked(move |_| {
         dialog_clone.close();
+        on_save(tags::parse_tags(&tags_entry.text()));
     });
+    dialog.show();
 }
 
-fn build_confirmation_dialog(
+/// Lets the user pick a saved preset, replaces `selected_commands` with its commands and
+/// calls `on_load`.
+fn show_load_preset_dialog(
     parent: &gtk::Window,
-    title: &str,
-    message: &str,
-) -> (gtk::Window, gtk::Button, gtk::Button) {
+    state: Rc<RefCell<AppState>>,
+    on_load: impl Fn() + 'static,
+) {
     let dialog = gtk::Window::builder()
-        .title(title)
+        .title("Load preset")
         .transient_for(parent)
         .modal(true)
         .default_width(420)
-        .default_height(180)
+        .default_height(360)
         .build();
-    dialog.set_accessible_role(gtk::AccessibleRole::AlertDialog);
     dialog.update_property(&[
-        gtk::accessible::Property::Label(title),
-        gtk::accessible::Property::Description(message),
+        gtk::accessible::Property::Label("Load preset"),
+        gtk::accessible::Property::Description("Pick a saved preset to queue its commands."),
     ]);
 
     let box_root = gtk::Box::new(gtk::Orientation::Vertical, 12);
@@ -714,48 +4934,123 @@ fn build_confirmation_dialog(
     box_root.set_margin_bottom(12);
     box_root.set_margin_start(12);
     box_root.set_margin_end(12);
-    let label = gtk::TextView::new();
-    label.set_editable(false);
-    label.set_cursor_visible(false);
-    label.set_wrap_mode(gtk::WrapMode::WordChar);
-    label.set_focusable(true);
-    label.set_accessible_role(gtk::AccessibleRole::TextBox);
-    label.buffer().set_text(message);
-    label.update_property(&[
-        gtk::accessible::Property::Label("Commands to run"),
-        gtk::accessible::Property::Description(message),
-        gtk::accessible::Property::ReadOnly(true),
+    let preset_list = gtk::ListBox::new();
+    preset_list.set_selection_mode(gtk::SelectionMode::Single);
+    preset_list.set_placeholder(Some(&gtk::Label::new(Some("No presets saved."))));
+    preset_list.update_property(&[
+        gtk::accessible::Property::Label("Presets"),
+        gtk::accessible::Property::Description("Saved presets, by name."),
     ]);
+    let shown: Rc<RefCell<Vec<Preset>>> = Rc::new(RefCell::new(Vec::new()));
+    let fill_list = {
+        let preset_list = preset_list.clone();
+        let shown = shown.clone();
+        move || {
+            while let Some(row) = preset_list.first_child() {
+                preset_list.remove(&row);
+            }
+            let presets = presets::list_presets();
+            for preset in &presets {
+                let label = gtk::Label::new(Some(&preset.name));
+                label.set_xalign(0.0);
+                preset_list.append(&label);
+            }
+            preset_list.select_row(preset_list.row_at_index(0).as_ref());
+            *shown.borrow_mut() = presets;
+        }
+    };
+    fill_list();
+    let scroll = gtk::ScrolledWindow::new();
+    scroll.set_policy(gtk::PolicyType::Never, gtk::PolicyType::Automatic);
+    scroll.set_vexpand(true);
+    scroll.set_child(Some(&preset_list));
 
     let button_box = gtk::Box::new(gtk::Orientation::Horizontal, 8);
-    button_box.set_halign(gtk::Align::End);
-    let cancel = gtk::Button::with_label("Cancel");
-    let run = gtk::Button::with_label("Run");
-    cancel.update_property(&[
-        gtk::accessible::Property::Label("Cancel"),
-        gtk::accessible::Property::Description("Cancel running the selected commands."),
+    let delete = gtk::Button::with_label("Delete");
+    delete.set_hexpand(true);
+    delete.set_halign(gtk::Align::Start);
+    delete.update_property(&[
+        gtk::accessible::Property::Label("Delete"),
+        gtk::accessible::Property::Description("Delete the selected preset."),
     ]);
-    run.update_property(&[
-        gtk::accessible::Property::Label("Run"),
-        gtk::accessible::Property::Description("Run the selected commands."),
+    let cancel = gtk::Button::with_label("Cancel");
+    let load = gtk::Button::with_label("Load");
+    load.update_property(&[
+        gtk::accessible::Property::Label("Load"),
+        gtk::accessible::Property::Description("Replace the queue with the selected preset."),
     ]);
+    button_box.append(&delete);
     button_box.append(&cancel);
-    button_box.append(&run);
+    button_box.append(&load);
 
-    box_root.append(&label);
+    box_root.append(&scroll);
     box_root.append(&button_box);
     dialog.set_child(Some(&box_root));
-    dialog.update_relation(&[
-        gtk::accessible::Relation::LabelledBy(&[label.upcast_ref()]),
-        gtk::accessible::Relation::DescribedBy(&[label.upcast_ref()]),
-    ]);
-    dialog.set_default_widget(Some(&run));
-    gtk::prelude::GtkWindowExt::set_focus(&dialog, Some(&label));
+    dialog.set_default_widget(Some(&load));
+
+    let preset_list_clone = preset_list.clone();
+    let shown_clone = shown.clone();
+    let dialog_clone = dialog.clone();
+    delete.connect_clicked(move |_| {
+        let Some(row) = preset_list_clone.selected_row() else {
+            return;
+        };
+        let result = match shown_clone.borrow().get(row.index() as usize) {
+            Some(preset) => presets::delete_preset(preset),
+            None => return,
+        };
+        match result {
+            Ok(()) => fill_list(),
+            Err(err) => show_info_dialog(
+                dialog_clone.upcast_ref(),
+                "Could not delete preset",
+                &format!("Deleting the preset failed: {err}"),
+            ),
+        }
+    });
+    let dialog_clone = dialog.clone();
+    cancel.connect_clicked(move |_| dialog_clone.close());
+    let dialog_clone = dialog.clone();
+    let parent = parent.clone();
+    load.connect_clicked(move |_| {
+        let Some(row) = preset_list.selected_row() else {
+            return;
+        };
+        let Some(path) = shown
+            .borrow()
+            .get(row.index() as usize)
+            .map(|preset| preset.path.clone())
+        else {
+            return;
+        };
+        dialog_clone.close();
+        let loaded = presets::load_preset(&path, &state.borrow().tabs);
+        match loaded {
+            Ok((commands, missing)) => {
+                state.borrow_mut().selected_commands = commands;
+                on_load();
+                if !missing.is_empty() {
+                    show_toast(
+                        &parent,
+                        "Some commands were not found",
+                        &format!(
+                            "These commands of the preset do not exist and were skipped:\n{}",
+                            missing.join(", ")
+                        ),
+                    );
+                }
+            }
+            Err(err) => show_info_dialog(
+                &parent,
+                "Could not load preset",
+                &format!("Loading the preset failed: {err}"),
+            ),
+        }
+    });
     dialog.show();
-    (dialog, run, cancel)
 }
 
-fn show_info_dialog(parent: &gtk::Window, title: &str, message: &str) {
+pub fn show_info_dialog(parent: &gtk::Window, title: &str, message: &str) {
     let dialog = gtk::Window::builder()
         .title(title)
         .transient_for(parent)
@@ -800,42 +5095,238 @@ fn show_info_dialog(parent: &gtk::Window, title: &str, message: &str) {
     dialog.show();
 }
 
-fn open_command_window(app: &gtk::Application, commands: Vec<Rc<ListNode>>) {
+/// Progress line of a run, e.g. `Command 3 of 7: Applications Setup / Kitty (1m 5s)`, the
+/// fraction of commands done and their number.
+fn run_progress(records: &[CommandRecord]) -> (String, f64, usize) {
+    let total = records.len();
+    let done = records
+        .iter()
+        .filter(|record| {
+            matches!(
+                record.status,
+                CommandStatus::Finished { .. } | CommandStatus::Skipped
+            )
+        })
+        .count();
+    let running = records
+        .iter()
+        .enumerate()
+        .find(|(_, record)| matches!(record.status, CommandStatus::Running));
+    let mut text = match running {
+        Some((index, record)) => {
+            let elapsed = record
+                .started_at
+                .and_then(|started| Duration::try_from(runner::now() - started).ok())
+                .unwrap_or_default();
+            format!(
+                "Command {} of {total}: {} ({})",
+                index + 1,
+                record.path,
+                report::format_duration(elapsed)
+            )
+        }
+        None if done == total => format!("{done} of {total} commands done"),
+        None => format!("Starting command {} of {total}", done + 1),
+    };
+    // The last command that ran, so its time can be seen after it finished
+    let previous = records
+        .iter()
+        .rfind(|record| matches!(record.status, CommandStatus::Finished { .. }));
+    if let Some((name, duration)) =
+        previous.and_then(|record| Some((&record.name, record.duration?)))
+    {
+        text.push_str(&format!(
+            " · {name} took {}",
+            report::format_duration(duration)
+        ));
+    }
+    let fraction = if total == 0 {
+        1.0
+    } else {
+        done as f64 / total as f64
+    };
+    (text, fraction, done)
+}
+
+/// Runs `commands`, showing their output in the pane of the main window when the settings ask
+/// for it and the pane is not busy with another run, otherwise in a new window.
+fn open_command_window(
+    app: &gtk::Application,
+    commands: Vec<QueuedCommand>,
+    on_failure: FailurePolicy,
+) {
+    let settings = Settings::load();
+    let pane = settings
+        .runs
+        .output_pane
+        .then(|| OUTPUT_PANE.with(|pane| pane.borrow().as_ref().and_then(OutputPane::idle)))
+        .flatten();
+    if let Some(pane) = pane {
+        let pane_clone = pane.clone();
+        let view = command_view(commands, on_failure, &settings, true, move || {
+            pane_clone.set_visible(false);
+            pane_clone.set_child(None::<&gtk::Widget>);
+        });
+        pane.set_child(Some(&view.widget));
+        pane.set_expanded(true);
+        pane.set_visible(true);
+        OUTPUT_PANE.with(|output_pane| {
+            if let Some(output_pane) = output_pane.borrow_mut().as_mut() {
+                output_pane.finished = view.finished;
+            }
+        });
+        return;
+    }
+
     let window = gtk::ApplicationWindow::builder()
         .application(app)
         .title("Command Output")
-        .default_width(900)
-        .default_height(600)
         .build();
+    let window_weak = window.downgrade();
+    let view = command_view(commands, on_failure, &settings, false, move || {
+        if let Some(window) = window_weak.upgrade() {
+            window.close();
+        }
+    });
+    let finished = view.finished.clone();
+    let runner = view.runner.clone();
+    confirm_close_while_running(
+        &window,
+        "A command is still running. Stop it and close the window, or keep it running in the \
+         background. It stays listed under Jobs in the main window and its log is saved when \
+         it is done.",
+        {
+            let runner = runner.clone();
+            move || !finished.get() && !runner.borrow().is_stopped()
+        },
+        move || runner.borrow_mut().kill(),
+    );
+    remember_geometry(&window, &settings.windows.output, |windows| {
+        &mut windows.output
+    });
+    window.set_child(Some(&view.widget));
+    window.show();
+}
+
+/// Pane at the bottom of the main window showing the output of runs, used instead of output
+/// windows when [`RunSettings::output_pane`] is set.
+struct OutputPane {
+    expander: glib::WeakRef<gtk::Expander>,
+    /// Whether the run shown in the pane is done.
+    finished: Rc<Cell<bool>>,
+}
+
+impl OutputPane {
+    /// The pane, unless it shows a run that is still going.
+    fn idle(&self) -> Option<gtk::Expander> {
+        let expander = self.expander.upgrade()?;
+        (self.finished.get() || expander.child().is_none()).then_some(expander)
+    }
+}
+
+thread_local! {
+    static OUTPUT_PANE: RefCell<Option<OutputPane>> = const { RefCell::new(None) };
+}
+
+/// Output, input and controls of a run.
+struct CommandView {
+    widget: gtk::Box,
+    /// Set once every command is done.
+    finished: Rc<Cell<bool>>,
+    runner: Rc<RefCell<CommandRunner>>,
+}
 
+/// Starts running `commands` and builds the view following them. `embedded` gives the output a
+/// fixed height for the pane of the main window, and Close calls `on_close`.
+fn command_view(
+    commands: Vec<QueuedCommand>,
+    on_failure: FailurePolicy,
+    settings: &Settings,
+    embedded: bool,
+    on_close: impl Fn() + 'static,
+) -> CommandView {
+    let settings = settings.clone();
     let root_box = gtk::Box::new(gtk::Orientation::Vertical, 8);
     root_box.set_hexpand(true);
-    root_box.set_vexpand(true);
-    root_box.set_margin_top(12);
-    root_box.set_margin_bottom(12);
-    root_box.set_margin_start(12);
-    root_box.set_margin_end(12);
+    root_box.set_vexpand(!embedded);
+    if !embedded {
+        root_box.set_margin_top(12);
+        root_box.set_margin_bottom(12);
+        root_box.set_margin_start(12);
+        root_box.set_margin_end(12);
+    }
 
     let status_box = gtk::Box::new(gtk::Orientation::Horizontal, 8);
     let status_label = gtk::Label::new(Some("Running..."));
     status_label.set_xalign(0.0);
     status_label.set_hexpand(true);
     status_label.update_property(&[gtk::accessible::Property::Label("Command status")]);
+    let policy_label = gtk::Label::new(Some(on_failure.label()));
+    policy_label.update_property(&[
+        gtk::accessible::Property::Label("Failure policy"),
+        gtk::accessible::Property::Description(
+            "Whether the remaining commands run after a command fails.",
+        ),
+    ]);
     let stop_button = gtk::Button::with_label("Stop");
     let save_button = gtk::Button::with_label("Save Log");
+    let report_format = gtk::DropDown::from_strings(
+        &ReportFormat::ALL
+            .iter()
+            .map(ReportFormat::label)
+            .collect::<Vec<_>>(),
+    );
+    let export_button = gtk::Button::with_label("Export Report");
+    export_button.set_sensitive(false);
     let close_button = gtk::Button::with_label("Close");
     stop_button.update_property(&[
         gtk::accessible::Property::Label("Stop"),
         gtk::accessible::Property::Description("Stop the running command."),
     ]);
+    // Entries activate actions of the view's `run` group added further down
+    let signal_menu = gio::Menu::new();
+    signal_menu.append(Some("Send End of Input (Ctrl+D)"), Some("run.send-eof"));
+    for (label, signal) in [
+        ("Interrupt (SIGINT)", "SIGINT"),
+        ("Terminate (SIGTERM)", "SIGTERM"),
+        ("Kill (SIGKILL)", "SIGKILL"),
+    ] {
+        signal_menu.append(Some(label), Some(&format!("run.signal::{signal}")));
+    }
+    let signal_button = gtk::MenuButton::new();
+    signal_button.set_label("Signal");
+    signal_button.set_menu_model(Some(&signal_menu));
+    signal_button.update_property(&[
+        gtk::accessible::Property::Label("Signal"),
+        gtk::accessible::Property::Description(
+            "End the input of the running command or send it a signal. The queued commands \
+             still run after it exits.",
+        ),
+    ]);
     save_button.update_property(&[
         gtk::accessible::Property::Label("Save log"),
         gtk::accessible::Property::Description("Save the command output to a file."),
     ]);
+    report_format.update_property(&[
+        gtk::accessible::Property::Label("Report format"),
+        gtk::accessible::Property::Description("Choose the file format of the exported report."),
+    ]);
+    export_button.update_property(&[
+        gtk::accessible::Property::Label("Export report"),
+        gtk::accessible::Property::Description(
+            "Export a summary of the finished commands with durations, exit codes and logs.",
+        ),
+    ]);
     close_button.update_property(&[gtk::accessible::Property::Label("Close")]);
     status_box.append(&status_label);
+    if commands.len() > 1 {
+        status_box.append(&policy_label);
+    }
     status_box.append(&stop_button);
+    status_box.append(&signal_button);
     status_box.append(&save_button);
+    status_box.append(&report_format);
+    status_box.append(&export_button);
     status_box.append(&close_button);
 
     let output_view = gtk::TextView::new();
@@ -848,56 +5339,202 @@ fn open_command_window(app: &gtk::Application, commands: Vec<Rc<ListNode>>) {
     let output_scroll = gtk::ScrolledWindow::new();
     output_scroll.set_policy(gtk::PolicyType::Automatic, gtk::PolicyType::Automatic);
     output_scroll.set_hexpand(true);
-    output_scroll.set_vexpand(true);
+    output_scroll.set_vexpand(!embedded);
+    if embedded {
+        output_scroll.set_min_content_height(220);
+    }
     output_scroll.set_child(Some(&output_view));
 
     let input_entry = gtk::Entry::new();
-    input_entry.set_placeholder_text(Some("Type input for the command and press Enter"));
-    input_entry.update_property(&[
-        gtk::accessible::Property::Label("Command input"),
+    input_entry.update_property(&[gtk::accessible::Property::Label("Command input")]);
+    mask_input(&input_entry, false);
+    input_entry.set_hexpand(true);
+    // Sends keys typed in the output to the command, for editors and menus
+    let terminal_toggle = gtk::ToggleButton::with_label("Terminal Input");
+    terminal_toggle.update_property(&[
+        gtk::accessible::Property::Label("Terminal input"),
         gtk::accessible::Property::Description(
-            "Type input and press Enter to send it to the command.",
+            "Send every key typed in the output to the command, including arrows, Tab and \
+             Ctrl+C, and text pasted with Ctrl+Shift+V. Ctrl+Shift+T turns it off again.",
         ),
-        gtk::accessible::Property::Placeholder("Type input for the command and press Enter"),
     ]);
+    // Buttons answering the question the command asks, if it is a common one
+    let answers_box = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+    answers_box.add_css_class("linked");
+    answers_box.set_visible(false);
+    answers_box.update_property(&[gtk::accessible::Property::Label("Quick answers")]);
+    let (paste_button, paste_view) = paste_lines_button();
+    let input_box = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+    input_box.append(&input_entry);
+    input_box.append(&paste_button);
+    input_box.append(&answers_box);
+    input_box.append(&terminal_toggle);
+
+    let progress_box = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+    let progress_label = gtk::Label::new(None);
+    progress_label.set_xalign(0.0);
+    progress_label.set_hexpand(true);
+    progress_label.set_ellipsize(gtk::pango::EllipsizeMode::Middle);
+    progress_label.update_property(&[gtk::accessible::Property::Label("Current command")]);
+    let progress_bar = gtk::ProgressBar::new();
+    progress_bar.set_show_text(true);
+    progress_bar.set_valign(gtk::Align::Center);
+    progress_bar.set_size_request(200, -1);
+    progress_bar.update_property(&[
+        gtk::accessible::Property::Label("Run progress"),
+        gtk::accessible::Property::Description("How many of the queued commands are done."),
+    ]);
+    progress_box.append(&progress_label);
+    progress_box.append(&progress_bar);
 
     root_box.append(&status_box);
+    root_box.append(&progress_box);
     root_box.append(&output_scroll);
-    root_box.append(&input_entry);
-    window.set_child(Some(&root_box));
+    root_box.append(&input_box);
+    let finished = Rc::new(Cell::new(false));
+    // Closed windows and replaced panes drop out of the list of jobs
+    root_box.connect_unmap(|_| {
+        glib::idle_add_local_once(jobs::changed);
+    });
 
     let output_buffer = output_view.buffer();
-    let runner = Rc::new(RefCell::new(CommandRunner::spawn(&commands)));
-    let last_len = Rc::new(RefCell::new(0usize));
+    let runner = Rc::new(RefCell::new(CommandRunner::spawn(
+        &commands, &settings, on_failure,
+    )));
+    let recorded = recording::add_run(&commands, on_failure);
+    let names: Vec<&str> = commands
+        .iter()
+        .map(|command| command.node.name.as_str())
+        .collect();
+    jobs::register(&names, runner.clone(), &root_box, finished.clone());
+    let events = runner.borrow().events();
     let output_buffer_clone = output_buffer.clone();
     let output_view_clone = output_view.clone();
     let status_label_clone = status_label.clone();
     let stop_button_clone = stop_button.clone();
+    let export_button_clone = export_button.clone();
     let input_entry_clone = input_entry.clone();
+    let terminal_toggle_clone = terminal_toggle.clone();
+    let answers_box_clone = answers_box.clone();
+    let paste_button_clone = paste_button.clone();
+    let signal_button_clone = signal_button.clone();
     let runner_clone = runner.clone();
-    let last_len_clone = last_len.clone();
-    timeout_add_local(Duration::from_millis(50), move || {
-        let mut offset = last_len_clone.borrow_mut();
-        let chunk = runner_clone.borrow().read_output_since(&mut offset);
-        if !chunk.is_empty() {
-            let mut end = output_buffer_clone.end_iter();
-            output_buffer_clone.insert(&mut end, &chunk);
-            let mut end = output_buffer_clone.end_iter();
-            output_view_clone.scroll_to_iter(&mut end, 0.0, false, 0.0, 0.0);
-        }
-
-        if let Some(success) = runner_clone.borrow().finished() {
-            if success {
-                status_label_clone.set_text("Finished successfully.");
-            } else {
-                status_label_clone.set_text("Finished with errors.");
+    let finished_clone = finished.clone();
+    let root_box_weak = root_box.downgrade();
+    let shown_prompt = Cell::new(None);
+    let batch_bytes = settings.performance.output_batch_kib.max(1) * 1024;
+    glib::spawn_future_local(async move {
+        while let Ok(event) = events.recv().await {
+            // Coalesce everything that is already waiting into one insert
+            let mut batch = String::new();
+            // Whether the batch replaces the last line shown
+            let mut rewrite = false;
+            let mut finished = None;
+            let mut next = Some(event);
+            while let Some(event) = next.take() {
+                match event {
+                    RunEvent::Output(chunk) => batch.push_str(&chunk),
+                    RunEvent::Rewrite(chunk) => {
+                        match batch.rfind('\n') {
+                            Some(index) => batch.truncate(index + 1),
+                            None => {
+                                batch.clear();
+                                rewrite = true;
+                            }
+                        }
+                        batch.push_str(&chunk);
+                    }
+                    RunEvent::Finished { success } => finished = Some(success),
+                }
+                if batch.len() < batch_bytes && finished.is_none() {
+                    next = events.try_recv().ok();
+                }
+            }
+
+            if !batch.is_empty() || rewrite {
+                append_output(
+                    &output_view_clone,
+                    &output_buffer_clone,
+                    &batch,
+                    rewrite,
+                    &settings.performance,
+                );
+                mask_input(
+                    &input_entry_clone,
+                    runner_clone.borrow().awaiting_password(),
+                );
+                let prompt = Prompt::detect(&runner_clone.borrow().last_line());
+                if shown_prompt.replace(prompt) != prompt {
+                    show_quick_answers(&answers_box_clone, prompt, &runner_clone);
+                }
+            }
+            if let Some(success) = finished {
+                let outcome = if success {
+                    "Finished successfully."
+                } else {
+                    "Finished with errors."
+                };
+                match runner_clone.borrow().save_log() {
+                    Ok(path) => {
+                        status_label_clone.set_text(&format!("{outcome} Log saved to {path}"))
+                    }
+                    Err(err) => {
+                        status_label_clone.set_text(&format!("{outcome} Failed to save log: {err}"))
+                    }
+                }
+                logs::apply_retention(&settings.logging);
+                history::record_run(&runner_clone.borrow().records());
+                if let Some(first) = recorded {
+                    recording::add_answers(first, &runner_clone.borrow().typed_answers());
+                }
+                stop_button_clone.set_sensitive(false);
+                export_button_clone.set_sensitive(true);
+                input_entry_clone.set_sensitive(false);
+                paste_button_clone.set_sensitive(false);
+                signal_button_clone.set_sensitive(false);
+                terminal_toggle_clone.set_active(false);
+                terminal_toggle_clone.set_sensitive(false);
+                answers_box_clone.set_visible(false);
+                finished_clone.set(true);
+                jobs::changed();
+                // Only the main window has this, for runs in its pane
+                if let Some(root_box) = root_box_weak.upgrade() {
+                    let _ = root_box.activate_action("win.history-changed", None);
+                }
+            }
+            // Pausing between updates lets GTK draw and handle input under heavy output
+            if !batch.is_empty() && settings.performance.output_interval_ms > 0 {
+                glib::timeout_future(Duration::from_millis(
+                    settings.performance.output_interval_ms,
+                ))
+                .await;
             }
-            stop_button_clone.set_sensitive(false);
-            input_entry_clone.set_sensitive(false);
-            return ControlFlow::Break;
         }
+    });
 
-        ControlFlow::Continue
+    let runner_clone = runner.clone();
+    let root_box_weak = root_box.downgrade();
+    let update_progress = move || {
+        let records = runner_clone.borrow().records();
+        let (text, fraction, done) = run_progress(&records);
+        progress_label.set_text(&text);
+        progress_bar.set_fraction(fraction);
+        progress_bar.set_text(Some(&format!("{done} of {} done", records.len())));
+        done == records.len()
+    };
+    update_progress();
+    // Ticks every second so the elapsed time of the running command stays current
+    glib::timeout_add_local(Duration::from_secs(1), move || {
+        // Closing removes the view from its pane or closes its window
+        let closed = root_box_weak
+            .upgrade()
+            .and_then(|root_box| root_box.root())
+            .is_none_or(|window| !window.is_visible());
+        if closed || update_progress() {
+            ControlFlow::Break
+        } else {
+            ControlFlow::Continue
+        }
     });
 
     let runner_clone = runner.clone();
@@ -905,6 +5542,24 @@ fn open_command_window(app: &gtk::Application, commands: Vec<Rc<ListNode>>) {
         runner_clone.borrow_mut().kill();
     });
 
+    let run_actions = gio::SimpleActionGroup::new();
+    let send_eof_action = gio::SimpleAction::new("send-eof", None);
+    let runner_clone = runner.clone();
+    send_eof_action.connect_activate(move |_, _| runner_clone.borrow().send_eof());
+    run_actions.add_action(&send_eof_action);
+    let signal_action = gio::SimpleAction::new("signal", Some(glib::VariantTy::STRING));
+    let runner_clone = runner.clone();
+    signal_action.connect_activate(move |_, parameter| {
+        if let Some(signal) = parameter
+            .and_then(|parameter| parameter.get::<String>())
+            .and_then(|name| name.parse().ok())
+        {
+            runner_clone.borrow().signal(signal);
+        }
+    });
+    run_actions.add_action(&signal_action);
+    root_box.insert_action_group("run", Some(&run_actions));
+
     let runner_clone = runner.clone();
     input_entry.connect_activate(move |entry| {
         let text = entry.text().to_string();
@@ -912,6 +5567,7 @@ fn open_command_window(app: &gtk::Application, commands: Vec<Rc<ListNode>>) {
         if !text.is_empty() {
             runner_clone.borrow().send_input(&format!("{text}\n"));
         }
+        mask_input(entry, false);
     });
 
     let runner_clone = runner.clone();
@@ -921,27 +5577,130 @@ fn open_command_window(app: &gtk::Application, commands: Vec<Rc<ListNode>>) {
         Err(err) => status_label_clone.set_text(&format!("Failed to save log: {err}")),
     });
 
-    let window_clone = window.clone();
-    close_button.connect_clicked(move |_| window_clone.close());
+    let runner_clone = runner.clone();
+    let status_label_clone = status_label.clone();
+    let report_format_clone = report_format.clone();
+    export_button.connect_clicked(move |_| {
+        let format = ReportFormat::ALL[report_format_clone.selected() as usize];
+        let runner = runner_clone.borrow();
+        match report::save_report(format, &runner.records(), &runner.output()) {
+            Ok(path) => status_label_clone.set_text(&format!("Saved report to {path}")),
+            Err(err) => status_label_clone.set_text(&format!("Failed to save report: {err}")),
+        }
+    });
+
+    close_button.connect_clicked(move |_| on_close());
+
+    let runner_clone = runner.clone();
+    let paste_button_clone = paste_button.clone();
+    paste_view.connect_send(move |text| {
+        paste_button_clone.popdown();
+        // One line per main loop turn keeps large pastes from blocking the window
+        let mut lines = terminal_input::paste_lines(text).into_iter();
+        let runner = runner_clone.clone();
+        glib::timeout_add_local(Duration::from_millis(20), move || match lines.next() {
+            Some(line) => {
+                runner.borrow().send_input(&line);
+                ControlFlow::Continue
+            }
+            None => ControlFlow::Break,
+        });
+    });
+
+    // Runs before the text view handles keys itself, e.g. Ctrl+C to copy
+    let terminal_keys = gtk::EventControllerKey::new();
+    terminal_keys.set_propagation_phase(gtk::PropagationPhase::Capture);
+    // Composes accented and non-Latin characters typed through an input method
+    let im_context = gtk::IMMulticontext::new();
+    im_context.set_client_widget(Some(&output_view));
+    let runner_clone = runner.clone();
+    im_context.connect_commit(move |_, text| {
+        runner_clone.borrow().type_bytes(text.as_bytes());
+    });
+
+    let output_view_clone = output_view.clone();
+    let terminal_keys_clone = terminal_keys.clone();
+    terminal_toggle.connect_toggled(move |toggle| {
+        if toggle.is_active() {
+            terminal_keys_clone.set_im_context(Some(&im_context));
+            im_context.focus_in();
+            output_view_clone.grab_focus();
+        } else {
+            im_context.focus_out();
+            im_context.reset();
+            terminal_keys_clone.set_im_context(None::<&gtk::IMContext>);
+        }
+        output_view_clone.update_property(&[gtk::accessible::Property::Description(
+            if toggle.is_active() {
+                "Keys typed here go to the command. Ctrl+Shift+T stops sending them."
+            } else {
+                "Live output from the command."
+            },
+        )]);
+    });
+    let terminal_toggle_clone = terminal_toggle.clone();
+    let output_view_clone = output_view.clone();
+    let runner_clone = runner.clone();
+    terminal_keys.connect_key_pressed(move |_, key, _, modifiers| {
+        let ctrl = modifiers.contains(gtk::gdk::ModifierType::CONTROL_MASK);
+        let shift = modifiers.contains(gtk::gdk::ModifierType::SHIFT_MASK);
+        let alt = modifiers.contains(gtk::gdk::ModifierType::ALT_MASK);
+        let text = key.to_unicode();
+        // Left for the view's shortcut turning terminal input off
+        let toggles = ctrl && shift && text.map(|c| c.to_ascii_lowercase()) == Some('t');
+        if !terminal_toggle_clone.is_active() || toggles {
+            return Propagation::Proceed;
+        }
+        if ctrl && shift && text.map(|c| c.to_ascii_lowercase()) == Some('v') {
+            let clipboard = output_view_clone.clipboard();
+            let runner_clone = runner_clone.clone();
+            glib::spawn_future_local(async move {
+                if let Ok(Some(text)) = clipboard.read_text_future().await {
+                    runner_clone.borrow().paste(&text);
+                }
+            });
+            return Propagation::Stop;
+        }
+        let Some(bytes) = key
+            .name()
+            .and_then(|name| terminal_input::encode_key(&name, text, ctrl, alt))
+        else {
+            return Propagation::Proceed;
+        };
+        runner_clone.borrow().type_bytes(&bytes);
+        Propagation::Stop
+    });
+    output_view.add_controller(terminal_keys);
 
     let input_entry_clone = input_entry.clone();
     let output_view_clone = output_view.clone();
     let stop_button_clone = stop_button.clone();
     let save_button_clone = save_button.clone();
     let close_button_clone = close_button.clone();
+    let terminal_toggle_clone = terminal_toggle.clone();
     let key_controller = gtk::EventControllerKey::new();
     key_controller.connect_key_pressed(move |_, key, _, modifiers| {
         let ctrl = modifiers.contains(gtk::gdk::ModifierType::CONTROL_MASK);
+        let shift = modifiers.contains(gtk::gdk::ModifierType::SHIFT_MASK);
         let key_char = key.to_unicode().map(|c| c.to_ascii_lowercase());
 
         if ctrl && key_char == Some('s') {
             save_button_clone.emit_clicked();
             return Propagation::Stop;
         }
+        if ctrl && shift && key_char == Some('t') && terminal_toggle_clone.is_sensitive() {
+            terminal_toggle_clone.set_active(!terminal_toggle_clone.is_active());
+            return Propagation::Stop;
+        }
         if ctrl && key_char == Some('w') {
             close_button_clone.emit_clicked();
             return Propagation::Stop;
         }
+        // Works in every output window, and in the pane before the main window sees it
+        if ctrl && shift && key_char == Some('k') {
+            jobs::stop_and_close_all();
+            return Propagation::Stop;
+        }
         if ctrl && key_char == Some('k') {
             stop_button_clone.emit_clicked();
             return Propagation::Stop;
@@ -956,169 +5715,181 @@ fn open_command_window(app: &gtk::Application, commands: Vec<Rc<ListNode>>) {
         }
         Propagation::Proceed
     });
-    window.add_controller(key_controller);
+    root_box.add_controller(key_controller);
 
-    window.show();
+    CommandView {
+        widget: root_box,
+        finished,
+        runner,
+    }
 }
 
-impl CommandRunner {
-    fn spawn(commands: &[Rc<ListNode>]) -> Self {
-        let pty_system = NativePtySystem::default();
-        let mut cmd: CommandBuilder = CommandBuilder::new("sh");
-        cmd.arg("-c");
-
-        cmd.env("TERM", "xterm-256color");
-        cmd.env("COLORTERM", "truecolor");
-        cmd.env("FORCE_COLOR", "1");
-        cmd.env("NO_COLOR", "");
-
-        let mut script = String::new();
-        for node in commands {
-            match &node.command {
-                Command::Raw(prompt) => {
-                    script.push_str(prompt);
-                    script.push('\n');
-                }
-                Command::LocalFile { executable, args, file } => {
-                    if let Some(parent) = file.parent() {
-                        script.push_str(&format!("cd {}\n", parent.display()));
-                    }
-                    script.push_str(executable);
-                    for arg in args {
-                        script.push(' ');
-                        script.push_str(arg);
-                    }
-                    script.push('\n');
-                }
-                Command::None => {}
-            }
-        }
-
-        cmd.arg(script);
-
-        let pair = pty_system
-            .openpty(PtySize {
-                rows: 24,
-                cols: 80,
-                pixel_width: 0,
-                pixel_height: 0,
-            })
-            .unwrap();
-
-        let mut child = pair.slave.spawn_command(cmd).unwrap();
-        let child_killer = child.clone_killer();
-        let output = Arc::new(Mutex::new(String::new()));
-        let output_clone = output.clone();
-        let finished = Arc::new(Mutex::new(None));
-        let finished_clone = finished.clone();
-
-        let mut reader = pair.master.try_clone_reader().unwrap();
-        thread::spawn(move || {
-            let mut buf = [0u8; 8192];
-            loop {
-                match reader.read(&mut buf) {
-                    Ok(size) if size == 0 => break,
-                    Ok(size) => {
-                        let chunk = String::from_utf8_lossy(&buf[..size]).to_string();
-                        let chunk = strip_ansi(&chunk);
-                        if !chunk.is_empty() {
-                            if let Ok(mut output) = output_clone.lock() {
-                                output.push_str(&chunk);
-                            }
-                        }
-                    }
-                    Err(_) => break,
-                }
-            }
-        });
+/// Editor for several lines of input, e.g. a config snippet a script asks for, which the
+/// command gets one line at a time.
+struct PasteView {
+    text_view: gtk::TextView,
+    send_button: gtk::Button,
+}
 
-        thread::spawn(move || {
-            let status = child.wait().unwrap();
-            if let Ok(mut finished) = finished_clone.lock() {
-                *finished = Some(status.success());
+impl PasteView {
+    /// Calls `on_send` with the text when Send is pressed, then empties the editor.
+    fn connect_send(&self, on_send: impl Fn(&str) + 'static) {
+        let buffer = self.text_view.buffer();
+        self.send_button.connect_clicked(move |_| {
+            let text = buffer.text(&buffer.start_iter(), &buffer.end_iter(), false);
+            if !text.is_empty() {
+                on_send(&text);
             }
+            buffer.set_text("");
         });
+    }
+}
 
-        let writer = pair.master.take_writer().unwrap();
+/// Button opening the editor for several lines of input.
+fn paste_lines_button() -> (gtk::MenuButton, PasteView) {
+    let text_view = gtk::TextView::new();
+    text_view.set_monospace(true);
+    text_view.update_property(&[
+        gtk::accessible::Property::Label("Lines to send"),
+        gtk::accessible::Property::Description(
+            "Paste or type several lines. Send gives them to the command one line at a time.",
+        ),
+        gtk::accessible::Property::MultiLine(true),
+    ]);
+    let scroll = gtk::ScrolledWindow::new();
+    scroll.set_min_content_width(420);
+    scroll.set_min_content_height(160);
+    scroll.set_child(Some(&text_view));
+    let send_button = gtk::Button::with_label("Send");
+    send_button.set_halign(gtk::Align::End);
+    send_button.update_property(&[gtk::accessible::Property::Description(
+        "Send the lines to the command, each followed by Enter.",
+    )]);
+    let popover_box = gtk::Box::new(gtk::Orientation::Vertical, 8);
+    popover_box.append(&scroll);
+    popover_box.append(&send_button);
+    let popover = gtk::Popover::new();
+    popover.set_child(Some(&popover_box));
+    let text_view_clone = text_view.clone();
+    popover.connect_show(move |_| {
+        text_view_clone.grab_focus();
+    });
 
-        Self {
-            output,
-            writer: Arc::new(Mutex::new(writer)),
-            child_killer: Arc::new(Mutex::new(Some(child_killer))),
-            finished,
-            _pty_master: pair.master,
-        }
-    }
+    let button = gtk::MenuButton::new();
+    button.set_label("Lines…");
+    button.set_popover(Some(&popover));
+    button.update_property(&[
+        gtk::accessible::Property::Label("Send several lines"),
+        gtk::accessible::Property::Description(
+            "Open an editor for input of several lines, such as a config snippet.",
+        ),
+    ]);
+    (
+        button,
+        PasteView {
+            text_view,
+            send_button,
+        },
+    )
+}
 
-    fn send_input(&self, input: &str) {
-        if let Ok(mut writer) = self.writer.lock() {
-            let _ = writer.write_all(input.as_bytes());
-            let _ = writer.flush();
-        }
+/// Fills `answers_box` with buttons sending the answers to `prompt`, hiding it without one.
+/// Answering hides the buttons until the command asks again.
+fn show_quick_answers(
+    answers_box: &gtk::Box,
+    prompt: Option<Prompt>,
+    runner: &Rc<RefCell<CommandRunner>>,
+) {
+    while let Some(child) = answers_box.first_child() {
+        answers_box.remove(&child);
     }
-
-    fn kill(&mut self) {
-        if let Ok(mut killer) = self.child_killer.lock() {
-            if let Some(mut killer) = killer.take() {
-                let _ = killer.kill();
+    answers_box.set_visible(prompt.is_some());
+    let Some(prompt) = prompt else {
+        return;
+    };
+    for (label, input) in prompt.answers() {
+        let button = gtk::Button::with_label(label);
+        let answer = input.trim_end();
+        button.update_property(&[
+            gtk::accessible::Property::Label(&format!("Answer {label}")),
+            gtk::accessible::Property::Description(&if answer.is_empty() {
+                "Press Enter to take the default answer.".to_string()
+            } else {
+                format!("Send “{answer}” and Enter to the command.")
+            }),
+        ]);
+        let runner = runner.clone();
+        // The buttons belong to the box, so holding a strong reference would leak both
+        let answers_box_weak = answers_box.downgrade();
+        button.connect_clicked(move |_| {
+            runner.borrow().send_input(input);
+            if let Some(answers_box) = answers_box_weak.upgrade() {
+                answers_box.set_visible(false);
             }
-        }
+        });
+        answers_box.append(&button);
     }
+}
 
-    fn save_log(&self) -> Result<String, std::io::Error> {
-        let mut log_path = std::env::temp_dir();
-        let date_format = format_description!("[year]-[month]-[day]-[hour]-[minute]-[second]");
-        log_path.push(format!(
-            "linutil_log_{}.log",
-            OffsetDateTime::now_local()
-                .unwrap_or(OffsetDateTime::now_utc())
-                .format(&date_format)
-                .unwrap()
-        ));
+/// Hides what is typed in `input_entry` while the command asks for a password, e.g. for
+/// `sudo`, and shows it again otherwise.
+fn mask_input(input_entry: &gtk::Entry, password: bool) {
+    let (placeholder, description) = if password {
+        (
+            "Type the password and press Enter",
+            "The command asks for a password. What is typed stays hidden.",
+        )
+    } else {
+        (
+            "Type input for the command and press Enter",
+            "Type input and press Enter to send it to the command.",
+        )
+    };
+    input_entry.set_visibility(!password);
+    input_entry.set_placeholder_text(Some(placeholder));
+    input_entry.update_property(&[
+        gtk::accessible::Property::Description(description),
+        gtk::accessible::Property::Placeholder(placeholder),
+    ]);
+}
 
-        let output = self.output.lock().unwrap();
-        std::fs::write(&log_path, output.as_str())?;
-        Ok(log_path.to_string_lossy().into_owned())
-    }
+/// Appends `text` to the output view, drops the oldest lines beyond the configured limit
+/// and scrolls as configured.
+fn append_output(
+    output_view: &gtk::TextView,
+    buffer: &gtk::TextBuffer,
+    text: &str,
+    rewrite: bool,
+    performance: &PerformanceSettings,
+) {
+    perf::measure("render_output", || {
+        let follow = match performance.output_scroll {
+            OutputScroll::Follow => true,
+            OutputScroll::AtBottom => output_view.vadjustment().is_none_or(|adjustment| {
+                adjustment.value() + adjustment.page_size() >= adjustment.upper() - 1.0
+            }),
+            OutputScroll::Off => false,
+        };
 
-    fn read_output_since(&self, offset: &mut usize) -> String {
-        let output = self.output.lock().unwrap();
-        if *offset >= output.len() {
-            return String::new();
+        let mut end = buffer.end_iter();
+        if rewrite {
+            let mut line_start = end;
+            line_start.set_line_offset(0);
+            buffer.delete(&mut line_start, &mut end);
         }
-        let chunk = output[*offset..].to_string();
-        *offset = output.len();
-        chunk
-    }
-
-    fn finished(&self) -> Option<bool> {
-        let finished = self.finished.lock().unwrap();
-        *finished
-    }
-}
+        buffer.insert(&mut end, text);
 
-fn strip_ansi(input: &str) -> String {
-    let mut result = String::new();
-    let mut chars = input.chars().peekable();
-    while let Some(ch) = chars.next() {
-        if ch == '\u{1b}' {
-            if chars.peek() == Some(&'[') {
-                chars.next();
-                while let Some(next) = chars.next() {
-                    if ('@'..='~').contains(&next) {
-                        break;
-                    }
-                }
+        let max_lines = i32::try_from(performance.output_max_lines).unwrap_or(i32::MAX);
+        let excess = buffer.line_count() - max_lines;
+        if max_lines > 0 && excess > 0 {
+            if let Some(mut cut) = buffer.iter_at_line(excess) {
+                buffer.delete(&mut buffer.start_iter(), &mut cut);
             }
-            continue;
         }
-        result.push(ch);
-    }
-    result
-}
 
-fn clear_list_box(list_box: &gtk::ListBox) {
-    while let Some(child) = list_box.first_child() {
-        list_box.remove(&child);
-    }
+        if follow {
+            let mut end = buffer.end_iter();
+            output_view.scroll_to_iter(&mut end, 0.0, false, 0.0, 0.0);
+        }
+    })
 }
@@ -0,0 +1,200 @@
+// Quick read-only checks of the system's health for the Health view. Each finding names the
+// linutil command that helps, if there is one.
+
+use nix::sys::statvfs::statvfs;
+use std::{fs, path::Path, process::Command};
+
+// Free space below this share of a file system is reported
+const LOW_DISK_PERCENT: u64 = 10;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Finding {
+    pub check: &'static str,
+    pub ok: bool,
+    pub summary: String,
+    /// Name of the command fixing the problem.
+    pub fix: Option<&'static str>,
+}
+
+/// Runs every check that applies to this system. Blocks while asking systemd and the journal.
+pub fn run_checks() -> Vec<Finding> {
+    [
+        failed_units(),
+        Some(disk_space()),
+        Some(pending_reboot()),
+        missing_firmware(),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+fn failed_units() -> Option<Finding> {
+    let output = Command::new("systemctl")
+        .args(["--failed", "--no-legend", "--plain"])
+        .output()
+        .ok()?;
+    let units = parse_failed_units(&String::from_utf8_lossy(&output.stdout));
+    Some(Finding {
+        check: "Failed services",
+        ok: units.is_empty(),
+        summary: if units.is_empty() {
+            "No systemd units failed.".to_string()
+        } else {
+            format!("Failed: {}", units.join(", "))
+        },
+        fix: (!units.is_empty()).then_some("Service Manager"),
+    })
+}
+
+/// Unit names from `systemctl --failed --no-legend --plain`.
+fn parse_failed_units(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .map(ToString::to_string)
+        .collect()
+}
+
+fn disk_space() -> Finding {
+    let mut low = Vec::new();
+    let mut seen = Vec::new();
+    for mount in ["/", "/home"] {
+        let Ok(stats) = statvfs(mount) else {
+            continue;
+        };
+        // `/home` is often on the root file system
+        if seen.contains(&stats.filesystem_id()) {
+            continue;
+        }
+        seen.push(stats.filesystem_id());
+        let percent = free_percent(stats.blocks_available(), stats.blocks());
+        if percent < LOW_DISK_PERCENT {
+            low.push(format!("{mount} has {percent}% free"));
+        }
+    }
+    Finding {
+        check: "Disk space",
+        ok: low.is_empty(),
+        summary: if low.is_empty() {
+            format!("At least {LOW_DISK_PERCENT}% free on / and /home.")
+        } else {
+            low.join(", ")
+        },
+        fix: (!low.is_empty()).then_some("Full System Cleanup"),
+    }
+}
+
+fn free_percent(available: u64, total: u64) -> u64 {
+    (available * 100).checked_div(total).unwrap_or(100)
+}
+
+fn pending_reboot() -> Finding {
+    let kernel = fs::read_to_string("/proc/sys/kernel/osrelease").unwrap_or_default();
+    let reason = reboot_reason(
+        Path::new("/run/reboot-required").exists(),
+        kernel.trim(),
+        |kernel| {
+            ["/usr/lib/modules", "/lib/modules"]
+                .iter()
+                .any(|dir| Path::new(dir).join(kernel).is_dir())
+        },
+    );
+    Finding {
+        check: "Pending reboot",
+        ok: reason.is_none(),
+        summary: reason.unwrap_or_else(|| "No reboot needed.".to_string()),
+        fix: None,
+    }
+}
+
+/// Why a reboot is due: Debian-based systems flag it in `/run/reboot-required`, and after
+/// a kernel update on other systems the modules of the running kernel are gone.
+fn reboot_reason(
+    flagged: bool,
+    kernel: &str,
+    has_modules: impl Fn(&str) -> bool,
+) -> Option<String> {
+    if flagged {
+        Some("Updates asked for a reboot.".to_string())
+    } else if !kernel.is_empty() && !has_modules(kernel) {
+        Some(format!(
+            "The running kernel {kernel} was replaced by an update; reboot to use the new one."
+        ))
+    } else {
+        None
+    }
+}
+
+fn missing_firmware() -> Option<Finding> {
+    let output = Command::new("journalctl")
+        .args(["--dmesg", "--boot", "--quiet", "--no-pager", "--output=cat"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let files = parse_missing_firmware(&String::from_utf8_lossy(&output.stdout));
+    Some(Finding {
+        check: "Firmware",
+        ok: files.is_empty(),
+        summary: if files.is_empty() {
+            "No firmware failed to load.".to_string()
+        } else {
+            format!("Failed to load: {}", files.join(", "))
+        },
+        fix: (!files.is_empty()).then_some("Full System Update"),
+    })
+}
+
+/// Firmware files the kernel could not find, from lines like
+/// `Direct firmware load for iwlwifi-ty-a0-gf-a0-89.ucode failed with error -2`.
+fn parse_missing_firmware(log: &str) -> Vec<String> {
+    let mut files: Vec<String> = Vec::new();
+    for line in log.lines() {
+        let Some((_, rest)) = line.split_once("Direct firmware load for ") else {
+            continue;
+        };
+        let Some((file, _)) = rest.split_once(" failed") else {
+            continue;
+        };
+        if !files.iter().any(|known| known == file) {
+            files.push(file.to_string());
+        }
+    }
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            parse_failed_units(
+                "bluetooth.service loaded failed failed Bluetooth service\n\
+                 fwupd-refresh.service loaded failed failed Refresh fwupd metadata\n"
+            ),
+            ["bluetooth.service", "fwupd-refresh.service"]
+        );
+        assert!(parse_failed_units("").is_empty());
+
+        let log = "iwlwifi 0000:00:14.3: Direct firmware load for iwlwifi-so-a0-gf-a0-89.ucode failed with error -2\n\
+                   usb 1-1: new high-speed USB device\n\
+                   iwlwifi 0000:00:14.3: Direct firmware load for iwlwifi-so-a0-gf-a0-89.ucode failed with error -2\n";
+        assert_eq!(
+            parse_missing_firmware(log),
+            ["iwlwifi-so-a0-gf-a0-89.ucode"]
+        );
+    }
+
+    #[test]
+    fn test_checks() {
+        assert_eq!(free_percent(5, 100), 5);
+        assert_eq!(free_percent(0, 0), 100);
+
+        assert!(reboot_reason(true, "6.11.4-arch1-1", |_| true).is_some());
+        assert!(reboot_reason(false, "6.11.4-arch1-1", |_| false).is_some());
+        assert_eq!(reboot_reason(false, "6.11.4-arch1-1", |_| true), None);
+        assert_eq!(reboot_reason(false, "", |_| false), None);
+    }
+}
@@ -0,0 +1,118 @@
+use crate::health::{run_checks, Finding};
+use gtk::{gio, glib, prelude::*};
+use gtk4 as gtk;
+
+/// Health view: the findings of the read-only checks, each with a button running the command
+/// that fixes it through `win.run-command`.
+pub struct HealthView {
+    pub widget: gtk::Box,
+    status: gtk::Label,
+    findings: gtk::ListBox,
+}
+
+impl HealthView {
+    pub fn new() -> Self {
+        let widget = gtk::Box::new(gtk::Orientation::Vertical, 12);
+        widget.set_vexpand(true);
+        widget.update_property(&[
+            gtk::accessible::Property::Label("Health"),
+            gtk::accessible::Property::Description(
+                "Checks of the system with commands that fix what they find.",
+            ),
+        ]);
+
+        let title_box = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+        let title = gtk::Label::new(Some("System health"));
+        title.set_xalign(0.0);
+        title.set_hexpand(true);
+        title.add_css_class("title-2");
+        let recheck_button = gtk::Button::with_label("Check Again");
+        recheck_button.update_property(&[
+            gtk::accessible::Property::Label("Check again"),
+            gtk::accessible::Property::Description("Run the health checks again."),
+        ]);
+        title_box.append(&title);
+        title_box.append(&recheck_button);
+
+        let status = gtk::Label::new(None);
+        status.set_xalign(0.0);
+        status.add_css_class("dim-label");
+        status.set_accessible_role(gtk::AccessibleRole::Status);
+        let findings = gtk::ListBox::new();
+        findings.set_selection_mode(gtk::SelectionMode::None);
+        findings.update_property(&[gtk::accessible::Property::Label("Findings")]);
+
+        widget.append(&title_box);
+        widget.append(&status);
+        widget.append(&findings);
+
+        let view = Self {
+            widget,
+            status,
+            findings,
+        };
+        let status = view.status.clone();
+        let findings = view.findings.clone();
+        recheck_button.connect_clicked(move |_| check(&status, &findings));
+        view
+    }
+
+    /// Runs the checks in the background and shows their findings.
+    pub fn refresh(&self) {
+        check(&self.status, &self.findings);
+    }
+}
+
+fn check(status: &gtk::Label, findings: &gtk::ListBox) {
+    status.set_text("Checking…");
+    let status = status.downgrade();
+    let findings = findings.downgrade();
+    glib::spawn_future_local(async move {
+        let results = gio::spawn_blocking(run_checks).await.unwrap_or_default();
+        let (Some(status), Some(findings)) = (status.upgrade(), findings.upgrade()) else {
+            return;
+        };
+        while let Some(child) = findings.first_child() {
+            findings.remove(&child);
+        }
+        let problems = results.iter().filter(|finding| !finding.ok).count();
+        status.set_text(&match problems {
+            0 => "Everything looks fine.".to_string(),
+            1 => "1 problem found.".to_string(),
+            count => format!("{count} problems found."),
+        });
+        for finding in &results {
+            findings.append(&finding_row(finding));
+        }
+    });
+}
+
+fn finding_row(finding: &Finding) -> gtk::Box {
+    let row = gtk::Box::new(gtk::Orientation::Horizontal, 12);
+    row.set_margin_top(6);
+    row.set_margin_bottom(6);
+    let mark = gtk::Label::new(Some(if finding.ok { "✓" } else { "⚠" }));
+    let text = gtk::Label::new(Some(&format!("{}\n{}", finding.check, finding.summary)));
+    text.set_xalign(0.0);
+    text.set_wrap(true);
+    text.set_hexpand(true);
+    text.set_selectable(true);
+    let state = if finding.ok { "OK" } else { "Problem" };
+    row.update_property(&[gtk::accessible::Property::Label(&format!(
+        "{}: {state}. {}",
+        finding.check, finding.summary
+    ))]);
+    row.append(&mark);
+    row.append(&text);
+    if let Some(fix) = finding.fix {
+        let fix_button = gtk::Button::with_label(&format!("Run {fix}"));
+        fix_button.set_valign(gtk::Align::Center);
+        fix_button.set_action_name(Some("win.run-command"));
+        fix_button.set_action_target_value(Some(&fix.to_variant()));
+        fix_button.update_property(&[gtk::accessible::Property::Description(&format!(
+            "Run the {fix} command to fix this."
+        ))]);
+        row.append(&fix_button);
+    }
+    row
+}
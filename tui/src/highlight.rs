@@ -0,0 +1,153 @@
+// Minimal shell syntax highlighting for the script viewer. It only has to make the scripts
+// bundled with linutil readable, so it recognizes comments, quoted strings, variables and
+// keywords instead of parsing the full shell grammar.
+
+use std::ops::Range;
+
+const KEYWORDS: [&str; 20] = [
+    "if", "then", "else", "elif", "fi", "for", "while", "until", "do", "done", "case", "esac",
+    "in", "function", "return", "local", "export", "readonly", "break", "continue",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Token {
+    Comment,
+    String,
+    Variable,
+    Keyword,
+}
+
+/// Highlighted parts of `source` as ranges of character offsets, in order.
+pub fn highlight_shell(source: &str) -> Vec<(Range<usize>, Token)> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+    while pos < chars.len() {
+        let ch = chars[pos];
+        let at_word_start = pos == 0 || !is_word_char(chars[pos - 1]);
+        let end = match ch {
+            '#' if pos == 0 || chars[pos - 1].is_whitespace() => {
+                let end = find_from(&chars, pos, |ch| ch == '\n');
+                tokens.push((pos..end, Token::Comment));
+                end
+            }
+            '\'' => {
+                let end = (find_from(&chars, pos + 1, |ch| ch == '\'') + 1).min(chars.len());
+                tokens.push((pos..end, Token::String));
+                end
+            }
+            '"' => {
+                let end = double_quoted_end(&chars, pos);
+                tokens.push((pos..end, Token::String));
+                end
+            }
+            '$' => {
+                let end = variable_end(&chars, pos);
+                if end > pos + 1 {
+                    tokens.push((pos..end, Token::Variable));
+                }
+                end
+            }
+            _ if at_word_start && is_word_char(ch) => {
+                let end = find_from(&chars, pos, |ch| !is_word_char(ch));
+                let word: String = chars[pos..end].iter().collect();
+                if KEYWORDS.contains(&word.as_str()) {
+                    tokens.push((pos..end, Token::Keyword));
+                }
+                end
+            }
+            _ => pos + 1,
+        };
+        pos = end.max(pos + 1);
+    }
+    tokens
+}
+
+fn is_word_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_' || ch == '-'
+}
+
+/// Offset of the first character from `start` matching `pred`, or the end.
+fn find_from(chars: &[char], start: usize, pred: impl Fn(char) -> bool) -> usize {
+    chars[start.min(chars.len())..]
+        .iter()
+        .position(|ch| pred(*ch))
+        .map_or(chars.len(), |offset| start + offset)
+}
+
+/// End of the double-quoted string starting at `start`, skipping escaped quotes.
+fn double_quoted_end(chars: &[char], start: usize) -> usize {
+    let mut pos = start + 1;
+    while pos < chars.len() {
+        match chars[pos] {
+            '\\' => pos += 2,
+            '"' => return pos + 1,
+            _ => pos += 1,
+        }
+    }
+    chars.len()
+}
+
+/// End of the variable starting at `start`: `${...}`, `$name`, `$1` or a special parameter
+/// such as `$?`.
+fn variable_end(chars: &[char], start: usize) -> usize {
+    match chars.get(start + 1) {
+        Some('{') => (find_from(chars, start + 2, |ch| ch == '}') + 1).min(chars.len()),
+        Some(ch) if ch.is_alphabetic() || *ch == '_' => {
+            find_from(chars, start + 1, |ch| !(ch.is_alphanumeric() || ch == '_'))
+        }
+        Some(ch) if ch.is_ascii_digit() || matches!(ch, '?' | '@' | '#' | '*' | '$' | '!') => {
+            start + 2
+        }
+        _ => start + 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(source: &str) -> Vec<(String, Token)> {
+        let chars: Vec<char> = source.chars().collect();
+        highlight_shell(source)
+            .into_iter()
+            .map(|(range, token)| (chars[range].iter().collect(), token))
+            .collect()
+    }
+
+    #[test]
+    fn test_highlight_shell() {
+        let source =
+            "#!/bin/sh -e\nif [ \"$ID\" = 'arch' ]; then # Arch\n  echo ${HOME} $1 fi-x\nfi";
+
+        assert_eq!(
+            tokens(source),
+            [
+                ("#!/bin/sh -e".to_string(), Token::Comment),
+                ("if".to_string(), Token::Keyword),
+                ("\"$ID\"".to_string(), Token::String),
+                ("'arch'".to_string(), Token::String),
+                ("then".to_string(), Token::Keyword),
+                ("# Arch".to_string(), Token::Comment),
+                ("${HOME}".to_string(), Token::Variable),
+                ("$1".to_string(), Token::Variable),
+                ("fi".to_string(), Token::Keyword),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unterminated() {
+        assert_eq!(
+            tokens("echo \"a\\\"b"),
+            [("\"a\\\"b".to_string(), Token::String)]
+        );
+        assert_eq!(
+            tokens("echo $# x#y ${a"),
+            [
+                ("$#".to_string(), Token::Variable),
+                ("${a".to_string(), Token::Variable),
+            ]
+        );
+    }
+}
@@ -0,0 +1,283 @@
+// Outcome of the commands run on this machine, kept in `history.toml` in the state directory
+// so the command list can show what was already applied. Commands are identified by name,
+// like in `auto_execute`. The latest runs are kept one by one as well, for the statistics.
+
+use crate::{
+    runner::{self, CommandRecord, CommandStatus},
+    settings::state_dir,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    cell::{Cell, RefCell},
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+// Single runs kept, so the file stays small
+const MAX_PAST_RUNS: usize = 5000;
+
+#[derive(Default, Deserialize, Serialize)]
+pub struct History {
+    #[serde(default)]
+    commands: BTreeMap<String, CommandHistory>,
+    /// Runs of single commands, oldest first.
+    #[serde(default)]
+    past_runs: Vec<PastRun>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct CommandHistory {
+    /// Unix time the last run finished.
+    pub last_run: i64,
+    pub last_success: bool,
+    pub runs: u32,
+}
+
+/// One finished command.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct PastRun {
+    pub name: String,
+    /// Location of the command, e.g. `System Setup / Arch / Paru`.
+    pub path: String,
+    /// Unix time the run finished.
+    pub finished_at: i64,
+    pub success: bool,
+    pub duration_secs: f64,
+}
+
+thread_local! {
+    static HISTORY: RefCell<Option<History>> = const { RefCell::new(None) };
+    static GENERATION: Cell<u64> = const { Cell::new(0) };
+}
+
+impl History {
+    pub fn path() -> PathBuf {
+        state_dir().join("history.toml")
+    }
+
+    fn load_from(path: &Path) -> Self {
+        match fs::read_to_string(path) {
+            Ok(content) => toml::from_str(&content).unwrap_or_else(|err| {
+                eprintln!("Failed to parse history file {}: {err}", path.display());
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    fn save_to(&self, path: &Path) -> io::Result<()> {
+        let content =
+            toml::to_string(self).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, content)
+    }
+
+    fn most_used(&self, limit: usize) -> Vec<String> {
+        let mut commands: Vec<(&String, &CommandHistory)> = self.commands.iter().collect();
+        // Ties go to the command that ran last
+        commands.sort_by_key(|(_, command)| std::cmp::Reverse((command.runs, command.last_run)));
+        commands
+            .into_iter()
+            .take(limit)
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// Adds the commands of a run that finished at `now`. Skipped commands did not run.
+    fn record(&mut self, records: &[CommandRecord], now: i64) {
+        for record in records {
+            let CommandStatus::Finished { success, .. } = record.status else {
+                continue;
+            };
+            let entry = self
+                .commands
+                .entry(record.name.clone())
+                .or_insert(CommandHistory {
+                    last_run: now,
+                    last_success: success,
+                    runs: 0,
+                });
+            entry.last_run = now;
+            entry.last_success = success;
+            entry.runs += 1;
+            self.past_runs.push(PastRun {
+                name: record.name.clone(),
+                path: record.path.clone(),
+                finished_at: now,
+                success,
+                duration_secs: record.duration.unwrap_or_default().as_secs_f64(),
+            });
+        }
+        let excess = self.past_runs.len().saturating_sub(MAX_PAST_RUNS);
+        self.past_runs.drain(..excess);
+    }
+}
+
+/// Runs `f` with the history, read from disk the first time.
+pub fn with<T>(f: impl FnOnce(&History) -> T) -> T {
+    HISTORY.with(|history| {
+        let mut history = history.borrow_mut();
+        f(history.get_or_insert_with(|| History::load_from(&History::path())))
+    })
+}
+
+/// Last run of the command called `name`, if it ever ran.
+pub fn last_run(name: &str) -> Option<CommandHistory> {
+    with(|history| history.commands.get(name).cloned())
+}
+
+/// How often the command called `name` ran.
+pub fn runs(name: &str) -> u32 {
+    with(|history| history.commands.get(name).map_or(0, |command| command.runs))
+}
+
+/// Every kept run of a single command, oldest first.
+pub fn past_runs() -> Vec<PastRun> {
+    with(|history| history.past_runs.clone())
+}
+
+/// Names of up to `limit` commands that ran most often, most used first.
+pub fn most_used(limit: usize) -> Vec<String> {
+    with(|history| history.most_used(limit))
+}
+
+/// Adds the finished commands of a run to the history and saves it.
+pub fn record_run(records: &[CommandRecord]) {
+    let now = runner::now().unix_timestamp();
+    HISTORY.with(|history| {
+        let mut history = history.borrow_mut();
+        let history = history.get_or_insert_with(|| History::load_from(&History::path()));
+        history.record(records, now);
+        if let Err(err) = history.save_to(&History::path()) {
+            eprintln!("Failed to save history: {err}");
+        }
+    });
+    GENERATION.with(|generation| generation.set(generation.get() + 1));
+}
+
+/// Changes whenever a run is recorded, so views know when to update.
+pub fn generation() -> u64 {
+    GENERATION.with(Cell::get)
+}
+
+/// Badge for a command's last run, e.g. `✓ 3 h ago` or `✗ just now`.
+pub fn badge(last: &CommandHistory, now: i64) -> String {
+    let mark = if last.last_success { "✓" } else { "✗" };
+    format!("{mark} {}", format_ago(now - last.last_run))
+}
+
+/// Usage of a command for its details, e.g. `Runs: 3, last succeeded 2 h ago`.
+pub fn usage(last: Option<&CommandHistory>, now: i64) -> String {
+    let Some(last) = last else {
+        return "Runs: never".to_string();
+    };
+    let outcome = if last.last_success {
+        "succeeded"
+    } else {
+        "failed"
+    };
+    format!(
+        "Runs: {}, last {outcome} {}",
+        last.runs,
+        format_ago(now - last.last_run)
+    )
+}
+
+/// Short relative time for a number of seconds in the past.
+fn format_ago(seconds: i64) -> String {
+    match seconds.max(0) {
+        0..=59 => "just now".to_string(),
+        seconds @ 60..=3599 => format!("{} min ago", seconds / 60),
+        seconds @ 3600..=86_399 => format!("{} h ago", seconds / 3600),
+        seconds => format!("{} d ago", seconds / 86_400),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn record(name: &str, status: CommandStatus) -> CommandRecord {
+        CommandRecord {
+            name: name.to_string(),
+            path: format!("System Setup / {name}"),
+            status,
+            started_at: None,
+            duration: Some(Duration::from_secs(1)),
+            output_range: (0, 0),
+        }
+    }
+
+    #[test]
+    fn test_record_run() {
+        let mut history = History::default();
+        let finished = |success: bool| CommandStatus::Finished {
+            exit_code: u32::from(!success),
+            success,
+        };
+        history.record(&[record("Paru", finished(true))], 100);
+        history.record(
+            &[
+                record("Paru", finished(false)),
+                record("Yay", CommandStatus::Skipped),
+            ],
+            200,
+        );
+
+        let path = std::env::temp_dir().join(format!(
+            "linutil-history-test-{}/history.toml",
+            std::process::id()
+        ));
+        history.save_to(&path).unwrap();
+        let loaded = History::load_from(&path);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+        assert_eq!(
+            loaded.commands.get("Paru"),
+            Some(&CommandHistory {
+                last_run: 200,
+                last_success: false,
+                runs: 2,
+            })
+        );
+        assert!(!loaded.commands.contains_key("Yay"));
+        assert_eq!(
+            loaded.past_runs.last(),
+            Some(&PastRun {
+                name: "Paru".to_string(),
+                path: "System Setup / Paru".to_string(),
+                finished_at: 200,
+                success: false,
+                duration_secs: 1.0,
+            })
+        );
+        assert_eq!(loaded.past_runs.len(), 2);
+
+        history.record(&[record("Yay", finished(true))], 300);
+        assert_eq!(history.most_used(1), ["Paru"]);
+        assert_eq!(history.most_used(5), ["Paru", "Yay"]);
+    }
+
+    #[test]
+    fn test_badge() {
+        let last = CommandHistory {
+            last_run: 1_000_000,
+            last_success: true,
+            runs: 1,
+        };
+
+        assert_eq!(badge(&last, 1_000_030), "✓ just now");
+        assert_eq!(badge(&last, 1_000_000 + 2 * 3600), "✓ 2 h ago");
+        assert_eq!(format_ago(90), "1 min ago");
+        assert_eq!(
+            usage(Some(&last), 1_000_000 + 2 * 3600),
+            "Runs: 1, last succeeded 2 h ago"
+        );
+        assert_eq!(usage(None, 1_000_000), "Runs: never");
+        assert_eq!(format_ago(3 * 86_400), "3 d ago");
+    }
+}
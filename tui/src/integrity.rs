@@ -0,0 +1,253 @@
+// Checks that the scripts about to run are unchanged before running them. By default they are
+// compared with SHA-256 sums taken when linutil extracted them at startup, so a script edited
+// behind the GUI's back in the shared temp directory is noticed, which matters most when the
+// GUI runs as root. A manifest in `sha256sum` format can be given instead to pin known scripts.
+
+use crate::{
+    details,
+    runner::QueuedCommand,
+    settings::{IntegrityPolicy, IntegritySettings},
+};
+use linutil_core::Command;
+use sha2::{Digest, Sha256};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fmt, fs, io,
+    path::{Component, Path, PathBuf},
+};
+
+/// Expected SHA-256 sums of the files below a directory.
+pub struct Manifest {
+    root: PathBuf,
+    /// Lowercase hex sums by path relative to `root`.
+    sums: HashMap<PathBuf, String>,
+}
+
+/// A file that does not match the manifest.
+#[derive(Debug, PartialEq)]
+pub enum Problem {
+    Modified(PathBuf),
+    Unlisted(PathBuf),
+    Unreadable(PathBuf),
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Modified(path) => write!(f, "{} was modified", path.display()),
+            Self::Unlisted(path) => write!(f, "{} is not in the manifest", path.display()),
+            Self::Unreadable(path) => write!(f, "{} could not be read", path.display()),
+        }
+    }
+}
+
+thread_local! {
+    static MANIFEST: RefCell<Option<(IntegrityPolicy, Manifest)>> = const { RefCell::new(None) };
+}
+
+impl Manifest {
+    /// Sums of every file below `root`.
+    pub fn generate(root: &Path) -> io::Result<Self> {
+        let mut sums = HashMap::new();
+        let mut dirs = vec![root.to_path_buf()];
+        while let Some(dir) = dirs.pop() {
+            for entry in fs::read_dir(&dir)? {
+                let path = entry?.path();
+                if path.is_dir() {
+                    dirs.push(path);
+                } else if let Ok(relative) = path.strip_prefix(root) {
+                    sums.insert(relative.to_path_buf(), hex(&sha256(&fs::read(&path)?)));
+                }
+            }
+        }
+        Ok(Self {
+            root: root.to_path_buf(),
+            sums,
+        })
+    }
+
+    /// Reads `sha256sum` output whose paths are relative to `root`, e.g.
+    /// `<sum>  ./system-setup/arch/paru-setup.sh`. Malformed lines are ignored.
+    pub fn parse(root: &Path, text: &str) -> Self {
+        let sums = text
+            .lines()
+            .filter_map(|line| {
+                let (sum, path) = line.trim().split_once(char::is_whitespace)?;
+                // `*` marks binary mode, which makes no difference here
+                let path = path.trim_start().trim_start_matches('*');
+                let valid = sum.len() == 64 && sum.chars().all(|ch| ch.is_ascii_hexdigit());
+                valid.then(|| (normalize(Path::new(path)), sum.to_ascii_lowercase()))
+            })
+            .collect();
+        Self {
+            root: root.to_path_buf(),
+            sums,
+        }
+    }
+
+    /// Short sum over all sums, identifying a set of scripts in bug reports.
+    pub fn fingerprint(&self) -> String {
+        let mut lines: Vec<String> = self
+            .sums
+            .iter()
+            .map(|(path, sum)| format!("{sum}  {}\n", path.display()))
+            .collect();
+        lines.sort();
+        hex(&sha256(lines.concat().as_bytes()))[..12].to_string()
+    }
+
+    /// Compares `file` with its expected sum.
+    pub fn verify(&self, file: &Path) -> Result<(), Problem> {
+        let file = normalize(file);
+        let expected = file
+            .strip_prefix(&self.root)
+            .ok()
+            .and_then(|relative| self.sums.get(relative))
+            .ok_or_else(|| Problem::Unlisted(file.clone()))?;
+        let content = fs::read(&file).map_err(|_| Problem::Unreadable(file.clone()))?;
+        if hex(&sha256(&content)) == *expected {
+            Ok(())
+        } else {
+            Err(Problem::Modified(file))
+        }
+    }
+}
+
+/// Sets up checking the scripts extracted to `script_dir`. Call it right after loading the
+/// tabs, before the scripts could have been touched.
+pub fn init(script_dir: &Path, settings: &IntegritySettings) {
+    let manifest = match (&settings.policy, &settings.manifest) {
+        (IntegrityPolicy::Off, _) => return,
+        (_, Some(path)) => {
+            let text = fs::read_to_string(path).unwrap_or_else(|err| {
+                // An empty manifest reports every script, rather than none of them
+                eprintln!("Failed to read script manifest {}: {err}", path.display());
+                String::new()
+            });
+            Manifest::parse(script_dir, &text)
+        }
+        (_, None) => match Manifest::generate(script_dir) {
+            Ok(manifest) => manifest,
+            Err(err) => {
+                eprintln!("Failed to hash the scripts: {err}");
+                Manifest::parse(script_dir, "")
+            }
+        },
+    };
+    MANIFEST.with(|cell| *cell.borrow_mut() = Some((settings.policy, manifest)));
+}
+
+/// Policy and problems of the scripts `commands` run, including the files they source.
+/// Without [`init`] or with the `off` policy nothing is checked, and neither are files
+/// outside the script directory, such as the user's own scripts.
+pub fn check(commands: &[QueuedCommand]) -> (IntegrityPolicy, Vec<Problem>) {
+    MANIFEST.with(|cell| {
+        let cell = cell.borrow();
+        let Some((policy, manifest)) = cell.as_ref() else {
+            return (IntegrityPolicy::Off, Vec::new());
+        };
+        let mut problems = Vec::new();
+        for command in commands {
+            let Command::LocalFile { file, .. } = &command.node.command else {
+                continue;
+            };
+            let files = std::iter::once(file.clone()).chain(details::related_files(&command.node));
+            for file in files.filter(|file| normalize(file).starts_with(&manifest.root)) {
+                if let Err(problem) = manifest.verify(&file) {
+                    if !problems.contains(&problem) {
+                        problems.push(problem);
+                    }
+                }
+            }
+        }
+        (*policy, problems)
+    })
+}
+
+/// `path` with `.` and `..` resolved without touching the file system.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// SHA-256 digest of `data`.
+pub fn sha256(data: &[u8]) -> [u8; 32] {
+    Sha256::digest(data).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sha256() {
+        assert_eq!(
+            hex(&sha256(b"")),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            hex(&sha256(b"abc")),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        // Longer than one block
+        assert_eq!(
+            hex(&sha256(
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+            )),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+    }
+
+    #[test]
+    fn test_verify() {
+        let dir =
+            std::env::temp_dir().join(format!("linutil-integrity-test-{}", std::process::id()));
+        fs::create_dir_all(dir.join("utils")).unwrap();
+        let script = dir.join("utils/clean.sh");
+        fs::write(dir.join("common-script.sh"), "checkEnv\n").unwrap();
+        fs::write(&script, "echo abc").unwrap();
+
+        let generated = Manifest::generate(&dir).unwrap();
+        let fingerprint = generated.fingerprint();
+        let parsed = Manifest::parse(
+            &dir,
+            "not a sum\n\
+             a3e9d0e8b7e8f5c8b1c1dd1c3e0b7d1d6e88e1e8e2a7a1a4d4e2d1c3b1a2f3e4  ./common-script.sh\n",
+        );
+        fs::write(&script, "echo modified").unwrap();
+        let modified_fingerprint = Manifest::generate(&dir).unwrap().fingerprint();
+        let results = (
+            generated.verify(&dir.join("utils/../common-script.sh")),
+            generated.verify(&script),
+            parsed.verify(&dir.join("common-script.sh")),
+            parsed.verify(&script),
+            generated.verify(&dir.join("missing.sh")),
+        );
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(fingerprint.len(), 12);
+        assert_ne!(fingerprint, modified_fingerprint);
+        assert_eq!(results.0, Ok(()));
+        assert_eq!(results.1, Err(Problem::Modified(script.clone())));
+        assert_eq!(
+            results.2,
+            Err(Problem::Modified(dir.join("common-script.sh")))
+        );
+        assert_eq!(results.3, Err(Problem::Unlisted(script)));
+        assert_eq!(results.4, Err(Problem::Unlisted(dir.join("missing.sh"))));
+    }
+}
@@ -0,0 +1,450 @@
+// Registry of the runs started in this session, whether their output is in a window of its
+// own or in the pane of the main window, for the Jobs popover of the main window. While any
+// runs, it holds off suspend if the settings ask for it.
+
+use crate::{
+    power,
+    runner::{CommandRecord, CommandRunner, CommandStatus},
+    settings::Settings,
+};
+use gtk::{gio, glib, prelude::*};
+use gtk4 as gtk;
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+    time::Duration,
+};
+
+struct Job {
+    title: String,
+    runner: Rc<RefCell<CommandRunner>>,
+    /// Root of the run's output view, gone once its window closed.
+    view: glib::WeakRef<gtk::Box>,
+    finished: Rc<Cell<bool>>,
+}
+
+impl Job {
+    /// Finished jobs are dropped once nothing shows them anymore.
+    fn is_stale(&self) -> bool {
+        self.finished.get() && self.view.upgrade().and_then(|view| view.root()).is_none()
+    }
+}
+
+thread_local! {
+    static JOBS: RefCell<Vec<Job>> = const { RefCell::new(Vec::new()) };
+    static ON_CHANGE: RefCell<Vec<Box<dyn Fn()>>> = const { RefCell::new(Vec::new()) };
+    static WATCHING: Cell<bool> = const { Cell::new(false) };
+    static BACKGROUND: RefCell<Option<Background>> = const { RefCell::new(None) };
+}
+
+/// Runs keeping the application alive after their windows were closed.
+struct Background {
+    _hold: gio::ApplicationHoldGuard,
+    app: gtk::Application,
+    runners: Vec<Rc<RefCell<CommandRunner>>>,
+}
+
+impl Background {
+    /// Tells the user how the runs went once nothing is left on screen to show it.
+    fn notify_done(&self) {
+        if !self.app.windows().is_empty() {
+            return;
+        }
+        let statuses: Vec<String> = self
+            .runners
+            .iter()
+            .map(|runner| job_status(&runner.borrow().records(), true))
+            .collect();
+        let notification = gio::Notification::new("Linutil jobs finished");
+        notification.set_body(Some(&format!(
+            "{} Their logs are in the Logs window.",
+            background_summary(&statuses)
+        )));
+        self.app
+            .send_notification(Some("jobs-finished"), &notification);
+    }
+}
+
+/// Adds a run whose output `view` shows. `finished` is set when it is done, which the view
+/// reports with [`changed`].
+pub fn register(
+    commands: &[&str],
+    runner: Rc<RefCell<CommandRunner>>,
+    view: &gtk::Box,
+    finished: Rc<Cell<bool>>,
+) {
+    JOBS.with(|jobs| {
+        jobs.borrow_mut().push(Job {
+            title: commands.join(", "),
+            runner,
+            view: view.downgrade(),
+            finished,
+        })
+    });
+    if Settings::load().runs.inhibit_suspend {
+        power::inhibit_suspend();
+    }
+    changed();
+    watch_running();
+}
+
+/// Calls `on_change` whenever a job starts, finishes or moves on to its next command.
+pub fn connect_changed(on_change: impl Fn() + 'static) {
+    ON_CHANGE.with(|callbacks| callbacks.borrow_mut().push(Box::new(on_change)));
+}
+
+/// Notices jobs moving on to their next command while any job runs.
+fn watch_running() {
+    if WATCHING.with(|watching| watching.replace(true)) {
+        return;
+    }
+    let mut last = running_commands();
+    glib::timeout_add_local(Duration::from_secs(1), move || {
+        let current = running_commands();
+        if current != last {
+            last = current;
+            changed();
+        }
+        if running() > 0 {
+            glib::ControlFlow::Continue
+        } else {
+            WATCHING.with(|watching| watching.set(false));
+            glib::ControlFlow::Break
+        }
+    });
+}
+
+/// Updates the Jobs button after a job started, finished or was closed.
+pub fn changed() {
+    JOBS.with(|jobs| jobs.borrow_mut().retain(|job| !job.is_stale()));
+    if running() == 0 {
+        power::allow_suspend();
+        if let Some(background) = BACKGROUND.with(|background| background.take()) {
+            background.notify_done();
+        }
+    }
+    ON_CHANGE.with(|callbacks| {
+        for on_change in callbacks.borrow().iter() {
+            on_change();
+        }
+    });
+}
+
+/// Number of jobs whose commands are still running.
+pub fn running() -> usize {
+    JOBS.with(|jobs| {
+        jobs.borrow()
+            .iter()
+            .filter(|job| !job.finished.get())
+            .count()
+    })
+}
+
+/// Names of the commands running right now, one per running job.
+pub fn running_commands() -> Vec<String> {
+    JOBS.with(|jobs| {
+        jobs.borrow()
+            .iter()
+            .filter(|job| !job.finished.get())
+            .flat_map(|job| job.runner.borrow().records())
+            .filter(|record| matches!(record.status, CommandStatus::Running))
+            .map(|record| record.name)
+            .collect()
+    })
+}
+
+/// Stops the commands of every running job.
+pub fn stop_all() {
+    JOBS.with(|jobs| {
+        for job in jobs.borrow().iter().filter(|job| !job.finished.get()) {
+            job.runner.borrow_mut().kill();
+        }
+    });
+}
+
+/// Stops every running job and closes the windows showing jobs, emptying the output pane of
+/// the main window if a job is shown there.
+pub fn stop_and_close_all() {
+    stop_all();
+    // The stopped runs still save their logs
+    if let Some(app) = gio::Application::default().and_downcast::<gtk::Application>() {
+        hold_until_done(&app);
+    }
+    let views: Vec<gtk::Box> = JOBS.with(|jobs| {
+        jobs.borrow()
+            .iter()
+            .filter_map(|job| job.view.upgrade())
+            .collect()
+    });
+    for view in views {
+        if let Some(pane) = view.parent().and_downcast::<gtk::Expander>() {
+            pane.set_child(None::<&gtk::Widget>);
+            pane.set_visible(false);
+        } else if let Some(window) = view.root().and_downcast::<gtk::Window>() {
+            window.close();
+        }
+    }
+}
+
+/// Keeps `app` alive once its windows are closed until every job is done, so the commands
+/// finish and their logs are saved. A notification tells when the last one is done if no
+/// window is left by then.
+pub fn hold_until_done(app: &gtk::Application) {
+    let running: Vec<Rc<RefCell<CommandRunner>>> = JOBS.with(|jobs| {
+        jobs.borrow()
+            .iter()
+            .filter(|job| !job.finished.get())
+            .map(|job| job.runner.clone())
+            .collect()
+    });
+    if running.is_empty() {
+        return;
+    }
+    BACKGROUND.with(|background| {
+        let mut background = background.borrow_mut();
+        let background = background.get_or_insert_with(|| Background {
+            _hold: app.hold(),
+            app: app.clone(),
+            runners: Vec::new(),
+        });
+        for runner in running {
+            if !background
+                .runners
+                .iter()
+                .any(|known| Rc::ptr_eq(known, &runner))
+            {
+                background.runners.push(runner);
+            }
+        }
+    });
+}
+
+/// Button opening a list of the jobs with their status and buttons to show and stop them.
+/// It is hidden while there are no jobs.
+pub fn jobs_button() -> gtk::MenuButton {
+    let list = gtk::Box::new(gtk::Orientation::Vertical, 8);
+    list.set_margin_top(8);
+    list.set_margin_bottom(8);
+    list.set_margin_start(8);
+    list.set_margin_end(8);
+    list.update_property(&[gtk::accessible::Property::Label("Jobs")]);
+    let popover = gtk::Popover::new();
+    popover.set_child(Some(&list));
+    let button = gtk::MenuButton::new();
+    button.set_label("Jobs");
+    button.set_popover(Some(&popover));
+    button.set_visible(false);
+    button.update_property(&[
+        gtk::accessible::Property::Label("Jobs"),
+        gtk::accessible::Property::Description(
+            "Runs of this session, with their status and buttons to show or stop them.",
+        ),
+    ]);
+
+    let list_clone = list.clone();
+    popover.connect_show(move |_| fill_list(&list_clone));
+    let button_weak = button.downgrade();
+    let popover_weak = popover.downgrade();
+    connect_changed(move || {
+        let (Some(button), Some(popover)) = (button_weak.upgrade(), popover_weak.upgrade()) else {
+            return;
+        };
+        let running = running();
+        let total = JOBS.with(|jobs| jobs.borrow().len());
+        button.set_visible(total > 0);
+        button.set_label(&if running > 0 {
+            format!("Jobs ({running} running)")
+        } else {
+            "Jobs".to_string()
+        });
+        if popover.is_visible() {
+            fill_list(&list);
+        }
+    });
+    button
+}
+
+fn fill_list(list: &gtk::Box) {
+    while let Some(child) = list.first_child() {
+        list.remove(&child);
+    }
+    JOBS.with(|jobs| {
+        for job in jobs.borrow().iter() {
+            list.append(&job_row(job));
+        }
+    });
+}
+
+fn job_row(job: &Job) -> gtk::Box {
+    let status = job_status(&job.runner.borrow().records(), job.finished.get());
+    let row = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+    let text = gtk::Label::new(Some(&format!("{}\n{status}", job.title)));
+    text.set_xalign(0.0);
+    text.set_hexpand(true);
+    text.set_max_width_chars(40);
+    text.set_ellipsize(gtk::pango::EllipsizeMode::End);
+    row.update_property(&[gtk::accessible::Property::Label(&format!(
+        "{}: {status}",
+        job.title
+    ))]);
+
+    let show_button = gtk::Button::with_label("Show");
+    show_button.set_sensitive(job.view.upgrade().and_then(|view| view.root()).is_some());
+    show_button.update_property(&[gtk::accessible::Property::Description(
+        "Bring the output of this job to the front.",
+    )]);
+    let view = job.view.clone();
+    show_button.connect_clicked(move |_| {
+        let Some(view) = view.upgrade() else {
+            return;
+        };
+        if let Some(pane) = view.parent().and_downcast::<gtk::Expander>() {
+            pane.set_expanded(true);
+        }
+        if let Some(window) = view.root().and_downcast::<gtk::Window>() {
+            window.present();
+        }
+    });
+
+    let stop_button = gtk::Button::with_label("Stop");
+    stop_button.set_sensitive(!job.finished.get());
+    stop_button.update_property(&[gtk::accessible::Property::Description(
+        "Stop the running command of this job.",
+    )]);
+    let runner = job.runner.clone();
+    stop_button.connect_clicked(move |button| {
+        runner.borrow_mut().kill();
+        button.set_sensitive(false);
+    });
+
+    row.append(&text);
+    row.append(&show_button);
+    row.append(&stop_button);
+    row
+}
+
+/// Short status of a run, e.g. `Running 2 of 3` or `Failed 1 of 3`.
+fn job_status(records: &[CommandRecord], finished: bool) -> String {
+    let total = records.len();
+    let failed = records
+        .iter()
+        .filter(|record| {
+            matches!(
+                record.status,
+                CommandStatus::Finished { success: false, .. }
+            )
+        })
+        .count();
+    let stopped = records
+        .iter()
+        .any(|record| matches!(record.status, CommandStatus::Skipped));
+    if !finished {
+        let current = records
+            .iter()
+            .position(|record| {
+                matches!(
+                    record.status,
+                    CommandStatus::Pending | CommandStatus::Running
+                )
+            })
+            .map_or(total, |index| index + 1);
+        format!("Running {current} of {total}")
+    } else if stopped {
+        "Stopped".to_string()
+    } else if failed > 0 {
+        format!("Failed {failed} of {total}")
+    } else {
+        "Finished".to_string()
+    }
+}
+
+/// One line on how the runs that went on in the background ended, from their statuses.
+fn background_summary(statuses: &[String]) -> String {
+    let failed = statuses
+        .iter()
+        .filter(|status| status.starts_with("Failed"))
+        .count();
+    let stopped = statuses
+        .iter()
+        .filter(|status| status.as_str() == "Stopped")
+        .count();
+    let mut problems = Vec::new();
+    if failed > 0 {
+        problems.push(format!("{failed} failed"));
+    }
+    if stopped > 0 {
+        problems.push(format!("{stopped} stopped"));
+    }
+    let total = statuses.len();
+    let jobs = if total == 1 { "job" } else { "jobs" };
+    if problems.is_empty() {
+        format!("{total} {jobs} finished.")
+    } else {
+        format!("{total} {jobs} finished: {}.", problems.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(status: CommandStatus) -> CommandRecord {
+        CommandRecord {
+            name: "Kitty".to_string(),
+            path: "Applications Setup / Kitty".to_string(),
+            status,
+            started_at: None,
+            duration: None,
+            output_range: (0, 0),
+        }
+    }
+
+    #[test]
+    fn test_job_status() {
+        let ok = || {
+            record(CommandStatus::Finished {
+                exit_code: 0,
+                success: true,
+            })
+        };
+        let failed = || {
+            record(CommandStatus::Finished {
+                exit_code: 1,
+                success: false,
+            })
+        };
+
+        assert_eq!(
+            job_status(
+                &[
+                    ok(),
+                    record(CommandStatus::Running),
+                    record(CommandStatus::Pending)
+                ],
+                false
+            ),
+            "Running 2 of 3"
+        );
+        assert_eq!(job_status(&[ok(), ok()], true), "Finished");
+        assert_eq!(job_status(&[ok(), failed()], true), "Failed 1 of 2");
+        assert_eq!(
+            job_status(&[failed(), record(CommandStatus::Skipped)], true),
+            "Stopped"
+        );
+    }
+
+    #[test]
+    fn test_background_summary() {
+        let statuses = |statuses: &[&str]| -> Vec<String> {
+            statuses.iter().map(|status| status.to_string()).collect()
+        };
+        assert_eq!(
+            background_summary(&statuses(&["Finished"])),
+            "1 job finished."
+        );
+        assert_eq!(
+            background_summary(&statuses(&["Finished", "Failed 1 of 2", "Stopped"])),
+            "3 jobs finished: 1 failed, 1 stopped."
+        );
+    }
+}
@@ -0,0 +1,94 @@
+// Minimal client for the systemd journal's native protocol, see
+// https://systemd.io/JOURNAL_NATIVE_PROTOCOL/. Entries are written as a single datagram to
+// the journal socket; when journald is not running the events are silently dropped.
+
+use crate::runner::{CommandRecord, CommandStatus};
+use std::{io, os::unix::net::UnixDatagram, path::Path};
+
+const JOURNAL_SOCKET: &str = "/run/systemd/journal/socket";
+const SYSLOG_IDENTIFIER: &str = "linutil";
+
+// Syslog priorities used by journald
+const PRIORITY_ERR: &str = "3";
+const PRIORITY_INFO: &str = "6";
+
+/// Records that a command of a run has started, finished or failed.
+pub fn log_command_event(record: &CommandRecord) {
+    let (event, priority, message) = match record.status {
+        CommandStatus::Running => ("started", PRIORITY_INFO, "Started command"),
+        CommandStatus::Finished { success: true, .. } => {
+            ("finished", PRIORITY_INFO, "Finished command")
+        }
+        CommandStatus::Finished { success: false, .. } => {
+            ("failed", PRIORITY_ERR, "Command failed")
+        }
+        CommandStatus::Pending | CommandStatus::Skipped => return,
+    };
+
+    let message = format!("{message}: {}", record.path);
+    let exit_code = match record.status {
+        CommandStatus::Finished { exit_code, .. } => Some(exit_code.to_string()),
+        _ => None,
+    };
+    let duration = record
+        .duration
+        .map(|duration| duration.as_millis().to_string());
+
+    let mut fields = vec![
+        ("MESSAGE", message.as_str()),
+        ("PRIORITY", priority),
+        ("SYSLOG_IDENTIFIER", SYSLOG_IDENTIFIER),
+        ("LINUTIL_EVENT", event),
+        ("LINUTIL_COMMAND", record.name.as_str()),
+        ("LINUTIL_COMMAND_PATH", record.path.as_str()),
+    ];
+    if let Some(exit_code) = &exit_code {
+        fields.push(("LINUTIL_EXIT_CODE", exit_code));
+    }
+    if let Some(duration) = &duration {
+        fields.push(("LINUTIL_DURATION_MS", duration));
+    }
+    let _ = send(&fields);
+}
+
+fn send(fields: &[(&str, &str)]) -> io::Result<()> {
+    if !Path::new(JOURNAL_SOCKET).exists() {
+        return Ok(());
+    }
+    let socket = UnixDatagram::unbound()?;
+    socket.send_to(&encode(fields), JOURNAL_SOCKET)?;
+    Ok(())
+}
+
+/// Serializes fields as `KEY=value` lines. Values containing a newline use the binary
+/// form: the key, a newline, the little-endian 64 bit length and the raw value.
+fn encode(fields: &[(&str, &str)]) -> Vec<u8> {
+    let mut data = Vec::new();
+    for (key, value) in fields {
+        data.extend_from_slice(key.as_bytes());
+        if value.contains('\n') {
+            data.push(b'\n');
+            data.extend_from_slice(&(value.len() as u64).to_le_bytes());
+        } else {
+            data.push(b'=');
+        }
+        data.extend_from_slice(value.as_bytes());
+        data.push(b'\n');
+    }
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        let data = encode(&[("MESSAGE", "hello"), ("LINUTIL_COMMAND", "a\nb")]);
+
+        let mut expected = b"MESSAGE=hello\nLINUTIL_COMMAND\n".to_vec();
+        expected.extend_from_slice(&3u64.to_le_bytes());
+        expected.extend_from_slice(b"a\nb\n");
+        assert_eq!(data, expected);
+    }
+}
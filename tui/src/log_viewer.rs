@@ -0,0 +1,337 @@
+use crate::gtk_app::build_confirmation_dialog;
+use crate::logs::{self, SearchHit};
+use gtk::glib::Propagation;
+use gtk::prelude::*;
+use gtk::{gio, glib};
+use gtk4 as gtk;
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+const EMPTY_TEXT: &str = "Select a log to view its contents.";
+const MATCH_TAG: &str = "search-match";
+
+/// Opens the window listing logs saved by previous runs.
+pub fn open_log_viewer(app: &gtk::Application) {
+    let window = gtk::ApplicationWindow::builder()
+        .application(app)
+        .title("Logs")
+        .default_width(960)
+        .default_height(600)
+        .build();
+
+    let root_box = gtk::Box::new(gtk::Orientation::Vertical, 8);
+    root_box.set_margin_top(12);
+    root_box.set_margin_bottom(12);
+    root_box.set_margin_start(12);
+    root_box.set_margin_end(12);
+
+    let top_bar = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+    let search_entry = gtk::SearchEntry::new();
+    search_entry.set_hexpand(true);
+    search_entry.set_placeholder_text(Some("Search logs"));
+    search_entry.update_property(&[
+        gtk::accessible::Property::Label("Search logs"),
+        gtk::accessible::Property::Description(
+            "Type to search the names and contents of all saved logs.",
+        ),
+        gtk::accessible::Property::Placeholder("Search logs"),
+    ]);
+    let matches_only_check = gtk::CheckButton::with_label("Matches only");
+    matches_only_check.set_active(true);
+    matches_only_check.set_sensitive(false);
+    matches_only_check.update_property(&[
+        gtk::accessible::Property::Label("Matches only"),
+        gtk::accessible::Property::Description(
+            "Show only the matching lines of the selected log with surrounding context.",
+        ),
+    ]);
+    let refresh_button = gtk::Button::with_label("Refresh");
+    refresh_button.update_property(&[
+        gtk::accessible::Property::Label("Refresh"),
+        gtk::accessible::Property::Description("Reload the list of saved logs."),
+    ]);
+    let delete_button = gtk::Button::with_label("Delete");
+    delete_button.set_sensitive(false);
+    delete_button.update_property(&[
+        gtk::accessible::Property::Label("Delete"),
+        gtk::accessible::Property::Description("Delete the selected log file."),
+    ]);
+    top_bar.append(&search_entry);
+    top_bar.append(&matches_only_check);
+    top_bar.append(&refresh_button);
+    top_bar.append(&delete_button);
+
+    let content_box = gtk::Box::new(gtk::Orientation::Horizontal, 12);
+    content_box.set_vexpand(true);
+
+    let log_list = gtk::ListBox::new();
+    log_list.set_selection_mode(gtk::SelectionMode::Single);
+    log_list.update_property(&[
+        gtk::accessible::Property::Label("Saved logs"),
+        gtk::accessible::Property::Description("Select a log to view its contents."),
+    ]);
+    let list_scroll = gtk::ScrolledWindow::new();
+    list_scroll.set_policy(gtk::PolicyType::Never, gtk::PolicyType::Automatic);
+    list_scroll.set_min_content_width(300);
+    list_scroll.set_child(Some(&log_list));
+
+    let log_view = gtk::TextView::new();
+    log_view.set_monospace(true);
+    log_view.set_editable(false);
+    log_view.set_cursor_visible(false);
+    log_view.buffer().set_text(EMPTY_TEXT);
+    log_view.buffer().create_tag(
+        Some(MATCH_TAG),
+        &[
+            ("weight", &700),
+            ("underline", &gtk::pango::Underline::Single),
+        ],
+    );
+    log_view.update_property(&[
+        gtk::accessible::Property::Label("Log contents"),
+        gtk::accessible::Property::ReadOnly(true),
+    ]);
+    let view_scroll = gtk::ScrolledWindow::new();
+    view_scroll.set_hexpand(true);
+    view_scroll.set_vexpand(true);
+    view_scroll.set_child(Some(&log_view));
+
+    content_box.append(&list_scroll);
+    content_box.append(&view_scroll);
+
+    let status_label = gtk::Label::new(None);
+    status_label.set_xalign(0.0);
+    status_label.update_property(&[gtk::accessible::Property::Label("Logs status")]);
+
+    root_box.append(&top_bar);
+    root_box.append(&content_box);
+    root_box.append(&status_label);
+    window.set_child(Some(&root_box));
+
+    let shown: Rc<RefCell<Vec<SearchHit>>> = Rc::new(RefCell::new(Vec::new()));
+    // Counts reloads, so a search finishing after the query changed again is dropped
+    let generation = Rc::new(Cell::new(0u64));
+    let reload = {
+        let shown = shown.clone();
+        let log_list = log_list.clone();
+        let search_entry = search_entry.clone();
+        let matches_only_check = matches_only_check.clone();
+        let status_label = status_label.clone();
+        Rc::new(move || {
+            let query = search_entry.text().trim().to_string();
+            generation.set(generation.get() + 1);
+            let current = generation.get();
+            let generation = generation.clone();
+            let shown = shown.clone();
+            let log_list = log_list.clone();
+            let matches_only_check = matches_only_check.clone();
+            let status_label = status_label.clone();
+            if !query.is_empty() {
+                status_label.set_text("Searching logs…");
+            }
+            glib::spawn_future_local(async move {
+                let search_query = query.clone();
+                // Searching reads and decompresses every log
+                let Ok((all, matches)) = gio::spawn_blocking(move || {
+                    let all = logs::list_logs();
+                    let matches: Vec<SearchHit> = if search_query.is_empty() {
+                        all.iter()
+                            .map(|log| SearchHit {
+                                log: log.clone(),
+                                excerpts: Vec::new(),
+                                match_count: 0,
+                            })
+                            .collect()
+                    } else {
+                        logs::search_logs(&all, &search_query)
+                    };
+                    (all, matches)
+                })
+                .await
+                else {
+                    return;
+                };
+                if generation.get() != current {
+                    return;
+                }
+                show_logs(
+                    &log_list,
+                    &matches_only_check,
+                    &status_label,
+                    &query,
+                    all.len(),
+                    &matches,
+                );
+                *shown.borrow_mut() = matches;
+            });
+        })
+    };
+    reload();
+
+    let show_selected = {
+        let shown = shown.clone();
+        let log_list = log_list.clone();
+        let log_view = log_view.clone();
+        let search_entry = search_entry.clone();
+        let matches_only_check = matches_only_check.clone();
+        let delete_button = delete_button.clone();
+        Rc::new(move || {
+            let hit = log_list
+                .selected_row()
+                .and_then(|row| shown.borrow().get(row.index() as usize).cloned());
+            delete_button.set_sensitive(hit.is_some());
+            let query = search_entry.text().trim().to_string();
+            let text = match hit {
+                Some(hit) if matches_only_check.is_active() && !hit.excerpts.is_empty() => {
+                    logs::format_excerpts(&hit.excerpts)
+                }
+                Some(hit) => logs::read_log(&hit.log.path).unwrap_or_else(|err| {
+                    format!("Failed to read {}: {err}", hit.log.path.display())
+                }),
+                None => EMPTY_TEXT.to_string(),
+            };
+            log_view.buffer().set_text(&text);
+            highlight_matches(&log_view, &query);
+        })
+    };
+
+    let show_selected_clone = show_selected.clone();
+    log_list.connect_row_selected(move |_, _| show_selected_clone());
+
+    let show_selected_clone = show_selected.clone();
+    matches_only_check.connect_toggled(move |_| show_selected_clone());
+
+    let reload_clone = reload.clone();
+    search_entry.connect_search_changed(move |_| reload_clone());
+
+    let reload_clone = reload.clone();
+    refresh_button.connect_clicked(move |_| reload_clone());
+
+    let window_clone = window.clone();
+    let log_list_clone = log_list.clone();
+    let status_label_clone = status_label.clone();
+    delete_button.connect_clicked(move |_| {
+        let Some(row) = log_list_clone.selected_row() else {
+            return;
+        };
+        let Some(log) = shown
+            .borrow()
+            .get(row.index() as usize)
+            .map(|hit| hit.log.clone())
+        else {
+            return;
+        };
+        let (dialog, confirm, cancel) = build_confirmation_dialog(
+            window_clone.upcast_ref(),
+            "Delete Log",
+            &format!("Delete {}?", log.path.display()),
+        );
+        confirm.set_label("Delete");
+        confirm.update_property(&[
+            gtk::accessible::Property::Label("Delete"),
+            gtk::accessible::Property::Description("Delete the selected log file."),
+        ]);
+        let dialog_clone = dialog.clone();
+        let reload = reload.clone();
+        let status_label = status_label_clone.clone();
+        confirm.connect_clicked(move |_| {
+            dialog_clone.close();
+            match logs::delete_log(&log.path) {
+                Ok(()) => {
+                    reload();
+                    status_label.set_text(&format!("Deleted {}", log.file_name()));
+                }
+                Err(err) => status_label.set_text(&format!("Failed to delete log: {err}")),
+            }
+        });
+        cancel.connect_clicked(move |_| dialog.close());
+    });
+
+    let search_entry_clone = search_entry.clone();
+    let delete_button_clone = delete_button.clone();
+    let window_clone = window.clone();
+    let key_controller = gtk::EventControllerKey::new();
+    key_controller.connect_key_pressed(move |_, key, _, modifiers| {
+        let ctrl = modifiers.contains(gtk::gdk::ModifierType::CONTROL_MASK);
+        let key_char = key.to_unicode().map(|c| c.to_ascii_lowercase());
+
+        if ctrl && key_char == Some('f') {
+            search_entry_clone.grab_focus();
+            return Propagation::Stop;
+        }
+        if ctrl && key_char == Some('w') {
+            window_clone.close();
+            return Propagation::Stop;
+        }
+        if key.name().as_deref() == Some("Delete") && !search_entry_clone.has_focus() {
+            delete_button_clone.emit_clicked();
+            return Propagation::Stop;
+        }
+        Propagation::Proceed
+    });
+    window.add_controller(key_controller);
+
+    window.show();
+}
+
+/// Marks every case-insensitive occurrence of `query` in the log view.
+/// Lists `matches`, the logs found for `query` out of `total`.
+fn show_logs(
+    log_list: &gtk::ListBox,
+    matches_only_check: &gtk::CheckButton,
+    status_label: &gtk::Label,
+    query: &str,
+    total: usize,
+    matches: &[SearchHit],
+) {
+    matches_only_check.set_sensitive(!query.is_empty());
+    while let Some(child) = log_list.first_child() {
+        log_list.remove(&child);
+    }
+    for hit in matches {
+        let log = &hit.log;
+        let mut text = format!(
+            "{}\n{} · {}",
+            log.file_name(),
+            logs::format_modified(log.modified),
+            logs::format_size(log.size)
+        );
+        if !query.is_empty() {
+            text.push_str(&format!(" · {} matches", hit.match_count));
+        }
+        let label = gtk::Label::new(Some(&text));
+        label.set_xalign(0.0);
+        let row = gtk::ListBoxRow::new();
+        row.update_property(&[gtk::accessible::Property::Label(&log.file_name())]);
+        row.set_child(Some(&label));
+        log_list.append(&row);
+    }
+    status_label.set_text(&format!(
+        "{} of {} logs shown, {} matching lines · {}",
+        matches.len(),
+        total,
+        matches.iter().map(|hit| hit.match_count).sum::<usize>(),
+        logs::log_dir().display()
+    ));
+}
+
+fn highlight_matches(log_view: &gtk::TextView, query: &str) {
+    if query.is_empty() {
+        return;
+    }
+    let buffer = log_view.buffer();
+    let mut first_match = None;
+    let mut iter = buffer.start_iter();
+    while let Some((start, end)) =
+        iter.forward_search(query, gtk::TextSearchFlags::CASE_INSENSITIVE, None)
+    {
+        buffer.apply_tag_by_name(MATCH_TAG, &start, &end);
+        first_match.get_or_insert(start);
+        iter = end;
+    }
+    if let Some(mut start) = first_match {
+        log_view.scroll_to_iter(&mut start, 0.0, true, 0.0, 0.3);
+    }
+}
@@ -0,0 +1,647 @@
+use crate::{
+    runner::{CommandRecord, CommandStatus},
+    settings::{state_dir, LoggingSettings, Settings},
+};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use std::{
+    cmp::Reverse,
+    fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+use time::{macros::format_description, OffsetDateTime, UtcOffset};
+
+const LOG_PREFIX: &str = "linutil_log_";
+const LOG_EXTENSION: &str = "log";
+const GZIP_EXTENSION: &str = "gz";
+
+/// A saved command log on disk.
+#[derive(Clone, Debug)]
+pub struct LogFile {
+    pub path: PathBuf,
+    pub modified: SystemTime,
+    pub size: u64,
+}
+
+impl LogFile {
+    pub fn file_name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+}
+
+/// Directory where logs and reports are written: the one in the settings, otherwise
+/// [`default_log_dir`].
+pub fn log_dir() -> PathBuf {
+    Settings::load()
+        .logging
+        .directory
+        .unwrap_or_else(default_log_dir)
+}
+
+/// `logs` in [`state_dir`].
+pub fn default_log_dir() -> PathBuf {
+    state_dir().join("logs")
+}
+
+/// Writes the combined output of the run started at `timestamp` to [`log_dir`]. When the run
+/// had several commands, each command's output is also written to its own file named after
+/// its position and path, e.g. `linutil_log_<time>_2-system-setup-arch-paru.log`. Retention
+/// counts these files and the combined log as one run.
+pub fn save_run_logs(
+    timestamp: &str,
+    output: &str,
+    records: &[CommandRecord],
+) -> io::Result<PathBuf> {
+    let log_path = save_file(&run_log_name(timestamp), output)?;
+    if records.len() > 1 {
+        for (index, record) in records.iter().enumerate() {
+            if matches!(
+                record.status,
+                CommandStatus::Pending | CommandStatus::Skipped
+            ) {
+                continue;
+            }
+            save_file(
+                &format!(
+                    "{LOG_PREFIX}{timestamp}_{}-{}.{LOG_EXTENSION}",
+                    index + 1,
+                    slug(&record.path)
+                ),
+                record.output(output),
+            )?;
+        }
+    }
+    Ok(log_path)
+}
+
+fn run_log_name(timestamp: &str) -> String {
+    format!("{LOG_PREFIX}{timestamp}.{LOG_EXTENSION}")
+}
+
+/// Creates the combined log of the run started at `timestamp` so output can be appended
+/// while the run is in progress. [`save_run_logs`] later overwrites it with the same name.
+pub fn create_live_log(timestamp: &str) -> io::Result<fs::File> {
+    let dir = log_dir();
+    fs::create_dir_all(&dir)?;
+    fs::File::create(dir.join(run_log_name(timestamp)))
+}
+
+/// Writes `contents` to `file_name` inside [`log_dir`], creating the directory if needed.
+pub fn save_file(file_name: &str, contents: &str) -> io::Result<PathBuf> {
+    let dir = log_dir();
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(file_name);
+    fs::write(&path, contents)?;
+    Ok(path)
+}
+
+/// Saved logs, newest first. Logs written to the temporary directory by older versions are
+/// included so they can still be found and cleaned up.
+pub fn list_logs() -> Vec<LogFile> {
+    let mut logs: Vec<LogFile> = [log_dir(), std::env::temp_dir()]
+        .iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flatten()
+        .filter_map(Result::ok)
+        .filter(|entry| is_log_file(&entry.path()))
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            metadata.is_file().then(|| LogFile {
+                path: entry.path(),
+                modified: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+                size: metadata.len(),
+            })
+        })
+        .collect();
+    logs.sort_by_key(|log| Reverse(log.modified));
+    logs
+}
+
+/// Reads a saved log, decompressing it if it was archived by [`apply_retention`].
+pub fn read_log(path: &Path) -> io::Result<String> {
+    let mut bytes = fs::read(path)?;
+    if is_compressed(path) {
+        let mut decoded = Vec::new();
+        GzDecoder::new(bytes.as_slice()).read_to_end(&mut decoded)?;
+        bytes = decoded;
+    }
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+pub fn delete_log(path: &Path) -> io::Result<()> {
+    fs::remove_file(path)
+}
+
+/// Deletes saved logs that fall outside the retention limits and returns how many were
+/// removed, then compresses the remaining logs that are old enough. Runs at startup and
+/// after every run.
+pub fn apply_retention(policy: &LoggingSettings) -> usize {
+    let now = SystemTime::now();
+    let logs = list_logs();
+    let expired = expired_logs(&logs, policy, now);
+    let removed = expired
+        .iter()
+        .filter(|log| delete_log(&log.path).is_ok())
+        .count();
+    for log in logs_to_compress(&logs, policy, now) {
+        if !expired.iter().any(|expired| expired.path == log.path) {
+            if let Err(err) = compress_log(log) {
+                eprintln!("Failed to compress {}: {err}", log.path.display());
+            }
+        }
+    }
+    removed
+}
+
+/// Uncompressed logs older than the compression age.
+fn logs_to_compress<'a>(
+    logs: &'a [LogFile],
+    policy: &LoggingSettings,
+    now: SystemTime,
+) -> Vec<&'a LogFile> {
+    if policy.compress_after_days == 0 {
+        return Vec::new();
+    }
+    let min_age = Duration::from_secs(policy.compress_after_days * 24 * 60 * 60);
+    logs.iter()
+        .filter(|log| !is_compressed(&log.path))
+        .filter(|log| {
+            now.duration_since(log.modified)
+                .is_ok_and(|age| age > min_age)
+        })
+        .collect()
+}
+
+/// Replaces `log` with a gzip-compressed copy named `<name>.gz`. The modification time is
+/// kept so the archived log still sorts and expires by the time of its run.
+fn compress_log(log: &LogFile) -> io::Result<PathBuf> {
+    let contents = fs::read(&log.path)?;
+    let mut file_name = log.path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(format!(".{GZIP_EXTENSION}"));
+    let path = log.path.with_file_name(file_name);
+
+    let file = fs::File::create(&path)?;
+    let mut encoder = GzEncoder::new(file, Compression::default());
+    encoder.write_all(&contents)?;
+    let file = encoder.finish()?;
+    file.set_modified(log.modified)?;
+    fs::remove_file(&log.path)?;
+    Ok(path)
+}
+
+/// Logs (sorted newest first) of runs exceeding the count, age or total size limit. The
+/// combined log of a run and those of its commands count as one and expire together, and the
+/// newest run is always kept so the run that just finished never loses its record.
+fn expired_logs<'a>(
+    logs: &'a [LogFile],
+    policy: &LoggingSettings,
+    now: SystemTime,
+) -> Vec<&'a LogFile> {
+    let max_age = Duration::from_secs(policy.max_age_days * 24 * 60 * 60);
+    let max_bytes = policy.max_total_mib * 1024 * 1024;
+    let mut runs: Vec<(&str, Vec<&LogFile>)> = Vec::new();
+    for log in logs {
+        let run = run_of(log);
+        match runs.iter_mut().find(|(known, _)| *known == run) {
+            Some((_, files)) => files.push(log),
+            None => runs.push((run, vec![log])),
+        }
+    }
+    let mut total_size = 0;
+    let mut expired = Vec::new();
+    for (index, (_, files)) in runs.into_iter().enumerate() {
+        total_size += files.iter().map(|log| log.size).sum::<u64>();
+        if index == 0 {
+            continue;
+        }
+        // Files are newest first, so the first one tells when the run ended
+        let too_many = policy.max_logs > 0 && index >= policy.max_logs;
+        let too_old = policy.max_age_days > 0
+            && now
+                .duration_since(files[0].modified)
+                .is_ok_and(|age| age > max_age);
+        let too_large = policy.max_total_mib > 0 && total_size > max_bytes;
+        if too_many || too_old || too_large {
+            expired.extend(files);
+        }
+    }
+    expired
+}
+
+/// The run `log` belongs to: the timestamp its name starts with, shared by the combined log
+/// and those of the run's commands, e.g. `2024-10-01-13-37-00` for
+/// `linutil_log_2024-10-01-13-37-00_2-system-setup-arch-paru.log`.
+fn run_of(log: &LogFile) -> &str {
+    let name = log.path.file_name().and_then(|name| name.to_str());
+    let name = name.unwrap_or_default();
+    let name = name.strip_prefix(LOG_PREFIX).unwrap_or(name);
+    name.split(['_', '.']).next().unwrap_or(name)
+}
+
+/// A log matching a search, with the parts of its contents that matched.
+#[derive(Clone, Debug)]
+pub struct SearchHit {
+    pub log: LogFile,
+    /// Around the first [`MAX_EXCERPT_MATCHES`] matching lines.
+    pub excerpts: Vec<Excerpt>,
+    /// Lines containing the query, including those past the excerpts kept.
+    pub match_count: usize,
+}
+
+/// Consecutive lines of a log around one or more matching lines.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Excerpt {
+    /// Line number of the first line, starting at 1.
+    pub first_line: usize,
+    pub lines: Vec<String>,
+    /// Line numbers of the lines containing the query.
+    pub matches: Vec<usize>,
+}
+
+/// Lines shown before and after each match.
+pub const SEARCH_CONTEXT_LINES: usize = 2;
+
+/// Matching lines per log shown with their context, so a query matching most lines of large
+/// logs stays cheap to show.
+pub const MAX_EXCERPT_MATCHES: usize = 500;
+
+/// Logs whose file name or contents contain `query`, ignoring case, searching archived logs
+/// too. Content matches come with [`SEARCH_CONTEXT_LINES`] lines of context. Reads and
+/// decompresses every log, so it runs off the main thread.
+pub fn search_logs(logs: &[LogFile], query: &str) -> Vec<SearchHit> {
+    let query = query.to_lowercase();
+    logs.iter()
+        .filter_map(|log| {
+            let (excerpts, match_count) = read_log(&log.path)
+                .map(|text| find_excerpts(&text, &query, SEARCH_CONTEXT_LINES, MAX_EXCERPT_MATCHES))
+                .unwrap_or_default();
+            (match_count > 0 || log.file_name().to_lowercase().contains(&query)).then(|| {
+                SearchHit {
+                    log: log.clone(),
+                    excerpts,
+                    match_count,
+                }
+            })
+        })
+        .collect()
+}
+
+/// Groups the lines of `text` containing `query` (already lowercase) with `context` lines
+/// on each side, merging groups that overlap like `grep -C` does. Only the first `limit`
+/// matching lines get excerpts, all are counted in the number returned with them.
+fn find_excerpts(text: &str, query: &str, context: usize, limit: usize) -> (Vec<Excerpt>, usize) {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut excerpts: Vec<Excerpt> = Vec::new();
+    let mut match_count = 0;
+    for (index, line) in lines.iter().enumerate() {
+        if !line.to_lowercase().contains(query) {
+            continue;
+        }
+        match_count += 1;
+        if match_count > limit {
+            continue;
+        }
+        let start = index.saturating_sub(context);
+        let end = (index + context + 1).min(lines.len());
+        match excerpts.last_mut() {
+            Some(excerpt) if excerpt.first_line - 1 + excerpt.lines.len() >= start => {
+                let covered = excerpt.first_line - 1 + excerpt.lines.len();
+                excerpt.lines.extend(
+                    lines[covered.max(start)..end]
+                        .iter()
+                        .map(|line| line.to_string()),
+                );
+                excerpt.matches.push(index + 1);
+            }
+            _ => excerpts.push(Excerpt {
+                first_line: start + 1,
+                lines: lines[start..end]
+                    .iter()
+                    .map(|line| line.to_string())
+                    .collect(),
+                matches: vec![index + 1],
+            }),
+        }
+    }
+    (excerpts, match_count)
+}
+
+/// Excerpts in `grep -n -C` style: `12:` marks a matching line, `13-` a context line and
+/// `--` separates excerpts.
+pub fn format_excerpts(excerpts: &[Excerpt]) -> String {
+    let mut text = String::new();
+    for (index, excerpt) in excerpts.iter().enumerate() {
+        if index > 0 {
+            text.push_str("--\n");
+        }
+        for (offset, line) in excerpt.lines.iter().enumerate() {
+            let number = excerpt.first_line + offset;
+            let separator = if excerpt.matches.contains(&number) {
+                ':'
+            } else {
+                '-'
+            };
+            text.push_str(&format!("{number}{separator}{line}\n"));
+        }
+    }
+    text
+}
+
+/// File name friendly form of a command path: lowercase ASCII letters and digits separated
+/// by single dashes.
+pub fn slug(text: &str) -> String {
+    let mut slug = String::new();
+    for ch in text.chars() {
+        if ch.is_ascii_alphanumeric() {
+            slug.push(ch.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    slug.get(..80)
+        .unwrap_or(slug)
+        .trim_end_matches('-')
+        .to_string()
+}
+
+fn is_log_file(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return false;
+    };
+    let name = name
+        .strip_suffix(&format!(".{GZIP_EXTENSION}"))
+        .unwrap_or(name);
+    name.starts_with(LOG_PREFIX) && name.ends_with(&format!(".{LOG_EXTENSION}"))
+}
+
+fn is_compressed(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension == GZIP_EXTENSION)
+}
+
+/// Local date and time of `time`, e.g. `2024-10-01 13:37`.
+pub fn format_modified(time: SystemTime) -> String {
+    let offset = UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC);
+    OffsetDateTime::from(time)
+        .to_offset(offset)
+        .format(format_description!("[year]-[month]-[day] [hour]:[minute]"))
+        .unwrap_or_default()
+}
+
+/// Human readable file size, e.g. `12.3 KiB`.
+pub fn format_size(size: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = size as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{size} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_log_file() {
+        assert!(is_log_file(Path::new(
+            "/tmp/linutil_log_2024-10-01-13-37-00.log"
+        )));
+        assert!(!is_log_file(Path::new(
+            "/tmp/linutil_report_2024-10-01-13-37-00.md"
+        )));
+        assert!(!is_log_file(Path::new("/tmp/other.log")));
+        assert!(is_log_file(Path::new(
+            "/tmp/linutil_log_2024-10-01-13-37-00.log.gz"
+        )));
+    }
+
+    fn log(name: &str, age_days: u64, size: u64, now: SystemTime) -> LogFile {
+        LogFile {
+            path: PathBuf::from(name),
+            modified: now - Duration::from_secs(age_days * 24 * 60 * 60),
+            size,
+        }
+    }
+
+    #[test]
+    fn test_expired_logs() {
+        let now = SystemTime::now();
+        let logs = vec![
+            log("a", 0, 400, now),
+            log("b", 1, 400, now),
+            log("c", 10, 400, now),
+            log("d", 40, 400, now),
+        ];
+        let names = |policy: &LoggingSettings| {
+            expired_logs(&logs, policy, now)
+                .iter()
+                .map(|log| log.file_name())
+                .collect::<Vec<_>>()
+        };
+
+        let unlimited = LoggingSettings {
+            max_logs: 0,
+            max_age_days: 0,
+            max_total_mib: 0,
+            compress_after_days: 0,
+            journald: false,
+            live_tee: false,
+            redact_secrets: false,
+            directory: None,
+        };
+        assert!(names(&unlimited).is_empty());
+        assert_eq!(
+            names(&LoggingSettings {
+                max_logs: 2,
+                ..unlimited.clone()
+            }),
+            ["c", "d"]
+        );
+        assert_eq!(
+            names(&LoggingSettings {
+                max_age_days: 30,
+                ..unlimited.clone()
+            }),
+            ["d"]
+        );
+        assert_eq!(
+            names(&LoggingSettings {
+                max_logs: 1,
+                ..unlimited.clone()
+            }),
+            ["b", "c", "d"]
+        );
+    }
+
+    #[test]
+    fn test_expired_logs_keeps_newest() {
+        let now = SystemTime::now();
+        let logs = vec![log("big", 100, 5 * 1024 * 1024, now)];
+        let policy = LoggingSettings {
+            max_logs: 1,
+            max_age_days: 1,
+            max_total_mib: 1,
+            compress_after_days: 0,
+            journald: false,
+            live_tee: false,
+            redact_secrets: false,
+            directory: None,
+        };
+
+        assert!(expired_logs(&logs, &policy, now).is_empty());
+    }
+
+    #[test]
+    fn test_expired_logs_by_run() {
+        let now = SystemTime::now();
+        let logs = vec![
+            log("linutil_log_2024-10-03-09-00-00_2-neovim.log", 0, 100, now),
+            log("linutil_log_2024-10-03-09-00-00_1-paru.log", 0, 100, now),
+            log("linutil_log_2024-10-03-09-00-00.log", 0, 200, now),
+            log("linutil_log_2024-10-02-09-00-00.log", 1, 200, now),
+            log(
+                "linutil_log_2024-10-01-09-00-00_1-paru.log.gz",
+                40,
+                100,
+                now,
+            ),
+            log("linutil_log_2024-10-01-09-00-00.log.gz", 40, 100, now),
+        ];
+        let names = |policy: &LoggingSettings| {
+            expired_logs(&logs, policy, now)
+                .iter()
+                .map(|log| log.file_name())
+                .collect::<Vec<_>>()
+        };
+        let policy = LoggingSettings {
+            max_logs: 2,
+            max_age_days: 0,
+            max_total_mib: 0,
+            compress_after_days: 0,
+            journald: false,
+            live_tee: false,
+            redact_secrets: false,
+            directory: None,
+        };
+
+        // The three files of the last run count as one
+        assert_eq!(
+            names(&policy),
+            [
+                "linutil_log_2024-10-01-09-00-00_1-paru.log.gz",
+                "linutil_log_2024-10-01-09-00-00.log.gz"
+            ]
+        );
+        assert_eq!(
+            names(&LoggingSettings {
+                max_logs: 1,
+                ..policy.clone()
+            })
+            .len(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_logs_to_compress() {
+        let now = SystemTime::now();
+        let logs = vec![
+            log("a.log", 0, 400, now),
+            log("b.log", 10, 400, now),
+            log("c.log.gz", 20, 400, now),
+        ];
+        let policy = LoggingSettings {
+            compress_after_days: 7,
+            ..LoggingSettings::default()
+        };
+
+        let names: Vec<_> = logs_to_compress(&logs, &policy, now)
+            .iter()
+            .map(|log| log.file_name())
+            .collect();
+        assert_eq!(names, ["b.log"]);
+    }
+
+    #[test]
+    fn test_compressed_log_round_trip() {
+        let dir = std::env::temp_dir().join(format!("linutil-logs-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("linutil_log_test.log");
+        fs::write(&path, "hello\nworld\n").unwrap();
+        let log = LogFile {
+            path: path.clone(),
+            modified: SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+            size: 12,
+        };
+
+        let compressed = compress_log(&log).unwrap();
+        assert!(!path.exists());
+        assert_eq!(read_log(&compressed).unwrap(), "hello\nworld\n");
+        assert_eq!(
+            fs::metadata(&compressed).unwrap().modified().unwrap(),
+            log.modified
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_find_excerpts() {
+        let text = "one\ntwo nvidia\nthree\nfour\nfive\nsix\nseven NVIDIA\neight";
+
+        let (excerpts, match_count) = find_excerpts(text, "nvidia", 1, MAX_EXCERPT_MATCHES);
+        assert_eq!(match_count, 2);
+        assert_eq!(
+            excerpts,
+            [
+                Excerpt {
+                    first_line: 1,
+                    lines: vec!["one".into(), "two nvidia".into(), "three".into()],
+                    matches: vec![2],
+                },
+                Excerpt {
+                    first_line: 6,
+                    lines: vec!["six".into(), "seven NVIDIA".into(), "eight".into()],
+                    matches: vec![7],
+                },
+            ]
+        );
+
+        let (capped, match_count) = find_excerpts(text, "nvidia", 1, 1);
+        assert_eq!((capped.len(), match_count), (1, 2));
+
+        let (merged, _) = find_excerpts(text, "nvidia", 3, MAX_EXCERPT_MATCHES);
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].lines.len(), 8);
+        assert_eq!(merged[0].matches, [2, 7]);
+        assert_eq!(
+            format_excerpts(&excerpts),
+            "1-one\n2:two nvidia\n3-three\n--\n6-six\n7:seven NVIDIA\n8-eight\n"
+        );
+    }
+
+    #[test]
+    fn test_slug() {
+        assert_eq!(slug("System Setup / Arch / Paru"), "system-setup-arch-paru");
+        assert_eq!(slug("  Neovim (CTT) "), "neovim-ctt");
+        assert_eq!(slug(&"a".repeat(100)).len(), 80);
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(2048), "2.0 KiB");
+        assert_eq!(format_size(5 * 1024 * 1024 + 512 * 1024), "5.5 MiB");
+    }
+}
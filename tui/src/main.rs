@@ -1,14 +1,70 @@
+mod about_dialog;
+mod bundles;
+mod catalog;
 mod cli;
+mod dashboard;
+mod details;
+mod diagnostics;
+mod fresh;
 mod gtk_app;
+mod health;
+mod health_view;
+mod highlight;
+mod history;
+mod integrity;
+mod jobs;
+mod journal;
+mod log_viewer;
+mod logs;
+mod network;
+mod output;
+mod packages;
+mod perf;
+mod pins;
+mod platform;
+mod playbook;
+mod plugins;
+mod power;
+mod presets;
+mod prompts;
+mod recording;
+mod redact;
+mod report;
+mod responses;
+mod runner;
+mod script_view;
+mod search_index;
+mod self_update;
+mod settings;
+mod signature;
+mod sources_window;
+mod stats;
+mod stats_window;
+mod system_info;
+mod tags;
+mod terminal_input;
 mod theme;
+mod toast;
+mod updates;
+mod user_scripts;
+mod versions_window;
+mod watch;
+mod welcome;
 
+#[cfg(test)]
+mod test_util;
 #[cfg(feature = "tips")]
 mod tips;
+#[cfg(feature = "tips")]
+mod tips_window;
 
 use clap::Parser;
 
 fn main() {
     let args = cli::Args::parse();
+    if args.headless {
+        std::process::exit(playbook::run_headless(&args));
+    }
     if let Err(err) = gtk_app::run(args) {
         eprintln!("linutil: {err}");
     }
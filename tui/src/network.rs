@@ -0,0 +1,90 @@
+// Network preflight for commands that download: their scripts are recognized by the tools and
+// package manager calls they use, and a quick name lookup and connection to a well-known host
+// before the run tells whether the downloads can work, rather than a script failing minutes in.
+
+use linutil_core::{Command, ListNode};
+use std::{
+    fs,
+    net::{TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+
+// Host most scripts download from, reached over HTTPS
+const CHECK_HOST: (&str, u16) = ("github.com", 443);
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Words of download tools and the package manager variables `common-script.sh` sets
+const NETWORK_WORDS: [&str; 9] = [
+    "curl",
+    "wget",
+    "aria2c",
+    "git clone",
+    "flatpak install",
+    "$PACKAGER",
+    "$AUR_HELPER",
+    "http://",
+    "https://",
+];
+
+/// Whether running `node` downloads something, judged by its script or raw command.
+pub fn needs_network(node: &ListNode) -> bool {
+    match &node.command {
+        Command::LocalFile { file, .. } => {
+            fs::read_to_string(file).is_ok_and(|source| uses_network(&source))
+        }
+        Command::Raw(command) => uses_network(command),
+        Command::None => false,
+    }
+}
+
+/// Whether the shell source calls a download tool, a package manager or names a URL.
+/// Comments do not count.
+fn uses_network(source: &str) -> bool {
+    source
+        .lines()
+        .map(str::trim_start)
+        .filter(|line| !line.starts_with('#'))
+        .any(|line| NETWORK_WORDS.iter().any(|word| line.contains(word)))
+}
+
+/// Looks up and connects to a well-known host, blocking for a few seconds at most when the
+/// network is down. The error says which of the two failed.
+pub fn check() -> Result<(), String> {
+    let (host, port) = CHECK_HOST;
+    let addresses: Vec<_> = (host, port)
+        .to_socket_addrs()
+        .map_err(|err| format!("Could not look up {host}, name resolution (DNS) failed: {err}"))?
+        .collect();
+    let mut last_error = None;
+    for address in addresses {
+        match TcpStream::connect_timeout(&address, CONNECT_TIMEOUT) {
+            Ok(_) => return Ok(()),
+            Err(err) => last_error = Some(err),
+        }
+    }
+    Err(match last_error {
+        Some(err) => format!("Could not connect to {host}: {err}"),
+        None => format!("Could not look up {host}, it has no addresses."),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uses_network() {
+        assert!(uses_network(
+            "curl -fsSL https://example.org/install.sh | sh\n"
+        ));
+        assert!(uses_network(
+            "    \"$ESCALATION_TOOL\" \"$PACKAGER\" install -y git\n"
+        ));
+        assert!(uses_network("git clone --depth 1 \"$REPO\" \"$DIR\"\n"));
+        assert!(!uses_network(
+            "# fetched with curl before\nsystemctl enable --now sshd\n"
+        ));
+        assert!(!uses_network("rm -rf \"$HOME/.cache/thumbnails\"\n"));
+    }
+}
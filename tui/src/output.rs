@@ -0,0 +1,283 @@
+use std::collections::VecDeque;
+
+/// Output of a run, kept as the UTF-8 bytes of each chunk that was read. Appending never
+/// copies the output read before, which a single growing `String` does when it reallocates.
+#[derive(Default)]
+pub struct OutputBuffer {
+    chunks: VecDeque<Vec<u8>>,
+    len: usize,
+    /// Offset where the last, unfinished line starts.
+    line_start: usize,
+}
+
+impl OutputBuffer {
+    pub fn push(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        if let Some(index) = text.rfind('\n') {
+            self.line_start = self.len + index + 1;
+        }
+        self.len += text.len();
+        self.chunks.push_back(text.as_bytes().to_vec());
+    }
+
+    /// Replaces the last, unfinished line with `text`.
+    pub fn rewrite_line(&mut self, text: &str) {
+        while self.len > self.line_start {
+            let Some(chunk) = self.chunks.back_mut() else {
+                break;
+            };
+            let excess = self.len - self.line_start;
+            if chunk.len() > excess {
+                chunk.truncate(chunk.len() - excess);
+                self.len = self.line_start;
+            } else {
+                self.len -= chunk.len();
+                self.chunks.pop_back();
+            }
+        }
+        self.push(text);
+    }
+
+    /// Length in bytes. Offsets taken from it always fall between two chunks, so they are
+    /// valid character boundaries of [`OutputBuffer::text`].
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn text(&self) -> String {
+        let mut bytes = Vec::with_capacity(self.len);
+        for chunk in &self.chunks {
+            bytes.extend_from_slice(chunk);
+        }
+        // Only whole strings are pushed, so this never has to replace anything
+        String::from_utf8_lossy(&bytes).into_owned()
+    }
+}
+
+/// Decodes a byte stream read in arbitrary pieces. A character split between two reads is
+/// held back until the rest of it arrives instead of being replaced by U+FFFD.
+#[derive(Default)]
+pub struct Utf8Decoder {
+    pending: Vec<u8>,
+}
+
+impl Utf8Decoder {
+    pub fn decode(&mut self, bytes: &[u8]) -> String {
+        self.pending.extend_from_slice(bytes);
+        let mut text = String::new();
+        let mut rest = self.pending.as_slice();
+        loop {
+            match std::str::from_utf8(rest) {
+                Ok(valid) => {
+                    text.push_str(valid);
+                    rest = &[];
+                    break;
+                }
+                Err(err) => {
+                    let (valid, after) = rest.split_at(err.valid_up_to());
+                    text.push_str(std::str::from_utf8(valid).unwrap_or_default());
+                    match err.error_len() {
+                        Some(len) => {
+                            text.push(char::REPLACEMENT_CHARACTER);
+                            rest = &after[len..];
+                        }
+                        // The input ends in the middle of a character
+                        None => {
+                            rest = after;
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        self.pending = rest.to_vec();
+        text
+    }
+}
+
+/// A change to the output as a terminal shows it.
+#[derive(Debug, PartialEq, Eq)]
+pub enum LineEdit {
+    /// Text added at the end.
+    Append(String),
+    /// Text replacing the last, unfinished line.
+    Rewrite(String),
+}
+
+/// Turns decoded output into the text a terminal shows for it. ANSI escapes are stripped,
+/// except that carriage returns, backspaces and erasing the line are applied, so progress
+/// indicators redrawing their line update it in place.
+#[derive(Default)]
+pub struct LineEditor {
+    /// The last, unfinished line.
+    line: Vec<char>,
+    column: usize,
+    /// The start of an escape sequence split between two reads.
+    pending: String,
+    bracketed_paste: bool,
+}
+
+impl LineEditor {
+    /// Whether the program asked for pasted text to be marked as such with `ESC[?2004h`, so
+    /// it is not run as if typed.
+    pub fn bracketed_paste(&self) -> bool {
+        self.bracketed_paste
+    }
+
+    /// How `text`, the output read next, changes the output read before.
+    pub fn feed(&mut self, text: &str) -> LineEdit {
+        let text = std::mem::take(&mut self.pending) + text;
+        let mut out = String::new();
+        // Characters of the current line that were output before
+        let mut shown = self.line.len();
+        let mut rewrite = false;
+        let mut changed = |from: usize, shown: &mut usize| {
+            if from < *shown {
+                rewrite = true;
+                *shown = 0;
+            }
+        };
+
+        let mut chars = text.char_indices().peekable();
+        while let Some((index, ch)) = chars.next() {
+            match ch {
+                '\n' => {
+                    out.extend(&self.line[shown.min(self.line.len())..]);
+                    out.push('\n');
+                    self.line.clear();
+                    self.column = 0;
+                    shown = 0;
+                }
+                '\r' => self.column = 0,
+                '\u{8}' => self.column = self.column.saturating_sub(1),
+                '\u{1b}' => {
+                    if chars.peek().is_none_or(|&(_, next)| next == '[') {
+                        chars.next();
+                        let mut sequence = String::new();
+                        let final_char = chars.by_ref().find_map(|(_, next)| {
+                            if ('@'..='~').contains(&next) {
+                                Some(next)
+                            } else {
+                                sequence.push(next);
+                                None
+                            }
+                        });
+                        match final_char {
+                            Some('K') => {
+                                let from = match sequence.as_str() {
+                                    "" | "0" => self.column,
+                                    _ => 0,
+                                };
+                                changed(from, &mut shown);
+                                match sequence.as_str() {
+                                    "" | "0" => self.line.truncate(self.column),
+                                    "1" => {
+                                        let end = (self.column + 1).min(self.line.len());
+                                        self.line[..end].fill(' ');
+                                    }
+                                    _ => self.line.clear(),
+                                }
+                            }
+                            Some(mode @ ('h' | 'l')) if sequence == "?2004" => {
+                                self.bracketed_paste = mode == 'h';
+                            }
+                            Some(_) => {}
+                            None if text.len() - index < 64 => self.pending = text[index..].into(),
+                            None => {}
+                        }
+                    }
+                }
+                _ => {
+                    changed(self.column, &mut shown);
+                    if self.column < self.line.len() {
+                        self.line[self.column] = ch;
+                    } else {
+                        self.line.resize(self.column, ' ');
+                        self.line.push(ch);
+                    }
+                    self.column += 1;
+                }
+            }
+        }
+        out.extend(&self.line[shown.min(self.line.len())..]);
+
+        if rewrite {
+            LineEdit::Rewrite(out)
+        } else {
+            LineEdit::Append(out)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_buffer() {
+        let mut output = OutputBuffer::default();
+        output.push("héllo ");
+        output.push("");
+        output.push("wörld");
+
+        assert_eq!(output.len(), "héllo wörld".len());
+        assert_eq!(output.text(), "héllo wörld");
+    }
+
+    #[test]
+    fn test_rewrite_line() {
+        let mut output = OutputBuffer::default();
+        output.push("done\n10");
+        output.push("%");
+        output.rewrite_line("50%");
+        assert_eq!(output.text(), "done\n50%");
+        output.rewrite_line("100%\nnext");
+        output.rewrite_line("");
+        assert_eq!(output.text(), "done\n100%\n");
+        assert_eq!(output.len(), "done\n100%\n".len());
+    }
+
+    #[test]
+    fn test_line_editor() {
+        let mut editor = LineEditor::default();
+        assert_eq!(
+            editor.feed("\x1b[1mStart\x1b[0m\r\nGetting 10%"),
+            LineEdit::Append("Start\nGetting 10%".into())
+        );
+        assert_eq!(
+            editor.feed("\rGetting 50%"),
+            LineEdit::Rewrite("Getting 50%".into())
+        );
+        // A redraw split between two reads, and an escape split in the middle
+        assert_eq!(editor.feed("\r\x1b["), LineEdit::Append(String::new()));
+        assert_eq!(editor.feed("KDone"), LineEdit::Rewrite("Done".into()));
+        assert_eq!(editor.feed("!\n"), LineEdit::Append("!\n".into()));
+        assert_eq!(editor.feed("abc\rx\n"), LineEdit::Append("xbc\n".into()));
+        assert_eq!(editor.feed("ab\u{8}c"), LineEdit::Append("ac".into()));
+        assert_eq!(editor.feed("\x1b[2K\rok"), LineEdit::Rewrite("ok".into()));
+
+        assert!(!editor.bracketed_paste());
+        editor.feed("\x1b[?2004h$ ");
+        assert!(editor.bracketed_paste());
+        editor.feed("\x1b[?2004l");
+        assert!(!editor.bracketed_paste());
+    }
+
+    #[test]
+    fn test_utf8_decoder() {
+        let bytes = "ok ✓ 🙀".as_bytes();
+        let mut decoder = Utf8Decoder::default();
+
+        let mut text = String::new();
+        for byte in bytes {
+            text.push_str(&decoder.decode(&[*byte]));
+        }
+        assert_eq!(text, "ok ✓ 🙀");
+
+        assert_eq!(decoder.decode(b"a\xffb"), "a\u{fffd}b");
+        assert_eq!(decoder.decode(&bytes[3..5]), "");
+        assert_eq!(decoder.decode(&bytes[5..6]), "✓");
+    }
+}
@@ -0,0 +1,184 @@
+// Packages a command's script installs with the package manager of this system, found in its
+// `case "$PACKAGER"` branches, and the package manager's dry run listing what installing them
+// would change, so it can be previewed before the real run.
+
+use crate::platform::Platform;
+use linutil_core::{Command, ListNode};
+use std::fs;
+
+/// Shell command previewing what the script of `node` would install here, or `None` when it
+/// installs nothing this system's package manager can preview.
+pub fn preview_command(node: &ListNode) -> Option<String> {
+    let Command::LocalFile { file, .. } = &node.command else {
+        return None;
+    };
+    let manager = Platform::current().package_manager?;
+    let source = fs::read_to_string(file).ok()?;
+    dry_run(manager, &install_packages(&source, manager))
+}
+
+/// The dry run of installing `packages` with `manager`.
+fn dry_run(manager: &str, packages: &[String]) -> Option<String> {
+    if packages.is_empty() {
+        return None;
+    }
+    let command = match manager {
+        "pacman" => "pacman -S --print --print-format '%n %v'",
+        "apt-get" => "apt-get -s install",
+        "dnf" => "dnf install --assumeno",
+        "zypper" => "zypper --non-interactive install --dry-run",
+        "apk" => "apk add --simulate",
+        "xbps-install" => "xbps-install --dry-run",
+        "emerge" => "emerge --pretend",
+        _ => return None,
+    };
+    Some(format!("{command} {}", packages.join(" ")))
+}
+
+/// Packages `source` installs with `manager`, in the order it names them. Only calls in `case`
+/// branches for `manager`, or outside any branch, count.
+fn install_packages(source: &str, manager: &str) -> Vec<String> {
+    let mut packages: Vec<String> = Vec::new();
+    // Patterns of the `case` branch the line is in
+    let mut branch: Option<Vec<&str>> = None;
+    for line in source.lines() {
+        let mut line = line.trim();
+        if line.starts_with('#') {
+            continue;
+        }
+        if let Some((pattern, rest)) = line.split_once(')') {
+            let is_pattern = !pattern.is_empty()
+                && pattern.chars().all(|c| {
+                    c.is_ascii_alphanumeric() || matches!(c, '|' | '-' | '_' | '*' | '"' | ' ')
+                });
+            if is_pattern {
+                branch = Some(
+                    pattern
+                        .split('|')
+                        .map(|word| word.trim_matches([' ', '"']))
+                        .collect(),
+                );
+                line = rest;
+            }
+        }
+        let applies = branch.as_ref().is_none_or(|patterns| {
+            patterns
+                .iter()
+                .any(|pattern| manager_matches(manager, pattern))
+        });
+        if applies {
+            for package in installed_by(line, manager) {
+                if !packages.contains(&package) {
+                    packages.push(package);
+                }
+            }
+        }
+        if line.ends_with(";;") || line == "esac" {
+            branch = None;
+        }
+    }
+    packages
+}
+
+/// Whether `word`, a `case` pattern or a program called, stands for `manager`.
+fn manager_matches(manager: &str, word: &str) -> bool {
+    match manager {
+        "apt-get" => matches!(word, "apt-get" | "apt" | "nala"),
+        manager => word == manager,
+    }
+}
+
+/// Packages the package manager call in `line` installs, if it is one.
+fn installed_by(line: &str, manager: &str) -> Vec<String> {
+    let words = line
+        .split_whitespace()
+        .map(|word| word.trim_matches(['"', '\'']))
+        .skip_while(|&word| word != "$PACKAGER" && !manager_matches(manager, word))
+        .skip(1)
+        .take_while(|word| !matches!(*word, "&&" | "||" | "|" | ";" | ";;") && !word.contains('>'));
+    let mut installs = manager == "xbps-install";
+    let mut packages = Vec::new();
+    for word in words {
+        if let Some(flags) = word.strip_prefix('-') {
+            // `-S` and `-Sy` install, `-Ss` searches and `-Si` shows information
+            if manager == "pacman" && flags.starts_with('S') && !flags.contains(['s', 'i', 'c']) {
+                installs = true;
+            }
+            continue;
+        }
+        if !installs && packages.is_empty() && matches!(word, "install" | "add" | "in" | "it") {
+            installs = true;
+            continue;
+        }
+        let is_name = !word.is_empty()
+            && word.chars().all(|c| {
+                c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '+' | '@' | ':')
+            });
+        if is_name {
+            packages.push(word.to_string());
+        }
+    }
+    if installs {
+        packages
+    } else {
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCRIPT: &str = r#"#!/bin/sh -e
+. ../common-script.sh
+
+installNeovim() {
+    case "$PACKAGER" in
+        pacman)
+            "$ESCALATION_TOOL" "$PACKAGER" -S --needed --noconfirm neovim ripgrep git
+            ;;
+        apt-get|nala)
+            "$ESCALATION_TOOL" "$PACKAGER" update
+            "$ESCALATION_TOOL" "$PACKAGER" install -y ripgrep fd-find git >/dev/null
+            ;;
+        dnf) "$ESCALATION_TOOL" "$PACKAGER" install -y neovim ripgrep ;;
+        *)
+            printf "Unsupported package manager: %s\n" "$PACKAGER"
+            ;;
+    esac
+    "$ESCALATION_TOOL" "$PACKAGER" -Ss neovim
+}
+"#;
+
+    #[test]
+    fn test_install_packages() {
+        assert_eq!(
+            install_packages(SCRIPT, "pacman"),
+            ["neovim", "ripgrep", "git"]
+        );
+        assert_eq!(
+            install_packages(SCRIPT, "apt-get"),
+            ["ripgrep", "fd-find", "git"]
+        );
+        assert_eq!(install_packages(SCRIPT, "dnf"), ["neovim", "ripgrep"]);
+        assert!(install_packages(SCRIPT, "zypper").is_empty());
+        assert_eq!(
+            install_packages("sudo xbps-install -Sy flatpak\n", "xbps-install"),
+            ["flatpak"]
+        );
+    }
+
+    #[test]
+    fn test_dry_run() {
+        assert_eq!(
+            dry_run("apt-get", &["git".into(), "fd-find".into()]).as_deref(),
+            Some("apt-get -s install git fd-find")
+        );
+        assert_eq!(
+            dry_run("pacman", &["git".into()]).as_deref(),
+            Some("pacman -S --print --print-format '%n %v' git")
+        );
+        assert_eq!(dry_run("dnf", &[]), None);
+        assert_eq!(dry_run("nix-env", &["git".into()]), None);
+    }
+}
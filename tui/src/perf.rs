@@ -0,0 +1,113 @@
+// Timing instrumentation enabled by `--trace-perf`. Samples are collected on the GTK main
+// thread while the app runs and written as a Markdown report next to the logs on exit.
+
+use crate::{logs, runner};
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    fmt::Write as _,
+    io,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+thread_local! {
+    static SAMPLES: RefCell<Option<BTreeMap<&'static str, Vec<Duration>>>> =
+        const { RefCell::new(None) };
+}
+
+/// Starts recording; until then [`measure`] only runs the closure.
+pub fn enable() {
+    SAMPLES.with(|samples| *samples.borrow_mut() = Some(BTreeMap::new()));
+}
+
+/// Runs `f` and, when tracing is enabled, records how long it took under `name`.
+pub fn measure<T>(name: &'static str, f: impl FnOnce() -> T) -> T {
+    let enabled = SAMPLES.with(|samples| samples.borrow().is_some());
+    if !enabled {
+        return f();
+    }
+    let start = Instant::now();
+    let result = f();
+    let elapsed = start.elapsed();
+    SAMPLES.with(|samples| {
+        if let Some(samples) = samples.borrow_mut().as_mut() {
+            samples.entry(name).or_default().push(elapsed);
+        }
+    });
+    result
+}
+
+/// Writes the recorded timings to the log directory. Returns `None` when tracing is off.
+pub fn save_report() -> Option<io::Result<PathBuf>> {
+    let samples = SAMPLES.with(|samples| samples.borrow_mut().take())?;
+    Some(logs::save_file(
+        &format!("linutil_perf_{}.md", runner::file_timestamp()),
+        &render(&samples),
+    ))
+}
+
+fn render(samples: &BTreeMap<&'static str, Vec<Duration>>) -> String {
+    let mut report = String::from("# Linutil performance trace\n\n");
+    let _ = writeln!(report, "- Version: {}\n", env!("CARGO_PKG_VERSION"));
+    report.push_str(
+        "| Operation | Calls | Total (ms) | Mean (ms) | Median (ms) | P95 (ms) | Max (ms) |\n",
+    );
+    report.push_str("| --- | ---: | ---: | ---: | ---: | ---: | ---: |\n");
+    for (name, durations) in samples {
+        let mut sorted = durations.clone();
+        sorted.sort();
+        let total: Duration = sorted.iter().sum();
+        let _ = writeln!(
+            report,
+            "| {name} | {} | {} | {} | {} | {} | {} |",
+            sorted.len(),
+            millis(total),
+            millis(total / sorted.len() as u32),
+            millis(percentile(&sorted, 50)),
+            millis(percentile(&sorted, 95)),
+            millis(sorted.last().copied().unwrap_or_default()),
+        );
+    }
+    report
+}
+
+/// Nearest-rank percentile of sorted durations.
+fn percentile(sorted: &[Duration], percent: usize) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = (percent * sorted.len()).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+fn millis(duration: Duration) -> String {
+    format!("{:.3}", duration.as_secs_f64() * 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentile() {
+        let sorted: Vec<Duration> = (1..=20).map(Duration::from_millis).collect();
+
+        assert_eq!(percentile(&sorted, 50), Duration::from_millis(10));
+        assert_eq!(percentile(&sorted, 95), Duration::from_millis(19));
+        assert_eq!(percentile(&sorted[..1], 95), Duration::from_millis(1));
+        assert_eq!(percentile(&[], 50), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_render() {
+        let mut samples = BTreeMap::new();
+        samples.insert(
+            "refresh_list",
+            vec![Duration::from_millis(3), Duration::from_millis(1)],
+        );
+
+        let report = render(&samples);
+        assert!(report.contains("| refresh_list | 2 | 4.000 | 2.000 | 1.000 | 3.000 | 3.000 |"));
+    }
+}
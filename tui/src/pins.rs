@@ -0,0 +1,99 @@
+// Commands pinned by the user, shown in a strip above the command list whatever folder is
+// open. Stored in `pins.toml` in the config directory by command name, in pinning order.
+
+use crate::settings::config_dir;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+#[derive(Default, Deserialize, Serialize)]
+pub struct Pins {
+    #[serde(default)]
+    commands: Vec<String>,
+}
+
+impl Pins {
+    pub fn path() -> PathBuf {
+        config_dir().join("pins.toml")
+    }
+
+    pub fn load() -> Self {
+        Self::load_from(&Self::path())
+    }
+
+    fn load_from(path: &Path) -> Self {
+        match fs::read_to_string(path) {
+            Ok(content) => toml::from_str(&content).unwrap_or_else(|err| {
+                eprintln!("Failed to parse pins file {}: {err}", path.display());
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        self.save_to(&Self::path())
+    }
+
+    fn save_to(&self, path: &Path) -> io::Result<()> {
+        let content =
+            toml::to_string(self).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, content)
+    }
+
+    pub fn names(&self) -> &[String] {
+        &self.commands
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.commands.iter().any(|pinned| pinned == name)
+    }
+
+    /// Pins the command called `name` after the others, returning false when `max` commands
+    /// are pinned already.
+    pub fn pin(&mut self, name: &str, max: usize) -> bool {
+        if self.contains(name) {
+            return true;
+        }
+        if self.commands.len() >= max {
+            return false;
+        }
+        self.commands.push(name.to_string());
+        true
+    }
+
+    pub fn unpin(&mut self, name: &str) {
+        self.commands.retain(|pinned| pinned != name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pins() {
+        let mut pins = Pins::default();
+        assert!(pins.pin("Full System Update", 2));
+        assert!(pins.pin("Docker", 2));
+        assert!(pins.pin("Docker", 2));
+        assert!(!pins.pin("Flatpak", 2));
+        assert_eq!(pins.names(), ["Full System Update", "Docker"]);
+
+        pins.unpin("Full System Update");
+        assert!(!pins.contains("Full System Update"));
+        assert!(pins.pin("Flatpak", 2));
+
+        let path =
+            std::env::temp_dir().join(format!("linutil-pins-test-{}.toml", std::process::id()));
+        pins.save_to(&path).unwrap();
+        let loaded = Pins::load_from(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.names(), ["Docker", "Flatpak"]);
+    }
+}
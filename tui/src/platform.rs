@@ -0,0 +1,814 @@
+// What kind of system Linutil runs on: the distribution family, package manager, init system,
+// display server, desktop, CPU architecture and whether it is image-based, runs under WSL or in
+// a container. Read once at startup, shown in the status bar of the main window and used to
+// tell commands written for other systems and desktops apart.
+
+use crate::updates::in_path;
+use linutil_core::{Command, ListNode};
+use std::{cell::RefCell, collections::HashMap, env, fs, path::Path, process, sync::OnceLock};
+
+/// Package managers scripts call, with the command each one is found by.
+const PACKAGE_MANAGERS: [&str; 9] = [
+    "pacman",
+    "apt-get",
+    "dnf",
+    "zypper",
+    "apk",
+    "xbps-install",
+    "emerge",
+    "eopkg",
+    "nix-env",
+];
+
+// Desktops and window managers commands are written for, with the words naming them in
+// `XDG_CURRENT_DESKTOP` and in command names and tags
+const DESKTOPS: [(&str, &[&str]); 12] = [
+    ("GNOME", &["gnome"]),
+    ("KDE", &["kde", "plasma"]),
+    ("XFCE", &["xfce"]),
+    ("Cinnamon", &["cinnamon", "x-cinnamon"]),
+    ("MATE", &["mate"]),
+    ("Budgie", &["budgie"]),
+    ("LXQt", &["lxqt"]),
+    ("COSMIC", &["cosmic"]),
+    ("Hyprland", &["hyprland"]),
+    ("Sway", &["sway"]),
+    ("i3", &["i3"]),
+    ("DWM", &["dwm"]),
+];
+
+// CPU architectures scripts are written for, with the names `uname -m`, Debian packages and
+// download URLs use for them
+const ARCHITECTURES: [(&str, &[&str]); 5] = [
+    ("x86_64", &["x86_64", "amd64", "x64"]),
+    ("aarch64", &["aarch64", "arm64"]),
+    ("armv7", &["armv7l", "armv7", "armhf"]),
+    ("i686", &["i686", "i386"]),
+    ("riscv64", &["riscv64"]),
+];
+
+// Words in scripts that set up graphics drivers, display managers or the boot loader
+const HARDWARE_WORDS: [&str; 9] = [
+    "nvidia",
+    "nvidia-dkms",
+    "sddm",
+    "gdm",
+    "lightdm",
+    "grub",
+    "grub-mkconfig",
+    "plymouth",
+    "xserver-xorg",
+];
+
+// Tools acting on a running X11 session, which do nothing under Wayland
+const X11_WORDS: [&str; 9] = [
+    "xrandr",
+    "xinput",
+    "setxkbmap",
+    "xset",
+    "xmodmap",
+    "xdotool",
+    "xrdb",
+    "xprop",
+    "xinitrc",
+];
+
+// Tools acting on a running Wayland compositor
+const WAYLAND_WORDS: [&str; 3] = ["swaymsg", "hyprctl", "wlr-randr"];
+
+// Distributions other distributions name in `ID_LIKE`, so derivatives get the family of the
+// distribution they are based on
+const FAMILIES: [&str; 9] = [
+    "arch", "debian", "ubuntu", "fedora", "rhel", "suse", "alpine", "void", "gentoo",
+];
+
+#[derive(Debug, Default)]
+pub struct Platform {
+    /// Name of the distribution, e.g. `Fedora Linux 41 (Workstation Edition)`.
+    pub distro: Option<String>,
+    /// Distribution the system belongs to or is based on, e.g. `arch` for EndeavourOS.
+    pub family: Option<String>,
+    /// Command of the package manager, e.g. `pacman`.
+    pub package_manager: Option<&'static str>,
+    pub init_system: Option<&'static str>,
+    pub display_server: Option<&'static str>,
+    /// Desktop or window manager of the session, e.g. `KDE`.
+    pub desktop: Option<&'static str>,
+    /// CPU architecture from `uname -m`, e.g. `aarch64`.
+    pub architecture: Option<&'static str>,
+    /// How an image-based system with a read-only `/usr` takes packages, if it is one.
+    pub immutable: Option<Immutable>,
+    /// Whether this is a distribution running under the Windows Subsystem for Linux.
+    pub wsl: bool,
+    /// Container Linutil runs in, whose changes do not reach the host, if it runs in one.
+    pub container: Option<Container>,
+}
+
+/// Image-based systems, where package managers cannot change the system as scripts expect.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Immutable {
+    /// Fedora Silverblue, Kinoite, Bazzite and other systems booted from an ostree image.
+    Ostree,
+    /// SteamOS, whose root is read-only and reset by updates.
+    SteamOs,
+    /// openSUSE MicroOS and Aeon, which change a snapshot used from the next boot.
+    Transactional,
+}
+
+impl Immutable {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Ostree => "ostree",
+            Self::SteamOs => "SteamOS",
+            Self::Transactional => "transactional-update",
+        }
+    }
+
+    /// The way packages are added on the system instead.
+    pub fn alternative(self) -> &'static str {
+        match self {
+            Self::Ostree => "Layer packages with rpm-ostree install, or use Flatpak or a toolbox.",
+            Self::SteamOs => "Use Flatpak or a Distrobox container instead.",
+            Self::Transactional => {
+                "Use transactional-update pkg install and reboot, or Flatpak or a Distrobox \
+                 container."
+            }
+        }
+    }
+}
+
+/// Containers Linutil can run in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Container {
+    Docker,
+    Podman,
+    /// A Toolbx container, which reaches the host through `flatpak-spawn --host`.
+    Toolbox,
+    /// A Distrobox container, which reaches the host through `distrobox-host-exec`.
+    Distrobox,
+    /// Another container, named by the `container` variable its manager sets.
+    Other,
+}
+
+impl Container {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Docker => "Docker",
+            Self::Podman => "Podman",
+            Self::Toolbox => "Toolbx",
+            Self::Distrobox => "Distrobox",
+            Self::Other => "container",
+        }
+    }
+
+    /// Command prefix running a command on the host instead, for containers that share the
+    /// home directory with the host and offer one.
+    pub fn host_exec(self) -> Option<&'static [&'static str]> {
+        match self {
+            Self::Toolbox => Some(&["flatpak-spawn", "--host"]),
+            Self::Distrobox => Some(&["distrobox-host-exec"]),
+            Self::Docker | Self::Podman | Self::Other => None,
+        }
+    }
+}
+
+impl Platform {
+    /// The system, detected on first use.
+    pub fn current() -> &'static Self {
+        static PLATFORM: OnceLock<Platform> = OnceLock::new();
+        PLATFORM.get_or_init(Self::detect)
+    }
+
+    fn detect() -> Self {
+        let os_release = fs::read_to_string("/etc/os-release").unwrap_or_default();
+        let init_process = fs::read_to_string("/proc/1/comm").unwrap_or_default();
+        Self {
+            distro: os_release_value(&os_release, "PRETTY_NAME")
+                .or_else(|| os_release_value(&os_release, "NAME")),
+            family: distro_family(&os_release),
+            package_manager: PACKAGE_MANAGERS
+                .into_iter()
+                .find(|program| in_path(program)),
+            init_system: init_system(
+                init_process.trim(),
+                Path::new("/run/systemd/system").is_dir(),
+                Path::new("/run/openrc").is_dir(),
+            ),
+            display_server: display_server(
+                env::var_os("WAYLAND_DISPLAY").is_some(),
+                env::var_os("DISPLAY").is_some(),
+                env::var("XDG_SESSION_TYPE").ok().as_deref(),
+            ),
+            desktop: [
+                "XDG_CURRENT_DESKTOP",
+                "XDG_SESSION_DESKTOP",
+                "DESKTOP_SESSION",
+            ]
+            .into_iter()
+            .find_map(|var| desktop_named(&env::var(var).ok()?)),
+            architecture: process::Command::new("uname")
+                .arg("-m")
+                .output()
+                .ok()
+                .and_then(|output| {
+                    architecture_named(String::from_utf8_lossy(&output.stdout).trim())
+                })
+                .or_else(|| architecture_named(env::consts::ARCH)),
+            immutable: immutable(
+                Path::new("/run/ostree-booted").exists(),
+                os_release_value(&os_release, "ID").as_deref(),
+                in_path("transactional-update"),
+            ),
+            wsl: is_wsl(
+                &fs::read_to_string("/proc/sys/kernel/osrelease").unwrap_or_default(),
+                env::var_os("WSL_DISTRO_NAME").is_some(),
+            ),
+            container: container(
+                Path::new("/.dockerenv").exists(),
+                Path::new("/run/.containerenv").exists(),
+                Path::new("/run/.toolboxenv").exists(),
+                env::var_os("DISTROBOX_ENTER_PATH").is_some() || in_path("distrobox-host-exec"),
+                env::var("container").ok().as_deref(),
+            ),
+        }
+    }
+
+    /// The facts for the status bar, e.g.
+    /// `Arch Linux · pacman · systemd · Wayland · KDE · x86_64`.
+    pub fn summary(&self) -> String {
+        let parts: Vec<&str> = [
+            self.distro.as_deref(),
+            self.package_manager,
+            self.init_system,
+            self.display_server,
+            self.desktop,
+            self.architecture,
+            self.immutable.map(Immutable::name),
+            self.wsl.then_some("WSL"),
+            self.container.map(Container::name),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if parts.is_empty() {
+            "Unknown system".to_string()
+        } else {
+            parts.join(" · ")
+        }
+    }
+
+    /// Whether a command with the script `source` can work here: it calls no package manager,
+    /// or at least the one of this system. Unknown package managers fit anything.
+    pub fn fits(&self, source: &str) -> bool {
+        let used = package_managers_in(source);
+        used.is_empty()
+            || self
+                .package_manager
+                .is_none_or(|manager| used.contains(&manager))
+    }
+}
+
+/// Desktops `node` is written for, judged by the words in its name and tags. Commands that
+/// install a desktop are for any desktop, since they are run to get it.
+pub fn desktops_of(node: &ListNode) -> Vec<&'static str> {
+    let installs = [&node.name, &node.description]
+        .iter()
+        .any(|text| text.to_lowercase().contains("install"));
+    if installs {
+        return Vec::new();
+    }
+    let text = format!("{} {}", node.name, node.tags.join(" ")).to_lowercase();
+    let words: Vec<&str> = text
+        .split(|c: char| !(c.is_alphanumeric() || c == '-'))
+        .collect();
+    DESKTOPS
+        .into_iter()
+        .filter(|(_, names)| words.iter().any(|word| names.contains(word)))
+        .map(|(desktop, _)| desktop)
+        .collect()
+}
+
+/// Whether `node` is written for no desktop in particular or for the one of this session.
+/// Everything fits when the desktop is unknown.
+pub fn fits_desktop(node: &ListNode) -> bool {
+    let desktops = desktops_of(node);
+    desktops.is_empty()
+        || Platform::current()
+            .desktop
+            .is_none_or(|desktop| desktops.contains(&desktop))
+}
+
+/// The desktop in the value of `XDG_CURRENT_DESKTOP`, e.g. `ubuntu:GNOME`.
+fn desktop_named(value: &str) -> Option<&'static str> {
+    value.split(':').find_map(|part| {
+        let part = part.trim().to_lowercase();
+        DESKTOPS
+            .into_iter()
+            .find(|(_, names)| names.contains(&part.as_str()))
+            .map(|(desktop, _)| desktop)
+    })
+}
+
+/// Package managers `source` calls, e.g. every one a script supports in its `case` on the
+/// package manager.
+pub fn package_managers_in(source: &str) -> Vec<&'static str> {
+    let words: Vec<&str> = source
+        .split(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_'))
+        .collect();
+    PACKAGE_MANAGERS
+        .into_iter()
+        .filter(|manager| {
+            words.iter().any(|word| match *manager {
+                // Scripts often use plain `apt`, or `nala` in its place
+                "apt-get" => matches!(*word, "apt-get" | "apt" | "nala"),
+                // AUR helpers wrap pacman
+                "pacman" => matches!(*word, "pacman" | "yay" | "paru"),
+                manager => *word == manager,
+            })
+        })
+        .collect()
+}
+
+/// Architectures a script is only for: those in its `tags`, and those named by the download
+/// URLs and file names in `source`, e.g. the `amd64` of `driver_amd64.deb`. Other mentions,
+/// like `dpkg --add-architecture i386` or multilib packages such as `glibc-devel.i686`, add
+/// support rather than limit it. Scripts naming none are taken to run anywhere.
+pub fn architectures_in(source: &str, tags: &[String]) -> Vec<&'static str> {
+    let source = source.to_lowercase();
+    let downloads = source
+        .split(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | '=' | '(' | ')' | ';'))
+        .filter(|token| token.contains("://") || is_download_name(token));
+    // `x86_64` as a whole, and `amd64` in `driver_amd64`
+    let mut words: Vec<String> = downloads
+        .flat_map(|token| token.split(|c: char| !(c.is_alphanumeric() || c == '_')))
+        .flat_map(|word| std::iter::once(word).chain(word.split('_')))
+        .map(str::to_string)
+        .collect();
+    words.extend(tags.iter().map(|tag| tag.to_lowercase()));
+    ARCHITECTURES
+        .into_iter()
+        .filter(|(_, names)| words.iter().any(|word| names.contains(&word.as_str())))
+        .map(|(architecture, _)| architecture)
+        .collect()
+}
+
+/// Whether `token` is the name of a downloaded package or archive, e.g. `ngrok-amd64.tgz`.
+fn is_download_name(token: &str) -> bool {
+    const EXTENSIONS: [&str; 10] = [
+        ".deb",
+        ".rpm",
+        ".tgz",
+        ".gz",
+        ".xz",
+        ".bz2",
+        ".zst",
+        ".zip",
+        ".appimage",
+        ".run",
+    ];
+    EXTENSIONS
+        .iter()
+        .any(|extension| token.trim_end_matches(['/', ',']).ends_with(extension))
+}
+
+fn architecture_named(name: &str) -> Option<&'static str> {
+    ARCHITECTURES
+        .into_iter()
+        .find(|(_, names)| names.contains(&name))
+        .map(|(architecture, _)| architecture)
+}
+
+/// What the script of a command says about the systems it works on.
+#[derive(Clone)]
+struct ScriptFacts {
+    /// See [`Platform::fits`].
+    fits: bool,
+    /// Architectures the script is only for, see [`architectures_in`].
+    architectures: Vec<&'static str>,
+    /// Whether the script calls a package manager.
+    installs: bool,
+    /// Whether the script manages services with `systemctl`.
+    systemd: bool,
+    /// Whether the script changes drivers, the display manager or the boot loader.
+    hardware: bool,
+    /// Display server the script only works under, see [`session_of`].
+    session: Option<&'static str>,
+}
+
+thread_local! {
+    /// Facts of each script, by path, or by command line for commands without a file.
+    static SCRIPTS: RefCell<HashMap<String, ScriptFacts>> = RefCell::new(HashMap::new());
+}
+
+/// The facts of the script of `node`, read once per command.
+fn script_facts(node: &ListNode) -> Option<ScriptFacts> {
+    let key = match &node.command {
+        Command::LocalFile { file, .. } => file.display().to_string(),
+        Command::Raw(command) => command.clone(),
+        Command::None => return None,
+    };
+    if let Some(facts) = SCRIPTS.with(|cache| cache.borrow().get(&key).cloned()) {
+        return Some(facts);
+    }
+    let source = match &node.command {
+        Command::LocalFile { file, .. } => fs::read_to_string(file).unwrap_or_default(),
+        _ => key.clone(),
+    };
+    let facts = ScriptFacts {
+        fits: Platform::current().fits(&source),
+        installs: !package_managers_in(&source).is_empty(),
+        systemd: mentions(&source, &["systemctl"]),
+        hardware: mentions(&source, &HARDWARE_WORDS),
+        session: session_of(&source),
+        architectures: architectures_in(&source, &node.tags),
+    };
+    SCRIPTS.with(|cache| cache.borrow_mut().insert(key, facts.clone()));
+    Some(facts)
+}
+
+/// Forgets the facts of the scripts in `dir`, so they are read again after being edited.
+pub fn forget_scripts(dir: &Path) {
+    SCRIPTS.with(|cache| {
+        cache
+            .borrow_mut()
+            .retain(|key, _| !Path::new(key).starts_with(dir))
+    });
+}
+
+/// Whether `node` suits this system: [`Platform::fits`] its script, it is not only for other
+/// architectures or display servers, it installs no packages on an image-based system and it
+/// can run under WSL.
+pub fn node_fits(node: &ListNode) -> bool {
+    script_facts(node).is_none_or(|facts| facts.fits)
+        && unsupported_architecture(node).is_none()
+        && unsupported_session(node).is_none()
+        && unsupported_on_immutable(node).is_none()
+        && unsupported_on_wsl(node).is_none()
+}
+
+/// The display server `source` only works under: `X11` when it calls tools like `xrandr` or
+/// `setxkbmap` and no Wayland ones, `Wayland` the other way round.
+fn session_of(source: &str) -> Option<&'static str> {
+    match (
+        mentions(source, &X11_WORDS),
+        mentions(source, &WAYLAND_WORDS),
+    ) {
+        (true, false) => Some("X11"),
+        (false, true) => Some("Wayland"),
+        _ => None,
+    }
+}
+
+/// The display server `node` only works under, if its script is written for one.
+pub fn session_of_node(node: &ListNode) -> Option<&'static str> {
+    script_facts(node)?.session
+}
+
+/// Why `node` does nothing in this session, e.g. `Only works in X11 sessions, this session
+/// is Wayland.`, if its script is written for the other display server.
+pub fn unsupported_session(node: &ListNode) -> Option<String> {
+    let current = Platform::current().display_server?;
+    let session = session_of_node(node).filter(|&session| session != current)?;
+    Some(format!(
+        "Only works in {session} sessions, this session is {current}."
+    ))
+}
+
+/// Why `node` cannot run under WSL, if its script needs systemd where it is not running, or
+/// changes drivers, the display manager or the boot loader, which WSL does not use.
+pub fn unsupported_on_wsl(node: &ListNode) -> Option<String> {
+    let platform = Platform::current();
+    if !platform.wsl {
+        return None;
+    }
+    let facts = script_facts(node)?;
+    if facts.hardware {
+        return Some(
+            "Changes drivers, the display manager or the boot loader, which WSL does not use."
+                .to_string(),
+        );
+    }
+    (facts.systemd && platform.init_system != Some("systemd")).then(|| {
+        "Needs systemd, which this WSL distribution does not run. Set systemd=true under \
+         [boot] in /etc/wsl.conf to turn it on."
+            .to_string()
+    })
+}
+
+/// `path`, the value of `PATH` under WSL, without the Windows directories WSL adds, e.g.
+/// `/mnt/c/Windows/system32`, so scripts neither find Windows programs nor wait on the slow
+/// Windows file system when they look for commands.
+pub fn linux_path(path: &str) -> String {
+    path.split(':')
+        .filter(|dir| !dir.starts_with("/mnt/"))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// Whether the kernel release, e.g. `5.15.153.1-microsoft-standard-WSL2`, or the
+/// `WSL_DISTRO_NAME` WSL sets say this runs under WSL.
+fn is_wsl(kernel_release: &str, distro_name_set: bool) -> bool {
+    let kernel_release = kernel_release.to_lowercase();
+    distro_name_set || kernel_release.contains("microsoft") || kernel_release.contains("wsl")
+}
+
+/// The container the marker files and variables show, checking the more specific ones first:
+/// Toolbx and Distrobox containers are Podman containers too, and Distrobox sets `container`.
+fn container(
+    dockerenv: bool,
+    containerenv: bool,
+    toolboxenv: bool,
+    distrobox: bool,
+    variable: Option<&str>,
+) -> Option<Container> {
+    if toolboxenv {
+        Some(Container::Toolbox)
+    } else if distrobox {
+        Some(Container::Distrobox)
+    } else if containerenv || variable == Some("podman") {
+        Some(Container::Podman)
+    } else if dockerenv || variable == Some("docker") {
+        Some(Container::Docker)
+    } else {
+        variable
+            .filter(|value| !value.is_empty())
+            .map(|_| Container::Other)
+    }
+}
+
+/// Why `node` cannot run on an image-based system, if its script installs packages with a
+/// package manager there.
+pub fn unsupported_on_immutable(node: &ListNode) -> Option<String> {
+    let immutable = Platform::current().immutable?;
+    script_facts(node)?.installs.then(|| {
+        format!(
+            "Installs packages, which this image-based system does not allow. {}",
+            immutable.alternative()
+        )
+    })
+}
+
+/// Why `node` cannot run on this device's architecture, e.g. `Only for x86_64, this device
+/// is aarch64.`, if its script names only others.
+pub fn unsupported_architecture(node: &ListNode) -> Option<String> {
+    let architecture = Platform::current().architecture?;
+    let architectures = script_facts(node)?.architectures;
+    (!architectures.is_empty() && !architectures.contains(&architecture)).then(|| {
+        format!(
+            "Only for {}, this device is {architecture}.",
+            architectures.join(", ")
+        )
+    })
+}
+
+fn os_release_value(text: &str, key: &str) -> Option<String> {
+    text.lines().find_map(|line| {
+        let value = line.strip_prefix(key)?.strip_prefix('=')?;
+        let value = value.trim().trim_matches(['"', '\'']);
+        (!value.is_empty()).then(|| value.to_string())
+    })
+}
+
+/// The family in `/etc/os-release`: `ID` if it is one of the known families, otherwise the
+/// first known one in `ID_LIKE`, otherwise `ID` itself.
+fn distro_family(os_release: &str) -> Option<String> {
+    let id = os_release_value(os_release, "ID")?.to_lowercase();
+    if FAMILIES.contains(&id.as_str()) {
+        return Some(id);
+    }
+    let like = os_release_value(os_release, "ID_LIKE").unwrap_or_default();
+    let family = like
+        .split_whitespace()
+        .map(str::to_lowercase)
+        .find(|like| FAMILIES.contains(&like.as_str()));
+    Some(family.unwrap_or(id))
+}
+
+/// Whether `source` contains any of `words` as a whole word.
+fn mentions(source: &str, words: &[&str]) -> bool {
+    source
+        .split(|c: char| !(c.is_alphanumeric() || c == '-'))
+        .any(|word| words.contains(&word))
+}
+
+/// The kind of image-based system from whether it was booted from an ostree image, the `ID` in
+/// `/etc/os-release` and whether `transactional-update` is installed.
+fn immutable(ostree_booted: bool, id: Option<&str>, transactional: bool) -> Option<Immutable> {
+    if ostree_booted {
+        Some(Immutable::Ostree)
+    } else if id == Some("steamos") {
+        Some(Immutable::SteamOs)
+    } else if transactional {
+        Some(Immutable::Transactional)
+    } else {
+        None
+    }
+}
+
+/// The init system from the name of process 1 and the runtime directories systemd and
+/// OpenRC create.
+fn init_system(process: &str, systemd_dir: bool, openrc_dir: bool) -> Option<&'static str> {
+    if systemd_dir || process == "systemd" {
+        return Some("systemd");
+    }
+    if openrc_dir || process == "openrc-init" {
+        return Some("OpenRC");
+    }
+    match process {
+        "runit" => Some("runit"),
+        "s6-svscan" => Some("s6"),
+        "dinit" => Some("dinit"),
+        "init" => Some("SysV init"),
+        _ => None,
+    }
+}
+
+/// The display server of the session, or `None` outside a graphical session.
+fn display_server(wayland: bool, x11: bool, session_type: Option<&str>) -> Option<&'static str> {
+    match session_type {
+        _ if wayland => Some("Wayland"),
+        _ if x11 => Some("X11"),
+        Some("wayland") => Some("Wayland"),
+        Some("x11") => Some("X11"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distro_family() {
+        assert_eq!(
+            distro_family("NAME=\"EndeavourOS\"\nID=\"endeavouros\"\nID_LIKE=\"arch\"\n")
+                .as_deref(),
+            Some("arch")
+        );
+        assert_eq!(
+            distro_family("ID=linuxmint\nID_LIKE=\"ubuntu debian\"\n").as_deref(),
+            Some("ubuntu")
+        );
+        assert_eq!(distro_family("ID=fedora\n").as_deref(), Some("fedora"));
+        assert_eq!(distro_family("ID=nixos\n").as_deref(), Some("nixos"));
+        assert_eq!(distro_family("NAME=Linux\n"), None);
+    }
+
+    #[test]
+    fn test_init_and_display() {
+        assert_eq!(init_system("systemd", false, false), Some("systemd"));
+        assert_eq!(init_system("init", false, true), Some("OpenRC"));
+        assert_eq!(init_system("runit", false, false), Some("runit"));
+        assert_eq!(init_system("bash", false, false), None);
+
+        assert_eq!(display_server(true, true, None), Some("Wayland"));
+        assert_eq!(display_server(false, true, Some("wayland")), Some("X11"));
+        assert_eq!(display_server(false, false, Some("tty")), None);
+    }
+
+    #[test]
+    fn test_desktops() {
+        assert_eq!(desktop_named("ubuntu:GNOME"), Some("GNOME"));
+        assert_eq!(desktop_named("KDE"), Some("KDE"));
+        assert_eq!(desktop_named("X-Cinnamon"), Some("Cinnamon"));
+        assert_eq!(desktop_named("Unity"), None);
+
+        let node = |name: &str, description: &str, tags: &[&str]| ListNode {
+            name: name.to_string(),
+            description: description.to_string(),
+            command: Command::None,
+            task_list: String::new(),
+            multi_select: true,
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        };
+        assert_eq!(desktops_of(&node("KDE Plasma Theme", "", &[])), ["KDE"]);
+        assert_eq!(desktops_of(&node("Dark Theme", "", &["gnome"])), ["GNOME"]);
+        assert!(desktops_of(&node(
+            "Hyprland JaKooLit",
+            "Install JaKooLit's Hyprland configuration",
+            &[]
+        ))
+        .is_empty());
+        // Named in the description only, e.g. as an example
+        assert!(desktops_of(&node("Numlock on Startup", "unlike KDE or GNOME", &[])).is_empty());
+        assert!(desktops_of(&node("Terminal Mates", "", &[])).is_empty());
+    }
+
+    #[test]
+    fn test_architectures() {
+        assert_eq!(
+            architectures_in("curl -LO https://example.com/driver_amd64.deb", &[]),
+            ["x86_64"]
+        );
+        assert_eq!(
+            architectures_in(
+                "case \"$(uname -m)\" in\n  x86_64) DEB=\"tool-amd64.deb\" ;;\n  \
+                 aarch64|arm64) DEB=\"tool-arm64.deb\" ;;\nesac",
+                &[]
+            ),
+            ["x86_64", "aarch64"]
+        );
+        assert_eq!(
+            architectures_in("sudo pacman -S foo", &["arm64".to_string()]),
+            ["aarch64"]
+        );
+        assert!(architectures_in("sudo pacman -S firefox", &[]).is_empty());
+        // Adding 32-bit libraries and multilib packages does not limit a script to them
+        for script in [
+            include_str!("../../core/tabs/system-setup/gaming-setup.sh"),
+            include_str!("../../core/tabs/system-setup/compile-setup.sh"),
+        ] {
+            assert!(architectures_in(script, &[]).is_empty());
+        }
+        assert_eq!(architecture_named("arm64"), Some("aarch64"));
+        assert_eq!(architecture_named("mips"), None);
+    }
+
+    #[test]
+    fn test_immutable() {
+        assert_eq!(
+            immutable(true, Some("fedora"), false),
+            Some(Immutable::Ostree)
+        );
+        assert_eq!(
+            immutable(false, Some("steamos"), false),
+            Some(Immutable::SteamOs)
+        );
+        assert_eq!(
+            immutable(false, Some("opensuse-microos"), true),
+            Some(Immutable::Transactional)
+        );
+        assert_eq!(immutable(false, Some("arch"), false), None);
+    }
+
+    #[test]
+    fn test_session_of() {
+        assert_eq!(
+            session_of("setxkbmap -option caps:escape\nxset r rate 200 40\n"),
+            Some("X11")
+        );
+        assert_eq!(session_of("hyprctl reload\n"), Some("Wayland"));
+        assert_eq!(session_of("xrandr --auto || wlr-randr\n"), None);
+        assert_eq!(
+            session_of("gsettings set org.gnome.desktop.interface gtk-theme Adwaita\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_wsl() {
+        assert!(is_wsl("5.15.153.1-microsoft-standard-WSL2", false));
+        assert!(is_wsl("", true));
+        assert!(!is_wsl("6.11.4-arch1-1", false));
+        assert_eq!(
+            linux_path(
+                "/usr/local/bin:/usr/bin:/mnt/c/Windows/system32:/mnt/c/Program Files/Git/cmd"
+            ),
+            "/usr/local/bin:/usr/bin"
+        );
+        assert!(mentions(
+            "sudo grub-mkconfig -o /boot/grub/grub.cfg",
+            &HARDWARE_WORDS
+        ));
+        assert!(!mentions("echo nvidia-settings", &HARDWARE_WORDS));
+    }
+
+    #[test]
+    fn test_container() {
+        assert_eq!(
+            container(false, true, true, false, Some("oci")),
+            Some(Container::Toolbox)
+        );
+        assert_eq!(
+            container(false, true, false, true, Some("podman")),
+            Some(Container::Distrobox)
+        );
+        assert_eq!(
+            container(true, false, false, false, None),
+            Some(Container::Docker)
+        );
+        assert_eq!(
+            container(false, false, false, false, Some("lxc")),
+            Some(Container::Other)
+        );
+        assert_eq!(container(false, false, false, false, None), None);
+        assert!(Container::Docker.host_exec().is_none());
+    }
+
+    #[test]
+    fn test_fits() {
+        let arch = Platform {
+            package_manager: Some("pacman"),
+            ..Platform::default()
+        };
+        let script = "case \"$PACKAGER\" in\n  pacman) \"$AUR_HELPER\" -S --needed foo ;;\n  apt-get|nala) \"$ESCALATION_TOOL\" apt-get install -y foo ;;\nesac\n";
+        assert_eq!(package_managers_in(script), ["pacman", "apt-get"]);
+        assert!(arch.fits(script));
+        assert!(!arch.fits("sudo dnf install -y foo"));
+        assert!(arch.fits("paru -S foo"));
+        assert!(arch.fits("echo done"));
+        assert!(Platform::default().fits("sudo dnf install -y foo"));
+        // Part of a longer word, not a call of the package manager
+        assert!(package_managers_in("install_dnfdragora").is_empty());
+    }
+}
@@ -0,0 +1,387 @@
+// Playbooks: TOML files running commands in a fixed order, e.g. to set up another machine like
+// this one. Unlike the `auto_execute` list of `--config` files, each step can set environment
+// variables, answer the questions its command asks and decide whether the rest runs after it
+// fails:
+//
+//     name = "Workstation"
+//     on_failure = "abort"
+//
+//     [[step]]
+//     command = "Applications Setup / Developer Tools / Neovim"
+//     on_failure = "continue"
+//     env = { NVIM_CONFIG = "kickstart" }
+//
+//     [[step.answer]]
+//     pattern = "*[Y/n]*"
+//     reply = "y"
+//
+// A step names its command by the path shown in the output window, or by its name alone when
+// that is unique. Answers are rules like the ones of `responses.toml` that only apply to their
+// step. `linutil --playbook FILE` runs a playbook in the window, and with `--headless` in the
+// terminal without one.
+
+use crate::{
+    catalog,
+    cli::Args,
+    integrity, plugins,
+    responses::Rule,
+    runner::{CommandRunner, CommandStatus, QueuedCommand, RunEvent},
+    settings::{FailurePolicy, IntegrityPolicy, Settings},
+    user_scripts,
+};
+use linutil_core::{ListNode, Tab};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, BufRead, Write},
+    path::Path,
+    rc::Rc,
+    sync::mpsc,
+    thread,
+    time::Duration,
+};
+
+#[derive(Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Playbook {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    /// What the rest does when a step without a policy of its own fails.
+    #[serde(default)]
+    pub on_failure: FailurePolicy,
+    #[serde(default, rename = "step")]
+    pub steps: Vec<Step>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Step {
+    /// Path of the command in the tabs, e.g. `System Setup / Arch Linux / Paru AUR Helper`.
+    pub command: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_failure: Option<FailurePolicy>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    #[serde(default, rename = "answer", skip_serializing_if = "Vec::is_empty")]
+    pub answers: Vec<Rule>,
+}
+
+impl Playbook {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|err| format!("Could not read {}: {err}", path.display()))?;
+        Self::parse(&content).map_err(|err| format!("{}: {err}", path.display()))
+    }
+
+    fn parse(text: &str) -> Result<Self, String> {
+        let playbook: Self = toml::from_str(text).map_err(|err| err.to_string())?;
+        if playbook.steps.is_empty() {
+            return Err("The playbook has no steps.".to_string());
+        }
+        let invalid = playbook
+            .steps
+            .iter()
+            .flat_map(|step| step.env.keys())
+            .find(|key| !is_env_name(key));
+        if let Some(key) = invalid {
+            return Err(format!("{key:?} is not a valid environment variable name."));
+        }
+        Ok(playbook)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let content =
+            toml::to_string(self).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        fs::write(path, content)
+    }
+
+    /// The steps as commands of `tabs` to run, each with its policy set. Fails naming the
+    /// steps whose command is not in `tabs`, as running only some of them could leave the
+    /// system half set up.
+    pub fn commands(&self, tabs: &[Tab]) -> Result<Vec<QueuedCommand>, String> {
+        let available = command_paths(tabs);
+        let mut commands = Vec::new();
+        let mut missing = Vec::new();
+        for step in &self.steps {
+            let Some((path, node)) = find_command(&available, &step.command) else {
+                missing.push(step.command.as_str());
+                continue;
+            };
+            commands.push(QueuedCommand {
+                node: node.clone(),
+                path: path.clone(),
+                env: step.env.clone().into_iter().collect(),
+                answers: step.answers.clone(),
+                on_failure: Some(step.on_failure.unwrap_or(self.on_failure)),
+            });
+        }
+        if missing.is_empty() {
+            Ok(commands)
+        } else {
+            Err(format!("Commands not found: {}", missing.join(", ")))
+        }
+    }
+
+    /// Name to show for the playbook, from the file it was loaded from when it has none.
+    pub fn title(&self, path: &Path) -> String {
+        if self.name.is_empty() {
+            path.file_stem()
+                .map_or_else(String::new, |stem| stem.to_string_lossy().into_owned())
+        } else {
+            self.name.clone()
+        }
+    }
+}
+
+/// Whether `key` can be exported by a POSIX shell.
+fn is_env_name(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+}
+
+/// Every command of `tabs` with its path, in tab order.
+fn command_paths(tabs: &[Tab]) -> Vec<(String, Rc<ListNode>)> {
+    let mut commands = Vec::new();
+    for tab in tabs.iter() {
+        for node in tab.tree.root().descendants() {
+            if node.has_children() || node.parent().is_none() {
+                continue;
+            }
+            let mut path: Vec<&str> = node
+                .ancestors()
+                .filter(|ancestor| ancestor.parent().is_some())
+                .map(|ancestor| ancestor.value().name.as_str())
+                .collect();
+            path.reverse();
+            path.insert(0, &tab.name);
+            path.push(&node.value().name);
+            commands.push((path.join(" / "), node.value().clone()));
+        }
+    }
+    commands
+}
+
+/// The command at `path`, or else the only command named `path`.
+fn find_command<'a>(
+    commands: &'a [(String, Rc<ListNode>)],
+    path: &str,
+) -> Option<&'a (String, Rc<ListNode>)> {
+    let path = path.trim();
+    if let Some(command) = commands.iter().find(|(candidate, _)| candidate == path) {
+        return Some(command);
+    }
+    let mut named = commands.iter().filter(|(_, node)| node.name == path);
+    match (named.next(), named.next()) {
+        (Some(command), None) => Some(command),
+        _ => None,
+    }
+}
+
+/// Runs the playbook of `--playbook` without a window, printing the output of its commands
+/// and reading answers to their questions from the terminal. Returns the exit code: 0 when
+/// every step succeeded.
+pub fn run_headless(args: &Args) -> i32 {
+    let Some(path) = &args.playbook else {
+        return 2;
+    };
+    let playbook = match Playbook::load(path) {
+        Ok(playbook) => playbook,
+        Err(err) => {
+            eprintln!("linutil: {err}");
+            return 2;
+        }
+    };
+    let settings = Settings::load();
+    let validate = !args.override_validation;
+    let mut tabs = catalog::to_start_from(&settings.updates)
+        .and_then(|snapshot| linutil_core::load_tabs_from(&snapshot.dir, validate).ok())
+        .unwrap_or_else(|| linutil_core::get_tabs(validate));
+    tabs.extend(plugins::PluginLoader::new(validate));
+    tabs.extend(user_scripts::load_tab());
+    integrity::init(tabs.script_dir(), &settings.integrity);
+
+    let commands = match playbook.commands(&tabs) {
+        Ok(commands) => commands,
+        Err(err) => {
+            eprintln!("linutil: {err}");
+            return 2;
+        }
+    };
+    // Nobody is there to choose running modified scripts anyway
+    let (policy, problems) = integrity::check(&commands);
+    if policy != IntegrityPolicy::Off && !problems.is_empty() {
+        eprintln!("linutil: Not running, the scripts do not match their checksums:");
+        for problem in problems {
+            eprintln!("  {problem}");
+        }
+        return 1;
+    }
+
+    println!(
+        "Running {} ({} steps)",
+        playbook.title(path),
+        commands.len()
+    );
+    let runner = CommandRunner::spawn(&commands, &settings, playbook.on_failure);
+    let events = runner.events();
+    let (done_sender, done) = mpsc::channel();
+    thread::spawn(move || {
+        let mut stdout = io::stdout();
+        let mut finished = None;
+        loop {
+            // Output still queued behind the end of the run is printed before the summary
+            let event = match finished {
+                None => events.recv_blocking().ok(),
+                Some(_) => events.try_recv().ok(),
+            };
+            let Some(event) = event else {
+                break;
+            };
+            match event {
+                RunEvent::Output(chunk) => {
+                    let _ = stdout.write_all(chunk.as_bytes());
+                }
+                RunEvent::Rewrite(chunk) => {
+                    let _ = stdout.write_all(format!("\r\x1b[2K{chunk}").as_bytes());
+                }
+                RunEvent::Finished { success } => finished = Some(success),
+            }
+            let _ = stdout.flush();
+        }
+        if let Some(success) = finished {
+            let _ = done_sender.send(success);
+        }
+    });
+    // Lines typed in the terminal go to the command, e.g. a password for sudo
+    let (input_sender, input) = mpsc::channel();
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            if input_sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
+    let success = loop {
+        match done.recv_timeout(Duration::from_millis(100)) {
+            Ok(success) => break success,
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                while let Ok(line) = input.try_recv() {
+                    runner.send_input(&format!("{line}\n"));
+                }
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => break false,
+        }
+    };
+
+    println!();
+    for record in runner.records() {
+        let outcome = match record.status {
+            CommandStatus::Finished { success: true, .. } => "done".to_string(),
+            CommandStatus::Finished { exit_code, .. } => format!("failed ({exit_code})"),
+            CommandStatus::Skipped => "skipped".to_string(),
+            CommandStatus::Pending | CommandStatus::Running => "not run".to_string(),
+        };
+        println!("{outcome:>12}  {}", record.path);
+    }
+    match runner.save_log() {
+        Ok(log) => println!("Log saved to {log}"),
+        Err(err) => eprintln!("linutil: Failed to save the log: {err}"),
+    }
+    if success {
+        0
+    } else {
+        1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::test_node;
+    use linutil_core::{ego_tree::Tree, Command};
+
+    const PLAYBOOK: &str = r#"
+name = "Workstation"
+on_failure = "abort"
+
+[[step]]
+command = "System Setup / Arch / Paru"
+env = { PARU_FLAGS = "--needed" }
+
+[[step.answer]]
+pattern = "*[Y/n]*"
+reply = "y"
+
+[[step]]
+command = "Neovim"
+on_failure = "continue"
+"#;
+
+    #[test]
+    fn test_parse() {
+        let playbook = Playbook::parse(PLAYBOOK).unwrap();
+        assert_eq!(playbook.name, "Workstation");
+        assert_eq!(playbook.on_failure, FailurePolicy::Abort);
+        assert_eq!(playbook.steps.len(), 2);
+        assert_eq!(playbook.steps[0].env["PARU_FLAGS"], "--needed");
+        assert_eq!(playbook.steps[0].answers.len(), 1);
+        assert_eq!(playbook.steps[1].on_failure, Some(FailurePolicy::Continue));
+
+        let saved = toml::to_string(&playbook).unwrap();
+        assert_eq!(Playbook::parse(&saved).unwrap(), playbook);
+
+        assert!(Playbook::parse("name = \"Empty\"\n").is_err());
+        assert!(
+            Playbook::parse("[[step]]\ncommand = \"Paru\"\nenv = { \"A-B\" = \"1\" }\n").is_err()
+        );
+        assert!(Playbook::parse("[[step]]\ncommand = \"Paru\"\nretries = 3\n").is_err());
+    }
+
+    #[test]
+    fn test_commands() {
+        let node = |name: &str| test_node(name, Command::Raw(format!("echo {name}")));
+        let mut system = Tree::new(node("root"));
+        system.root_mut().append(node("Arch")).append(node("Paru"));
+        system
+            .root_mut()
+            .append(node("Fedora"))
+            .append(node("Paru"));
+        let mut applications = Tree::new(node("root"));
+        applications.root_mut().append(node("Neovim"));
+        let tabs = [
+            Tab {
+                name: "System Setup".to_string(),
+                tree: system,
+            },
+            Tab {
+                name: "Applications".to_string(),
+                tree: applications,
+            },
+        ];
+
+        let playbook = Playbook::parse(PLAYBOOK).unwrap();
+        let commands = playbook.commands(&tabs).unwrap();
+        assert_eq!(commands[0].path, "System Setup / Arch / Paru");
+        assert_eq!(
+            commands[0].env,
+            [("PARU_FLAGS".to_string(), "--needed".to_string())]
+        );
+        assert_eq!(commands[0].on_failure, Some(FailurePolicy::Abort));
+        assert_eq!(commands[1].path, "Applications / Neovim");
+        assert_eq!(commands[1].on_failure, Some(FailurePolicy::Continue));
+
+        // Paru alone matches two commands
+        let ambiguous = Playbook::parse("[[step]]\ncommand = \"Paru\"\n").unwrap();
+        assert_eq!(
+            ambiguous.commands(&tabs).err().as_deref(),
+            Some("Commands not found: Paru")
+        );
+    }
+}
@@ -0,0 +1,167 @@
+// Tabs provided by plugins, so distributions and communities can add commands without
+// changing the GUI. A plugin is a directory in `plugins/` of the config directory or of
+// `/usr/share/linutil-gui`, holding a `tab_data.toml` in the format of the built-in tabs
+// and the scripts it names. A `plugin.toml` can instead name a provider, a program printing
+// the tab data when the GUI starts, e.g. to list what is installed on the system.
+
+use crate::settings::config_dir;
+use linutil_core::Tab;
+use serde::Deserialize;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+const SYSTEM_PLUGIN_DIR: &str = "/usr/share/linutil-gui/plugins";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Manifest {
+    /// Command line printing the tab data, run in the plugin's directory. A program given
+    /// with a `/` is relative to that directory, others are looked up in `PATH`.
+    provider: Option<String>,
+}
+
+/// Loads the tabs of the plugins one at a time, skipping plugins that fail to load.
+pub struct PluginLoader {
+    plugins: std::vec::IntoIter<PathBuf>,
+    validate: bool,
+}
+
+impl PluginLoader {
+    pub fn new(validate: bool) -> Self {
+        Self {
+            plugins: plugin_dirs().into_iter(),
+            validate,
+        }
+    }
+
+    #[cfg(test)]
+    fn from_dirs(dirs: &[&Path], validate: bool) -> Self {
+        Self {
+            plugins: find_plugins(dirs).into_iter(),
+            validate,
+        }
+    }
+}
+
+/// Directories of the installed plugins, in the order their tabs are shown.
+pub fn plugin_dirs() -> Vec<PathBuf> {
+    let user_dir = config_dir().join("plugins");
+    find_plugins(&[Path::new(SYSTEM_PLUGIN_DIR), &user_dir])
+}
+
+/// Plugins in `dirs` sorted by name. A plugin in a later directory replaces the one of the
+/// same name in an earlier one, so users can override what their distribution ships.
+fn find_plugins(dirs: &[&Path]) -> Vec<PathBuf> {
+    let mut plugins: Vec<PathBuf> = Vec::new();
+    for dir in dirs {
+        let Ok(entries) = fs::read_dir(dir) else {
+            continue;
+        };
+        for path in entries.filter_map(Result::ok).map(|entry| entry.path()) {
+            if !path.is_dir() {
+                continue;
+            }
+            plugins.retain(|known| known.file_name() != path.file_name());
+            plugins.push(path);
+        }
+    }
+    plugins.sort_by(|a, b| a.file_name().cmp(&b.file_name()));
+    plugins
+}
+
+impl Iterator for PluginLoader {
+    type Item = Tab;
+
+    fn next(&mut self) -> Option<Tab> {
+        for dir in self.plugins.by_ref() {
+            match load_plugin(&dir, self.validate) {
+                Ok(tab) => return Some(tab),
+                Err(err) => eprintln!("Failed to load plugin {}: {err}", dir.display()),
+            }
+        }
+        None
+    }
+}
+
+/// The tab of the plugin in `dir`, read again from its files or its provider.
+pub fn load_plugin(dir: &Path, validate: bool) -> Result<Tab, String> {
+    let manifest: Manifest = match fs::read_to_string(dir.join("plugin.toml")) {
+        Ok(content) => toml::from_str(&content).map_err(|err| err.to_string())?,
+        Err(_) => Manifest::default(),
+    };
+    let data = match manifest.provider {
+        Some(provider) => run_provider(dir, &provider)?,
+        None => fs::read_to_string(dir.join("tab_data.toml")).map_err(|err| err.to_string())?,
+    };
+    linutil_core::parse_tab(&data, dir, validate)
+}
+
+fn run_provider(dir: &Path, provider: &str) -> Result<String, String> {
+    let mut parts = provider.split_whitespace();
+    let program = parts.next().ok_or("the provider is empty")?;
+    let program = if program.contains('/') {
+        dir.join(program)
+    } else {
+        PathBuf::from(program)
+    };
+    let output = Command::new(&program)
+        .args(parts)
+        .current_dir(dir)
+        .output()
+        .map_err(|err| format!("{}: {err}", program.display()))?;
+    if !output.status.success() {
+        return Err(format!(
+            "the provider failed with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    String::from_utf8(output.stdout).map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TAB_DATA: &str =
+        "name = \"Extra\"\n\n[[data]]\nname = \"Hello\"\nscript = \"hello.sh\"\n";
+
+    #[test]
+    fn test_load_plugins() {
+        let root =
+            std::env::temp_dir().join(format!("linutil-plugins-test-{}", std::process::id()));
+        let system = root.join("system");
+        let user = root.join("user");
+        for plugin in [
+            system.join("extra"),
+            system.join("broken"),
+            user.join("extra"),
+        ] {
+            fs::create_dir_all(&plugin).unwrap();
+            fs::write(plugin.join("hello.sh"), "echo hello\n").unwrap();
+        }
+        fs::write(
+            system.join("extra/tab_data.toml"),
+            "name = \"Replaced\"\ndata = []\n",
+        )
+        .unwrap();
+        fs::write(system.join("broken/plugin.toml"), "provider = \"false\"\n").unwrap();
+        // The user's plugin prints its tab data instead of shipping it
+        fs::write(user.join("extra/tabs.txt"), TAB_DATA).unwrap();
+        fs::write(
+            user.join("extra/plugin.toml"),
+            "provider = \"cat tabs.txt\"\n",
+        )
+        .unwrap();
+
+        let tabs: Vec<Tab> = PluginLoader::from_dirs(&[&system, &user], false).collect();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(tabs.len(), 1);
+        assert_eq!(tabs[0].name, "Extra");
+        assert!(tabs[0].find_command_by_name("Hello").is_some());
+    }
+}
@@ -0,0 +1,92 @@
+// Power state around long runs: whether the system runs on battery, asked from UPower, to
+// warn before updates and driver changes that should not be cut off, and a logind inhibitor
+// keeping the system from suspending while commands run.
+
+use gtk::{gio, glib, prelude::*};
+use gtk4 as gtk;
+use std::cell::RefCell;
+
+// Words in a command's name or path marking it as long and harmful to interrupt
+const LONG_WORDS: [&str; 6] = [
+    "update", "upgrade", "driver", "kernel", "firmware", "nvidia",
+];
+
+// D-Bus calls give up after this many milliseconds
+const CALL_TIMEOUT: i32 = 1000;
+
+thread_local! {
+    /// File descriptor list holding the logind inhibitor lock, released when dropped.
+    static INHIBITOR: RefCell<Option<gio::UnixFDList>> = const { RefCell::new(None) };
+}
+
+/// Whether `path`, a command's name with its categories, names a system update or a driver
+/// or kernel change.
+pub fn is_long(path: &str) -> bool {
+    let path = path.to_lowercase();
+    LONG_WORDS.iter().any(|word| path.contains(word))
+}
+
+/// Whether UPower reports the system running on battery. `None` when UPower is not there to
+/// ask, e.g. on desktops without it.
+pub fn on_battery() -> Option<bool> {
+    let bus = gio::bus_get_sync(gio::BusType::System, gio::Cancellable::NONE).ok()?;
+    let reply = bus
+        .call_sync(
+            Some("org.freedesktop.UPower"),
+            "/org/freedesktop/UPower",
+            "org.freedesktop.DBus.Properties",
+            "Get",
+            Some(&("org.freedesktop.UPower", "OnBattery").to_variant()),
+            Some(glib::VariantTy::new("(v)").ok()?),
+            gio::DBusCallFlags::NONE,
+            CALL_TIMEOUT,
+            gio::Cancellable::NONE,
+        )
+        .ok()?;
+    reply.child_value(0).as_variant()?.get::<bool>()
+}
+
+/// Keeps the system from suspending, sleeping on idle included, until [`allow_suspend`].
+/// Taking the lock again while holding it does nothing.
+pub fn inhibit_suspend() {
+    if INHIBITOR.with(|inhibitor| inhibitor.borrow().is_some()) {
+        return;
+    }
+    let Ok(bus) = gio::bus_get_sync(gio::BusType::System, gio::Cancellable::NONE) else {
+        return;
+    };
+    let reply = bus.call_with_unix_fd_list_sync(
+        Some("org.freedesktop.login1"),
+        "/org/freedesktop/login1",
+        "org.freedesktop.login1.Manager",
+        "Inhibit",
+        Some(&("sleep:idle", "Linutil", "Commands are running", "block").to_variant()),
+        glib::VariantTy::new("(h)").ok(),
+        gio::DBusCallFlags::NONE,
+        CALL_TIMEOUT,
+        gio::UnixFDList::NONE,
+        gio::Cancellable::NONE,
+    );
+    match reply {
+        Ok((_, fds)) => INHIBITOR.with(|inhibitor| *inhibitor.borrow_mut() = Some(fds)),
+        Err(err) => eprintln!("Failed to inhibit suspend: {err}"),
+    }
+}
+
+/// Releases the lock taken by [`inhibit_suspend`], if any.
+pub fn allow_suspend() {
+    INHIBITOR.with(|inhibitor| inhibitor.borrow_mut().take());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_long() {
+        assert!(is_long("System Setup / Full System Update"));
+        assert!(is_long("System Setup / Nvidia Drivers"));
+        assert!(is_long("Utilities / Firmware Updates"));
+        assert!(!is_long("Applications Setup / Alacritty"));
+    }
+}
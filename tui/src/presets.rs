@@ -0,0 +1,133 @@
+use crate::settings::config_dir;
+use linutil_core::{ListNode, TabList};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+// Named multi-selections, stored as `$XDG_CONFIG_HOME/linutil-gui/presets/<name>.toml`.
+// They use the `auto_execute` list of `--config` files, so a preset can be shared and passed
+// to `linutil --config`, and a config file can be loaded as a preset.
+#[derive(Deserialize, Serialize)]
+struct PresetFile {
+    #[serde(default)]
+    auto_execute: Vec<String>,
+}
+
+pub struct Preset {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+pub fn preset_dir() -> PathBuf {
+    config_dir().join("presets")
+}
+
+/// Saved presets, sorted by name.
+pub fn list_presets() -> Vec<Preset> {
+    let Ok(entries) = fs::read_dir(preset_dir()) else {
+        return Vec::new();
+    };
+    let mut presets: Vec<Preset> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+        .filter_map(|path| {
+            let name = path.file_stem()?.to_str()?.to_string();
+            Some(Preset { name, path })
+        })
+        .collect();
+    presets.sort_by_key(|preset| preset.name.to_lowercase());
+    presets
+}
+
+/// Saves `commands` under `name`, replacing a preset with the same name.
+pub fn save_preset(name: &str, commands: &[Rc<ListNode>]) -> io::Result<PathBuf> {
+    let file_name = preset_file_name(name)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "The name is empty"))?;
+    let dir = preset_dir();
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(file_name);
+    let names: Vec<&str> = commands.iter().map(|node| node.name.as_str()).collect();
+    fs::write(&path, format_preset(&names))?;
+    Ok(path)
+}
+
+/// Commands of the preset at `path` in their saved order, and the names that match no
+/// command of `tabs`.
+pub fn load_preset(path: &Path, tabs: &TabList) -> io::Result<(Vec<Rc<ListNode>>, Vec<String>)> {
+    let names = parse_preset(&fs::read_to_string(path)?)?;
+    let mut commands = Vec::new();
+    let mut missing = Vec::new();
+    for name in names {
+        match tabs.iter().find_map(|tab| tab.find_command_by_name(&name)) {
+            Some(node) => commands.push(node),
+            None => missing.push(name),
+        }
+    }
+    Ok((commands, missing))
+}
+
+pub fn delete_preset(preset: &Preset) -> io::Result<()> {
+    fs::remove_file(&preset.path)
+}
+
+fn format_preset(names: &[&str]) -> String {
+    let file = PresetFile {
+        auto_execute: names.iter().map(|name| name.to_string()).collect(),
+    };
+    toml::to_string(&file).unwrap_or_default()
+}
+
+fn parse_preset(text: &str) -> io::Result<Vec<String>> {
+    // Other `--config` keys are ignored
+    toml::from_str::<PresetFile>(text)
+        .map(|file| file.auto_execute)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// File name for a preset called `name`, keeping it readable since it is shown as the name.
+fn preset_file_name(name: &str) -> Option<String> {
+    let name: String = name
+        .trim()
+        .chars()
+        .map(|ch| if matches!(ch, '/' | '\\') { '-' } else { ch })
+        .collect();
+    let name = name.trim_start_matches('.');
+    (!name.is_empty()).then(|| format!("{name}.toml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preset_round_trip() {
+        let text = format_preset(&["Add Chaotic AUR", "Install Paru"]);
+
+        assert_eq!(
+            parse_preset(&text).unwrap(),
+            ["Add Chaotic AUR", "Install Paru"]
+        );
+        assert_eq!(
+            parse_preset("auto_execute = [\"Yay\"]\nskip_confirmation = true\n").unwrap(),
+            ["Yay"]
+        );
+        assert!(parse_preset("auto_execute = 3").is_err());
+    }
+
+    #[test]
+    fn test_preset_file_name() {
+        assert_eq!(
+            preset_file_name(" Fresh Arch setup ").as_deref(),
+            Some("Fresh Arch setup.toml")
+        );
+        assert_eq!(
+            preset_file_name("../etc/passwd").as_deref(),
+            Some("-etc-passwd.toml")
+        );
+        assert_eq!(preset_file_name("  "), None);
+    }
+}
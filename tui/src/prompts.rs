@@ -0,0 +1,64 @@
+// Questions commands commonly ask, recognized in the last line of their output so the output
+// view can offer buttons answering them.
+
+/// A question with a fixed set of answers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Prompt {
+    /// Answered with `y` or `n`, or Enter for the default, e.g. `Proceed? [Y/n]`.
+    YesNo,
+    /// Answered with the whole word, e.g. ssh's `(yes/no/[fingerprint])?`.
+    YesNoWords,
+}
+
+impl Prompt {
+    /// The question `line`, the last and unfinished line of output, asks, if any.
+    pub fn detect(line: &str) -> Option<Self> {
+        let lower = line.trim_end().to_lowercase();
+        if lower.is_empty() {
+            return None;
+        }
+        if lower.contains("(yes/no") || lower.contains("[yes/no") {
+            return Some(Self::YesNoWords);
+        }
+        let choices = ["[y/n]", "(y/n)", "[y/n/", "(y/n/"];
+        let asks_to_go_on = lower.ends_with('?')
+            && ["proceed", "continue"]
+                .iter()
+                .any(|word| lower.contains(word));
+        (choices.iter().any(|choice| lower.contains(choice)) || asks_to_go_on)
+            .then_some(Self::YesNo)
+    }
+
+    /// Labels of the buttons answering the question, with the input each one sends.
+    pub fn answers(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Self::YesNo => &[("Y", "y\n"), ("N", "n\n"), ("Enter", "\n")],
+            Self::YesNoWords => &[("Yes", "yes\n"), ("No", "no\n")],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_prompt() {
+        for line in [
+            ":: Proceed with installation? [Y/n] ",
+            "Do you want to continue? [Y/n]",
+            "Is this ok [y/N]: ",
+            "Remove packages? (y/n)",
+            "Proceed?",
+        ] {
+            assert_eq!(Prompt::detect(line), Some(Prompt::YesNo), "{line}");
+        }
+        assert_eq!(
+            Prompt::detect("Are you sure you want to continue connecting (yes/no/[fingerprint])? "),
+            Some(Prompt::YesNoWords)
+        );
+        for line in ["", "Installing yay...", "[sudo] password for mimi: "] {
+            assert_eq!(Prompt::detect(line), None, "{line}");
+        }
+    }
+}
@@ -0,0 +1,149 @@
+// Recording of the commands run while Record Playbook is on, to do the same on another
+// machine. Every run adds its commands as playbook steps in the order they ran, and once it
+// finished, the lines typed at their questions as answers of those steps. Password prompts are
+// never recorded. Turning recording off saves the steps as a playbook for `--playbook`.
+
+use crate::{
+    playbook::{Playbook, Step},
+    responses::Rule,
+    runner::{QueuedCommand, TypedAnswer},
+    settings::FailurePolicy,
+};
+use std::cell::RefCell;
+
+thread_local! {
+    static RECORDING: RefCell<Option<Recording>> = const { RefCell::new(None) };
+}
+
+#[derive(Debug, Default)]
+struct Recording {
+    steps: Vec<Step>,
+}
+
+impl Recording {
+    /// Adds the commands of a run as steps, returning the index of the first.
+    fn add_run(&mut self, commands: &[QueuedCommand], on_failure: FailurePolicy) -> usize {
+        let first = self.steps.len();
+        self.steps.extend(commands.iter().map(|command| {
+            let policy = command.on_failure.unwrap_or(on_failure);
+            Step {
+                command: command.path.clone(),
+                // The playbook continues after failures unless a step says otherwise
+                on_failure: (policy == FailurePolicy::Abort).then_some(policy),
+                env: command.env.iter().cloned().collect(),
+                answers: command.answers.clone(),
+            }
+        }));
+        first
+    }
+
+    /// Adds `answers`, typed during the run whose steps start at `first`, to those steps.
+    fn add_answers(&mut self, first: usize, answers: &[TypedAnswer]) {
+        for answer in answers {
+            let Some(step) = self.steps.get_mut(first + answer.command) else {
+                continue;
+            };
+            let rule = Rule::new(&answer.prompt, &answer.input);
+            if !step.answers.iter_mut().any(|known| known.absorb(&rule)) {
+                step.answers.push(rule);
+            }
+        }
+    }
+}
+
+pub fn start() {
+    RECORDING.with(|recording| *recording.borrow_mut() = Some(Recording::default()));
+}
+
+pub fn is_recording() -> bool {
+    RECORDING.with(|recording| recording.borrow().is_some())
+}
+
+/// Ends the recording, returning the playbook of what was run, or `None` when nothing ran.
+pub fn stop() -> Option<Playbook> {
+    let recording = RECORDING.with(|recording| recording.borrow_mut().take())?;
+    (!recording.steps.is_empty()).then(|| Playbook {
+        steps: recording.steps,
+        ..Playbook::default()
+    })
+}
+
+/// Records a run of `commands` that starts now, returning where its steps start for
+/// [`add_answers`], or `None` when not recording.
+pub fn add_run(commands: &[QueuedCommand], on_failure: FailurePolicy) -> Option<usize> {
+    RECORDING.with(|recording| {
+        recording
+            .borrow_mut()
+            .as_mut()
+            .map(|recording| recording.add_run(commands, on_failure))
+    })
+}
+
+/// Records the answers typed during a finished run whose steps start at `first`, unless the
+/// recording was stopped meanwhile.
+pub fn add_answers(first: usize, answers: &[TypedAnswer]) {
+    RECORDING.with(|recording| {
+        if let Some(recording) = recording.borrow_mut().as_mut() {
+            recording.add_answers(first, answers);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::test_command;
+
+    fn queued(path: &str, on_failure: Option<FailurePolicy>) -> QueuedCommand {
+        QueuedCommand {
+            on_failure,
+            ..test_command(path, "true")
+        }
+    }
+
+    fn typed(command: usize, prompt: &str, input: &str) -> TypedAnswer {
+        TypedAnswer {
+            command,
+            prompt: prompt.to_string(),
+            input: input.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_recording() {
+        let mut recording = Recording::default();
+        let first = recording.add_run(
+            &[queued("System Setup / Arch / Paru", None)],
+            FailurePolicy::Continue,
+        );
+        assert_eq!(first, 0);
+        let second = recording.add_run(
+            &[
+                queued("Applications / Neovim", None),
+                queued("Applications / Kitty", Some(FailurePolicy::Continue)),
+            ],
+            FailurePolicy::Abort,
+        );
+        assert_eq!(second, 1);
+        recording.add_answers(
+            second,
+            &[
+                typed(1, "Proceed? [Y/n]", "y"),
+                typed(1, "Proceed? [Y/n]", "y"),
+                typed(1, "Theme:", "dark"),
+                typed(5, "Ignored?", "n"),
+            ],
+        );
+
+        let steps = &recording.steps;
+        assert_eq!(steps.len(), 3);
+        assert_eq!(steps[0].on_failure, None);
+        assert_eq!(steps[1].on_failure, Some(FailurePolicy::Abort));
+        assert_eq!(steps[2].on_failure, None);
+        assert!(steps[1].answers.is_empty());
+
+        let mut twice = Rule::new("Proceed? [Y/n]", "y");
+        assert!(twice.absorb(&Rule::new("Proceed? [Y/n]", "y")));
+        assert_eq!(steps[2].answers, [twice, Rule::new("Theme:", "dark")]);
+    }
+}
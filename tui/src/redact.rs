@@ -0,0 +1,60 @@
+use std::borrow::Cow;
+
+pub const REDACTED: &str = "********";
+
+// Secrets shorter than this are not masked; replacing one or two characters everywhere in
+// the output would make it unreadable.
+const MIN_SECRET_LEN: usize = 4;
+
+/// Whether `line` (the last, unfinished line of output) asks for a password, as printed by
+/// e.g. `sudo`, `su`, `ssh` or `passwd`.
+pub fn is_password_prompt(line: &str) -> bool {
+    let line = line.trim_end();
+    let lower = line.to_lowercase();
+    (lower.contains("password") || lower.contains("passphrase") || lower.contains("pin for"))
+        && line.ends_with(':')
+}
+
+/// Whether `secret` is long enough to be masked by [`redact`].
+pub fn is_maskable(secret: &str) -> bool {
+    secret.chars().count() >= MIN_SECRET_LEN
+}
+
+/// Replaces every occurrence of a known secret in `text` with [`REDACTED`].
+pub fn redact<'a>(text: &'a str, secrets: &[String]) -> Cow<'a, str> {
+    let mut text = Cow::Borrowed(text);
+    for secret in secrets.iter().filter(|secret| is_maskable(secret)) {
+        if text.contains(secret.as_str()) {
+            text = Cow::Owned(text.replace(secret.as_str(), REDACTED));
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_password_prompt() {
+        assert!(is_password_prompt("[sudo] password for mimi: "));
+        assert!(is_password_prompt("Password:"));
+        assert!(is_password_prompt("mimi@host's password:"));
+        assert!(is_password_prompt(
+            "Enter passphrase for key '/home/mimi/.ssh/id_ed25519': "
+        ));
+        assert!(!is_password_prompt("Changing password for mimi."));
+        assert!(!is_password_prompt("Proceed with installation? [Y/n] "));
+    }
+
+    #[test]
+    fn test_redact() {
+        let secrets = vec!["hunter22".to_string(), "ab".to_string()];
+
+        assert_eq!(
+            redact("echo hunter22\nok ab", &secrets),
+            "echo ********\nok ab"
+        );
+        assert!(matches!(redact("nothing here", &secrets), Cow::Borrowed(_)));
+    }
+}
@@ -0,0 +1,269 @@
+use crate::logs;
+use crate::runner::{self, CommandRecord, CommandStatus};
+use std::{fmt::Write as _, time::Duration};
+use time::macros::format_description;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+}
+
+impl ReportFormat {
+    pub const ALL: [ReportFormat; 2] = [ReportFormat::Markdown, ReportFormat::Html];
+
+    pub const fn label(&self) -> &'static str {
+        match self {
+            ReportFormat::Markdown => "Markdown",
+            ReportFormat::Html => "HTML",
+        }
+    }
+
+    pub const fn extension(&self) -> &'static str {
+        match self {
+            ReportFormat::Markdown => "md",
+            ReportFormat::Html => "html",
+        }
+    }
+}
+
+/// Writes a report for a finished run next to the saved logs and returns its path.
+pub fn save_report(
+    format: ReportFormat,
+    records: &[CommandRecord],
+    output: &str,
+) -> Result<String, std::io::Error> {
+    let path = logs::save_file(
+        &format!(
+            "linutil_report_{}.{}",
+            runner::file_timestamp(),
+            format.extension()
+        ),
+        &render(format, records, output),
+    )?;
+    Ok(path.to_string_lossy().into_owned())
+}
+
+pub fn render(format: ReportFormat, records: &[CommandRecord], output: &str) -> String {
+    match format {
+        ReportFormat::Markdown => render_markdown(records, output),
+        ReportFormat::Html => render_html(records, output),
+    }
+}
+
+fn render_markdown(records: &[CommandRecord], output: &str) -> String {
+    let mut report = String::from("# Linutil run report\n\n");
+    let _ = writeln!(report, "- Generated: {}", generated_at());
+    let _ = writeln!(report, "- Commands: {}", summary(records));
+    let _ = writeln!(
+        report,
+        "- Total duration: {}\n",
+        format_duration(total_duration(records))
+    );
+
+    report.push_str("| # | Command | Status | Exit code | Duration |\n");
+    report.push_str("|---|---------|--------|-----------|----------|\n");
+    for (index, record) in records.iter().enumerate() {
+        let _ = writeln!(
+            report,
+            "| {} | {} | {} | {} | {} |",
+            index + 1,
+            record.name.replace('|', "\\|"),
+            status_text(record.status),
+            exit_code_text(record.status),
+            record.duration.map_or("-".to_string(), format_duration)
+        );
+    }
+
+    report.push_str("\n## Logs\n");
+    for (index, record) in records.iter().enumerate() {
+        let log = record.output(output);
+        let fence = code_fence(log);
+        let _ = write!(
+            report,
+            "\n<details>\n<summary>{}. {}</summary>\n\n{fence}text\n{}\n{fence}\n\n</details>\n",
+            index + 1,
+            escape_html(&record.name),
+            log.trim_end()
+        );
+    }
+    report
+}
+
+fn render_html(records: &[CommandRecord], output: &str) -> String {
+    let mut report = String::from(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>Linutil run report</title>\n</head>\n<body>\n<h1>Linutil run report</h1>\n<ul>\n",
+    );
+    let _ = writeln!(report, "<li>Generated: {}</li>", generated_at());
+    let _ = writeln!(report, "<li>Commands: {}</li>", summary(records));
+    let _ = writeln!(
+        report,
+        "<li>Total duration: {}</li>\n</ul>",
+        format_duration(total_duration(records))
+    );
+
+    report.push_str(
+        "<table>\n<tr><th>#</th><th>Command</th><th>Status</th><th>Exit code</th><th>Duration</th></tr>\n",
+    );
+    for (index, record) in records.iter().enumerate() {
+        let _ = writeln!(
+            report,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            index + 1,
+            escape_html(&record.name),
+            status_text(record.status),
+            exit_code_text(record.status),
+            record.duration.map_or("-".to_string(), format_duration)
+        );
+    }
+    report.push_str("</table>\n<h2>Logs</h2>\n");
+
+    for (index, record) in records.iter().enumerate() {
+        let _ = writeln!(
+            report,
+            "<details>\n<summary>{}. {}</summary>\n<pre>{}</pre>\n</details>",
+            index + 1,
+            escape_html(&record.name),
+            escape_html(record.output(output).trim_end())
+        );
+    }
+    report.push_str("</body>\n</html>\n");
+    report
+}
+
+fn generated_at() -> String {
+    let format = format_description!("[year]-[month]-[day] [hour]:[minute]:[second]");
+    runner::now().format(&format).unwrap_or_default()
+}
+
+fn summary(records: &[CommandRecord]) -> String {
+    let succeeded = records
+        .iter()
+        .filter(|record| matches!(record.status, CommandStatus::Finished { success: true, .. }))
+        .count();
+    format!(
+        "{} ({} succeeded, {} failed or skipped)",
+        records.len(),
+        succeeded,
+        records.len() - succeeded
+    )
+}
+
+fn total_duration(records: &[CommandRecord]) -> Duration {
+    records.iter().filter_map(|record| record.duration).sum()
+}
+
+fn status_text(status: CommandStatus) -> &'static str {
+    match status {
+        CommandStatus::Pending => "Pending",
+        CommandStatus::Running => "Running",
+        CommandStatus::Finished { success: true, .. } => "Succeeded",
+        CommandStatus::Finished { success: false, .. } => "Failed",
+        CommandStatus::Skipped => "Skipped",
+    }
+}
+
+fn exit_code_text(status: CommandStatus) -> String {
+    match status {
+        CommandStatus::Finished { exit_code, .. } => exit_code.to_string(),
+        _ => "-".to_string(),
+    }
+}
+
+/// Formats a duration as e.g. `1h 2m 3s`, `4m 5s` or `6s`.
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (hours, minutes, seconds) = (secs / 3600, (secs % 3600) / 60, secs % 60);
+    if hours > 0 {
+        format!("{hours}h {minutes}m {seconds}s")
+    } else if minutes > 0 {
+        format!("{minutes}m {seconds}s")
+    } else {
+        format!("{seconds}s")
+    }
+}
+
+/// Picks a backtick fence longer than any run of backticks inside `text`.
+fn code_fence(text: &str) -> String {
+    let longest = text.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    "`".repeat(longest.max(2) + 1)
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(name: &str, status: CommandStatus, output_range: (usize, usize)) -> CommandRecord {
+        CommandRecord {
+            name: name.to_string(),
+            path: format!("Tab / {name}"),
+            status,
+            started_at: None,
+            duration: Some(Duration::from_secs(83)),
+            output_range,
+        }
+    }
+
+    #[test]
+    fn test_render_markdown() {
+        let output = "first\n```\nsecond\n";
+        let records = vec![
+            record(
+                "Fast|fetch",
+                CommandStatus::Finished {
+                    exit_code: 0,
+                    success: true,
+                },
+                (0, 6),
+            ),
+            record(
+                "Kitty",
+                CommandStatus::Finished {
+                    exit_code: 2,
+                    success: false,
+                },
+                (6, output.len()),
+            ),
+        ];
+
+        let report = render(ReportFormat::Markdown, &records, output);
+
+        assert!(report.contains("| 1 | Fast\\|fetch | Succeeded | 0 | 1m 23s |"));
+        assert!(report.contains("| 2 | Kitty | Failed | 2 | 1m 23s |"));
+        assert!(report.contains("- Commands: 2 (1 succeeded, 1 failed or skipped)"));
+        assert!(report.contains("````text\n```\nsecond\n````"));
+    }
+
+    #[test]
+    fn test_render_html_escapes_output() {
+        let output = "<script>&</script>";
+        let records = vec![record("Neovim", CommandStatus::Skipped, (0, output.len()))];
+
+        let report = render(ReportFormat::Html, &records, output);
+
+        assert!(report.contains("<td>Skipped</td><td>-</td>"));
+        assert!(report.contains("<pre>&lt;script&gt;&amp;&lt;/script&gt;</pre>"));
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_secs(5)), "5s");
+        assert_eq!(format_duration(Duration::from_secs(65)), "1m 5s");
+        assert_eq!(format_duration(Duration::from_secs(3723)), "1h 2m 3s");
+    }
+}
@@ -0,0 +1,267 @@
+// Answers given automatically to questions commands ask, so scripts asking simple questions
+// can run unattended. Rules are kept in `responses.toml` in the config directory:
+//
+//     [[rule]]
+//     pattern = "*Proceed with installation? [Y/n]*"
+//     reply = "y"
+//     max_repeats = 3
+//     command = "Full System Update"
+//
+// A rule answers when its pattern matches the last, unfinished line of output, where `*`
+// stands for any text and `?` for any one character. Without `command` it applies to every
+// command. Patterns are globs rather than regular expressions since prompts are full of `?`,
+// `[`, `(` and `.`, which a prompt copied from the output would have to escape as a regex.
+
+use crate::settings::config_dir;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Rule {
+    pattern: String,
+    /// Sent followed by Enter.
+    reply: String,
+    /// How often the rule answers during one run.
+    #[serde(default = "default_max_repeats")]
+    max_repeats: u32,
+    /// Name of the command the rule is for.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    command: Option<String>,
+    /// Position in the run of the command the rule is for, set for the answers of a playbook
+    /// step.
+    #[serde(skip)]
+    step: Option<usize>,
+}
+
+impl Rule {
+    /// A rule answering the line `pattern` once with `reply`.
+    pub fn new(pattern: &str, reply: &str) -> Self {
+        Self {
+            pattern: pattern.to_string(),
+            reply: reply.to_string(),
+            max_repeats: default_max_repeats(),
+            command: None,
+            step: None,
+        }
+    }
+
+    /// Lets the rule answer once more when `other` gives the same reply to the same line,
+    /// returning whether it did.
+    pub fn absorb(&mut self, other: &Rule) -> bool {
+        let same = self.pattern == other.pattern
+            && self.reply == other.reply
+            && self.command == other.command
+            && self.step == other.step;
+        if same {
+            self.max_repeats += other.max_repeats;
+        }
+        same
+    }
+
+    /// The rule answering only while the command at `step` of the run runs, so steps running
+    /// the same command keep their own answers.
+    pub fn for_step(self, step: usize) -> Self {
+        Self {
+            step: Some(step),
+            ..self
+        }
+    }
+}
+
+fn default_max_repeats() -> u32 {
+    1
+}
+
+#[derive(Default, Deserialize)]
+pub struct Responses {
+    #[serde(default, rename = "rule")]
+    rules: Vec<Rule>,
+}
+
+impl Responses {
+    pub fn path() -> PathBuf {
+        config_dir().join("responses.toml")
+    }
+
+    pub fn load() -> Self {
+        Self::load_from(&Self::path())
+    }
+
+    fn load_from(path: &Path) -> Self {
+        match fs::read_to_string(path) {
+            Ok(content) => toml::from_str(&content).unwrap_or_else(|err| {
+                eprintln!("Failed to parse responses file {}: {err}", path.display());
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Puts `rules` before the others, so they answer first.
+    pub fn prepend(&mut self, rules: impl IntoIterator<Item = Rule>) {
+        self.rules.splice(0..0, rules);
+    }
+
+    /// Applies the rules to one run.
+    pub fn responder(self) -> Responder {
+        Responder {
+            used: vec![0; self.rules.len()],
+            rules: self.rules,
+            answered: None,
+        }
+    }
+}
+
+/// The rules during one run, counting how often each one answered.
+pub struct Responder {
+    rules: Vec<Rule>,
+    used: Vec<u32>,
+    /// Line last answered, which is not answered again while it is still the last line.
+    answered: Option<String>,
+}
+
+impl Responder {
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Input answering `line`, the last line of output while `running`, the position and name
+    /// of a command of the run, runs, if a rule that has answers left matches it.
+    pub fn reply(&mut self, running: Option<(usize, &str)>, line: &str) -> Option<String> {
+        let line = line.trim_end();
+        if self.answered.as_deref() == Some(line) {
+            return None;
+        }
+        // Output moved on, so the same prompt showing up again is a new question
+        self.answered = None;
+        if line.is_empty() {
+            return None;
+        }
+        let index = self.rules.iter().enumerate().position(|(index, rule)| {
+            self.used[index] < rule.max_repeats
+                && rule
+                    .command
+                    .as_deref()
+                    .is_none_or(|name| Some(name) == running.map(|(_, name)| name))
+                && rule
+                    .step
+                    .is_none_or(|step| Some(step) == running.map(|(step, _)| step))
+                && glob_match(rule.pattern.trim_end(), line)
+        })?;
+        self.used[index] += 1;
+        self.answered = Some(line.to_string());
+        Some(format!("{}\n", self.rules[index].reply))
+    }
+}
+
+/// Whether `text` matches `pattern` as a whole, where `*` matches any text and `?` any one
+/// character.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Where the last `*` was and the text position it currently matches up to
+    let mut star = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    star = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*[Y/n]", ":: Proceed with installation? [Y/n]"));
+        assert!(glob_match("Is this ok [?/?]:", "Is this ok [y/N]:"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("*[Y/n]", "Proceed? [y/N]"));
+        assert!(!glob_match("Continue?", "Continue? [Y/n]"));
+    }
+
+    #[test]
+    fn test_responder() {
+        let responses: Responses = toml::from_str(
+            "[[rule]]\npattern = \"*[Y/n]\"\nreply = \"y\"\nmax_repeats = 2\n\n\
+             [[rule]]\npattern = \"Name:\"\nreply = \"linutil\"\ncommand = \"Setup\"\n",
+        )
+        .unwrap();
+        let mut responder = responses.responder();
+
+        assert_eq!(responder.reply(None, "Proceed? [Y/n] "), Some("y\n".into()));
+        // The same prompt still being the last line was answered already
+        assert_eq!(responder.reply(None, "Proceed? [Y/n] "), None);
+        assert_eq!(responder.reply(None, "Continue? [Y/n]"), Some("y\n".into()));
+        assert_eq!(responder.reply(None, "Proceed? [Y/n]"), None);
+
+        // Asked again after other output, but out of answers
+        assert_eq!(responder.reply(None, ""), None);
+        assert_eq!(responder.reply(None, "Proceed? [Y/n]"), None);
+
+        assert_eq!(responder.reply(Some((0, "Other")), "Name:"), None);
+        assert_eq!(
+            responder.reply(Some((0, "Setup")), "Name:"),
+            Some("linutil\n".into())
+        );
+    }
+
+    #[test]
+    fn test_step_answers() {
+        let mut responses = Responses::default();
+        responses.prepend([
+            Rule::new("Theme:", "dark").for_step(0),
+            Rule::new("Theme:", "light").for_step(2),
+        ]);
+        let mut responder = responses.responder();
+
+        // The same command at another step gets its own answer, or none
+        assert_eq!(responder.reply(Some((1, "Kitty")), "Theme:"), None);
+        assert_eq!(
+            responder.reply(Some((2, "Kitty")), "Theme:"),
+            Some("light\n".into())
+        );
+        assert_eq!(responder.reply(Some((0, "Kitty")), "Theme: "), None);
+        assert_eq!(responder.reply(Some((0, "Kitty")), ""), None);
+        assert_eq!(
+            responder.reply(Some((0, "Kitty")), "Theme:"),
+            Some("dark\n".into())
+        );
+    }
+
+    #[test]
+    fn test_repeated_prompt() {
+        let responses: Responses =
+            toml::from_str("[[rule]]\npattern = \"*[Y/n]\"\nreply = \"y\"\nmax_repeats = 3\n")
+                .unwrap();
+        let mut responder = responses.responder();
+
+        assert_eq!(responder.reply(None, "Proceed? [Y/n]"), Some("y\n".into()));
+        // After the answer the line is empty until the next prompt, which asks the same
+        assert_eq!(responder.reply(None, ""), None);
+        assert_eq!(responder.reply(None, "Proceed? [Y/n]"), Some("y\n".into()));
+        assert_eq!(responder.reply(None, "Installing..."), None);
+        assert_eq!(responder.reply(None, "Proceed? [Y/n]"), Some("y\n".into()));
+    }
+}
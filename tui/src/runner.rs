@@ -0,0 +1,939 @@
+use crate::{
+    journal, logs,
+    output::{LineEdit, LineEditor, OutputBuffer, Utf8Decoder},
+    platform::{self, Platform},
+    redact,
+    responses::{Responses, Rule},
+    settings::{state_dir, FailurePolicy, Settings},
+    terminal_input,
+};
+use async_channel::{Receiver, Sender};
+use linutil_core::{copy_dir, Command, ListNode};
+use nix::{
+    sys::signal::{killpg, Signal},
+    unistd::Pid,
+};
+use portable_pty::{
+    ChildKiller, CommandBuilder, MasterPty, NativePtySystem, PtySize, PtySystem, SlavePty,
+};
+use std::{
+    cell::RefCell,
+    io::{Read, Write},
+    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, AtomicU32, Ordering},
+        mpsc, Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use time::{macros::format_description, OffsetDateTime};
+
+/// Lifecycle of a single command within a run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommandStatus {
+    Pending,
+    Running,
+    Finished {
+        exit_code: u32,
+        success: bool,
+    },
+    /// The run was stopped before this command started.
+    Skipped,
+}
+
+/// Progress of a run, streamed from the runner's threads to the UI as it happens.
+#[derive(Clone, Debug)]
+pub enum RunEvent {
+    /// New output with ANSI escapes stripped and secrets redacted.
+    Output(String),
+    /// Output like [`RunEvent::Output`] replacing the last, unfinished line, which a carriage
+    /// return or erasing the line redrew.
+    Rewrite(String),
+    /// Every command has exited or been skipped.
+    Finished { success: bool },
+}
+
+/// A command waiting to be run, with its location in the tab tree (e.g.
+/// `System Setup / Arch / Paru`).
+#[derive(Clone)]
+pub struct QueuedCommand {
+    pub node: Rc<ListNode>,
+    pub path: String,
+    /// Environment variables set for the command, e.g. by a playbook step.
+    pub env: Vec<(String, String)>,
+    /// Answers to the command's questions, tried before the rules of `responses.toml`.
+    pub answers: Vec<Rule>,
+    /// What the rest of the run does when the command fails, instead of the run's policy.
+    pub on_failure: Option<FailurePolicy>,
+}
+
+/// A line the user sent while a command's output ended in a question, e.g. `y` after
+/// `Proceed? [Y/n]`.
+#[derive(Clone, Debug)]
+pub struct TypedAnswer {
+    /// Index of the command in the run.
+    pub command: usize,
+    pub prompt: String,
+    /// The line, without its line break.
+    pub input: String,
+}
+
+/// What happened to one command of a run, used for reports and per-command logs.
+#[derive(Clone, Debug)]
+pub struct CommandRecord {
+    pub name: String,
+    pub path: String,
+    pub status: CommandStatus,
+    pub started_at: Option<OffsetDateTime>,
+    pub duration: Option<Duration>,
+    /// Byte range of the run output that was produced while this command was active.
+    pub output_range: (usize, usize),
+}
+
+impl CommandRecord {
+    /// Slice of the run output that belongs to this command.
+    pub fn output<'a>(&self, output: &'a str) -> &'a str {
+        let (start, end) = self.output_range;
+        output.get(start..end.min(output.len())).unwrap_or("")
+    }
+}
+
+pub struct CommandRunner {
+    output: Arc<Mutex<OutputBuffer>>,
+    writer: Arc<Mutex<Box<dyn Write + Send>>>,
+    child_killer: Arc<Mutex<Option<Box<dyn ChildKiller + Send + Sync>>>>,
+    /// Process ID of the active command, 0 between commands.
+    child_pid: Arc<AtomicU32>,
+    records: Arc<Mutex<Vec<CommandRecord>>>,
+    stopped: Arc<AtomicBool>,
+    events: Receiver<RunEvent>,
+    /// Start time of the run, shared by all log files written for it.
+    log_timestamp: String,
+    /// Input typed in answer to password prompts, masked in everything read afterwards.
+    secrets: Option<Arc<Mutex<Vec<String>>>>,
+    /// Set by the reader while the last line of output is a password prompt.
+    password_prompt: Arc<AtomicBool>,
+    /// Set by the reader while the running program wants pasted text bracketed.
+    bracketed_paste: Arc<AtomicBool>,
+    /// Last, unfinished line of output, which may be a question.
+    last_line: Arc<Mutex<String>>,
+    /// Lines sent in answer to questions other than password prompts.
+    typed_answers: RefCell<Vec<TypedAnswer>>,
+    /// Line being typed key by key with terminal input, and the prompt it answers, empty
+    /// for password prompts.
+    typed_line: RefCell<Option<(String, String)>>,
+    _pty_master: Box<dyn MasterPty + Send>,
+}
+
+impl CommandRunner {
+    /// Runs the commands one after another in a shared PTY. A failing command stops the
+    /// remaining ones only when its policy, or else `on_failure`, says so; the run only counts
+    /// as successful when every command succeeded.
+    pub fn spawn(
+        commands: &[QueuedCommand],
+        settings: &Settings,
+        on_failure: FailurePolicy,
+    ) -> Self {
+        let pty_system = NativePtySystem::default();
+        let pair = pty_system
+            .openpty(PtySize {
+                rows: 24,
+                cols: 80,
+                pixel_width: 0,
+                pixel_height: 0,
+            })
+            .unwrap();
+
+        let host_exec = host_exec(settings);
+        let scripts: Vec<String> = commands
+            .iter()
+            .map(|command| {
+                let script = match host_exec {
+                    Some(_) => {
+                        host_script(&command.node).unwrap_or_else(|| command_script(&command.node))
+                    }
+                    None => command_script(&command.node),
+                };
+                format!("{}{script}", env_script(&command.env))
+            })
+            .collect();
+        let policies: Vec<FailurePolicy> = commands
+            .iter()
+            .map(|command| command.on_failure.unwrap_or(on_failure))
+            .collect();
+        let records = Arc::new(Mutex::new(
+            commands
+                .iter()
+                .map(|command| CommandRecord {
+                    name: command.node.name.clone(),
+                    path: command.path.clone(),
+                    status: CommandStatus::Pending,
+                    started_at: None,
+                    duration: None,
+                    output_range: (0, 0),
+                })
+                .collect::<Vec<_>>(),
+        ));
+
+        let log_timestamp = file_timestamp();
+        let mut live_log = if settings.logging.live_tee {
+            logs::create_live_log(&log_timestamp)
+                .map_err(|err| eprintln!("Failed to create live log: {err}"))
+                .ok()
+        } else {
+            None
+        };
+
+        let secrets = settings
+            .logging
+            .redact_secrets
+            .then(|| Arc::new(Mutex::new(Vec::new())));
+        let password_prompt = Arc::new(AtomicBool::new(false));
+        let bracketed_paste = Arc::new(AtomicBool::new(false));
+        let shared_last_line = Arc::new(Mutex::new(String::new()));
+
+        let (events_sender, events) = async_channel::unbounded();
+        let output = Arc::new(Mutex::new(OutputBuffer::default()));
+        let output_clone = output.clone();
+        let events_sender_clone = events_sender.clone();
+        let secrets_clone = secrets.clone();
+        let password_prompt_clone = password_prompt.clone();
+        let bracketed_paste_clone = bracketed_paste.clone();
+        let shared_last_line_clone = shared_last_line.clone();
+        let writer = Arc::new(Mutex::new(pair.master.take_writer().unwrap()));
+        let writer_clone = writer.clone();
+        let records_clone = records.clone();
+        let mut responses = Responses::load();
+        responses.prepend(commands.iter().enumerate().flat_map(|(step, command)| {
+            command
+                .answers
+                .iter()
+                .map(move |answer| answer.clone().for_step(step))
+        }));
+        let mut responder = responses.responder();
+        let mut reader = pair.master.try_clone_reader().unwrap();
+        let (drained_sender, drained) = mpsc::channel();
+        thread::spawn(move || {
+            let mut buf = [0u8; 8192];
+            let mut splitter = MarkerSplitter::default();
+            let mut decoder = Utf8Decoder::default();
+            let mut editor = LineEditor::default();
+            let mut last_line = String::new();
+            loop {
+                let size = match reader.read(&mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(size) => size,
+                };
+                for piece in splitter.feed(&buf[..size]) {
+                    let bytes = match piece {
+                        Piece::Output(bytes) => bytes,
+                        // Everything the last command wrote was passed on above
+                        Piece::Marker => {
+                            let _ = drained_sender.send(());
+                            continue;
+                        }
+                    };
+                    let (mut chunk, rewrite) = match editor.feed(&decoder.decode(&bytes)) {
+                        LineEdit::Append(text) => (text, false),
+                        LineEdit::Rewrite(text) => (text, true),
+                    };
+                    bracketed_paste_clone.store(editor.bracketed_paste(), Ordering::SeqCst);
+                    if let Some(secrets) = &secrets_clone {
+                        if let Ok(secrets) = secrets.lock() {
+                            chunk = redact::redact(&chunk, &secrets).into_owned();
+                        }
+                    }
+                    match chunk.rfind('\n') {
+                        Some(index) => last_line = chunk[index + 1..].to_string(),
+                        None if rewrite => last_line.clone_from(&chunk),
+                        None => last_line.push_str(&chunk),
+                    }
+                    password_prompt_clone
+                        .store(redact::is_password_prompt(&last_line), Ordering::SeqCst);
+                    if let Ok(mut shared) = shared_last_line_clone.lock() {
+                        shared.clone_from(&last_line);
+                    }
+                    if !responder.is_empty() {
+                        let running = records_clone.lock().ok().and_then(|records| {
+                            records
+                                .iter()
+                                .position(|record| record.status == CommandStatus::Running)
+                                .map(|index| (index, records[index].name.clone()))
+                        });
+                        let running = running
+                            .as_ref()
+                            .map(|(index, name)| (*index, name.as_str()));
+                        if let Some(reply) = responder.reply(running, &last_line) {
+                            if let Ok(mut writer) = writer_clone.lock() {
+                                let _ = writer.write_all(reply.as_bytes());
+                                let _ = writer.flush();
+                            }
+                        }
+                    }
+                    if rewrite {
+                        if let Some(file) = &mut live_log {
+                            let _ = file.write_all(b"\r");
+                            let _ = file.write_all(chunk.as_bytes());
+                            let _ = file.flush();
+                        }
+                        if let Ok(mut output) = output_clone.lock() {
+                            output.rewrite_line(&chunk);
+                        }
+                        let _ = events_sender_clone.send_blocking(RunEvent::Rewrite(chunk));
+                    } else if !chunk.is_empty() {
+                        if let Some(file) = &mut live_log {
+                            // Flush every chunk so a crash keeps everything read so far
+                            let _ = file.write_all(chunk.as_bytes());
+                            let _ = file.flush();
+                        }
+                        if let Ok(mut output) = output_clone.lock() {
+                            output.push(&chunk);
+                        }
+                        // The receiver is gone once the output window was closed
+                        let _ = events_sender_clone.send_blocking(RunEvent::Output(chunk));
+                    }
+                }
+            }
+        });
+
+        let child_killer = Arc::new(Mutex::new(None));
+        let child_pid = Arc::new(AtomicU32::new(0));
+        let stopped = Arc::new(AtomicBool::new(false));
+        let worker = QueueWorker {
+            slave: pair.slave,
+            output: output.clone(),
+            child_killer: child_killer.clone(),
+            child_pid: child_pid.clone(),
+            records: records.clone(),
+            stopped: stopped.clone(),
+            events: events_sender,
+            drained,
+            journal: settings.logging.journald,
+            policies,
+            host_exec,
+        };
+        thread::spawn(move || worker.run(scripts));
+
+        Self {
+            output,
+            writer,
+            child_killer,
+            child_pid,
+            records,
+            stopped,
+            events,
+            log_timestamp,
+            secrets,
+            password_prompt,
+            bracketed_paste,
+            last_line: shared_last_line,
+            typed_answers: RefCell::new(Vec::new()),
+            typed_line: RefCell::new(None),
+            _pty_master: pair.master,
+        }
+    }
+
+    pub fn send_input(&self, input: &str) {
+        self.typed_line.replace(None);
+        self.record_answer(
+            &self.answered_prompt(),
+            input.trim_end_matches(['\r', '\n']),
+        );
+        if let Some(secrets) = &self.secrets {
+            if self.password_prompt.swap(false, Ordering::SeqCst) {
+                let secret = input.trim_end_matches(['\r', '\n']);
+                if redact::is_maskable(secret) {
+                    if let Ok(mut secrets) = secrets.lock() {
+                        secrets.push(secret.to_string());
+                    }
+                }
+            }
+        }
+        self.send_bytes(input.as_bytes());
+    }
+
+    /// Writes `bytes` typed with terminal input on, e.g. a key or text from an input method,
+    /// and records the line they make up once Enter is pressed, like [`send_input`] does.
+    ///
+    /// [`send_input`]: Self::send_input
+    pub fn type_bytes(&self, bytes: &[u8]) {
+        // Arrow keys and the like move around rather than type
+        if bytes.first() != Some(&0x1b) {
+            for c in String::from_utf8_lossy(bytes).chars() {
+                let mut typed_line = self.typed_line.borrow_mut();
+                let (prompt, input) =
+                    typed_line.get_or_insert_with(|| (self.answered_prompt(), String::new()));
+                match c {
+                    '\r' | '\n' => {
+                        self.record_answer(prompt, input);
+                        *typed_line = None;
+                    }
+                    '\x7f' | '\x08' => {
+                        input.pop();
+                    }
+                    // Ctrl+U clears the line in a terminal
+                    '\x15' => input.clear(),
+                    c if c.is_control() => {}
+                    c => input.push(c),
+                }
+            }
+        }
+        self.send_bytes(bytes);
+    }
+
+    /// The question input sent now answers, or an empty string for a password prompt.
+    fn answered_prompt(&self) -> String {
+        if self.awaiting_password() {
+            return String::new();
+        }
+        self.last_line().trim_end().to_string()
+    }
+
+    /// Remembers `input` as the answer to `prompt` of the running command, unless there is
+    /// no question to answer.
+    fn record_answer(&self, prompt: &str, input: &str) {
+        if prompt.trim().is_empty() {
+            return;
+        }
+        let running = self
+            .records()
+            .iter()
+            .position(|record| record.status == CommandStatus::Running);
+        if let Some(command) = running {
+            self.typed_answers.borrow_mut().push(TypedAnswer {
+                command,
+                prompt: prompt.to_string(),
+                input: input.to_string(),
+            });
+        }
+    }
+
+    /// Whether the last line of output asks for a password that has not been sent yet.
+    pub fn awaiting_password(&self) -> bool {
+        self.password_prompt.load(Ordering::SeqCst)
+    }
+
+    /// Last line of output when it is not finished by a newline, e.g. a question.
+    pub fn last_line(&self) -> String {
+        self.last_line
+            .lock()
+            .map(|line| line.clone())
+            .unwrap_or_default()
+    }
+
+    /// Writes `bytes` to the PTY as they are, e.g. the escape sequence of an arrow key.
+    pub fn send_bytes(&self, bytes: &[u8]) {
+        if let Ok(mut writer) = self.writer.lock() {
+            let _ = writer.write_all(bytes);
+            let _ = writer.flush();
+        }
+    }
+
+    /// Writes `text` pasted while terminal input is on, as a bracketed paste if the running
+    /// program asked for one.
+    pub fn paste(&self, text: &str) {
+        let bracketed = self.bracketed_paste.load(Ordering::SeqCst);
+        self.send_bytes(&terminal_input::paste_bytes(text, bracketed));
+    }
+
+    /// Stops the active command and skips everything still queued.
+    pub fn kill(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        if let Ok(mut killer) = self.child_killer.lock() {
+            if let Some(mut killer) = killer.take() {
+                let _ = killer.kill();
+            }
+        }
+    }
+
+    /// Sends `signal` to the active command and the processes it started. Unlike
+    /// [`kill`](Self::kill) the queued commands still run after it exits.
+    pub fn signal(&self, signal: Signal) {
+        let pid = self.child_pid.load(Ordering::SeqCst);
+        if pid != 0 {
+            // Commands lead a session of their own, so their process group has their ID
+            let _ = killpg(Pid::from_raw(pid as i32), signal);
+        }
+    }
+
+    /// Ends the input of the active command like Ctrl+D in a terminal.
+    pub fn send_eof(&self) {
+        self.send_bytes(b"\x04");
+    }
+
+    /// Whether the run was stopped with [`kill`](Self::kill).
+    pub fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::SeqCst)
+    }
+
+    /// Saves the combined output and, for runs of several commands, one log per command.
+    /// Returns the path of the combined log.
+    pub fn save_log(&self) -> Result<String, std::io::Error> {
+        let output = self.output();
+        let log_path = logs::save_run_logs(&self.log_timestamp, &output, &self.records())?;
+        Ok(log_path.to_string_lossy().into_owned())
+    }
+
+    /// Output and completion events of the run. Output read before the receiver is polled
+    /// is queued, so nothing is lost when it is consumed later.
+    pub fn events(&self) -> Receiver<RunEvent> {
+        self.events.clone()
+    }
+
+    pub fn output(&self) -> String {
+        self.output.lock().unwrap().text()
+    }
+
+    pub fn records(&self) -> Vec<CommandRecord> {
+        self.records.lock().unwrap().clone()
+    }
+
+    /// The lines sent in answer to questions so far, in order.
+    pub fn typed_answers(&self) -> Vec<TypedAnswer> {
+        self.typed_answers.borrow().clone()
+    }
+}
+
+/// Background side of a [`CommandRunner`], spawning each queued command once the previous
+/// one has exited.
+struct QueueWorker {
+    slave: Box<dyn SlavePty + Send>,
+    output: Arc<Mutex<OutputBuffer>>,
+    child_killer: Arc<Mutex<Option<Box<dyn ChildKiller + Send + Sync>>>>,
+    child_pid: Arc<AtomicU32>,
+    records: Arc<Mutex<Vec<CommandRecord>>>,
+    stopped: Arc<AtomicBool>,
+    events: Sender<RunEvent>,
+    /// Told by the reader when it reached the marker printed after a command.
+    drained: mpsc::Receiver<()>,
+    /// Whether command lifecycle events are sent to the systemd journal.
+    journal: bool,
+    /// Policy of each command, applied when it fails.
+    policies: Vec<FailurePolicy>,
+    /// Prefix running the commands on the host, when Linutil runs in a container.
+    host_exec: Option<&'static [&'static str]>,
+}
+
+impl QueueWorker {
+    fn run(self, scripts: Vec<String>) {
+        let mut all_succeeded = true;
+        let mut aborted = false;
+        for (index, script) in scripts.into_iter().enumerate() {
+            if aborted || self.stopped.load(Ordering::SeqCst) {
+                self.update_record(index, |record| record.status = CommandStatus::Skipped);
+                all_succeeded = false;
+                continue;
+            }
+
+            let start_offset = self.output_len();
+            let started = Instant::now();
+            self.update_record(index, |record| {
+                record.status = CommandStatus::Running;
+                record.started_at = Some(now());
+                record.output_range = (start_offset, start_offset);
+            });
+
+            let (exit_code, success) = match self
+                .slave
+                .spawn_command(shell_command(&script, self.host_exec))
+            {
+                Ok(mut child) => {
+                    if let Ok(mut killer) = self.child_killer.lock() {
+                        *killer = Some(child.clone_killer());
+                    }
+                    self.child_pid
+                        .store(child.process_id().unwrap_or(0), Ordering::SeqCst);
+                    // Stop may have been pressed while the child was being spawned.
+                    if self.stopped.load(Ordering::SeqCst) {
+                        let _ = child.kill();
+                    }
+                    let status = child.wait();
+                    self.child_pid.store(0, Ordering::SeqCst);
+                    if let Ok(mut killer) = self.child_killer.lock() {
+                        killer.take();
+                    }
+                    status.map_or((1, false), |status| (status.exit_code(), status.success()))
+                }
+                Err(err) => {
+                    let message = format!("Failed to start command: {err}\n");
+                    if let Ok(mut output) = self.output.lock() {
+                        output.push(&message);
+                    }
+                    let _ = self.events.send_blocking(RunEvent::Output(message));
+                    (1, false)
+                }
+            };
+
+            let duration = started.elapsed();
+            all_succeeded &= success;
+            aborted |= !success && self.policies.get(index) == Some(&FailurePolicy::Abort);
+            self.wait_for_output();
+            let end_offset = self.output_len();
+            self.update_record(index, |record| {
+                record.status = CommandStatus::Finished { exit_code, success };
+                record.duration = Some(duration);
+                record.output_range = (start_offset, end_offset);
+            });
+        }
+
+        // Dropping the slave lets the reader thread see EOF once the last child is gone.
+        drop(self.slave);
+        let _ = self.events.send_blocking(RunEvent::Finished {
+            success: all_succeeded,
+        });
+    }
+
+    /// Waits until the reader passed on everything the last command wrote, so its output ends
+    /// where the next command's begins and the run only finishes with all of it. The PTY keeps
+    /// output in a buffer after the command exited, so a marker printed after it tells when
+    /// the reader got there.
+    fn wait_for_output(&self) {
+        // Acknowledgements of markers that came too late
+        while self.drained.try_recv().is_ok() {}
+        let mut marker = CommandBuilder::new("sh");
+        marker.args(["-c", MARKER_SCRIPT]);
+        let Ok(mut child) = self.slave.spawn_command(marker) else {
+            return;
+        };
+        let _ = child.wait();
+        let _ = self.drained.recv_timeout(Duration::from_secs(2));
+    }
+
+    fn output_len(&self) -> usize {
+        self.output.lock().map(|output| output.len()).unwrap_or(0)
+    }
+
+    fn update_record(&self, index: usize, update: impl FnOnce(&mut CommandRecord)) {
+        let record = match self.records.lock() {
+            Ok(mut records) => records.get_mut(index).map(|record| {
+                update(record);
+                record.clone()
+            }),
+            Err(_) => None,
+        };
+        if let Some(record) = record.filter(|_| self.journal) {
+            journal::log_command_event(&record);
+        }
+    }
+}
+
+/// Printed into the PTY after each command, see [`QueueWorker::wait_for_output`]. An OSC
+/// sequence no program sends, which the reader takes out of the output.
+const MARKER: &[u8] = b"\x1b]7770;linutil-drained\x07";
+const MARKER_SCRIPT: &str = "printf '\\033]7770;linutil-drained\\007'";
+
+/// Part of what was read from the PTY.
+#[derive(Debug, PartialEq)]
+enum Piece {
+    Output(Vec<u8>),
+    Marker,
+}
+
+/// Finds the [`MARKER`]s in the output read from the PTY, which may be split across reads.
+#[derive(Default)]
+struct MarkerSplitter {
+    /// Bytes that may be the start of a marker, held back until the next read.
+    pending: Vec<u8>,
+}
+
+impl MarkerSplitter {
+    fn feed(&mut self, bytes: &[u8]) -> Vec<Piece> {
+        self.pending.extend_from_slice(bytes);
+        let mut pieces = Vec::new();
+        while let Some(start) = find(&self.pending, MARKER) {
+            if start > 0 {
+                pieces.push(Piece::Output(self.pending[..start].to_vec()));
+            }
+            pieces.push(Piece::Marker);
+            self.pending.drain(..start + MARKER.len());
+        }
+        // Keep a trailing start of a marker for the next read
+        let kept = (1..MARKER.len().min(self.pending.len() + 1))
+            .rev()
+            .find(|&len| self.pending.ends_with(&MARKER[..len]))
+            .unwrap_or(0);
+        let output: Vec<u8> = self.pending.drain(..self.pending.len() - kept).collect();
+        if !output.is_empty() {
+            pieces.push(Piece::Output(output));
+        }
+        pieces
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// Shell snippet that runs a single command node.
+pub fn command_script(node: &ListNode) -> String {
+    let mut script = String::new();
+    match &node.command {
+        Command::Raw(prompt) => {
+            script.push_str(prompt);
+            script.push('\n');
+        }
+        Command::LocalFile {
+            executable,
+            args,
+            file,
+        } => {
+            if let Some(parent) = file.parent() {
+                script.push_str(&format!("cd {}\n", parent.display()));
+            }
+            script.push_str(executable);
+            for arg in args {
+                script.push(' ');
+                script.push_str(arg);
+            }
+            script.push('\n');
+        }
+        Command::None => {}
+    }
+    script
+}
+
+// Environment commands run with, so tools keep their colors in the output view
+const SHELL_ENV: [(&str, &str); 4] = [
+    ("TERM", "xterm-256color"),
+    ("COLORTERM", "truecolor"),
+    ("FORCE_COLOR", "1"),
+    ("NO_COLOR", ""),
+];
+
+fn shell_command(script: &str, host_exec: Option<&[&str]>) -> CommandBuilder {
+    let mut cmd = match host_exec {
+        // The host side does not see the environment set here, so `env` sets it there
+        Some([program, args @ ..]) => {
+            let mut cmd = CommandBuilder::new(program);
+            cmd.args(args);
+            cmd.arg("env");
+            for (key, value) in SHELL_ENV {
+                cmd.arg(format!("{key}={value}"));
+            }
+            cmd.arg("sh");
+            cmd
+        }
+        _ => {
+            let mut cmd = CommandBuilder::new("sh");
+            for (key, value) in SHELL_ENV {
+                cmd.env(key, value);
+            }
+            cmd
+        }
+    };
+    cmd.arg("-c");
+
+    if Platform::current().wsl {
+        if let Ok(path) = std::env::var("PATH") {
+            cmd.env("PATH", platform::linux_path(&path));
+        }
+    }
+
+    cmd.arg(script);
+    cmd
+}
+
+/// Shell lines exporting `env` to the command after them.
+fn env_script(env: &[(String, String)]) -> String {
+    env.iter()
+        .map(|(key, value)| format!("export {key}={}\n", shell_quote(value)))
+        .collect()
+}
+
+/// The host-exec prefix commands run with, when the settings ask to run them on the host and
+/// the container Linutil runs in offers a way there.
+fn host_exec(settings: &Settings) -> Option<&'static [&'static str]> {
+    if !settings.runs.run_on_host {
+        return None;
+    }
+    Platform::current().container?.host_exec()
+}
+
+/// The script running `node` on the host. The host cannot see the temporary directory the
+/// tabs were extracted to inside the container, so the script's directory tree, from the
+/// one holding `common-script.sh`, is copied to the state directory in the shared home first.
+fn host_script(node: &ListNode) -> Option<String> {
+    let Command::LocalFile { file, .. } = &node.command else {
+        return Some(command_script(node));
+    };
+    let root = file
+        .ancestors()
+        .skip(1)
+        .find(|dir| dir.join("common-script.sh").is_file())
+        .or_else(|| file.parent())?;
+    let copy = state_dir().join("host-scripts").join(root.file_name()?);
+    if let Err(err) = copy_dir(root, &copy) {
+        eprintln!("Failed to copy scripts for the host: {err}");
+        return None;
+    }
+    Some(command_script(node).replace(&root.display().to_string(), &copy.display().to_string()))
+}
+
+/// The shell invocation running `node`, as it could be typed in a terminal. It is what
+/// [`CommandRunner::spawn`] runs for the command.
+pub fn command_invocation(node: &ListNode) -> String {
+    let mut invocation = String::new();
+    for (key, value) in SHELL_ENV {
+        invocation.push_str(&format!("{key}={} ", shell_quote(value)));
+    }
+    invocation.push_str("sh -c ");
+    invocation.push_str(&shell_quote(command_script(node).trim_end()));
+    invocation
+}
+
+/// `text` in single quotes, safe to paste into a POSIX shell.
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "'\\''"))
+}
+
+pub fn now() -> OffsetDateTime {
+    OffsetDateTime::now_local().unwrap_or(OffsetDateTime::now_utc())
+}
+
+/// Timestamp used in generated file names, e.g. `2024-10-01-13-37-00`.
+pub fn file_timestamp() -> String {
+    let date_format = format_description!("[year]-[month]-[day]-[hour]-[minute]-[second]");
+    now().format(&date_format).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::test_command;
+
+    #[test]
+    fn test_marker_splitter() {
+        let mut splitter = MarkerSplitter::default();
+        let mut bytes = b"END_A\r\n".to_vec();
+        bytes.extend_from_slice(&MARKER[..5]);
+        assert_eq!(
+            splitter.feed(&bytes),
+            [Piece::Output(b"END_A\r\n".to_vec())]
+        );
+        let mut bytes = MARKER[5..].to_vec();
+        bytes.extend_from_slice(b"END_B\x1b[0m");
+        assert_eq!(
+            splitter.feed(&bytes),
+            [Piece::Marker, Piece::Output(b"END_B\x1b[0m".to_vec())]
+        );
+        assert_eq!(splitter.feed(MARKER), [Piece::Marker]);
+    }
+
+    #[test]
+    fn test_output_ranges() {
+        let mut settings = Settings::default();
+        settings.logging.journald = false;
+        settings.logging.live_tee = false;
+        let runner = CommandRunner::spawn(
+            &[
+                test_command("A", "seq 1 30000; echo END_A"),
+                test_command("B", "echo END_B"),
+            ],
+            &settings,
+            FailurePolicy::Continue,
+        );
+        let events = runner.events();
+        while let Ok(event) = events.recv_blocking() {
+            if let RunEvent::Finished { success } = event {
+                assert!(success);
+                break;
+            }
+        }
+
+        let output = runner.output();
+        let records = runner.records();
+        let (a, b) = (records[0].output(&output), records[1].output(&output));
+        assert!(a.contains("29999\n30000\nEND_A"), "{}", &a[a.len() - 40..]);
+        assert!(!a.contains("END_B"));
+        assert!(b.contains("END_B"), "{b:?}");
+        assert!(!b.contains("END_A"));
+        assert!(!output.contains("linutil-drained"));
+    }
+
+    #[test]
+    fn test_typed_answers() {
+        let mut settings = Settings::default();
+        settings.logging.journald = false;
+        settings.logging.live_tee = false;
+        let runner = CommandRunner::spawn(
+            &[test_command(
+                "Greet",
+                "printf 'Name: '; read name; printf 'Theme: '; read theme",
+            )],
+            &settings,
+            FailurePolicy::Continue,
+        );
+        let wait_for = |prompt: &str| {
+            while runner.last_line() != prompt {
+                thread::sleep(Duration::from_millis(10));
+            }
+        };
+        wait_for("Name: ");
+        // Keys typed one by one, corrected with Backspace
+        for bytes in [
+            &b"l"[..],
+            b"x",
+            b"\x7f",
+            b"i",
+            b"\x1b[D",
+            "é".as_bytes(),
+            b"\r",
+        ] {
+            runner.type_bytes(bytes);
+        }
+        wait_for("Theme: ");
+        runner.send_input("dark\n");
+        let events = runner.events();
+        while let Ok(event) = events.recv_blocking() {
+            if let RunEvent::Finished { .. } = event {
+                break;
+            }
+        }
+
+        let answers: Vec<(String, String)> = runner
+            .typed_answers()
+            .into_iter()
+            .map(|answer| (answer.prompt, answer.input))
+            .collect();
+        assert_eq!(
+            answers,
+            [
+                ("Name:".to_string(), "lié".to_string()),
+                ("Theme:".to_string(), "dark".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_env_script() {
+        let env = [
+            ("EDITOR".to_string(), "nvim".to_string()),
+            ("GREETING".to_string(), "it's me".to_string()),
+        ];
+        assert_eq!(
+            env_script(&env),
+            "export EDITOR='nvim'\nexport GREETING='it'\\''s me'\n"
+        );
+    }
+
+    #[test]
+    fn test_command_invocation() {
+        let node = ListNode {
+            name: "Greet".to_string(),
+            description: String::new(),
+            command: Command::Raw("echo 'hi'".to_string()),
+            task_list: String::new(),
+            multi_select: true,
+            tags: Vec::new(),
+        };
+
+        assert_eq!(
+            command_invocation(&node),
+            "TERM='xterm-256color' COLORTERM='truecolor' FORCE_COLOR='1' NO_COLOR='' \
+             sh -c 'echo '\\''hi'\\'''"
+        );
+    }
+}
@@ -0,0 +1,87 @@
+use crate::highlight::{highlight_shell, Token};
+use gtk::prelude::*;
+use gtk4 as gtk;
+use std::{fs, path::Path};
+
+/// Opens a read-only window showing the script at `path` of the command `name`.
+pub fn open_script_view(app: &gtk::Application, name: &str, path: &Path) {
+    let window = gtk::ApplicationWindow::builder()
+        .application(app)
+        .title(format!("Script: {name}"))
+        .default_width(860)
+        .default_height(640)
+        .build();
+
+    let root_box = gtk::Box::new(gtk::Orientation::Vertical, 8);
+    root_box.set_margin_top(12);
+    root_box.set_margin_bottom(12);
+    root_box.set_margin_start(12);
+    root_box.set_margin_end(12);
+
+    let path_label = gtk::Label::new(Some(&path.display().to_string()));
+    path_label.set_xalign(0.0);
+    path_label.set_selectable(true);
+    path_label.add_css_class("path-label");
+    path_label.update_property(&[gtk::accessible::Property::Label("Script path")]);
+
+    let script_view = gtk::TextView::new();
+    script_view.set_monospace(true);
+    script_view.set_editable(false);
+    script_view.set_cursor_visible(false);
+    script_view.update_property(&[
+        gtk::accessible::Property::Label(&format!("Script of {name}")),
+        gtk::accessible::Property::ReadOnly(true),
+    ]);
+    let buffer = script_view.buffer();
+    // Mid-tone colors stay readable on light and dark themes
+    buffer.create_tag(
+        Some("comment"),
+        &[
+            ("foreground", &"#808080"),
+            ("style", &gtk::pango::Style::Italic),
+        ],
+    );
+    buffer.create_tag(Some("string"), &[("foreground", &"#3f9f3f")]);
+    buffer.create_tag(Some("variable"), &[("foreground", &"#c0792a")]);
+    buffer.create_tag(
+        Some("keyword"),
+        &[("foreground", &"#4c7fd9"), ("weight", &700)],
+    );
+    match fs::read_to_string(path) {
+        Ok(source) => {
+            buffer.set_text(&source);
+            for (range, token) in highlight_shell(&source) {
+                let start = buffer.iter_at_offset(range.start as i32);
+                let end = buffer.iter_at_offset(range.end as i32);
+                buffer.apply_tag_by_name(tag_name(token), &start, &end);
+            }
+        }
+        Err(err) => buffer.set_text(&format!("Failed to read {}: {err}", path.display())),
+    }
+    let view_scroll = gtk::ScrolledWindow::new();
+    view_scroll.set_hexpand(true);
+    view_scroll.set_vexpand(true);
+    view_scroll.set_child(Some(&script_view));
+
+    let close_button = gtk::Button::with_label("Close");
+    close_button.set_halign(gtk::Align::End);
+    close_button.update_property(&[gtk::accessible::Property::Label("Close")]);
+    let window_clone = window.clone();
+    close_button.connect_clicked(move |_| window_clone.close());
+
+    root_box.append(&path_label);
+    root_box.append(&view_scroll);
+    root_box.append(&close_button);
+    window.set_child(Some(&root_box));
+    window.show();
+    script_view.grab_focus();
+}
+
+fn tag_name(token: Token) -> &'static str {
+    match token {
+        Token::Comment => "comment",
+        Token::String => "string",
+        Token::Variable => "variable",
+        Token::Keyword => "keyword",
+    }
+}
@@ -0,0 +1,168 @@
+use linutil_core::{ego_tree::NodeId, ListNode, Tab};
+use std::rc::Rc;
+
+/// A command of one of the tabs, flattened out of its tree.
+pub struct IndexEntry {
+    pub node_id: NodeId,
+    pub node: Rc<ListNode>,
+    /// Location of the command, e.g. `System Setup / Arch`.
+    pub breadcrumb: String,
+    // Lowercase copies matched against the query
+    name: String,
+    description: String,
+}
+
+/// Every command of every tab, built once after the tabs are loaded so lookups do not walk
+/// the trees. Entries are sorted by name and identified by their position.
+pub struct SearchIndex {
+    entries: Vec<IndexEntry>,
+}
+
+impl SearchIndex {
+    pub fn build(tabs: &[Tab]) -> Self {
+        let mut entries = Vec::new();
+        for tab in tabs {
+            let root_id = tab.tree.root().id();
+            for node in tab.tree.root().descendants() {
+                if node.has_children() {
+                    continue;
+                }
+                let mut parts: Vec<&str> = node
+                    .ancestors()
+                    .filter(|ancestor| ancestor.id() != root_id)
+                    .map(|ancestor| ancestor.value().name.as_str())
+                    .collect();
+                parts.push(&tab.name);
+                parts.reverse();
+                entries.push(IndexEntry {
+                    node_id: node.id(),
+                    node: node.value().clone(),
+                    breadcrumb: parts.join(" / "),
+                    name: node.value().name.to_lowercase(),
+                    description: node.value().description.to_lowercase(),
+                });
+            }
+        }
+        entries.sort_by(|a, b| a.node.name.cmp(&b.node.name));
+        Self { entries }
+    }
+
+    pub fn get(&self, id: usize) -> Option<&IndexEntry> {
+        self.entries.get(id)
+    }
+
+    /// Ids of the entries matching `query`, ignoring case. Name matches come first, followed
+    /// by commands that only match in their description; both in name order.
+    pub fn search(&self, query: &str) -> Vec<usize> {
+        self.refine(0..self.entries.len(), query)
+    }
+
+    /// Like [`SearchIndex::search`], but only considers `ids`. Typing more of a query can
+    /// only narrow the results, so the previous results can be refined instead.
+    pub fn refine(&self, ids: impl IntoIterator<Item = usize>, query: &str) -> Vec<usize> {
+        let query = query.to_lowercase();
+        let mut ids: Vec<usize> = ids.into_iter().collect();
+        ids.sort_unstable();
+        let (mut by_name, mut by_description) = (Vec::new(), Vec::new());
+        for id in ids {
+            let Some(entry) = self.entries.get(id) else {
+                continue;
+            };
+            if entry.name.contains(&query) {
+                by_name.push(id);
+            } else if entry.description.contains(&query) {
+                by_description.push(id);
+            }
+        }
+        by_name.append(&mut by_description);
+        by_name
+    }
+}
+
+/// Splits the `tag:<name>` filters off a search query, e.g. `tag:aur paru` gives `paru` and
+/// the tags `aur`.
+pub fn split_query(query: &str) -> (String, Vec<String>) {
+    let mut words = Vec::new();
+    let mut tags = Vec::new();
+    for word in query.split_whitespace() {
+        match word.strip_prefix("tag:") {
+            Some(tag) => tags.push(tag.to_lowercase()),
+            None => words.push(word),
+        }
+    }
+    (words.join(" "), tags)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use linutil_core::{ego_tree::Tree, Command};
+
+    fn node(name: &str, description: &str) -> Rc<ListNode> {
+        Rc::new(ListNode {
+            name: name.to_string(),
+            description: description.to_string(),
+            command: Command::None,
+            task_list: String::new(),
+            multi_select: true,
+            tags: Vec::new(),
+        })
+    }
+
+    fn index() -> SearchIndex {
+        let mut tree = Tree::new(node("root", ""));
+        {
+            let mut root = tree.root_mut();
+            let mut arch = root.append(node("Arch", ""));
+            arch.append(node("Paru", "AUR helper"));
+            arch.append(node("Yay", "Another AUR helper"));
+        }
+        tree.root_mut()
+            .append(node("Aurora", "Not a package manager"));
+        SearchIndex::build(&[Tab {
+            name: "System Setup".to_string(),
+            tree,
+        }])
+    }
+
+    fn names(index: &SearchIndex, ids: &[usize]) -> Vec<String> {
+        ids.iter()
+            .map(|&id| index.get(id).unwrap().node.name.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_build() {
+        let index = index();
+
+        assert_eq!(names(&index, &[0, 1, 2]), ["Aurora", "Paru", "Yay"]);
+        assert_eq!(index.get(0).unwrap().breadcrumb, "System Setup");
+        assert_eq!(index.get(1).unwrap().breadcrumb, "System Setup / Arch");
+        assert!(index.get(3).is_none());
+    }
+
+    #[test]
+    fn test_search() {
+        let index = index();
+
+        let aur = index.search("AUR");
+        assert_eq!(names(&index, &aur), ["Aurora", "Paru", "Yay"]);
+        assert_eq!(names(&index, &index.refine(aur, "aur h")), ["Paru", "Yay"]);
+        assert!(index.search("pacman").is_empty());
+    }
+
+    #[test]
+    fn test_split_query() {
+        assert_eq!(
+            split_query("tag:AUR  paru tag:mine"),
+            (
+                "paru".to_string(),
+                vec!["aur".to_string(), "mine".to_string()]
+            )
+        );
+        assert_eq!(
+            split_query("aur helper"),
+            ("aur helper".to_string(), vec![])
+        );
+    }
+}
@@ -0,0 +1,263 @@
+// Checks GitHub for a newer release of the GUI itself, when the settings allow it, and
+// updates it the way it was installed: through the package manager when it lives in
+// `/usr/bin` or `/usr/lib`, or by replacing the binary with the matching release asset
+// otherwise, once it matches the SHA-256 sum published next to it.
+
+use crate::integrity::{hex, sha256};
+use serde::Deserialize;
+use std::{
+    env, fs,
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+const LATEST_RELEASE_URL: &str =
+    "https://api.github.com/repos/Boo15mario/linutil-gui/releases/latest";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Release {
+    /// Version from the release tag, without a leading `v`, e.g. `25.12.30`.
+    pub version: String,
+    /// Changelog written for the release, in Markdown.
+    pub notes: String,
+    /// Page of the release on GitHub.
+    pub page: String,
+    /// Download URLs of the files attached to the release.
+    pub assets: Vec<String>,
+}
+
+impl Release {
+    /// Download URL of the binary for `architecture`: `linutil-aarch64` and so on, or plain
+    /// `linutil` for x86_64.
+    pub fn binary_for(&self, architecture: &str) -> Option<&str> {
+        self.assets
+            .iter()
+            .find(|url| {
+                let name = url.rsplit('/').next().unwrap_or_default();
+                name == format!("linutil-{architecture}")
+                    || (architecture == "x86_64" && name == "linutil")
+            })
+            .map(String::as_str)
+    }
+
+    /// Download URL of the SHA-256 sum published for the asset at `binary`, named like it
+    /// with `.sha256` appended.
+    pub fn checksum_for(&self, binary: &str) -> Option<&str> {
+        self.assets
+            .iter()
+            .find(|url| **url == format!("{binary}.sha256"))
+            .map(String::as_str)
+    }
+}
+
+/// How this copy of the GUI gets updated.
+#[derive(Clone, Debug, PartialEq)]
+pub enum InstallMethod {
+    /// Installed by a package, which the package manager updates.
+    PackageManager,
+    /// A binary of its own, e.g. in `~/.local/bin`, replaced by the downloaded release.
+    Binary(PathBuf),
+}
+
+impl InstallMethod {
+    pub fn current() -> Option<Self> {
+        env::current_exe().ok().map(|exe| Self::from_path(&exe))
+    }
+
+    fn from_path(exe: &Path) -> Self {
+        // `/usr/local` is where binaries installed by hand go
+        if exe.starts_with("/usr/bin") || exe.starts_with("/usr/lib") {
+            Self::PackageManager
+        } else {
+            Self::Binary(exe.to_path_buf())
+        }
+    }
+}
+
+/// The latest release if it is newer than this build. Blocks while asking GitHub through
+/// `curl`, and gives `None` when the release cannot be fetched.
+pub fn newer_release() -> Option<Release> {
+    let output = Command::new("curl")
+        .args([
+            "--fail",
+            "--silent",
+            "--location",
+            "--max-time",
+            "15",
+            "--header",
+            "Accept: application/vnd.github+json",
+            LATEST_RELEASE_URL,
+        ])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    parse_release(&String::from_utf8_lossy(&output.stdout))
+        .filter(|release| is_newer(&release.version, env!("CARGO_PKG_VERSION")))
+}
+
+/// Downloads the binary at `url`, checks it against the SHA-256 sum at `checksum_url` and puts
+/// it in place of `exe`. The running GUI keeps using the old one until it is started again.
+pub fn install_binary(url: &str, checksum_url: &str, exe: &Path) -> Result<(), String> {
+    let output = Command::new("curl")
+        .args(["--fail", "--silent", "--location", checksum_url])
+        .output()
+        .map_err(|err| format!("Could not run curl: {err}"))?;
+    let expected = output
+        .status
+        .success()
+        .then(|| parse_checksum(&String::from_utf8_lossy(&output.stdout)))
+        .flatten()
+        .ok_or_else(|| format!("Could not download the checksum {checksum_url}."))?;
+
+    let download = exe.with_extension("new");
+    let status = Command::new("curl")
+        .args(["--fail", "--silent", "--location", "--output"])
+        .arg(&download)
+        .arg(url)
+        .status()
+        .map_err(|err| format!("Could not run curl: {err}"))?;
+    if !status.success() {
+        let _ = fs::remove_file(&download);
+        return Err(format!("Could not download {url}."));
+    }
+    let matches = fs::read(&download).is_ok_and(|content| hex(&sha256(&content)) == expected);
+    if !matches {
+        let _ = fs::remove_file(&download);
+        return Err(format!(
+            "The download of {url} does not match its published checksum, so Linutil was \
+             left as it is."
+        ));
+    }
+    fs::set_permissions(&download, fs::Permissions::from_mode(0o755))
+        .and_then(|()| fs::rename(&download, exe))
+        .map_err(|err| {
+            let _ = fs::remove_file(&download);
+            format!("Could not replace {}: {err}", exe.display())
+        })
+}
+
+/// The sum in `sha256sum` output, e.g. `<64 hex digits>  linutil`, in lowercase.
+fn parse_checksum(text: &str) -> Option<String> {
+    let sum = text.split_whitespace().next()?.to_lowercase();
+    (sum.len() == 64 && sum.chars().all(|c| c.is_ascii_hexdigit())).then_some(sum)
+}
+
+/// The fields used of a release in the GitHub releases API.
+#[derive(Deserialize)]
+struct ApiRelease {
+    tag_name: String,
+    /// `null` for releases without notes.
+    body: Option<String>,
+    html_url: String,
+    assets: Vec<ApiAsset>,
+}
+
+#[derive(Deserialize)]
+struct ApiAsset {
+    browser_download_url: String,
+}
+
+/// The release in a response of the GitHub releases API.
+fn parse_release(json: &str) -> Option<Release> {
+    let release: ApiRelease = serde_json::from_str(json)
+        .map_err(|err| eprintln!("Failed to parse the latest release: {err}"))
+        .ok()?;
+    Some(Release {
+        version: release.tag_name.trim_start_matches('v').to_string(),
+        notes: release.body.unwrap_or_default().replace("\r\n", "\n"),
+        page: release.html_url,
+        assets: release
+            .assets
+            .into_iter()
+            .map(|asset| asset.browser_download_url)
+            .collect(),
+    })
+}
+
+/// Whether the dotted version `latest` is above `current`, comparing number by number.
+fn is_newer(latest: &str, current: &str) -> bool {
+    let numbers = |version: &str| -> Vec<u64> {
+        version
+            .split(['.', '-'])
+            .map_while(|part| part.parse().ok())
+            .collect()
+    };
+    numbers(latest) > numbers(current)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESPONSE: &str = r#"{
+  "author": {"login": "Boo15mario", "html_url": "https://github.com/Boo15mario"},
+  "assets": [
+    {"name": "linutil", "uploader": {"html_url": "https://github.com/Boo15mario"}, "browser_download_url": "https://github.com/Boo15mario/linutil-gui/releases/download/v25.12.30/linutil"},
+    {"name": "linutil.sha256", "browser_download_url": "https://github.com/Boo15mario/linutil-gui/releases/download/v25.12.30/linutil.sha256"},
+    {"name": "linutil-aarch64", "browser_download_url": "https://github.com/Boo15mario/linutil-gui/releases/download/v25.12.30/linutil-aarch64"}
+  ],
+  "html_url": "https://github.com/Boo15mario/linutil-gui/releases/tag/v25.12.30",
+  "tag_name": "v25.12.30",
+  "body": "Changes:\r\n- Faster \"search\"\r\n- Caf\u00e9 fix"
+}"#;
+
+    #[test]
+    fn test_parse_release() {
+        let release = parse_release(RESPONSE).unwrap();
+        assert_eq!(release.version, "25.12.30");
+        assert_eq!(release.notes, "Changes:\n- Faster \"search\"\n- Café fix");
+        assert!(release.page.ends_with("/tag/v25.12.30"));
+        assert!(release.binary_for("x86_64").unwrap().ends_with("/linutil"));
+        assert!(release
+            .binary_for("aarch64")
+            .unwrap()
+            .ends_with("/linutil-aarch64"));
+        assert_eq!(release.binary_for("riscv64"), None);
+        assert_eq!(parse_release("{\"message\": \"Not Found\"}"), None);
+        let binary = release.binary_for("x86_64").unwrap();
+        assert!(release
+            .checksum_for(binary)
+            .unwrap()
+            .ends_with("/linutil.sha256"));
+        assert_eq!(
+            release.checksum_for(release.binary_for("aarch64").unwrap()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_checksum() {
+        let sum = "BA7816BF8F01CFEA414140DE5DAE2223B00361A396177A9CB410FF61F20015AD";
+        assert_eq!(
+            parse_checksum(&format!("{sum}  linutil\n")),
+            Some(sum.to_lowercase())
+        );
+        assert_eq!(parse_checksum("Not Found"), None);
+        assert_eq!(parse_checksum(""), None);
+    }
+
+    #[test]
+    fn test_is_newer() {
+        assert!(is_newer("25.12.30", "25.12.18"));
+        assert!(is_newer("26.1.2", "25.12.18"));
+        assert!(!is_newer("25.12.18", "25.12.18"));
+        assert!(!is_newer("25.9.1", "25.12.18"));
+    }
+
+    #[test]
+    fn test_install_method() {
+        assert_eq!(
+            InstallMethod::from_path(Path::new("/usr/bin/linutil")),
+            InstallMethod::PackageManager
+        );
+        assert_eq!(
+            InstallMethod::from_path(Path::new("/home/me/.local/bin/linutil")),
+            InstallMethod::Binary(PathBuf::from("/home/me/.local/bin/linutil"))
+        );
+        assert_eq!(
+            InstallMethod::from_path(Path::new("/usr/local/bin/linutil")),
+            InstallMethod::Binary(PathBuf::from("/usr/local/bin/linutil"))
+        );
+    }
+}
@@ -0,0 +1,460 @@
+use crate::theme::Theme;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+// Persisted GUI preferences, stored in `$XDG_CONFIG_HOME/linutil-gui/settings.toml`.
+// Unlike the `--config` file these describe how the GUI itself behaves, not what it runs.
+// Every section falls back to its defaults so older files keep loading.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Settings {
+    pub logging: LoggingSettings,
+    pub performance: PerformanceSettings,
+    pub runs: RunSettings,
+    pub tips: TipSettings,
+    pub integrity: IntegritySettings,
+    pub updates: UpdateSettings,
+    pub view: ViewSettings,
+    pub windows: WindowSettings,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct LoggingSettings {
+    /// Number of runs whose logs are kept, 0 for no limit.
+    pub max_logs: usize,
+    /// Delete logs older than this many days, 0 for no limit.
+    pub max_age_days: u64,
+    /// Upper bound for the total size of saved logs in MiB, 0 for no limit.
+    pub max_total_mib: u64,
+    /// Gzip logs older than this many days, 0 to never compress.
+    pub compress_after_days: u64,
+    /// Send command start, finish and failure events to the systemd journal.
+    pub journald: bool,
+    /// Write output to the log file as it arrives instead of only when the run ends.
+    pub live_tee: bool,
+    /// Mask input typed at password prompts wherever it shows up in output and logs.
+    pub redact_secrets: bool,
+    /// Where logs are saved instead of `logs` in the state directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub directory: Option<PathBuf>,
+}
+
+impl Default for LoggingSettings {
+    fn default() -> Self {
+        Self {
+            max_logs: 50,
+            max_age_days: 30,
+            max_total_mib: 100,
+            compress_after_days: 7,
+            journald: true,
+            live_tee: false,
+            redact_secrets: true,
+            directory: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PerformanceSettings {
+    /// Minimum time between two updates of the output view in milliseconds. Output arriving
+    /// in between is inserted together.
+    pub output_interval_ms: u64,
+    /// Largest amount of output inserted in one update, in KiB.
+    pub output_batch_kib: usize,
+    /// Lines kept in the output view, 0 for no limit. Saved logs always contain everything.
+    pub output_max_lines: usize,
+    pub output_scroll: OutputScroll,
+}
+
+impl Default for PerformanceSettings {
+    fn default() -> Self {
+        Self {
+            output_interval_ms: 16,
+            output_batch_kib: 64,
+            output_max_lines: 5000,
+            output_scroll: OutputScroll::Follow,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RunSettings {
+    /// What a batch does after one of its commands failed, unless changed when confirming.
+    pub on_failure: FailurePolicy,
+    /// Run commands without the confirmation dialog, like `--skip-confirmation`.
+    pub skip_confirmation: bool,
+    /// Show the output of runs in a pane at the bottom of the main window instead of a window
+    /// of their own.
+    pub output_pane: bool,
+    /// Run commands on the host instead of the container when Linutil runs in a Toolbx or
+    /// Distrobox container.
+    pub run_on_host: bool,
+    /// Keep the system from suspending while commands run.
+    pub inhibit_suspend: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TipSettings {
+    /// Show another tip every this many seconds, 0 to keep the first one.
+    pub rotate_secs: u32,
+    /// Open the tip of the day dialog at startup.
+    pub startup_dialog: bool,
+    /// Show the tip below the command list.
+    pub show_label: bool,
+}
+
+impl Default for TipSettings {
+    fn default() -> Self {
+        Self {
+            rotate_secs: 30,
+            startup_dialog: true,
+            show_label: true,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct UpdateSettings {
+    /// Ask the package manager for pending updates this often in minutes, 0 to never ask.
+    pub check_interval_mins: u32,
+    /// Ask GitHub for a newer release of Linutil at startup.
+    pub check_releases: bool,
+    /// Download the latest scripts at startup, starting from the last download meanwhile.
+    pub refresh_scripts: bool,
+    /// Where the signature of a script download is, with `{commit}` standing for the commit
+    /// downloaded. Defaults to the archive's URL followed by `.asc`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature_url: Option<String>,
+    /// Use downloaded scripts even without a signature by a pinned key.
+    pub allow_unsigned_scripts: bool,
+    /// Places to download the scripts from, tried in order until one works.
+    pub sources: Vec<CatalogSource>,
+    /// Number of downloaded script versions to keep for going back to.
+    pub keep_catalogs: usize,
+    /// Version of the scripts to stay on instead of the newest, by snapshot id, or
+    /// `built-in` for the scripts Linutil was built with.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pinned_catalog: Option<String>,
+}
+
+impl Default for UpdateSettings {
+    fn default() -> Self {
+        Self {
+            check_interval_mins: 60,
+            check_releases: false,
+            refresh_scripts: false,
+            signature_url: None,
+            allow_unsigned_scripts: false,
+            sources: vec![CatalogSource::official()],
+            keep_catalogs: 3,
+            pinned_catalog: None,
+        }
+    }
+}
+
+/// A place the scripts are downloaded from.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct CatalogSource {
+    pub name: String,
+    /// `github:OWNER/REPO` for the main branch of a repository on GitHub, the URL of a
+    /// tarball holding a `tabs.toml`, e.g. on a company mirror, or a directory or tarball on
+    /// this system.
+    pub location: String,
+    #[serde(default = "enabled")]
+    pub enabled: bool,
+    /// Where the signature of a download is, instead of its URL followed by `.asc`. Sources
+    /// on this system are not signed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+impl CatalogSource {
+    /// The linutil repository.
+    pub fn official() -> Self {
+        Self {
+            name: "Official".to_string(),
+            location: "github:ChrisTitusTech/linutil".to_string(),
+            enabled: true,
+            signature: None,
+        }
+    }
+}
+
+fn enabled() -> bool {
+    true
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct IntegritySettings {
+    /// What happens when a script changed since it was hashed.
+    pub policy: IntegrityPolicy,
+    /// Manifest in `sha256sum` format with paths relative to the tabs directory. Without it
+    /// the scripts are hashed when they are extracted at startup.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manifest: Option<PathBuf>,
+}
+
+/// What happens when a script about to run does not match its checksum.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum IntegrityPolicy {
+    /// Do not check scripts.
+    Off,
+    /// Ask before running them anyway.
+    #[default]
+    Warn,
+    /// Do not run them.
+    Refuse,
+}
+
+/// What happens to the rest of a batch when one of its commands fails.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FailurePolicy {
+    /// Run the remaining commands anyway.
+    #[default]
+    Continue,
+    /// Skip the remaining commands.
+    Abort,
+}
+
+impl FailurePolicy {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Continue => "On failure: continue",
+            Self::Abort => "On failure: stop",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ViewSettings {
+    /// How commands are shown, switched with the Grid button.
+    pub layout: CommandLayout,
+    /// Used unless `--theme` is given.
+    pub theme: Theme,
+    /// Number of commands that can be pinned above the list.
+    pub max_pins: usize,
+    /// List commands written for other desktops than the one of the session as well.
+    pub all_desktops: bool,
+}
+
+impl Default for ViewSettings {
+    fn default() -> Self {
+        Self {
+            layout: CommandLayout::List,
+            theme: Theme::Default,
+            max_pins: 8,
+            all_desktops: false,
+        }
+    }
+}
+
+/// Sizes of the windows when they were last closed. GTK 4 leaves where windows go to the
+/// window manager, so positions are not kept.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct WindowSettings {
+    pub main: WindowGeometry,
+    /// Windows showing the output of commands.
+    pub output: WindowGeometry,
+    /// Width of the list of tabs in the main window, set by dragging the divider beside it.
+    pub sidebar: i32,
+}
+
+impl WindowSettings {
+    const MIN_SIDEBAR: i32 = 120;
+
+    /// Width to open the list of tabs with.
+    pub fn sidebar_width(&self) -> i32 {
+        self.sidebar.max(Self::MIN_SIDEBAR)
+    }
+}
+
+impl Default for WindowSettings {
+    fn default() -> Self {
+        Self {
+            main: WindowGeometry::new(1100, 720),
+            output: WindowGeometry::new(900, 600),
+            sidebar: 240,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct WindowGeometry {
+    /// Size when not maximized.
+    pub width: i32,
+    pub height: i32,
+    #[serde(default)]
+    pub maximized: bool,
+}
+
+impl WindowGeometry {
+    // Smaller saved sizes are from a broken file rather than a user
+    const MIN_SIZE: i32 = 320;
+
+    pub const fn new(width: i32, height: i32) -> Self {
+        Self {
+            width,
+            height,
+            maximized: false,
+        }
+    }
+
+    /// Width and height to open the window with.
+    pub fn size(self) -> (i32, i32) {
+        (
+            self.width.max(Self::MIN_SIZE),
+            self.height.max(Self::MIN_SIZE),
+        )
+    }
+}
+
+/// How the commands of a category are shown.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CommandLayout {
+    /// One row per command with its tags and last run.
+    #[default]
+    List,
+    /// Large tiles, easier to hit on touch screens and from a couch.
+    Grid,
+}
+
+/// How the output view scrolls when output arrives.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OutputScroll {
+    /// Always jump to the newest output.
+    Follow,
+    /// Follow only while the view is scrolled to the bottom.
+    AtBottom,
+    /// Never scroll automatically.
+    Off,
+}
+
+/// Directory holding the GUI's own configuration: `$XDG_CONFIG_HOME/linutil-gui`, falling
+/// back to `~/.config/linutil-gui`.
+pub fn config_dir() -> PathBuf {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+        .unwrap_or_else(std::env::temp_dir)
+        .join("linutil-gui")
+}
+
+/// Directory for what the GUI keeps between runs, such as logs: `$XDG_STATE_HOME/linutil-gui`,
+/// falling back to `~/.local/state/linutil-gui`.
+pub fn state_dir() -> PathBuf {
+    std::env::var_os("XDG_STATE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".local/state")))
+        .unwrap_or_else(std::env::temp_dir)
+        .join("linutil-gui")
+}
+
+impl Settings {
+    pub fn path() -> PathBuf {
+        config_dir().join("settings.toml")
+    }
+
+    /// Loads the settings file, using defaults when it is missing or invalid.
+    pub fn load() -> Self {
+        let path = Self::path();
+        match fs::read_to_string(&path) {
+            Ok(content) => toml::from_str(&content).unwrap_or_else(|err| {
+                eprintln!("Failed to parse settings file {}: {err}", path.display());
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Writes the settings file. Comments in an existing file are not kept.
+    pub fn save(&self) -> io::Result<()> {
+        let content = toml::to_string_pretty(self)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        fs::create_dir_all(config_dir())?;
+        fs::write(Self::path(), content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_settings_use_defaults() {
+        let settings: Settings = toml::from_str("[logging]\nmax_logs = 5\n").unwrap();
+
+        assert_eq!(settings.logging.max_logs, 5);
+        assert_eq!(settings.logging.max_age_days, 30);
+        assert_eq!(settings.logging.max_total_mib, 100);
+        assert_eq!(settings.performance.output_max_lines, 5000);
+        assert_eq!(settings.windows.main, WindowGeometry::new(1100, 720));
+    }
+
+    #[test]
+    fn test_window_geometry() {
+        let settings: Settings = toml::from_str(
+            "[windows.main]\nwidth = 1400\nheight = 900\nmaximized = true\n\n\
+             [windows.output]\nwidth = 0\nheight = 500\n",
+        )
+        .unwrap();
+
+        assert_eq!(settings.windows.main.size(), (1400, 900));
+        assert!(settings.windows.main.maximized);
+        assert_eq!(settings.windows.output.size(), (320, 500));
+        assert!(!settings.windows.output.maximized);
+        assert_eq!(settings.windows.sidebar_width(), 240);
+
+        let settings: Settings = toml::from_str("[windows]\nsidebar = 40\n").unwrap();
+        assert_eq!(settings.windows.sidebar_width(), 120);
+    }
+
+    #[test]
+    fn test_output_scroll_names() {
+        let settings: Settings =
+            toml::from_str("[performance]\noutput_scroll = \"at-bottom\"\n").unwrap();
+
+        assert_eq!(settings.performance.output_scroll, OutputScroll::AtBottom);
+
+        let settings: Settings = toml::from_str("[runs]\non_failure = \"abort\"\n").unwrap();
+        assert_eq!(settings.runs.on_failure, FailurePolicy::Abort);
+
+        let settings: Settings = toml::from_str(
+            "[integrity]\npolicy = \"refuse\"\nmanifest = \"/etc/scripts.sha256\"\n",
+        )
+        .unwrap();
+        assert_eq!(settings.integrity.policy, IntegrityPolicy::Refuse);
+        assert_eq!(
+            settings.integrity.manifest,
+            Some(PathBuf::from("/etc/scripts.sha256"))
+        );
+    }
+
+    #[test]
+    fn test_settings_round_trip() {
+        let mut settings = Settings::default();
+        settings.logging.max_age_days = 0;
+
+        let content = toml::to_string_pretty(&settings).unwrap();
+        let parsed: Settings = toml::from_str(&content).unwrap();
+
+        assert_eq!(parsed.logging.max_age_days, 0);
+        assert_eq!(parsed.logging.max_logs, settings.logging.max_logs);
+    }
+}
@@ -0,0 +1,166 @@
+// OpenPGP signature checks for downloaded scripts, which often run as root. A detached
+// signature of the download is checked with `gpg` against pinned keys only: the ones a
+// distribution ships in `/usr/share/linutil-gui/keys` and the ones the user puts in `keys/` of
+// the config directory. Each check imports them into a keyring of its own, so keys in the
+// user's keyring never count, and checks running at the same time never share one.
+
+use crate::settings::{config_dir, state_dir};
+use std::{
+    fs,
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+    process::{self, Command},
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+const SYSTEM_KEY_DIR: &str = "/usr/share/linutil-gui/keys";
+
+/// Keyrings created by this process so far, numbering the next one.
+static KEYRINGS: AtomicU32 = AtomicU32::new(0);
+
+/// A keyring directory of one check, removed with everything in it when dropped.
+struct Keyring(PathBuf);
+
+impl Keyring {
+    /// Creates an empty keyring in [`state_dir`], whose path stays short enough for the
+    /// sockets `gpg` puts in it.
+    fn create() -> Result<Self, String> {
+        let number = KEYRINGS.fetch_add(1, Ordering::SeqCst);
+        let home = state_dir().join(format!("gnupg-{}-{number}", process::id()));
+        // A directory left over by a crashed process of the same ID is not reused
+        let _ = fs::remove_dir_all(&home);
+        fs::create_dir_all(state_dir())
+            .and_then(|()| fs::create_dir(&home))
+            .map_err(|err| format!("Could not create a keyring: {err}"))?;
+        let keyring = Self(home);
+        fs::set_permissions(&keyring.0, fs::Permissions::from_mode(0o700))
+            .map_err(|err| format!("Could not create a keyring: {err}"))?;
+        Ok(keyring)
+    }
+}
+
+impl Drop for Keyring {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// The pinned public key files, armored `.asc` or binary `.gpg`.
+pub fn key_files() -> Vec<PathBuf> {
+    let mut keys = Vec::new();
+    for dir in [PathBuf::from(SYSTEM_KEY_DIR), config_dir().join("keys")] {
+        let Ok(entries) = fs::read_dir(dir) else {
+            continue;
+        };
+        let mut files: Vec<PathBuf> = entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|extension| extension == "asc" || extension == "gpg")
+            })
+            .collect();
+        files.sort();
+        keys.extend(files);
+    }
+    keys
+}
+
+/// Checks that `signature` is a good signature of `file` by one of the pinned keys, returning
+/// the fingerprint of the key that made it. Blocks while `gpg` runs.
+pub fn verify(file: &Path, signature: &Path) -> Result<String, String> {
+    let keys = key_files();
+    if keys.is_empty() {
+        return Err(format!(
+            "No signing keys are pinned in {SYSTEM_KEY_DIR} or {}.",
+            config_dir().join("keys").display()
+        ));
+    }
+    let keyring = Keyring::create()?;
+    let home = &keyring.0;
+
+    let imported = Command::new("gpg")
+        .arg("--homedir")
+        .arg(home)
+        .args(["--batch", "--quiet", "--import"])
+        .args(&keys)
+        .output()
+        .map_err(|err| format!("Could not run gpg: {err}"))?;
+    if !imported.status.success() {
+        return Err(format!(
+            "Could not import the pinned keys: {}",
+            String::from_utf8_lossy(&imported.stderr).trim()
+        ));
+    }
+    let verified = Command::new("gpg")
+        .arg("--homedir")
+        .arg(home)
+        .args(["--batch", "--status-fd", "1", "--verify"])
+        .arg(signature)
+        .arg(file)
+        .output()
+        .map_err(|err| format!("Could not run gpg: {err}"))?;
+
+    match signer(&String::from_utf8_lossy(&verified.stdout)) {
+        Some(fingerprint) if verified.status.success() => Ok(fingerprint),
+        _ => Err(format!(
+            "The signature does not match a pinned key: {}",
+            String::from_utf8_lossy(&verified.stderr).trim()
+        )),
+    }
+}
+
+/// Fingerprint of the key behind a valid signature in `gpg --status-fd` output. Signatures
+/// of expired or revoked keys do not count.
+fn signer(status: &str) -> Option<String> {
+    let lines: Vec<&str> = status
+        .lines()
+        .filter_map(|line| line.strip_prefix("[GNUPG:] "))
+        .collect();
+    let rejected = lines.iter().any(|line| {
+        ["EXPKEYSIG", "REVKEYSIG", "BADSIG", "ERRSIG"]
+            .iter()
+            .any(|word| line.starts_with(word))
+    });
+    if rejected {
+        return None;
+    }
+    lines.iter().find_map(|line| {
+        line.strip_prefix("VALIDSIG ")?
+            .split_whitespace()
+            .next()
+            .map(str::to_string)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FINGERPRINT: &str = "4E1F799AA4FF2279C5C6F9D2F8E2C3A1B0D9E8F7";
+
+    #[test]
+    fn test_keyring() {
+        let (first, second) = (Keyring::create().unwrap(), Keyring::create().unwrap());
+        assert_ne!(first.0, second.0);
+        let path = first.0.clone();
+        assert!(path.is_dir());
+        drop(first);
+        assert!(!path.exists());
+        assert!(second.0.is_dir());
+    }
+
+    #[test]
+    fn test_signer() {
+        let good = format!(
+            "[GNUPG:] NEWSIG\n[GNUPG:] GOODSIG F8E2C3A1B0D9E8F7 Linutil\n\
+             [GNUPG:] VALIDSIG {FINGERPRINT} 2025-12-18 1766016000 0 4 0 22 10 00 {FINGERPRINT}\n"
+        );
+        assert_eq!(signer(&good).as_deref(), Some(FINGERPRINT));
+
+        let revoked = format!("{good}[GNUPG:] REVKEYSIG F8E2C3A1B0D9E8F7 Linutil\n");
+        assert_eq!(signer(&revoked), None);
+        assert_eq!(signer("[GNUPG:] BADSIG F8E2C3A1B0D9E8F7 Linutil\n"), None);
+        assert_eq!(signer("[GNUPG:] NO_PUBKEY F8E2C3A1B0D9E8F7\n"), None);
+    }
+}
@@ -0,0 +1,215 @@
+use crate::settings::{CatalogSource, Settings};
+use gtk::prelude::*;
+use gtk4 as gtk;
+use std::{cell::RefCell, rc::Rc};
+
+/// Opens a window managing the places Refresh Scripts downloads from: turning each on or off,
+/// moving it up or down in the order they are tried, removing it and adding new ones. Every
+/// change is saved right away.
+pub fn open_sources_window(app: &gtk::Application) {
+    let window = gtk::ApplicationWindow::builder()
+        .application(app)
+        .title("Script Sources")
+        .default_width(620)
+        .default_height(460)
+        .build();
+
+    let root_box = gtk::Box::new(gtk::Orientation::Vertical, 12);
+    root_box.set_margin_top(12);
+    root_box.set_margin_bottom(12);
+    root_box.set_margin_start(12);
+    root_box.set_margin_end(12);
+
+    let intro = gtk::Label::new(Some(
+        "Refresh Scripts tries the sources turned on from top to bottom and uses the first \
+         one that works.",
+    ));
+    intro.set_xalign(0.0);
+    intro.set_wrap(true);
+
+    let source_list = gtk::ListBox::new();
+    source_list.set_selection_mode(gtk::SelectionMode::None);
+    source_list.set_placeholder(Some(&gtk::Label::new(Some("No sources."))));
+    source_list.update_property(&[gtk::accessible::Property::Label("Script sources")]);
+    let list_scroll = gtk::ScrolledWindow::new();
+    list_scroll.set_policy(gtk::PolicyType::Never, gtk::PolicyType::Automatic);
+    list_scroll.set_vexpand(true);
+    list_scroll.set_child(Some(&source_list));
+
+    let name_entry = gtk::Entry::new();
+    name_entry.set_placeholder_text(Some("Name"));
+    name_entry.update_property(&[
+        gtk::accessible::Property::Label("Name of the new source"),
+        gtk::accessible::Property::Placeholder("Name"),
+    ]);
+    let location_entry = gtk::Entry::new();
+    location_entry.set_hexpand(true);
+    location_entry.set_placeholder_text(Some("https://mirror.example.com/linutil.tar.gz"));
+    location_entry.set_activates_default(true);
+    location_entry.update_property(&[
+        gtk::accessible::Property::Label("Location of the new source"),
+        gtk::accessible::Property::Description(
+            "github:OWNER/REPO, the URL of a tarball, or a directory or tarball on this system.",
+        ),
+    ]);
+    let add_button = gtk::Button::with_label("Add");
+    add_button.update_property(&[gtk::accessible::Property::Description(
+        "Add the source at the bottom of the list.",
+    )]);
+    let add_box = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+    add_box.append(&name_entry);
+    add_box.append(&location_entry);
+    add_box.append(&add_button);
+    let hint = gtk::Label::new(Some(
+        "A location is github:OWNER/REPO for the main branch of a repository, the URL of a \
+         tarball holding a tabs.toml, or a directory or tarball on this system. Downloads need \
+         a signature by a pinned key.",
+    ));
+    hint.set_xalign(0.0);
+    hint.set_wrap(true);
+    hint.add_css_class("dim-label");
+
+    let sources = Rc::new(RefCell::new(Settings::load().updates.sources));
+    fill_source_list(&source_list, &sources);
+
+    let source_list_clone = source_list.clone();
+    let sources_clone = sources.clone();
+    let name_entry_clone = name_entry.clone();
+    let location_entry_clone = location_entry.clone();
+    add_button.connect_clicked(move |_| {
+        let location = location_entry_clone.text().trim().to_string();
+        if location.is_empty() {
+            location_entry_clone.grab_focus();
+            return;
+        }
+        let name = match name_entry_clone.text().trim() {
+            "" => location.clone(),
+            name => name.to_string(),
+        };
+        sources_clone.borrow_mut().push(CatalogSource {
+            name,
+            location,
+            enabled: true,
+            signature: None,
+        });
+        save_sources(&sources_clone.borrow());
+        name_entry_clone.set_text("");
+        location_entry_clone.set_text("");
+        fill_source_list(&source_list_clone, &sources_clone);
+    });
+
+    root_box.append(&intro);
+    root_box.append(&list_scroll);
+    root_box.append(&add_box);
+    root_box.append(&hint);
+    window.set_child(Some(&root_box));
+    window.set_default_widget(Some(&add_button));
+    window.show();
+}
+
+/// Shows a row for each of `sources`, wired to change and save them.
+fn fill_source_list(source_list: &gtk::ListBox, sources: &Rc<RefCell<Vec<CatalogSource>>>) {
+    while let Some(row) = source_list.row_at_index(0) {
+        source_list.remove(&row);
+    }
+    let count = sources.borrow().len();
+    for (index, source) in sources.borrow().iter().enumerate() {
+        let enabled_switch = gtk::Switch::new();
+        enabled_switch.set_active(source.enabled);
+        enabled_switch.set_valign(gtk::Align::Center);
+        enabled_switch.update_property(&[gtk::accessible::Property::Label(&format!(
+            "Use {}",
+            source.name
+        ))]);
+        let name_label = gtk::Label::new(Some(&source.name));
+        name_label.set_xalign(0.0);
+        let location_label = gtk::Label::new(Some(&source.location));
+        location_label.set_xalign(0.0);
+        location_label.set_selectable(true);
+        location_label.set_ellipsize(gtk::pango::EllipsizeMode::Middle);
+        location_label.add_css_class("dim-label");
+        let text_box = gtk::Box::new(gtk::Orientation::Vertical, 2);
+        text_box.set_hexpand(true);
+        text_box.append(&name_label);
+        text_box.append(&location_label);
+
+        let up_button = gtk::Button::from_icon_name("go-up-symbolic");
+        up_button.set_sensitive(index > 0);
+        up_button.update_property(&[gtk::accessible::Property::Label(&format!(
+            "Try {} earlier",
+            source.name
+        ))]);
+        let down_button = gtk::Button::from_icon_name("go-down-symbolic");
+        down_button.set_sensitive(index + 1 < count);
+        down_button.update_property(&[gtk::accessible::Property::Label(&format!(
+            "Try {} later",
+            source.name
+        ))]);
+        let remove_button = gtk::Button::from_icon_name("list-remove-symbolic");
+        remove_button.update_property(&[gtk::accessible::Property::Label(&format!(
+            "Remove {}",
+            source.name
+        ))]);
+
+        let row_box = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+        row_box.set_margin_top(4);
+        row_box.set_margin_bottom(4);
+        row_box.append(&enabled_switch);
+        row_box.append(&text_box);
+        row_box.append(&up_button);
+        row_box.append(&down_button);
+        row_box.append(&remove_button);
+        let row = gtk::ListBoxRow::new();
+        row.set_child(Some(&row_box));
+        row.update_property(&[gtk::accessible::Property::Label(&format!(
+            "{}, {}",
+            source.name, source.location
+        ))]);
+        source_list.append(&row);
+
+        let sources_clone = sources.clone();
+        enabled_switch.connect_active_notify(move |switch| {
+            if let Some(source) = sources_clone.borrow_mut().get_mut(index) {
+                source.enabled = switch.is_active();
+            }
+            save_sources(&sources_clone.borrow());
+        });
+        for (button, change) in [
+            (&up_button, Change::Up),
+            (&down_button, Change::Down),
+            (&remove_button, Change::Remove),
+        ] {
+            let source_list = source_list.clone();
+            let sources = sources.clone();
+            button.connect_clicked(move |_| {
+                {
+                    let mut sources = sources.borrow_mut();
+                    match change {
+                        Change::Up => sources.swap(index - 1, index),
+                        Change::Down => sources.swap(index, index + 1),
+                        Change::Remove => {
+                            sources.remove(index);
+                        }
+                    }
+                    save_sources(&sources);
+                }
+                fill_source_list(&source_list, &sources);
+            });
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Change {
+    Up,
+    Down,
+    Remove,
+}
+
+fn save_sources(sources: &[CatalogSource]) {
+    let mut settings = Settings::load();
+    settings.updates.sources = sources.to_vec();
+    if let Err(err) = settings.save() {
+        eprintln!("Failed to save settings: {err}");
+    }
+}
@@ -0,0 +1,115 @@
+// Summary of the run history for the statistics window.
+
+use crate::history::PastRun;
+use std::{collections::HashMap, time::Duration};
+
+const WEEK_SECS: i64 = 7 * 24 * 3600;
+// Weeks shown in the runs per week chart, including the current one
+pub const WEEKS: usize = 8;
+// Categories listed at most
+const TOP_CATEGORIES: usize = 5;
+
+#[derive(Debug, PartialEq)]
+pub struct Statistics {
+    pub total_runs: usize,
+    pub successful_runs: usize,
+    /// Time spent running commands.
+    pub total_time: Duration,
+    /// Runs in each of the last [`WEEKS`] weeks, counted back from now, this week first.
+    pub runs_per_week: [usize; WEEKS],
+    /// Categories with the most runs and their run counts, e.g. `System Setup / Arch`.
+    pub top_categories: Vec<(String, usize)>,
+}
+
+impl Statistics {
+    /// Statistics over `runs` as of `now`, a Unix time.
+    pub fn compute(runs: &[PastRun], now: i64) -> Self {
+        let mut runs_per_week = [0; WEEKS];
+        let mut categories: HashMap<&str, usize> = HashMap::new();
+        for run in runs {
+            let week = (now - run.finished_at).max(0) / WEEK_SECS;
+            if let Some(count) = runs_per_week.get_mut(week as usize) {
+                *count += 1;
+            }
+            *categories.entry(category(&run.path)).or_default() += 1;
+        }
+        let mut top_categories: Vec<(String, usize)> = categories
+            .into_iter()
+            .map(|(category, count)| (category.to_string(), count))
+            .collect();
+        top_categories.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        top_categories.truncate(TOP_CATEGORIES);
+
+        Self {
+            total_runs: runs.len(),
+            successful_runs: runs.iter().filter(|run| run.success).count(),
+            total_time: runs
+                .iter()
+                .map(|run| Duration::from_secs_f64(run.duration_secs.max(0.0)))
+                .sum(),
+            runs_per_week,
+            top_categories,
+        }
+    }
+
+    /// Share of successful runs in percent, if anything ran.
+    pub fn success_rate(&self) -> Option<usize> {
+        (self.total_runs > 0).then(|| self.successful_runs * 100 / self.total_runs)
+    }
+}
+
+/// Category of the command at `path`: the tab and folders leading to it.
+fn category(path: &str) -> &str {
+    path.rsplit_once(" / ")
+        .map_or(path, |(category, _)| category)
+}
+
+/// Label of a week in the chart, e.g. `This week` or `3 weeks ago`.
+pub fn week_label(weeks_ago: usize) -> String {
+    match weeks_ago {
+        0 => "This week".to_string(),
+        1 => "Last week".to_string(),
+        weeks => format!("{weeks} weeks ago"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(path: &str, days_ago: i64, success: bool) -> PastRun {
+        PastRun {
+            name: path.rsplit(" / ").next().unwrap().to_string(),
+            path: path.to_string(),
+            finished_at: 100 * WEEK_SECS - days_ago * 24 * 3600,
+            success,
+            duration_secs: 30.0,
+        }
+    }
+
+    #[test]
+    fn test_compute() {
+        let runs = [
+            run("System Setup / Arch / Paru", 1, true),
+            run("System Setup / Arch / Yay", 2, false),
+            run("Utilities / Bluetooth", 9, true),
+            run("System Setup / Arch / Paru", 60, true),
+            run("Utilities / Bluetooth", 400, true),
+        ];
+
+        let stats = Statistics::compute(&runs, 100 * WEEK_SECS);
+
+        assert_eq!(stats.total_runs, 5);
+        assert_eq!(stats.success_rate(), Some(80));
+        assert_eq!(stats.total_time, Duration::from_secs(150));
+        assert_eq!(stats.runs_per_week, [2, 1, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(
+            stats.top_categories,
+            [
+                ("System Setup / Arch".to_string(), 3),
+                ("Utilities".to_string(), 2)
+            ]
+        );
+        assert_eq!(Statistics::compute(&[], 0).success_rate(), None);
+    }
+}
@@ -0,0 +1,114 @@
+use crate::{
+    history,
+    report::format_duration,
+    runner,
+    stats::{week_label, Statistics},
+};
+use gtk::prelude::*;
+use gtk4 as gtk;
+
+/// Opens a window summarizing the run history: runs per week, success rate, time spent in
+/// scripts and the categories run most.
+pub fn open_statistics_window(app: &gtk::Application) {
+    let window = gtk::ApplicationWindow::builder()
+        .application(app)
+        .title("Statistics")
+        .default_width(560)
+        .default_height(520)
+        .build();
+
+    let root_box = gtk::Box::new(gtk::Orientation::Vertical, 12);
+    root_box.set_margin_top(12);
+    root_box.set_margin_bottom(12);
+    root_box.set_margin_start(12);
+    root_box.set_margin_end(12);
+
+    let stats = Statistics::compute(&history::past_runs(), runner::now().unix_timestamp());
+    let summary = match stats.success_rate() {
+        Some(rate) => format!(
+            "{} runs, {rate}% successful ({} failed)\nTime spent in scripts: {}",
+            stats.total_runs,
+            stats.total_runs - stats.successful_runs,
+            format_duration(stats.total_time)
+        ),
+        None => "No commands have run yet.".to_string(),
+    };
+    let summary_label = gtk::Label::new(Some(&summary));
+    summary_label.set_xalign(0.0);
+    summary_label.set_selectable(true);
+    summary_label.update_property(&[gtk::accessible::Property::Label("Summary")]);
+
+    let weeks_title = heading("Runs per week");
+    // One bar per week, scaled to the busiest one
+    let weeks_grid = gtk::Grid::new();
+    weeks_grid.set_row_spacing(4);
+    weeks_grid.set_column_spacing(12);
+    weeks_grid.update_property(&[gtk::accessible::Property::Label("Runs per week")]);
+    let busiest = stats
+        .runs_per_week
+        .iter()
+        .copied()
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    for (weeks_ago, &count) in stats.runs_per_week.iter().enumerate() {
+        let label = week_label(weeks_ago);
+        let name_label = gtk::Label::new(Some(&label));
+        name_label.set_xalign(0.0);
+        let bar = gtk::LevelBar::for_interval(0.0, busiest as f64);
+        bar.set_value(count as f64);
+        bar.set_hexpand(true);
+        bar.set_valign(gtk::Align::Center);
+        bar.update_property(&[gtk::accessible::Property::Label(&format!(
+            "{label}: {count} runs"
+        ))]);
+        let count_label = gtk::Label::new(Some(&count.to_string()));
+        count_label.set_xalign(1.0);
+        let row = weeks_ago as i32;
+        weeks_grid.attach(&name_label, 0, row, 1, 1);
+        weeks_grid.attach(&bar, 1, row, 1, 1);
+        weeks_grid.attach(&count_label, 2, row, 1, 1);
+    }
+
+    let categories_title = heading("Most run categories");
+    let categories_text = if stats.top_categories.is_empty() {
+        "None yet.".to_string()
+    } else {
+        stats
+            .top_categories
+            .iter()
+            .map(|(category, count)| format!("{category}: {count}"))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    let categories_label = gtk::Label::new(Some(&categories_text));
+    categories_label.set_xalign(0.0);
+    categories_label.set_selectable(true);
+    categories_label.update_property(&[gtk::accessible::Property::Label("Most run categories")]);
+
+    let close_button = gtk::Button::with_label("Close");
+    close_button.set_halign(gtk::Align::End);
+    close_button.set_valign(gtk::Align::End);
+    close_button.set_vexpand(true);
+    close_button.update_property(&[gtk::accessible::Property::Label("Close")]);
+    let window_clone = window.clone();
+    close_button.connect_clicked(move |_| window_clone.close());
+
+    root_box.append(&summary_label);
+    root_box.append(&weeks_title);
+    root_box.append(&weeks_grid);
+    root_box.append(&categories_title);
+    root_box.append(&categories_label);
+    root_box.append(&close_button);
+    window.set_child(Some(&root_box));
+    window.show();
+    close_button.grab_focus();
+}
+
+fn heading(text: &str) -> gtk::Label {
+    let label = gtk::Label::new(Some(text));
+    label.set_xalign(0.0);
+    label.add_css_class("title-4");
+    label.set_accessible_role(gtk::AccessibleRole::Heading);
+    label
+}
@@ -0,0 +1,169 @@
+// Facts about the machine shown on the home view, read from `/proc` and `/etc` like
+// `hostnamectl` and `free` do.
+
+use crate::logs::format_size;
+use nix::sys::statvfs::statvfs;
+use std::{fs, time::Duration};
+
+#[derive(Debug, Default)]
+pub struct SystemInfo {
+    pub hostname: Option<String>,
+    pub distro: Option<String>,
+    pub kernel: Option<String>,
+    pub uptime: Option<Duration>,
+    pub memory: Option<Usage>,
+    /// Usage of the file system holding `/`.
+    pub disk: Option<Usage>,
+}
+
+/// Used and total amount of memory or disk space, in bytes.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Usage {
+    pub used: u64,
+    pub total: u64,
+}
+
+impl SystemInfo {
+    /// Reads the current values. Anything that cannot be read is left out.
+    pub fn read() -> Self {
+        let read_trimmed = |path| {
+            fs::read_to_string(path)
+                .ok()
+                .map(|text| text.trim().to_string())
+                .filter(|text| !text.is_empty())
+        };
+        Self {
+            hostname: read_trimmed("/proc/sys/kernel/hostname"),
+            distro: fs::read_to_string("/etc/os-release")
+                .ok()
+                .and_then(|text| parse_os_release(&text)),
+            kernel: read_trimmed("/proc/sys/kernel/osrelease"),
+            uptime: fs::read_to_string("/proc/uptime")
+                .ok()
+                .and_then(|text| parse_uptime(&text)),
+            memory: fs::read_to_string("/proc/meminfo")
+                .ok()
+                .and_then(|text| parse_meminfo(&text)),
+            disk: statvfs("/").ok().map(|stats| {
+                let block_size = stats.fragment_size();
+                let total = stats.blocks() * block_size;
+                Usage {
+                    used: total - stats.blocks_free() * block_size,
+                    total,
+                }
+            }),
+        }
+    }
+}
+
+/// Name of the distribution in `/etc/os-release`, e.g. `Arch Linux`.
+fn parse_os_release(text: &str) -> Option<String> {
+    let value = |key: &str| {
+        text.lines().find_map(|line| {
+            let value = line.strip_prefix(key)?.strip_prefix('=')?;
+            let value = value.trim().trim_matches(['"', '\'']);
+            (!value.is_empty()).then(|| value.to_string())
+        })
+    };
+    value("PRETTY_NAME").or_else(|| value("NAME"))
+}
+
+/// Time since boot from `/proc/uptime`.
+fn parse_uptime(text: &str) -> Option<Duration> {
+    let seconds: f64 = text.split_whitespace().next()?.parse().ok()?;
+    Some(Duration::from_secs(seconds as u64))
+}
+
+/// Memory in use from `/proc/meminfo`, not counting caches the kernel can drop.
+fn parse_meminfo(text: &str) -> Option<Usage> {
+    let kib = |key: &str| {
+        text.lines().find_map(|line| {
+            let value = line.strip_prefix(key)?.strip_prefix(':')?;
+            value.split_whitespace().next()?.parse::<u64>().ok()
+        })
+    };
+    let total = kib("MemTotal")? * 1024;
+    let available = kib("MemAvailable")? * 1024;
+    Some(Usage {
+        used: total.saturating_sub(available),
+        total,
+    })
+}
+
+/// Uptime to the minute, e.g. `3 d 4 h` or `5 h 12 min`.
+pub fn format_uptime(uptime: Duration) -> String {
+    let minutes = uptime.as_secs() / 60;
+    let (days, hours, minutes) = (minutes / 1440, minutes / 60 % 24, minutes % 60);
+    match (days, hours) {
+        (0, 0) => format!("{minutes} min"),
+        (0, _) => format!("{hours} h {minutes} min"),
+        _ => format!("{days} d {hours} h"),
+    }
+}
+
+/// Usage like `3.2 GiB of 15.5 GiB (21%)`.
+pub fn format_usage(usage: &Usage) -> String {
+    let percent = (usage.used * 100)
+        .checked_div(usage.total)
+        .unwrap_or_default();
+    format!(
+        "{} of {} ({percent}%)",
+        format_size(usage.used),
+        format_size(usage.total)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_os_release() {
+        let text = "NAME=\"Fedora Linux\"\nVERSION_ID=41\nPRETTY_NAME=\"Fedora Linux 41 (Workstation Edition)\"\n";
+
+        assert_eq!(
+            parse_os_release(text).as_deref(),
+            Some("Fedora Linux 41 (Workstation Edition)")
+        );
+        assert_eq!(
+            parse_os_release("NAME=Arch\nID=arch\n").as_deref(),
+            Some("Arch")
+        );
+        assert_eq!(parse_os_release("ID=arch\n"), None);
+    }
+
+    #[test]
+    fn test_parse_meminfo() {
+        let text = "MemTotal:       16000000 kB\nMemFree:         1000000 kB\nMemAvailable:   12000000 kB\n";
+
+        assert_eq!(
+            parse_meminfo(text),
+            Some(Usage {
+                used: 4_000_000 * 1024,
+                total: 16_000_000 * 1024,
+            })
+        );
+        assert_eq!(parse_meminfo("MemTotal: 1 kB\n"), None);
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(
+            parse_uptime("350735.47 234388.90\n"),
+            Some(Duration::from_secs(350_735))
+        );
+        assert_eq!(format_uptime(Duration::from_secs(350_735)), "4 d 1 h");
+        assert_eq!(
+            format_uptime(Duration::from_secs(3 * 3600 + 125)),
+            "3 h 2 min"
+        );
+        assert_eq!(format_uptime(Duration::from_secs(59)), "0 min");
+        assert_eq!(
+            format_usage(&Usage {
+                used: 512 * 1024 * 1024,
+                total: 2048 * 1024 * 1024,
+            }),
+            "512.0 MiB of 2.0 GiB (25%)"
+        );
+    }
+}
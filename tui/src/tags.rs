@@ -0,0 +1,136 @@
+// Tags users give commands themselves, stored in `tags.toml` in the config directory by
+// command name. They are shown and searched like the tags from the tab data.
+
+use crate::settings::config_dir;
+use gtk4::glib;
+use linutil_core::ListNode;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+#[derive(Default, Deserialize, Serialize)]
+pub struct UserTags {
+    #[serde(default)]
+    commands: BTreeMap<String, Vec<String>>,
+}
+
+impl UserTags {
+    pub fn path() -> PathBuf {
+        config_dir().join("tags.toml")
+    }
+
+    pub fn load() -> Self {
+        Self::load_from(&Self::path())
+    }
+
+    fn load_from(path: &Path) -> Self {
+        match fs::read_to_string(path) {
+            Ok(content) => toml::from_str(&content).unwrap_or_else(|err| {
+                eprintln!("Failed to parse tags file {}: {err}", path.display());
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        self.save_to(&Self::path())
+    }
+
+    fn save_to(&self, path: &Path) -> io::Result<()> {
+        let content =
+            toml::to_string(self).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, content)
+    }
+
+    /// Tags the user gave the command called `name`.
+    pub fn get(&self, name: &str) -> &[String] {
+        self.commands.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /// Replaces the user's tags of the command called `name`.
+    pub fn set(&mut self, name: &str, tags: Vec<String>) {
+        if tags.is_empty() {
+            self.commands.remove(name);
+        } else {
+            self.commands.insert(name.to_string(), tags);
+        }
+    }
+}
+
+/// Tags of `node`: those from the tab data followed by the user's, without duplicates.
+pub fn tags_of(node: &ListNode, user_tags: &UserTags) -> Vec<String> {
+    let mut tags = node.tags.clone();
+    for tag in user_tags.get(&node.name) {
+        if !tags.contains(tag) {
+            tags.push(tag.clone());
+        }
+    }
+    tags
+}
+
+/// Tags typed by the user, e.g. `Gaming, aur` gives `gaming` and `aur`. Tags are single
+/// words so they can be searched with `tag:<name>`.
+pub fn parse_tags(text: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in text.split(|ch: char| ch == ',' || ch.is_whitespace()) {
+        let tag = tag.trim().to_lowercase();
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+/// Pango markup showing `tags` as small pills.
+pub fn pills_markup(tags: &[String]) -> String {
+    tags.iter()
+        .map(|tag| {
+            format!(
+                "<span size=\"small\" background=\"#808080\" bgalpha=\"25%\"> {} </span>",
+                glib::markup_escape_text(tag)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use linutil_core::Command;
+
+    #[test]
+    fn test_tags_of() {
+        let node = ListNode {
+            name: "Paru AUR Helper".to_string(),
+            description: String::new(),
+            command: Command::None,
+            task_list: String::new(),
+            multi_select: true,
+            tags: vec!["aur".to_string()],
+        };
+        let mut user_tags = UserTags::default();
+        user_tags.set(&node.name, parse_tags("Mine, aur  fresh-install"));
+
+        assert_eq!(tags_of(&node, &user_tags), ["aur", "mine", "fresh-install"]);
+
+        let path = std::env::temp_dir().join(format!(
+            "linutil-tags-test-{}/tags.toml",
+            std::process::id()
+        ));
+        user_tags.save_to(&path).unwrap();
+        let mut loaded = UserTags::load_from(&path);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+        assert_eq!(loaded.get(&node.name), ["mine", "aur", "fresh-install"]);
+
+        loaded.set(&node.name, Vec::new());
+        assert!(loaded.get(&node.name).is_empty());
+    }
+}
@@ -0,0 +1,145 @@
+// Encoding of key presses as the bytes a terminal sends for them, so the output view can
+// forward keys to the command's PTY for interactive programs such as editors and menus, and
+// of pasted text as the lines or the terminal paste it is sent as.
+
+/// Bytes for the key called `name` by GDK, e.g. `Up` or `Return`, typing `text` if it is a
+/// character key. `None` for keys a terminal sends nothing for, such as Shift alone.
+pub fn encode_key(name: &str, text: Option<char>, ctrl: bool, alt: bool) -> Option<Vec<u8>> {
+    let mut bytes = match name {
+        "Return" | "KP_Enter" => b"\r".to_vec(),
+        "BackSpace" => b"\x7f".to_vec(),
+        "Tab" => b"\t".to_vec(),
+        "ISO_Left_Tab" => b"\x1b[Z".to_vec(),
+        "Escape" => b"\x1b".to_vec(),
+        "Up" | "KP_Up" => b"\x1b[A".to_vec(),
+        "Down" | "KP_Down" => b"\x1b[B".to_vec(),
+        "Right" | "KP_Right" => b"\x1b[C".to_vec(),
+        "Left" | "KP_Left" => b"\x1b[D".to_vec(),
+        "Home" | "KP_Home" => b"\x1b[H".to_vec(),
+        "End" | "KP_End" => b"\x1b[F".to_vec(),
+        "Insert" | "KP_Insert" => b"\x1b[2~".to_vec(),
+        "Delete" | "KP_Delete" => b"\x1b[3~".to_vec(),
+        "Page_Up" | "KP_Page_Up" => b"\x1b[5~".to_vec(),
+        "Page_Down" | "KP_Page_Down" => b"\x1b[6~".to_vec(),
+        _ => match function_key(name) {
+            Some(bytes) => bytes.to_vec(),
+            None => character(text?, ctrl)?,
+        },
+    };
+    if alt {
+        bytes.insert(0, 0x1b);
+    }
+    Some(bytes)
+}
+
+fn function_key(name: &str) -> Option<&'static [u8]> {
+    Some(match name {
+        "F1" => b"\x1bOP",
+        "F2" => b"\x1bOQ",
+        "F3" => b"\x1bOR",
+        "F4" => b"\x1bOS",
+        "F5" => b"\x1b[15~",
+        "F6" => b"\x1b[17~",
+        "F7" => b"\x1b[18~",
+        "F8" => b"\x1b[19~",
+        "F9" => b"\x1b[20~",
+        "F10" => b"\x1b[21~",
+        "F11" => b"\x1b[23~",
+        "F12" => b"\x1b[24~",
+        _ => return None,
+    })
+}
+
+/// A typed character, or with Ctrl the control character it stands for, e.g. Ctrl+C for ETX.
+fn character(text: char, ctrl: bool) -> Option<Vec<u8>> {
+    if text.is_control() {
+        return None;
+    }
+    if ctrl {
+        return match text.to_ascii_lowercase() {
+            letter @ 'a'..='z' => Some(vec![letter as u8 - b'a' + 1]),
+            ' ' | '@' | '2' => Some(vec![0]),
+            '[' | '3' => Some(vec![0x1b]),
+            '\\' | '4' => Some(vec![0x1c]),
+            ']' | '5' => Some(vec![0x1d]),
+            '^' | '6' => Some(vec![0x1e]),
+            '_' | '-' | '7' => Some(vec![0x1f]),
+            '?' | '8' => Some(vec![0x7f]),
+            _ => None,
+        };
+    }
+    Some(text.to_string().into_bytes())
+}
+
+/// Lines of pasted `text`, each ending with a newline as if typed and sent with Enter. A
+/// final newline does not add an empty line.
+pub fn paste_lines(text: &str) -> Vec<String> {
+    text.lines().map(|line| format!("{line}\n")).collect()
+}
+
+/// Bytes for `text` pasted into the output while terminal input is on. Line breaks become
+/// Enter, and with `bracketed` the text is marked as pasted the way the program asked for.
+pub fn paste_bytes(text: &str, bracketed: bool) -> Vec<u8> {
+    let mut text = text.replace("\r\n", "\r").replace('\n', "\r");
+    if bracketed {
+        // The pasted text must not end the paste early
+        text = text.replace("\x1b[201~", "").replace("\x1b[200~", "");
+        text = format!("\x1b[200~{text}\x1b[201~");
+    }
+    text.into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_key() {
+        assert_eq!(
+            encode_key("Up", None, false, false),
+            Some(b"\x1b[A".to_vec())
+        );
+        assert_eq!(
+            encode_key("Return", None, false, false),
+            Some(b"\r".to_vec())
+        );
+        assert_eq!(
+            encode_key("ISO_Left_Tab", None, false, false),
+            Some(b"\x1b[Z".to_vec())
+        );
+        assert_eq!(
+            encode_key("F5", None, false, false),
+            Some(b"\x1b[15~".to_vec())
+        );
+        assert_eq!(encode_key("c", Some('c'), true, false), Some(vec![3]));
+        assert_eq!(encode_key("X", Some('X'), true, false), Some(vec![24]));
+        assert_eq!(
+            encode_key("x", Some('x'), false, true),
+            Some(b"\x1bx".to_vec())
+        );
+        assert_eq!(
+            encode_key("eacute", Some('é'), false, false),
+            Some("é".into())
+        );
+        assert_eq!(encode_key("Shift_L", None, false, false), None);
+    }
+
+    #[test]
+    fn test_paste_lines() {
+        assert_eq!(
+            paste_lines("cat <<EOF\r\nkey = 1\n\nEOF\n"),
+            ["cat <<EOF\n", "key = 1\n", "\n", "EOF\n"]
+        );
+        assert!(paste_lines("").is_empty());
+    }
+
+    #[test]
+    fn test_paste_bytes() {
+        assert_eq!(paste_bytes("ls\r\ncd ~\n", false), b"ls\rcd ~\r");
+        assert_eq!(
+            paste_bytes("rm -rf build\x1b[201~\n", true),
+            b"\x1b[200~rm -rf build\r\x1b[201~"
+        );
+        assert_eq!(paste_bytes("ñandú", false), "ñandú".as_bytes());
+    }
+}
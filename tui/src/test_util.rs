@@ -0,0 +1,29 @@
+// Fixtures shared by the unit tests of several modules.
+
+use crate::runner::QueuedCommand;
+use linutil_core::{Command, ListNode};
+use std::rc::Rc;
+
+/// A command named `name` that runs `command`, without description or tags.
+pub fn test_node(name: &str, command: Command) -> Rc<ListNode> {
+    Rc::new(ListNode {
+        name: name.to_string(),
+        description: String::new(),
+        command,
+        task_list: String::new(),
+        multi_select: true,
+        tags: Vec::new(),
+    })
+}
+
+/// A queued run of `script`, named after the last part of `path`.
+pub fn test_command(path: &str, script: &str) -> QueuedCommand {
+    let name = path.rsplit(" / ").next().unwrap_or(path);
+    QueuedCommand {
+        node: test_node(name, Command::Raw(script.to_string())),
+        path: path.to_string(),
+        env: Vec::new(),
+        answers: Vec::new(),
+        on_failure: None,
+    }
+}
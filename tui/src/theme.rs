@@ -1,11 +1,13 @@
 use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
 
 // Add the Theme name here for a new theme
 // This is more secure than the previous list
 // We cannot index out of bounds, and we are giving
 // names to our various themes, making it very clear
 // This will make it easy to add new themes
-#[derive(Clone, Debug, PartialEq, Default, ValueEnum, Copy)]
+#[derive(Clone, Debug, PartialEq, Default, ValueEnum, Copy, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum Theme {
     #[default]
     Default,
@@ -33,7 +35,6 @@ impl Theme {
             Theme::Compatible => ">",
         }
     }
-
 }
 
 impl Theme {
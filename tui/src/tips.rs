@@ -1,13 +1,444 @@
-use rand::Rng;
+use crate::settings::{config_dir, state_dir};
+use rand::seq::SliceRandom;
+use serde::Deserialize;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock},
+};
 
 const TIPS: &str = include_str!("../cool_tips.txt");
 
+/// A tip, preferred while a tab or category whose name contains one of its tags is open. In
+/// text files a line may start with the tags, e.g. `[gaming,drivers] text`.
+#[derive(Deserialize)]
+pub struct Tip {
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub text: String,
+    /// Path of the command the tip is about, e.g. `System Setup / Arch / Paru AUR Helper`,
+    /// written after ` => ` in text files.
+    #[serde(default)]
+    pub command: Option<String>,
+}
+
+/// Layout of `tips.toml`: `[[tips]]` tables with `text` and optional `tags`.
+#[derive(Deserialize)]
+struct TipsFile {
+    #[serde(default)]
+    tips: Vec<Tip>,
+}
+
+fn parse_tip(line: &str) -> Tip {
+    let (line, command) = match line.rsplit_once(" => ") {
+        Some((line, command)) => (line, Some(command.trim().to_string())),
+        None => (line, None),
+    };
+    let (tags, text) = match line
+        .strip_prefix('[')
+        .and_then(|rest| rest.split_once("] "))
+    {
+        Some((tags, text)) => (
+            tags.split(',').map(|tag| tag.trim().to_string()).collect(),
+            text,
+        ),
+        None => (Vec::new(), line),
+    };
+    Tip {
+        tags,
+        text: text.trim().to_string(),
+        command,
+    }
+}
+
+fn parse_tips(text: &str) -> impl Iterator<Item = Tip> + '_ {
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(parse_tip)
+}
+
+/// Built-in tips followed by the user's own from `tips.txt` and `tips.toml` in the config
+/// directory, e.g. for internal guidance of an organization. Tips in `tips.<locale>.txt` and
+/// `tips.<locale>.toml` for the user's language replace all of them, so translated tips are
+/// not mixed with English ones. Read once.
+pub fn all_tips() -> &'static [Tip] {
+    static ALL_TIPS: OnceLock<Vec<Tip>> = OnceLock::new();
+    ALL_TIPS.get_or_init(|| {
+        let dir = config_dir();
+        let localized = locales(&locale_from_env())
+            .iter()
+            .map(|locale| load_user_tips(&dir, Some(locale)))
+            .find(|tips| !tips.is_empty());
+        if let Some(tips) = localized {
+            return tips;
+        }
+        let mut tips: Vec<Tip> = parse_tips(TIPS).collect();
+        tips.extend(load_user_tips(&dir, None));
+        tips
+    })
+}
+
+/// The user's language as set for messages, e.g. `de_DE.UTF-8`.
+fn locale_from_env() -> String {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty())
+        .unwrap_or_default()
+}
+
+/// Locales to look for tips in, most specific first: `de_DE.UTF-8@euro` gives `de_DE` and
+/// `de`. English and the `C` and `POSIX` locales use the built-in tips.
+fn locales(locale: &str) -> Vec<String> {
+    let locale = locale.split(['.', '@']).next().unwrap_or_default();
+    let language = locale.split('_').next().unwrap_or_default();
+    if language.is_empty() || matches!(language, "en" | "C" | "POSIX") {
+        return Vec::new();
+    }
+    let mut locales = vec![locale.to_string()];
+    if language != locale {
+        locales.push(language.to_string());
+    }
+    locales
+}
+
+/// Tips from `tips.txt` and `tips.toml` in `dir`, or from `tips.<locale>.txt` and
+/// `tips.<locale>.toml` for a `locale`.
+fn load_user_tips(dir: &Path, locale: Option<&str>) -> Vec<Tip> {
+    let stem = match locale {
+        Some(locale) => format!("tips.{locale}"),
+        None => "tips".to_string(),
+    };
+    let mut tips = Vec::new();
+    if let Ok(text) = fs::read_to_string(dir.join(format!("{stem}.txt"))) {
+        tips.extend(parse_tips(&text));
+    }
+    let path = dir.join(format!("{stem}.toml"));
+    if let Ok(text) = fs::read_to_string(&path) {
+        match toml::from_str::<TipsFile>(&text) {
+            Ok(file) => tips.extend(file.tips),
+            Err(err) => eprintln!("Failed to parse tips file {}: {err}", path.display()),
+        }
+    }
+    for tip in &mut tips {
+        for tag in &mut tip.tags {
+            *tag = tag.to_lowercase();
+        }
+    }
+    tips
+}
+
+/// A tip not shown before if there is one, otherwise the one shown longest ago.
 pub fn get_random_tip() -> &'static str {
-    let tips: Vec<&str> = TIPS.lines().collect();
+    let tips: Vec<&Tip> = all_tips().iter().collect();
+    let seen = seen_tips().lock().unwrap_or_else(|err| err.into_inner());
+    choose_unseen(&tips, &seen).map_or("", |tip| &tip.text)
+}
+
+/// Records that `text` was shown, so it is picked last on this and later launches.
+pub fn mark_seen(text: &str) {
+    let mut seen = seen_tips().lock().unwrap_or_else(|err| err.into_inner());
+    if seen.last().is_some_and(|last| last == text) {
+        return;
+    }
+    seen.retain(|seen| seen != text);
+    seen.push(text.to_string());
+    if let Err(err) = save_seen(&seen_path(), &seen) {
+        eprintln!("Failed to save shown tips: {err}");
+    }
+}
+
+/// Tips shown before, the most recent last. Tips that no longer exist are dropped.
+fn seen_tips() -> &'static Mutex<Vec<String>> {
+    static SEEN: OnceLock<Mutex<Vec<String>>> = OnceLock::new();
+    SEEN.get_or_init(|| {
+        let tips = all_tips();
+        let mut seen = load_seen(&seen_path());
+        seen.retain(|text| tips.iter().any(|tip| tip.text == *text));
+        Mutex::new(seen)
+    })
+}
+
+fn seen_path() -> PathBuf {
+    state_dir().join("seen_tips.txt")
+}
+
+fn load_seen(path: &Path) -> Vec<String> {
+    fs::read_to_string(path)
+        .map(|text| text.lines().map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+fn save_seen(path: &Path, seen: &[String]) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let lines: Vec<String> = seen.iter().map(|text| text.replace('\n', " ")).collect();
+    fs::write(path, lines.join("\n") + "\n")
+}
+
+/// A random tip of `candidates` that is not in `seen`, or when all were seen, the one seen
+/// longest ago.
+fn choose_unseen<'a>(candidates: &[&'a Tip], seen: &[String]) -> Option<&'a Tip> {
+    let last_seen = |tip: &Tip| seen.iter().position(|text| *text == tip.text);
+    let unseen: Vec<&Tip> = candidates
+        .iter()
+        .copied()
+        .filter(|tip| last_seen(tip).is_none())
+        .collect();
+    if let Some(tip) = unseen.choose(&mut rand::thread_rng()) {
+        return Some(tip);
+    }
+    candidates.iter().copied().min_by_key(|tip| last_seen(tip))
+}
+
+/// Path of the command the tip shown as `plain` refers to.
+pub fn command_of(plain: &str) -> Option<&'static str> {
+    all_tips()
+        .iter()
+        .find(|tip| to_plain(&tip.text) == plain)
+        .and_then(|tip| tip.command.as_deref())
+}
+
+/// Piece of a tip. Tips may use `**bold**`, `` `code` `` and `[links](https://...)`.
+enum Span<'a> {
+    Text(&'a str),
+    Bold(&'a str),
+    Code(&'a str),
+    Link { label: &'a str, url: &'a str },
+}
+
+fn spans(text: &str) -> Vec<Span<'_>> {
+    let mut spans = Vec::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let next = rest.find(['*', '`', '[']).unwrap_or(rest.len());
+        if next > 0 {
+            spans.push(Span::Text(&rest[..next]));
+            rest = &rest[next..];
+            continue;
+        }
+        let span = if let Some(after) = rest.strip_prefix("**") {
+            after
+                .split_once("**")
+                .map(|(bold, after)| (Span::Bold(bold), after))
+        } else if let Some(after) = rest.strip_prefix('`') {
+            after
+                .split_once('`')
+                .map(|(code, after)| (Span::Code(code), after))
+        } else if let Some(after) = rest.strip_prefix('[') {
+            after.split_once("](").and_then(|(label, after)| {
+                let (url, after) = after.split_once(')')?;
+                let web = url.starts_with("https://") || url.starts_with("http://");
+                web.then_some((Span::Link { label, url }, after))
+            })
+        } else {
+            None
+        };
+        match span {
+            Some((span, after)) => {
+                spans.push(span);
+                rest = after;
+            }
+            // Not the start of formatting, e.g. a lone `*`
+            None => {
+                let len = rest.chars().next().map_or(1, char::len_utf8);
+                spans.push(Span::Text(&rest[..len]));
+                rest = &rest[len..];
+            }
+        }
+    }
+    spans
+}
+
+/// Pango markup for a tip. Everything but the supported formatting is escaped, so a tip
+/// can never inject markup of its own.
+pub fn to_markup(text: &str) -> String {
+    let mut markup = String::new();
+    for span in spans(text) {
+        match span {
+            Span::Text(text) => markup.push_str(&escape_markup(text)),
+            Span::Bold(text) => markup.push_str(&format!("<b>{}</b>", escape_markup(text))),
+            Span::Code(text) => markup.push_str(&format!("<tt>{}</tt>", escape_markup(text))),
+            Span::Link { label, url } => markup.push_str(&format!(
+                "<a href=\"{}\">{}</a>",
+                escape_markup(url),
+                escape_markup(label)
+            )),
+        }
+    }
+    markup
+}
+
+/// The tip as it reads on screen, without formatting characters.
+pub fn to_plain(text: &str) -> String {
+    spans(text)
+        .into_iter()
+        .map(|span| match span {
+            Span::Text(text) | Span::Bold(text) | Span::Code(text) => text,
+            Span::Link { label, .. } => label,
+        })
+        .collect()
+}
+
+fn escape_markup(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+pub fn tip_count() -> usize {
+    all_tips().len()
+}
+
+/// The tip at `index`, wrapping around at the end.
+pub fn get_tip(index: usize) -> &'static str {
+    let tips = all_tips();
     if tips.is_empty() {
         return "";
     }
+    &tips[index % tips.len()].text
+}
 
-    let random_index = rand::thread_rng().gen_range(0..tips.len());
-    tips[random_index]
+/// A tip other than the one shown as `current` for the open tab and categories in `context`.
+/// Tips tagged for one of them are preferred, falling back to any tip, and unseen tips are
+/// preferred over seen ones.
+pub fn get_next_tip(current: &str, context: &[String]) -> &'static str {
+    let tips = all_tips();
+    let others: Vec<&Tip> = tips
+        .iter()
+        .filter(|tip| to_plain(&tip.text) != current)
+        .collect();
+    let matching: Vec<&Tip> = others
+        .iter()
+        .copied()
+        .filter(|tip| matches_context(tip, context))
+        .collect();
+    let candidates = if matching.is_empty() {
+        others
+    } else {
+        matching
+    };
+    let seen = seen_tips().lock().unwrap_or_else(|err| err.into_inner());
+    match choose_unseen(&candidates, &seen) {
+        Some(tip) => &tip.text,
+        None => {
+            drop(seen);
+            get_random_tip()
+        }
+    }
+}
+
+/// Whether a tag of `tip` is part of one of the lowercase names in `context`.
+fn matches_context(tip: &Tip, context: &[String]) -> bool {
+    tip.tags
+        .iter()
+        .any(|tag| context.iter().any(|name| name.contains(tag.as_str())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tagged_tips() {
+        let tip = parse_tip("[gaming,drivers] Steam keeps Proton versions per game.");
+        assert_eq!(tip.tags, ["gaming", "drivers"]);
+        assert_eq!(tip.text, "Steam keeps Proton versions per game.");
+        assert!(matches_context(&tip, &["gaming".to_string()]));
+        assert!(!matches_context(&tip, &["system setup".to_string()]));
+
+        let tip = parse_tip("ls: Lists files and directories in the current directory.");
+        assert!(tip.tags.is_empty());
+        assert!(tip.command.is_none());
+        assert!(!matches_context(&tip, &["gaming".to_string()]));
+
+        assert!(parse_tips(TIPS).all(|tip| !tip.text.starts_with('[')));
+
+        let tip = parse_tip("[system] Paru installs AUR packages. => System Setup / Arch / Paru");
+        assert_eq!(tip.text, "Paru installs AUR packages.");
+        assert_eq!(tip.command.as_deref(), Some("System Setup / Arch / Paru"));
+    }
+
+    #[test]
+    fn test_markup() {
+        let text =
+            "Press `Ctrl+R` to **run** <all> & see [the docs](https://example.com/?a=1&b=2) *";
+
+        assert_eq!(
+            to_markup(text),
+            "Press <tt>Ctrl+R</tt> to <b>run</b> &lt;all&gt; &amp; see \
+             <a href=\"https://example.com/?a=1&amp;b=2\">the docs</a> *"
+        );
+        assert_eq!(to_plain(text), "Press Ctrl+R to run <all> & see the docs *");
+        assert_eq!(
+            to_markup("[x](javascript:alert(1))"),
+            "[x](javascript:alert(1))"
+        );
+    }
+
+    #[test]
+    fn test_load_user_tips() {
+        let dir = std::env::temp_dir().join(format!("linutil-tips-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("tips.txt"), "[vpn] Connect to the VPN first.\n\n").unwrap();
+        fs::write(
+            dir.join("tips.toml"),
+            "[[tips]]\ntext = \"Ask IT before reinstalling.\"\ntags = [\"System\"]\n",
+        )
+        .unwrap();
+
+        fs::write(dir.join("tips.de.txt"), "Zuerst mit dem VPN verbinden.\n").unwrap();
+
+        let tips = load_user_tips(&dir, None);
+        let german = load_user_tips(&dir, Some("de"));
+        let french = load_user_tips(&dir, Some("fr"));
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(tips.len(), 2);
+        assert_eq!(tips[0].text, "Connect to the VPN first.");
+        assert_eq!(tips[1].tags, ["system"]);
+        assert_eq!(german.len(), 1);
+        assert_eq!(german[0].text, "Zuerst mit dem VPN verbinden.");
+        assert!(french.is_empty());
+    }
+
+    #[test]
+    fn test_choose_unseen() {
+        let tips: Vec<Tip> = ["a", "b", "c"].into_iter().map(parse_tip).collect();
+        let candidates: Vec<&Tip> = tips.iter().collect();
+
+        let seen = ["a".to_string(), "c".to_string()];
+        assert_eq!(choose_unseen(&candidates, &seen).unwrap().text, "b");
+        let seen = ["c".to_string(), "a".to_string(), "b".to_string()];
+        assert_eq!(choose_unseen(&candidates, &seen).unwrap().text, "c");
+        assert!(choose_unseen(&[], &seen).is_none());
+
+        let path = std::env::temp_dir().join(format!(
+            "linutil-seen-tips-test-{}/seen_tips.txt",
+            std::process::id()
+        ));
+        save_seen(&path, &seen).unwrap();
+        let loaded = load_seen(&path);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+        assert_eq!(loaded, seen);
+    }
+
+    #[test]
+    fn test_locales() {
+        assert_eq!(locales("de_DE.UTF-8@euro"), ["de_DE", "de"]);
+        assert_eq!(locales("fr"), ["fr"]);
+        assert!(locales("en_US.UTF-8").is_empty());
+        assert!(locales("C.UTF-8").is_empty());
+        assert!(locales("").is_empty());
+    }
 }
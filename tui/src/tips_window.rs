@@ -0,0 +1,89 @@
+use crate::tips;
+use gtk::prelude::*;
+use gtk4 as gtk;
+use std::{cell::RefCell, rc::Rc};
+
+/// Opens a window listing every tip, filtered by text and tags as the user types.
+pub fn open_tips_window(app: &gtk::Application) {
+    let window = gtk::ApplicationWindow::builder()
+        .application(app)
+        .title("Tips")
+        .default_width(640)
+        .default_height(560)
+        .build();
+
+    let root_box = gtk::Box::new(gtk::Orientation::Vertical, 8);
+    root_box.set_margin_top(12);
+    root_box.set_margin_bottom(12);
+    root_box.set_margin_start(12);
+    root_box.set_margin_end(12);
+
+    let search_entry = gtk::SearchEntry::new();
+    search_entry.set_placeholder_text(Some("Search tips"));
+    search_entry.update_property(&[
+        gtk::accessible::Property::Label("Search tips"),
+        gtk::accessible::Property::Description("Type to filter the tips by text or topic."),
+        gtk::accessible::Property::Placeholder("Search tips"),
+    ]);
+    let count_label = gtk::Label::new(None);
+    count_label.set_xalign(0.0);
+    count_label.update_property(&[gtk::accessible::Property::Label("Number of tips shown")]);
+
+    let tip_list = gtk::ListBox::new();
+    tip_list.set_selection_mode(gtk::SelectionMode::None);
+    tip_list.set_placeholder(Some(&gtk::Label::new(Some("No tips match the search."))));
+    tip_list.update_property(&[gtk::accessible::Property::Label("Tips")]);
+    let tips = tips::all_tips();
+    for tip in tips {
+        let label = gtk::Label::new(None);
+        label.set_markup(&tips::to_markup(&tip.text));
+        label.set_xalign(0.0);
+        label.set_wrap(true);
+        label.set_selectable(true);
+        label.set_margin_top(4);
+        label.set_margin_bottom(4);
+        if !tip.tags.is_empty() {
+            label.set_tooltip_text(Some(&format!("Topics: {}", tip.tags.join(", "))));
+        }
+        tip_list.append(&label);
+    }
+    let list_scroll = gtk::ScrolledWindow::new();
+    list_scroll.set_policy(gtk::PolicyType::Never, gtk::PolicyType::Automatic);
+    list_scroll.set_vexpand(true);
+    list_scroll.set_child(Some(&tip_list));
+
+    let query = Rc::new(RefCell::new(String::new()));
+    let query_clone = query.clone();
+    tip_list.set_filter_func(move |row| {
+        let query = query_clone.borrow();
+        tips.get(row.index() as usize)
+            .is_some_and(|tip| tip_matches(tip, &query))
+    });
+    count_label.set_text(&format!("{} tips", tips.len()));
+
+    let tip_list_clone = tip_list.clone();
+    let count_label_clone = count_label.clone();
+    search_entry.connect_search_changed(move |entry| {
+        *query.borrow_mut() = entry.text().trim().to_lowercase();
+        tip_list_clone.invalidate_filter();
+        let shown = tips
+            .iter()
+            .filter(|tip| tip_matches(tip, &query.borrow()))
+            .count();
+        count_label_clone.set_text(&format!("{shown} of {} tips", tips.len()));
+    });
+
+    root_box.append(&search_entry);
+    root_box.append(&count_label);
+    root_box.append(&list_scroll);
+    window.set_child(Some(&root_box));
+    search_entry.grab_focus();
+    window.show();
+}
+
+/// Whether the text or a tag of `tip` contains `query`, which is lowercase.
+fn tip_matches(tip: &tips::Tip, query: &str) -> bool {
+    query.is_empty()
+        || tips::to_plain(&tip.text).to_lowercase().contains(query)
+        || tip.tags.iter().any(|tag| tag.contains(query))
+}
@@ -0,0 +1,99 @@
+// Short messages shown at the bottom of the main window for a few seconds, for news that
+// needs no answer, so it does not interrupt with a dialog to dismiss.
+
+use crate::gtk_app::show_info_dialog;
+use gtk::{glib, prelude::*};
+use gtk4 as gtk;
+use std::{cell::Cell, cell::RefCell, rc::Rc, time::Duration};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+struct Toast {
+    revealer: glib::WeakRef<gtk::Revealer>,
+    label: gtk::Label,
+    /// Bumped by every message, so only the timeout of the latest one hides it.
+    shown: Rc<Cell<u32>>,
+}
+
+thread_local! {
+    static TOASTS: RefCell<Vec<Toast>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Wraps `content`, the child of a window, so [`show_toast`] can show messages over it.
+pub fn toast_overlay(content: &impl IsA<gtk::Widget>) -> gtk::Overlay {
+    let label = gtk::Label::new(None);
+    label.set_wrap(true);
+    label.set_max_width_chars(60);
+    label.set_xalign(0.0);
+    let close_button = gtk::Button::from_icon_name("window-close-symbolic");
+    close_button.add_css_class("flat");
+    close_button.set_valign(gtk::Align::Center);
+    close_button.update_property(&[gtk::accessible::Property::Label("Dismiss")]);
+    let toast_box = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+    toast_box.add_css_class("app-notification");
+    toast_box.set_accessible_role(gtk::AccessibleRole::Status);
+    toast_box.append(&label);
+    toast_box.append(&close_button);
+
+    let revealer = gtk::Revealer::new();
+    revealer.set_transition_type(gtk::RevealerTransitionType::SlideUp);
+    revealer.set_halign(gtk::Align::Center);
+    revealer.set_valign(gtk::Align::End);
+    revealer.set_margin_bottom(24);
+    revealer.set_child(Some(&toast_box));
+    let revealer_weak = revealer.downgrade();
+    close_button.connect_clicked(move |_| {
+        if let Some(revealer) = revealer_weak.upgrade() {
+            revealer.set_reveal_child(false);
+        }
+    });
+
+    let overlay = gtk::Overlay::new();
+    overlay.set_child(Some(content));
+    overlay.add_overlay(&revealer);
+    TOASTS.with(|toasts| {
+        let mut toasts = toasts.borrow_mut();
+        toasts.retain(|toast| toast.revealer.upgrade().is_some());
+        toasts.push(Toast {
+            revealer: revealer.downgrade(),
+            label,
+            shown: Rc::new(Cell::new(0)),
+        });
+    });
+    overlay
+}
+
+/// Shows `title` and `message` at the bottom of `window` for a few seconds, replacing the
+/// message shown before. Windows without a [`toast_overlay`] get a dialog instead.
+pub fn show_toast(window: &gtk::Window, title: &str, message: &str) {
+    let toast = TOASTS.with(|toasts| {
+        toasts.borrow().iter().find_map(|toast| {
+            let revealer = toast.revealer.upgrade()?;
+            (revealer.root().as_ref() == Some(window.upcast_ref()))
+                .then(|| (revealer, toast.label.clone(), toast.shown.clone()))
+        })
+    });
+    let Some((revealer, label, shown)) = toast else {
+        show_info_dialog(window, title, message);
+        return;
+    };
+    label.set_markup(&format!(
+        "<b>{}</b>\n{}",
+        glib::markup_escape_text(title),
+        glib::markup_escape_text(message)
+    ));
+    label.update_property(&[gtk::accessible::Property::Label(&format!(
+        "{title}. {message}"
+    ))]);
+    revealer.set_reveal_child(true);
+    shown.set(shown.get().wrapping_add(1));
+    let current = shown.get();
+    let revealer_weak = revealer.downgrade();
+    glib::timeout_add_local_once(TIMEOUT, move || {
+        if let Some(revealer) = revealer_weak.upgrade() {
+            if shown.get() == current {
+                revealer.set_reveal_child(false);
+            }
+        }
+    });
+}
@@ -0,0 +1,111 @@
+// Counts pending package updates with the package manager of the system, without root and
+// without refreshing its package lists, so it is safe to run in the background.
+
+use std::{env, path::Path, process::Command};
+
+/// Command in the tab data that updates the system.
+pub const UPDATE_COMMAND: &str = "Full System Update";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PackageManager {
+    /// Arch, through `checkupdates` from pacman-contrib, which uses a copy of the database.
+    Pacman,
+    Apt,
+    Dnf,
+    Zypper,
+    Apk,
+    Xbps,
+}
+
+impl PackageManager {
+    fn detect() -> Option<Self> {
+        [
+            ("checkupdates", Self::Pacman),
+            ("apt", Self::Apt),
+            ("dnf", Self::Dnf),
+            ("zypper", Self::Zypper),
+            ("apk", Self::Apk),
+            ("xbps-install", Self::Xbps),
+        ]
+        .into_iter()
+        .find_map(|(program, manager)| in_path(program).then_some(manager))
+    }
+
+    fn command(self) -> (&'static str, &'static [&'static str]) {
+        match self {
+            Self::Pacman => ("checkupdates", &[]),
+            Self::Apt => ("apt", &["list", "--upgradable"]),
+            Self::Dnf => ("dnf", &["check-update", "--quiet"]),
+            Self::Zypper => ("zypper", &["--quiet", "--non-interactive", "list-updates"]),
+            Self::Apk => ("apk", &["list", "--upgradable"]),
+            Self::Xbps => ("xbps-install", &["--memory-sync", "--dry-run", "--update"]),
+        }
+    }
+
+    /// Number of updates listed in the output of [`command`](Self::command).
+    fn count(self, output: &str) -> usize {
+        let lines = output
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty());
+        match self {
+            Self::Apt => lines
+                .filter(|line| line.contains("[upgradable from"))
+                .count(),
+            // Obsoleted packages are listed again after the updates
+            Self::Dnf => lines
+                .take_while(|line| !line.starts_with("Obsoleting"))
+                .filter(|line| line.split_whitespace().count() == 3)
+                .count(),
+            Self::Zypper => lines.filter(|line| line.starts_with("v ")).count(),
+            Self::Pacman | Self::Apk | Self::Xbps => lines.count(),
+        }
+    }
+}
+
+pub fn in_path(program: &str) -> bool {
+    env::var_os("PATH").is_some_and(|path| {
+        env::split_paths(&path).any(|dir| Path::new(&dir).join(program).is_file())
+    })
+}
+
+/// Number of pending updates, or `None` without a supported package manager or when it
+/// could not be asked. Blocks until the package manager answers.
+pub fn pending_updates() -> Option<usize> {
+    let manager = PackageManager::detect()?;
+    let (program, args) = manager.command();
+    let output = Command::new(program)
+        .args(args)
+        .env("LC_ALL", "C")
+        .output()
+        .ok()?;
+    Some(manager.count(&String::from_utf8_lossy(&output.stdout)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count() {
+        let apt = "Listing...\n\
+            curl/stable 7.88.1-10+deb12u8 amd64 [upgradable from: 7.88.1-10+deb12u7]\n\
+            libcurl4/stable 7.88.1-10+deb12u8 amd64 [upgradable from: 7.88.1-10+deb12u7]\n";
+        assert_eq!(PackageManager::Apt.count(apt), 2);
+
+        let dnf = "\nkernel.x86_64  6.11.4-301.fc41  updates\nvim-minimal.x86_64  2:9.1.785-1.fc41  updates\n\
+            Obsoleting Packages\ngrub2-tools.x86_64  1:2.12-4.fc41  updates\n";
+        assert_eq!(PackageManager::Dnf.count(dnf), 2);
+
+        let zypper = "S | Repository | Name | Current Version | Available Version | Arch\n\
+            --+------------+------+-----------------+-------------------+-------\n\
+            v | Main       | curl | 8.9.1-1.1       | 8.10.1-1.1        | x86_64\n";
+        assert_eq!(PackageManager::Zypper.count(zypper), 1);
+
+        assert_eq!(
+            PackageManager::Pacman.count("linux 6.11.3 -> 6.11.4\nmesa 24.2.4 -> 24.2.5\n"),
+            2
+        );
+        assert_eq!(PackageManager::Pacman.count(""), 0);
+    }
+}
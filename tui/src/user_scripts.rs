@@ -0,0 +1,225 @@
+// Scripts users keep in `scripts/` of the config directory, shown as an extra tab after the
+// built-in ones. Subdirectories become folders. A script describes itself in a header of
+// comments, e.g. `# description: Removes old downloads`, or in a TOML file with the same
+// stem next to it, which takes precedence.
+
+use crate::settings::config_dir;
+use linutil_core::{
+    ego_tree::{NodeMut, Tree},
+    Command, ListNode, Tab,
+};
+use serde::Deserialize;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+pub const TAB_NAME: &str = "My Scripts";
+
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(default)]
+struct Metadata {
+    /// Defaults to the file name without extension.
+    name: Option<String>,
+    description: String,
+    task_list: String,
+    tags: Vec<String>,
+    multi_select: Option<bool>,
+}
+
+pub fn scripts_dir() -> PathBuf {
+    config_dir().join("scripts")
+}
+
+/// The tab of the user's scripts, if there are any.
+pub fn load_tab() -> Option<Tab> {
+    load_tab_from(&scripts_dir())
+}
+
+fn load_tab_from(dir: &Path) -> Option<Tab> {
+    let mut tree = Tree::new(Rc::new(folder_node("root".to_string())));
+    add_directory(dir, &mut tree.root_mut()).then(|| Tab {
+        name: TAB_NAME.to_string(),
+        tree,
+    })
+}
+
+/// Adds the scripts and folders in `dir` below `node`, returning whether there were any.
+fn add_directory(dir: &Path, node: &mut NodeMut<Rc<ListNode>>) -> bool {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return false;
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .collect();
+    paths.sort();
+    let mut added = false;
+    for path in paths {
+        if path.is_dir() {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            let mut folder = node.append(Rc::new(folder_node(name.into_owned())));
+            if add_directory(&path, &mut folder) {
+                added = true;
+            } else {
+                folder.detach();
+            }
+        } else if path.extension().is_some_and(|ext| ext == "sh") {
+            if let Some(script) = script_node(&path) {
+                node.append(Rc::new(script));
+                added = true;
+            }
+        }
+    }
+    added
+}
+
+fn folder_node(name: String) -> ListNode {
+    ListNode {
+        name,
+        description: String::new(),
+        command: Command::None,
+        task_list: String::new(),
+        multi_select: true,
+        tags: Vec::new(),
+    }
+}
+
+fn script_node(path: &Path) -> Option<ListNode> {
+    let source = fs::read_to_string(path).ok()?;
+    let sidecar = path.with_extension("toml");
+    let metadata = match fs::read_to_string(&sidecar) {
+        Ok(content) => toml::from_str(&content).unwrap_or_else(|err| {
+            eprintln!("Failed to parse {}: {err}", sidecar.display());
+            parse_header(&source)
+        }),
+        Err(_) => parse_header(&source),
+    };
+    let (executable, mut args) = shebang(&source);
+    args.push(path.to_string_lossy().into_owned());
+    Some(ListNode {
+        name: metadata.name.unwrap_or_else(|| {
+            path.file_stem()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned()
+        }),
+        description: metadata.description,
+        command: Command::LocalFile {
+            executable,
+            args,
+            file: path.to_path_buf(),
+        },
+        task_list: metadata.task_list,
+        multi_select: metadata.multi_select.unwrap_or(true),
+        tags: metadata.tags.iter().map(|tag| tag.to_lowercase()).collect(),
+    })
+}
+
+/// Metadata from the comments at the top of a script, e.g. `# name: Clean Downloads`.
+/// Tags are separated by commas.
+fn parse_header(source: &str) -> Metadata {
+    let mut metadata = Metadata::default();
+    let comments = source
+        .lines()
+        .skip_while(|line| line.starts_with("#!"))
+        .map_while(|line| line.strip_prefix('#'));
+    for comment in comments {
+        let Some((key, value)) = comment.split_once(':') else {
+            continue;
+        };
+        let value = value.trim().to_string();
+        match key.trim() {
+            "name" => metadata.name = Some(value),
+            "description" => metadata.description = value,
+            "task_list" => metadata.task_list = value,
+            "tags" => {
+                metadata.tags = value
+                    .split(',')
+                    .map(|tag| tag.trim().to_string())
+                    .filter(|tag| !tag.is_empty())
+                    .collect();
+            }
+            "multi_select" => metadata.multi_select = Some(value == "true"),
+            _ => {}
+        }
+    }
+    metadata
+}
+
+/// Interpreter and its arguments from the shebang line, `/bin/sh -e` without one like for
+/// the built-in scripts.
+fn shebang(source: &str) -> (String, Vec<String>) {
+    let mut parts = source
+        .lines()
+        .next()
+        .and_then(|line| line.strip_prefix("#!"))
+        .unwrap_or_default()
+        .split_whitespace()
+        .map(ToString::to_string);
+    match parts.next() {
+        Some(executable) => (executable, parts.collect()),
+        None => ("/bin/sh".to_string(), vec!["-e".to_string()]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_header() {
+        let source = "#!/bin/bash\n# name: Clean Downloads\n# description: Removes old files\n\
+                      # tags: Cleanup, files\n# multi_select: false\necho done\n# tags: late\n";
+
+        assert_eq!(
+            parse_header(source),
+            Metadata {
+                name: Some("Clean Downloads".to_string()),
+                description: "Removes old files".to_string(),
+                task_list: String::new(),
+                tags: vec!["Cleanup".to_string(), "files".to_string()],
+                multi_select: Some(false),
+            }
+        );
+        assert_eq!(shebang(source), ("/bin/bash".to_string(), Vec::new()));
+        assert_eq!(
+            shebang("echo hi"),
+            ("/bin/sh".to_string(), vec!["-e".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_load_tab() {
+        let dir =
+            std::env::temp_dir().join(format!("linutil-user-scripts-test-{}", std::process::id()));
+        fs::create_dir_all(dir.join("backup")).unwrap();
+        fs::create_dir_all(dir.join("empty")).unwrap();
+        fs::write(
+            dir.join("backup/home.sh"),
+            "#!/bin/sh\n# tags: Backup\nrsync\n",
+        )
+        .unwrap();
+        fs::write(dir.join("backup/home.toml"), "name = \"Back Up Home\"\n").unwrap();
+        fs::write(dir.join("clean.sh"), "rm -rf ~/Downloads/old\n").unwrap();
+        fs::write(dir.join("notes.txt"), "").unwrap();
+
+        let tab = load_tab_from(&dir).unwrap();
+        let missing = load_tab_from(&dir.join("missing"));
+        fs::remove_dir_all(&dir).unwrap();
+
+        let names: Vec<&str> = tab
+            .tree
+            .root()
+            .descendants()
+            .skip(1)
+            .map(|node| node.value().name.as_str())
+            .collect();
+        assert_eq!(names, ["backup", "Back Up Home", "clean"]);
+        let backup = tab.find_command_by_name("Back Up Home").unwrap();
+        // The sidecar replaces the header
+        assert!(backup.tags.is_empty());
+        assert!(missing.is_none());
+    }
+}
@@ -0,0 +1,108 @@
+use crate::{catalog, logs::format_modified, settings::Settings};
+use gtk::prelude::*;
+use gtk4 as gtk;
+
+/// Opens a window listing the kept downloads of the scripts and the built-in ones, to go back
+/// to one when new scripts break something. Choosing one switches `parent` to it through its
+/// `use-catalog` action, which keeps it until the next Refresh Scripts.
+pub fn open_versions_window(parent: &gtk::ApplicationWindow) {
+    let window = gtk::Window::builder()
+        .title("Script Versions")
+        .transient_for(parent)
+        .default_width(520)
+        .default_height(400)
+        .build();
+
+    let root_box = gtk::Box::new(gtk::Orientation::Vertical, 12);
+    root_box.set_margin_top(12);
+    root_box.set_margin_bottom(12);
+    root_box.set_margin_start(12);
+    root_box.set_margin_end(12);
+
+    let settings = Settings::load().updates;
+    let intro = gtk::Label::new(Some(&format!(
+        "The last {} downloads of the scripts are kept. Use one to stay on it until the next \
+         Refresh Scripts.",
+        settings.keep_catalogs.max(1)
+    )));
+    intro.set_xalign(0.0);
+    intro.set_wrap(true);
+
+    let version_list = gtk::ListBox::new();
+    version_list.set_selection_mode(gtk::SelectionMode::None);
+    version_list.update_property(&[gtk::accessible::Property::Label("Script versions")]);
+    let mut versions: Vec<(String, String, String)> = catalog::snapshots()
+        .into_iter()
+        .map(|snapshot| {
+            let downloaded = format!("Downloaded {}", format_modified(snapshot.downloaded));
+            (snapshot.id.clone(), snapshot.version(), downloaded)
+        })
+        .collect();
+    versions.push((
+        catalog::BUILT_IN.to_string(),
+        format!("v{}", linutil_core::VERSION),
+        "Built into Linutil".to_string(),
+    ));
+    for (id, version, detail) in versions {
+        let pinned = settings.pinned_catalog.as_deref() == Some(id.as_str());
+        let version_label = gtk::Label::new(Some(&version));
+        version_label.set_xalign(0.0);
+        let detail = if pinned {
+            format!("{detail} · In use, pinned")
+        } else {
+            detail
+        };
+        let detail_label = gtk::Label::new(Some(&detail));
+        detail_label.set_xalign(0.0);
+        detail_label.add_css_class("dim-label");
+        let text_box = gtk::Box::new(gtk::Orientation::Vertical, 2);
+        text_box.set_hexpand(true);
+        text_box.append(&version_label);
+        text_box.append(&detail_label);
+
+        let use_button = gtk::Button::with_label("Use");
+        use_button.set_valign(gtk::Align::Center);
+        use_button.set_sensitive(!pinned);
+        use_button.update_property(&[
+            gtk::accessible::Property::Label(&format!("Use scripts {version}")),
+            gtk::accessible::Property::Description(
+                "Switch to these scripts and stay on them until the next Refresh Scripts.",
+            ),
+        ]);
+        let parent = parent.clone();
+        let window_clone = window.clone();
+        use_button.connect_clicked(move |_| {
+            window_clone.close();
+            ActionGroupExt::activate_action(&parent, "use-catalog", Some(&id.to_variant()));
+        });
+
+        let row_box = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+        row_box.set_margin_top(4);
+        row_box.set_margin_bottom(4);
+        row_box.append(&text_box);
+        row_box.append(&use_button);
+        let row = gtk::ListBoxRow::new();
+        row.set_child(Some(&row_box));
+        row.update_property(&[gtk::accessible::Property::Label(&format!(
+            "Scripts {version}, {detail}"
+        ))]);
+        version_list.append(&row);
+    }
+    let list_scroll = gtk::ScrolledWindow::new();
+    list_scroll.set_policy(gtk::PolicyType::Never, gtk::PolicyType::Automatic);
+    list_scroll.set_vexpand(true);
+    list_scroll.set_child(Some(&version_list));
+
+    let close_button = gtk::Button::with_label("Close");
+    close_button.set_halign(gtk::Align::End);
+    close_button.update_property(&[gtk::accessible::Property::Label("Close")]);
+    let window_clone = window.clone();
+    close_button.connect_clicked(move |_| window_clone.close());
+
+    root_box.append(&intro);
+    root_box.append(&list_scroll);
+    root_box.append(&close_button);
+    window.set_child(Some(&root_box));
+    window.show();
+    close_button.grab_focus();
+}
@@ -0,0 +1,230 @@
+// Reloads tabs whose scripts change on disk, so people writing plugins or their own scripts
+// see each edit in the GUI right away. A GIO monitor watches a single directory, so every
+// folder of a watched tree gets its own, made again after each change to follow folders
+// added or removed. Changes are collected until the files settle, as editors save in steps.
+
+use crate::{plugins, user_scripts};
+use gtk::{gio, glib, prelude::*};
+use gtk4 as gtk;
+use linutil_core::{Command, Tab};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::Duration,
+};
+
+// Quiet time after the last change before the tab is reloaded
+const SETTLE_TIME: Duration = Duration::from_millis(300);
+
+thread_local! {
+    /// Monitors of each watched tree, by its directory. They stop watching when dropped.
+    static MONITORS: RefCell<HashMap<PathBuf, Vec<gio::FileMonitor>>> =
+        RefCell::new(HashMap::new());
+}
+
+/// A directory tree whose files make up one tab.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Source {
+    Plugin(PathBuf),
+    UserScripts,
+}
+
+impl Source {
+    /// The source whose tab lives in `dir`.
+    pub fn from_dir(dir: PathBuf) -> Self {
+        if dir == user_scripts::scripts_dir() {
+            Self::UserScripts
+        } else {
+            Self::Plugin(dir)
+        }
+    }
+
+    pub fn dir(&self) -> PathBuf {
+        match self {
+            Self::Plugin(dir) => dir.clone(),
+            Self::UserScripts => user_scripts::scripts_dir(),
+        }
+    }
+
+    /// The tab read again from the files, or `None` when there is nothing left to show.
+    pub fn load(&self, validate: bool) -> Result<Option<Tab>, String> {
+        match self {
+            Self::Plugin(dir) => plugins::load_plugin(dir, validate).map(Some),
+            Self::UserScripts => Ok(user_scripts::load_tab()),
+        }
+    }
+}
+
+/// The installed plugins and the user's scripts directory.
+pub fn sources() -> Vec<Source> {
+    let mut sources: Vec<Source> = plugins::plugin_dirs()
+        .into_iter()
+        .map(Source::Plugin)
+        .collect();
+    sources.push(Source::UserScripts);
+    sources
+}
+
+/// Calls `on_change` with each of `sources` once its files changed and settled.
+pub fn watch(sources: Vec<Source>, on_change: impl Fn(&Source) + 'static) {
+    let on_change: Rc<dyn Fn(&Source)> = Rc::new(on_change);
+    for source in sources {
+        watch_source(Rc::new(source), on_change.clone());
+    }
+}
+
+fn watch_source(source: Rc<Source>, on_change: Rc<dyn Fn(&Source)>) {
+    let pending: Rc<RefCell<Option<glib::SourceId>>> = Rc::new(RefCell::new(None));
+    let mut monitors = Vec::new();
+    for dir in directories(&source.dir()) {
+        let monitor = match gio::File::for_path(&dir)
+            .monitor_directory(gio::FileMonitorFlags::WATCH_MOVES, gio::Cancellable::NONE)
+        {
+            Ok(monitor) => monitor,
+            Err(err) => {
+                eprintln!("Failed to watch {}: {err}", dir.display());
+                continue;
+            }
+        };
+        let source = source.clone();
+        let on_change = on_change.clone();
+        let pending = pending.clone();
+        monitor.connect_changed(move |_, file, _, event| {
+            if !is_edit(event) || file.path().is_some_and(|path| is_scratch(&path)) {
+                return;
+            }
+            if let Some(timeout) = pending.borrow_mut().take() {
+                timeout.remove();
+            }
+            let source = source.clone();
+            let on_change = on_change.clone();
+            let pending_clone = pending.clone();
+            let timeout = glib::timeout_add_local_once(SETTLE_TIME, move || {
+                pending_clone.borrow_mut().take();
+                on_change(&source);
+                watch_source(source, on_change);
+            });
+            *pending.borrow_mut() = Some(timeout);
+        });
+        monitors.push(monitor);
+    }
+    // Replacing the monitors of the tree drops the old ones
+    MONITORS.with(|watched| watched.borrow_mut().insert(source.dir(), monitors));
+}
+
+fn is_edit(event: gio::FileMonitorEvent) -> bool {
+    matches!(
+        event,
+        gio::FileMonitorEvent::ChangesDoneHint
+            | gio::FileMonitorEvent::Created
+            | gio::FileMonitorEvent::Deleted
+            | gio::FileMonitorEvent::MovedIn
+            | gio::FileMonitorEvent::MovedOut
+            | gio::FileMonitorEvent::Renamed
+    )
+}
+
+/// Whether `path` is a file editors keep while working, like Vim's swap files or backups
+/// ending in `~`, which change far more often than the scripts.
+fn is_scratch(path: &Path) -> bool {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    name.starts_with(".#")
+        || name.ends_with('~')
+        || name.ends_with(".swp")
+        || name.ends_with(".swx")
+        || (name.starts_with('.') && name.ends_with(".tmp"))
+}
+
+/// `dir` and all folders below it.
+fn directories(dir: &Path) -> Vec<PathBuf> {
+    let mut directories = vec![dir.to_path_buf()];
+    let mut index = 0;
+    while let Some(dir) = directories.get(index).cloned() {
+        index += 1;
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.filter_map(Result::ok) {
+            // Not following links keeps a link to a parent from looping forever
+            if entry.file_type().is_ok_and(|kind| kind.is_dir()) {
+                directories.push(entry.path());
+            }
+        }
+    }
+    directories
+}
+
+/// Index of the tab in `tabs` with scripts in `dir`, or else the one named `name`.
+pub fn tab_index(tabs: &[Tab], dir: &Path, name: Option<&str>) -> Option<usize> {
+    tabs.iter()
+        .position(|tab| {
+            tab.tree.root().descendants().any(|node| {
+                matches!(&node.value().command, Command::LocalFile { file, .. } if file.starts_with(dir))
+            })
+        })
+        .or_else(|| name.and_then(|name| tabs.iter().position(|tab| tab.name == name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use linutil_core::{ego_tree::Tree, ListNode};
+
+    fn tab(name: &str, file: Option<&str>) -> Tab {
+        let node = |name: &str, command: Command| {
+            Rc::new(ListNode {
+                name: name.to_string(),
+                description: String::new(),
+                command,
+                task_list: String::new(),
+                multi_select: true,
+                tags: Vec::new(),
+            })
+        };
+        let mut tree = Tree::new(node("root", Command::None));
+        if let Some(file) = file {
+            let command = Command::LocalFile {
+                executable: "sh".to_string(),
+                args: Vec::new(),
+                file: PathBuf::from(file),
+            };
+            tree.root_mut().append(node("Hello", command));
+        }
+        Tab {
+            name: name.to_string(),
+            tree,
+        }
+    }
+
+    #[test]
+    fn test_tab_index() {
+        let tabs = [
+            tab("System Setup", Some("/tmp/linutil/system-setup/arch.sh")),
+            tab(
+                "Extra",
+                Some("/home/me/.config/linutil-gui/plugins/extra/hello.sh"),
+            ),
+            tab("Installed", None),
+        ];
+        let extra = Path::new("/home/me/.config/linutil-gui/plugins/extra");
+        assert_eq!(tab_index(&tabs, extra, Some("Renamed")), Some(1));
+        let installed = Path::new("/usr/share/linutil-gui/plugins/installed");
+        assert_eq!(tab_index(&tabs, installed, Some("Installed")), Some(2));
+        assert_eq!(tab_index(&tabs, installed, None), None);
+    }
+
+    #[test]
+    fn test_is_scratch() {
+        assert!(is_scratch(Path::new("/scripts/.hello.sh.swp")));
+        assert!(is_scratch(Path::new("/scripts/hello.sh~")));
+        assert!(is_scratch(Path::new("/scripts/.#hello.sh")));
+        assert!(!is_scratch(Path::new("/scripts/hello.sh")));
+        assert!(!is_scratch(Path::new("/scripts/hello.toml")));
+    }
+}
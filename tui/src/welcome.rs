@@ -0,0 +1,221 @@
+use crate::{logs, settings::Settings, theme::Theme};
+use gtk::prelude::*;
+use gtk4 as gtk;
+use std::{path::PathBuf, rc::Rc};
+
+const PAGES: [&str; 4] = ["theme", "confirmation", "logs", "multi-select"];
+
+/// Opens the wizard shown on the first start, which walks through the theme, confirmations,
+/// the log directory and multi-select. Finishing or skipping it saves the settings, so it does
+/// not show again, and passes them to `on_finish`.
+pub fn open_welcome_wizard(parent: &gtk::Window, on_finish: impl Fn(&Settings) + 'static) {
+    let dialog = gtk::Window::builder()
+        .title("Welcome to Linutil")
+        .transient_for(parent)
+        .modal(true)
+        .default_width(520)
+        .default_height(360)
+        .build();
+
+    let root_box = gtk::Box::new(gtk::Orientation::Vertical, 12);
+    root_box.set_margin_top(12);
+    root_box.set_margin_bottom(12);
+    root_box.set_margin_start(12);
+    root_box.set_margin_end(12);
+
+    let settings = Settings::load();
+    let stack = gtk::Stack::new();
+    stack.set_vexpand(true);
+    stack.set_transition_type(gtk::StackTransitionType::SlideLeftRight);
+
+    let theme_page = page(
+        "Welcome to Linutil",
+        "Linutil runs scripts that set up and maintain your system. A few choices before you \
+         start, all of which can be changed later in settings.toml.",
+    );
+    let theme_dropdown = gtk::DropDown::from_strings(&["Default", "Compatible"]);
+    theme_dropdown.set_selected(match settings.view.theme {
+        Theme::Default => 0,
+        Theme::Compatible => 1,
+    });
+    theme_dropdown.update_property(&[gtk::accessible::Property::Label("Theme")]);
+    theme_page.append(&labelled_row("Theme", &theme_dropdown));
+
+    let confirmation_page = page(
+        "Running commands",
+        "Before commands run, a dialog lists them and asks whether to go ahead. `linutil -y` \
+         skips it for one session.",
+    );
+    let skip_check = gtk::CheckButton::with_label("Run commands without asking first");
+    skip_check.set_active(settings.runs.skip_confirmation);
+    confirmation_page.append(&skip_check);
+
+    let logs_page = page(
+        "Logs",
+        "The output of every run is saved so you can read it later from the Logs window. Old \
+         logs are deleted after 30 days.",
+    );
+    let log_dir_entry = gtk::Entry::new();
+    log_dir_entry.set_hexpand(true);
+    log_dir_entry.set_text(&logs::log_dir().to_string_lossy());
+    log_dir_entry.update_property(&[gtk::accessible::Property::Label("Log directory")]);
+    let choose_button = gtk::Button::with_label("Choose…");
+    choose_button.update_property(&[gtk::accessible::Property::Description(
+        "Pick the directory for logs.",
+    )]);
+    let log_dir_box = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+    log_dir_box.append(&log_dir_entry);
+    log_dir_box.append(&choose_button);
+    logs_page.append(&labelled_row("Save logs in", &log_dir_box));
+    let dialog_clone = dialog.clone();
+    let log_dir_entry_clone = log_dir_entry.clone();
+    choose_button.connect_clicked(move |_| {
+        let chooser = gtk::FileChooserNative::new(
+            Some("Log Directory"),
+            Some(&dialog_clone),
+            gtk::FileChooserAction::SelectFolder,
+            Some("Select"),
+            Some("Cancel"),
+        );
+        let log_dir_entry = log_dir_entry_clone.clone();
+        chooser.connect_response(move |chooser, response| {
+            if response == gtk::ResponseType::Accept {
+                if let Some(path) = chooser.file().and_then(|file| file.path()) {
+                    log_dir_entry.set_text(&path.to_string_lossy());
+                }
+            }
+        });
+        chooser.show();
+    });
+
+    let multi_select_page = page(
+        "Running several commands",
+        "Turn on Multi-select at the top to queue commands from any tab by ticking them. The \
+         queue on the right can be reordered and saved as a preset, and Run then runs the \
+         commands one after another. Commands marked “(single only)” have to run on their own.",
+    );
+
+    for (name, page) in PAGES.iter().zip([
+        &theme_page,
+        &confirmation_page,
+        &logs_page,
+        &multi_select_page,
+    ]) {
+        stack.add_named(page, Some(name));
+    }
+
+    let progress_label = gtk::Label::new(None);
+    progress_label.set_hexpand(true);
+    progress_label.set_xalign(0.0);
+    progress_label.add_css_class("dim-label");
+    let skip_button = gtk::Button::with_label("Skip");
+    skip_button.update_property(&[gtk::accessible::Property::Description(
+        "Keep the defaults and close the wizard.",
+    )]);
+    let back_button = gtk::Button::with_label("Back");
+    let next_button = gtk::Button::with_label("Next");
+    let button_box = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+    button_box.append(&progress_label);
+    button_box.append(&skip_button);
+    button_box.append(&back_button);
+    button_box.append(&next_button);
+
+    let show_page = {
+        let stack = stack.clone();
+        let progress_label = progress_label.clone();
+        let back_button = back_button.clone();
+        let next_button = next_button.clone();
+        move |index: usize| {
+            stack.set_visible_child_name(PAGES[index]);
+            progress_label.set_text(&format!("Step {} of {}", index + 1, PAGES.len()));
+            back_button.set_sensitive(index > 0);
+            next_button.set_label(if index + 1 == PAGES.len() {
+                "Finish"
+            } else {
+                "Next"
+            });
+        }
+    };
+    show_page(0);
+
+    let default_log_dir = logs::default_log_dir();
+    // Skipping keeps what was loaded, finishing takes what was chosen
+    let finish = move |dialog: &gtk::Window, settings: &Settings| {
+        if let Err(err) = settings.save() {
+            eprintln!("Failed to save settings: {err}");
+        }
+        on_finish(settings);
+        dialog.close();
+    };
+    let finish = Rc::new(finish);
+
+    let dialog_clone = dialog.clone();
+    let finish_clone = finish.clone();
+    let loaded = settings.clone();
+    skip_button.connect_clicked(move |_| finish_clone(&dialog_clone, &loaded));
+
+    let stack_clone = stack.clone();
+    let show_page_clone = show_page.clone();
+    back_button.connect_clicked(move |_| {
+        let index = current_page(&stack_clone);
+        show_page_clone(index.saturating_sub(1));
+    });
+
+    let dialog_clone = dialog.clone();
+    let stack_clone = stack.clone();
+    next_button.connect_clicked(move |_| {
+        let index = current_page(&stack_clone);
+        if index + 1 < PAGES.len() {
+            show_page(index + 1);
+            return;
+        }
+        let mut settings = settings.clone();
+        settings.view.theme = match theme_dropdown.selected() {
+            1 => Theme::Compatible,
+            _ => Theme::Default,
+        };
+        settings.runs.skip_confirmation = skip_check.is_active();
+        let log_dir = PathBuf::from(log_dir_entry.text().trim());
+        settings.logging.directory =
+            (!log_dir.as_os_str().is_empty() && log_dir != default_log_dir).then_some(log_dir);
+        finish(&dialog_clone, &settings);
+    });
+
+    root_box.append(&stack);
+    root_box.append(&button_box);
+    dialog.set_child(Some(&root_box));
+    dialog.show();
+    next_button.grab_focus();
+}
+
+fn current_page(stack: &gtk::Stack) -> usize {
+    let name = stack.visible_child_name().unwrap_or_default();
+    PAGES
+        .iter()
+        .position(|page| *page == name.as_str())
+        .unwrap_or(0)
+}
+
+/// Page with a heading and an explanation, to which the page's controls are appended.
+fn page(title: &str, text: &str) -> gtk::Box {
+    let page = gtk::Box::new(gtk::Orientation::Vertical, 12);
+    let title_label = gtk::Label::new(Some(title));
+    title_label.set_xalign(0.0);
+    title_label.add_css_class("title-2");
+    title_label.set_accessible_role(gtk::AccessibleRole::Heading);
+    let text_label = gtk::Label::new(Some(text));
+    text_label.set_xalign(0.0);
+    text_label.set_wrap(true);
+    page.append(&title_label);
+    page.append(&text_label);
+    page
+}
+
+fn labelled_row(label: &str, widget: &impl IsA<gtk::Widget>) -> gtk::Box {
+    let row = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+    let label = gtk::Label::new(Some(label));
+    label.set_xalign(0.0);
+    row.append(&label);
+    row.append(widget);
+    row
+}